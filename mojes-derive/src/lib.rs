@@ -311,6 +311,49 @@ fn transpile_fn(input_fn: ItemFn) -> TokenStream {
     output.into()
 }
 
+/// Expose a standalone utility function to JavaScript as a top-level
+/// `function name(...) { ... }`. Unlike `#[to_js]` on a function, the whole
+/// item goes through `mojes_mojo::transpile_fn_to_js`, so parameters are
+/// scoped like any other transpiled function and no type checks are added.
+#[proc_macro_attribute]
+pub fn js_export(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let input_fn = match syn::parse::<ItemFn>(item.clone()) {
+        Ok(input_fn) => input_fn,
+        Err(_) => {
+            return syn::Error::new_spanned(
+                proc_macro2::TokenStream::from(item),
+                "[mojes] #[js_export] can only be applied to a free function",
+            )
+            .to_compile_error()
+            .into();
+        }
+    };
+
+    let js_function = match mojes_mojo::transpile_fn_to_js(&input_fn) {
+        Ok(js) => js,
+        Err(reason) => {
+            return syn::Error::new_spanned(
+                &input_fn.sig,
+                format!(
+                    "[mojes] #[js_export] cannot transpile `{}` to JavaScript:\n  {}",
+                    input_fn.sig.ident, reason
+                ),
+            )
+            .to_compile_error()
+            .into();
+        }
+    };
+    let js_const_name = format_ident!("{}_JS", input_fn.sig.ident.to_string().to_uppercase());
+
+    quote! {
+        #input_fn
+
+        #[linkme::distributed_slice(crate::JS)]
+        static #js_const_name: &str = #js_function;
+    }
+    .into()
+}
+
 // New procedural macro for structs
 #[proc_macro_attribute]
 pub fn js_type(_attr: TokenStream, item: TokenStream) -> TokenStream {
@@ -321,10 +364,18 @@ fn transpile_type(item: TokenStream) -> TokenStream {
     let input = item.clone();
 
     // Try to parse as struct or enum
-    if let Ok(input_struct) = syn::parse::<ItemStruct>(input.clone()) {
+    if let Ok(mut input_struct) = syn::parse::<ItemStruct>(input.clone()) {
         let struct_name = input_struct.ident.to_string();
         let js_class = generate_js_class_for_struct(&input_struct);
 
+        // `#[js(..)]`, `#[js_ignore]` and `#[js_rename]` only concern the transpiler
+        input_struct.attrs.retain(|attr| !attr.path().is_ident("js"));
+        for field in input_struct.fields.iter_mut() {
+            field.attrs.retain(|attr| {
+                !attr.path().is_ident("js_ignore") && !attr.path().is_ident("js_rename")
+            });
+        }
+
         let js_const_name = format_ident!("{}_JS_CLASS", struct_name.to_uppercase());
 
         let output = quote! {
@@ -371,6 +422,11 @@ fn transpile_type(item: TokenStream) -> TokenStream {
     error.to_compile_error().into()
 }
 
+/// Whether `attrs` carries the bare marker attribute `#[name]`
+fn has_marker(attrs: &[syn::Attribute], name: &str) -> bool {
+    attrs.iter().any(|attr| attr.path().is_ident(name))
+}
+
 #[proc_macro_attribute]
 pub fn js_object(_attr: TokenStream, item: TokenStream) -> TokenStream {
     let input_impl = parse_macro_input!(item as ItemImpl);
@@ -419,8 +475,20 @@ fn transpile_impl(input_impl: ItemImpl) -> TokenStream {
 
     let js_const_name = format_ident!("{}_JS_METHODS", struct_name.to_uppercase());
 
+    // `#[mojes_only]` methods exist only in the JavaScript; the markers
+    // themselves are not attributes rustc knows about.
+    let mut rust_impl = input_impl;
+    rust_impl
+        .items
+        .retain(|item| !matches!(item, syn::ImplItem::Fn(method) if has_marker(&method.attrs, "mojes_only")));
+    for item in &mut rust_impl.items {
+        if let syn::ImplItem::Fn(method) = item {
+            method.attrs.retain(|attr| !attr.path().is_ident("js_ignore"));
+        }
+    }
+
     let output = quote! {
-        #input_impl
+        #rust_impl
 
         #[linkme::distributed_slice(crate::JS)]
         static #js_const_name: &str = #js_methods;
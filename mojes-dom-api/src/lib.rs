@@ -133,6 +133,228 @@ impl JSONGlobal {
 #[allow(non_upper_case_globals)]
 pub static JSON: JSONGlobal = JSONGlobal;
 
+/// Mock of the JavaScript global `Math` object.
+///
+/// `Math::PI` / `Math::floor(x)` in Rust transpile to `Math.PI` /
+/// `Math.floor(x)`; the bodies compute the same result natively.
+pub struct Math;
+
+impl Math {
+    pub const E: f64 = std::f64::consts::E;
+    pub const LN2: f64 = std::f64::consts::LN_2;
+    pub const LN10: f64 = std::f64::consts::LN_10;
+    pub const LOG2E: f64 = std::f64::consts::LOG2_E;
+    pub const LOG10E: f64 = std::f64::consts::LOG10_E;
+    pub const PI: f64 = std::f64::consts::PI;
+    pub const SQRT1_2: f64 = std::f64::consts::FRAC_1_SQRT_2;
+    pub const SQRT2: f64 = std::f64::consts::SQRT_2;
+
+    pub fn random() -> f64 {
+        use std::hash::{BuildHasher, Hasher};
+        let bits = std::collections::hash_map::RandomState::new().build_hasher().finish();
+        (bits >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn abs(x: f64) -> f64 {
+        x.abs()
+    }
+
+    pub fn sign(x: f64) -> f64 {
+        if x == 0.0 || x.is_nan() { x } else { x.signum() }
+    }
+
+    pub fn floor(x: f64) -> f64 {
+        x.floor()
+    }
+
+    pub fn ceil(x: f64) -> f64 {
+        x.ceil()
+    }
+
+    /// Halfway cases round towards +Infinity, as in JavaScript
+    pub fn round(x: f64) -> f64 {
+        (x + 0.5).floor()
+    }
+
+    pub fn trunc(x: f64) -> f64 {
+        x.trunc()
+    }
+
+    pub fn sqrt(x: f64) -> f64 {
+        x.sqrt()
+    }
+
+    pub fn cbrt(x: f64) -> f64 {
+        x.cbrt()
+    }
+
+    pub fn pow(base: f64, exponent: f64) -> f64 {
+        base.powf(exponent)
+    }
+
+    pub fn exp(x: f64) -> f64 {
+        x.exp()
+    }
+
+    pub fn log(x: f64) -> f64 {
+        x.ln()
+    }
+
+    pub fn log2(x: f64) -> f64 {
+        x.log2()
+    }
+
+    pub fn log10(x: f64) -> f64 {
+        x.log10()
+    }
+
+    pub fn sin(x: f64) -> f64 {
+        x.sin()
+    }
+
+    pub fn cos(x: f64) -> f64 {
+        x.cos()
+    }
+
+    pub fn tan(x: f64) -> f64 {
+        x.tan()
+    }
+
+    pub fn asin(x: f64) -> f64 {
+        x.asin()
+    }
+
+    pub fn acos(x: f64) -> f64 {
+        x.acos()
+    }
+
+    pub fn atan(x: f64) -> f64 {
+        x.atan()
+    }
+
+    pub fn atan2(y: f64, x: f64) -> f64 {
+        y.atan2(x)
+    }
+
+    pub fn hypot(x: f64, y: f64) -> f64 {
+        x.hypot(y)
+    }
+
+    pub fn min(a: f64, b: f64) -> f64 {
+        if a.is_nan() || b.is_nan() { f64::NAN } else { a.min(b) }
+    }
+
+    pub fn max(a: f64, b: f64) -> f64 {
+        if a.is_nan() || b.is_nan() { f64::NAN } else { a.max(b) }
+    }
+}
+
+/// Mock of the JavaScript `Date` built-in.
+///
+/// `Date::now()` transpiles to `Date.now()` and `Date::new()` to
+/// `new Date()`. The mock has no time zone: the local getters report UTC.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Date {
+    time: f64,
+}
+
+impl Date {
+    /// Milliseconds since the Unix epoch
+    pub fn now() -> f64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as f64)
+            .unwrap_or(0.0)
+    }
+
+    pub fn new() -> Date {
+        Date { time: Date::now() }
+    }
+
+    pub fn getTime(&self) -> f64 {
+        self.time
+    }
+
+    pub fn valueOf(&self) -> f64 {
+        self.time
+    }
+
+    fn days(&self) -> i64 {
+        (self.time / 86_400_000.0).floor() as i64
+    }
+
+    fn ms_of_day(&self) -> i64 {
+        self.time.rem_euclid(86_400_000.0) as i64
+    }
+
+    // Proleptic Gregorian (year, month 1-12, day) from days since 1970-01-01
+    fn civil(&self) -> (i64, i64, i64) {
+        let z = self.days() + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + i64::from(month <= 2);
+        (year, month, day)
+    }
+
+    pub fn getFullYear(&self) -> i32 {
+        self.civil().0 as i32
+    }
+
+    /// Zero-based, as in JavaScript
+    pub fn getMonth(&self) -> u32 {
+        (self.civil().1 - 1) as u32
+    }
+
+    pub fn getDate(&self) -> u32 {
+        self.civil().2 as u32
+    }
+
+    /// 0 is Sunday
+    pub fn getDay(&self) -> u32 {
+        (self.days() + 4).rem_euclid(7) as u32
+    }
+
+    pub fn getHours(&self) -> u32 {
+        (self.ms_of_day() / 3_600_000) as u32
+    }
+
+    pub fn getMinutes(&self) -> u32 {
+        (self.ms_of_day() / 60_000 % 60) as u32
+    }
+
+    pub fn getSeconds(&self) -> u32 {
+        (self.ms_of_day() / 1000 % 60) as u32
+    }
+
+    pub fn getMilliseconds(&self) -> u32 {
+        (self.ms_of_day() % 1000) as u32
+    }
+
+    pub fn toISOString(&self) -> String {
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+            self.getFullYear(),
+            self.getMonth() + 1,
+            self.getDate(),
+            self.getHours(),
+            self.getMinutes(),
+            self.getSeconds(),
+            self.getMilliseconds()
+        )
+    }
+}
+
+impl Default for Date {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[linkme::distributed_slice]
 pub static JS: [&str];
 
@@ -369,12 +591,13 @@ impl Element {
 
     pub fn addEventListener<F>(&self, _event_type: &str, _callback: F)
     where
-        F: Fn(Event) + 'static,
+        F: FnMut(Event) + 'static,
     {
         // Mock implementation for transpilation
     }
 
-    pub fn removeEventListener(&self, _event_type: &str, _callback: fn()) {
+    /// Takes a named `fn`: only the same function value can be removed in JS
+    pub fn removeEventListener(&self, _event_type: &str, _callback: fn(Event)) {
         // Mock implementation for transpilation
     }
 
@@ -757,16 +980,18 @@ impl Window {
         }
     */
 
-    pub fn requestAnimationFrame<F>(&self, callback: F) -> u32
+    /// The callback receives the frame timestamp, as in the browser; the
+    /// returned id is a JS number
+    pub fn requestAnimationFrame<F>(&self, _callback: F) -> f64
     where
-        F: FnOnce() + 'static,
+        F: FnOnce(f64) + 'static,
     {
         // Mock implementation - in real browser this would be handled differently
         println!("REQUEST_ANIMATION_FRAME: callback scheduled");
-        1
+        1.0
     }
 
-    pub fn cancelAnimationFrame(&self, frame_id: u32) {
+    pub fn cancelAnimationFrame(&self, frame_id: f64) {
         println!("CANCEL_ANIMATION_FRAME: frame {} cancelled", frame_id);
     }
 
@@ -805,7 +1030,7 @@ impl Window {
 
     pub fn addEventListener<F>(&self, _event_type: &str, _callback: F)
     where
-        F: Fn(Event) + 'static,
+        F: FnMut(Event) + 'static,
     {
         // Mock implementation for transpilation
     }
@@ -1127,9 +1352,12 @@ pub struct Event {
     pub bubbles: bool,
     pub cancelable: bool,
     pub defaultPrevented: bool,
-    pub target: Option<Element>,
-    pub currentTarget: Option<Element>,
+    /// Always present on dispatched events, so `e.target.value` reads directly
+    pub target: Element,
+    pub currentTarget: Element,
     pub timeStamp: f64,
+    /// Set for keyboard events: element handlers receive a plain `Event`
+    pub key: String,
 }
 
 use std::fmt::Display;
@@ -1146,9 +1374,10 @@ impl Event {
             bubbles: false,
             cancelable: false,
             defaultPrevented: false,
-            target: None,
-            currentTarget: None,
+            target: Element::new(""),
+            currentTarget: Element::new(""),
             timeStamp: 0.0,
+            key: String::new(),
         }
     }
 
@@ -1192,6 +1421,12 @@ pub struct KeyboardEvent {
     pub altKey: bool,
     pub metaKey: bool,
     pub repeat: bool,
+    // Inherits from Event
+    pub r#type: String,
+    pub target: Element,
+    pub currentTarget: Element,
+    pub defaultPrevented: bool,
+    pub timeStamp: f64,
 }
 
 impl KeyboardEvent {
@@ -1205,9 +1440,23 @@ impl KeyboardEvent {
             altKey: false,
             metaKey: false,
             repeat: false,
+            r#type: String::new(),
+            target: Element::new(""),
+            currentTarget: Element::new(""),
+            defaultPrevented: false,
+            timeStamp: 0.0,
         }
     }
 
+    pub fn preventDefault(&self) {
+        // Mock implementation for transpilation
+        println!("EVENT.PREVENT_DEFAULT: default action prevented");
+    }
+
+    pub fn stopPropagation(&self) {
+        println!("EVENT.STOP_PROPAGATION: event propagation stopped");
+    }
+
     pub fn getModifierState(&self, key: &str) -> bool {
         println!("KEYBOARD_EVENT.GET_MODIFIER_STATE: checking modifier {}", key);
         match key {
@@ -1236,6 +1485,12 @@ pub struct MouseEvent {
     pub shiftKey: bool,
     pub altKey: bool,
     pub metaKey: bool,
+    // Inherits from Event
+    pub r#type: String,
+    pub target: Element,
+    pub currentTarget: Element,
+    pub defaultPrevented: bool,
+    pub timeStamp: f64,
 }
 
 impl MouseEvent {
@@ -1254,8 +1509,22 @@ impl MouseEvent {
             shiftKey: false,
             altKey: false,
             metaKey: false,
+            r#type: String::new(),
+            target: Element::new(""),
+            currentTarget: Element::new(""),
+            defaultPrevented: false,
+            timeStamp: 0.0,
         }
     }
+
+    pub fn preventDefault(&self) {
+        // Mock implementation for transpilation
+        println!("EVENT.PREVENT_DEFAULT: default action prevented");
+    }
+
+    pub fn stopPropagation(&self) {
+        println!("EVENT.STOP_PROPAGATION: event propagation stopped");
+    }
 }
 
 // Touch interface
@@ -1291,6 +1560,12 @@ impl TouchEvent {
 pub struct InputEvent {
     pub data: String,
     pub inputType: String,
+    // Inherits from Event
+    pub r#type: String,
+    pub target: Element,
+    pub currentTarget: Element,
+    pub defaultPrevented: bool,
+    pub timeStamp: f64,
 }
 
 impl InputEvent {
@@ -1299,8 +1574,22 @@ impl InputEvent {
         Self {
             data: String::new(),
             inputType: String::new(),
+            r#type: String::new(),
+            target: Element::new(""),
+            currentTarget: Element::new(""),
+            defaultPrevented: false,
+            timeStamp: 0.0,
         }
     }
+
+    pub fn preventDefault(&self) {
+        // Mock implementation for transpilation
+        println!("EVENT.PREVENT_DEFAULT: default action prevented");
+    }
+
+    pub fn stopPropagation(&self) {
+        println!("EVENT.STOP_PROPAGATION: event propagation stopped");
+    }
 }
 
 // FocusEvent interface
@@ -1392,14 +1681,14 @@ pub fn requestAnimationFrame(callback: fn()) -> u32 {
     window.requestAnimationFrame(callback)
 }
 */
-pub fn requestAnimationFrame<F>(callback: F) -> u32
+pub fn requestAnimationFrame<F>(callback: F) -> f64
 where
-    F: FnOnce() + 'static,
+    F: FnOnce(f64) + 'static,
 {
     window.requestAnimationFrame(callback)
 }
 
-pub fn cancelAnimationFrame(frame_id: u32) {
+pub fn cancelAnimationFrame(frame_id: f64) {
     window.cancelAnimationFrame(frame_id)
 }
 
@@ -0,0 +1,13 @@
+use mojes_dom_api::*;
+
+#[test]
+fn test_request_animation_frame_takes_timestamp_callback() {
+    let id = requestAnimationFrame(|timestamp: f64| {
+        let _ = timestamp / 1000.0;
+    });
+    assert_eq!(id, 1.0);
+    cancelAnimationFrame(id);
+
+    let id = window.requestAnimationFrame(move |_t| {});
+    window.cancelAnimationFrame(id);
+}
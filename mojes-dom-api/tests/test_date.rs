@@ -0,0 +1,20 @@
+use mojes_dom_api::*;
+
+#[test]
+fn test_date_now_and_getters_agree() {
+    let before = Date::now();
+    let d = Date::new();
+    assert!(d.getTime() >= before && d.getTime() <= Date::now());
+    assert_eq!(d.valueOf(), d.getTime());
+
+    assert!(d.getFullYear() >= 2024);
+    assert!(d.getMonth() < 12);
+    assert!((1..=31).contains(&d.getDate()));
+    assert!(d.getDay() < 7);
+    assert!(d.getHours() < 24 && d.getMinutes() < 60 && d.getSeconds() < 60);
+
+    let iso = d.toISOString();
+    assert_eq!(iso.len(), 24, "{iso}");
+    assert!(iso.starts_with(&format!("{:04}-{:02}-{:02}T", d.getFullYear(), d.getMonth() + 1, d.getDate())));
+    assert!(iso.ends_with(&format!("{:03}Z", d.getMilliseconds())));
+}
@@ -0,0 +1,54 @@
+use mojes_dom_api::*;
+
+fn on_click(_event: Event) {}
+
+#[test]
+fn test_add_event_listener_accepts_closures() {
+    let element = Element::with_tag_name("button");
+
+    // A stateful handler needs FnMut
+    let mut clicks = 0;
+    element.addEventListener("click", move |event| {
+        clicks += 1;
+        console.log(&format!("click {} on {}", clicks, event.r#type));
+        event.preventDefault();
+    });
+    element.addEventListener("click", on_click);
+    element.removeEventListener("click", on_click);
+
+    window.addEventListener("resize", |_e| {});
+}
+
+#[test]
+fn test_event_fields_reachable_from_handlers() {
+    let input = Element::with_tag_name("input");
+    input.addEventListener("keydown", |e| {
+        if e.key == "Enter" && !e.target.value.is_empty() {
+            e.preventDefault();
+            e.stopPropagation();
+        }
+        console.log(&e.r#type);
+    });
+
+    let mut e = Event::new("input");
+    e.target.value = "typed".to_string();
+    assert_eq!(e.target.value, "typed");
+    assert!(e.key.is_empty());
+}
+
+#[test]
+fn test_event_subtypes_carry_base_fields() {
+    let key = KeyboardEvent::new();
+    key.preventDefault();
+    assert_eq!(key.target.value, "");
+    assert!(key.key.is_empty() && !key.defaultPrevented);
+
+    let mouse = MouseEvent::new();
+    mouse.stopPropagation();
+    assert_eq!(mouse.r#type, "");
+    assert_eq!(mouse.clientX, 0.0);
+
+    let input = InputEvent::new();
+    assert_eq!(input.target.tagName, "");
+    assert_eq!(input.inputType, "");
+}
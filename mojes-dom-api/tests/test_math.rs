@@ -0,0 +1,21 @@
+use mojes_dom_api::*;
+
+#[test]
+fn test_math_constants_and_functions() {
+    assert_eq!(Math::PI, std::f64::consts::PI);
+    assert_eq!(Math::floor(2.7), 2.0);
+    assert_eq!(Math::round(2.5), 3.0);
+    assert_eq!(Math::round(-2.5), -2.0);
+    assert_eq!(Math::max(1.0, 4.0), 4.0);
+    assert!(Math::min(1.0, f64::NAN).is_nan());
+    assert_eq!(Math::pow(2.0, 10.0), 1024.0);
+    assert_eq!(Math::sign(-3.0), -1.0);
+}
+
+#[test]
+fn test_math_random_is_in_unit_interval() {
+    for _ in 0..100 {
+        let r = Math::random();
+        assert!((0.0..1.0).contains(&r));
+    }
+}
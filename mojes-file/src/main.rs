@@ -127,6 +127,15 @@ fn transpile_rust_file(syntax_tree: &syn::File) -> Result<String, Box<dyn std::e
         },
     )));
 
+    // Calls to the file's tuple structs construct them, wherever they appear
+    for item in &syntax_tree.items {
+        if let Item::Struct(item_struct) = item
+            && matches!(item_struct.fields, syn::Fields::Unnamed(_))
+        {
+            state.register_tuple_struct(&item_struct.ident.to_string());
+        }
+    }
+
     // Process each top-level item in the Rust file
     for item in &syntax_tree.items {
         println!("TRANSPILE: {:?}", &item);
@@ -1,5 +1,6 @@
-use std::collections::HashMap;
-use swc_common::{DUMMY_SP, SyntaxContext};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use swc_common::{BytePos, DUMMY_SP, Span, SyntaxContext};
 use swc_ecma_ast as js;
 use swc_ecma_codegen;
 use syn::punctuated::Punctuated;
@@ -113,6 +114,9 @@ pub struct TranspilerState {
     symbol_table: HashMap<String, SymbolInfo>,
     /// Stack of scopes for proper variable resolution
     scope_stack: Vec<HashMap<String, String>>,
+    /// Declared Rust types, one map per entry of `scope_stack`; `None` marks
+    /// a binding whose type isn't known
+    type_scopes: Vec<HashMap<String, Option<Type>>>,
     /// Collected errors during transpilation
     errors: Vec<String>,
     /// Collected warnings during transpilation
@@ -123,6 +127,86 @@ pub struct TranspilerState {
     current_struct_name: Option<String>,
     /// Whether we're currently in a static method context
     is_in_static_method: bool,
+    /// How the method being transpiled takes `self`, if it does
+    current_receiver: Option<ReceiverKind>,
+    /// Emit `i64`/`u64`/`i128`/`u128` integers as BigInt
+    bigint_integers: bool,
+    /// Keep Rust doc comments as JS comments
+    doc_comments: bool,
+    /// `print!`/`eprint!` write to process.stdout/stderr instead of the console
+    print_to_stdout: bool,
+    /// Converting an operand of 64/128-bit integer arithmetic, so untyped
+    /// integer literals become BigInt too
+    in_wide_int: bool,
+    /// Set while transpiling an impl block for an enum: the Rust -> JS names
+    /// of its instance methods. Enum values are strings and plain objects
+    /// without a prototype, so these methods take the value as a `self`
+    /// parameter instead of using `this`.
+    enum_methods: Option<HashMap<String, String>>,
+    /// Types known to be enums, with the Rust -> JS names of the instance
+    /// methods their impls define so far; a receiver declared as one calls
+    /// `Enum.method(value, ..)`
+    enum_types: HashMap<String, HashMap<String, String>>,
+    /// Types known to be tuple structs; calling one by name constructs it
+    tuple_structs: HashSet<String>,
+    /// How many IIFEs whose result is used as a value enclose the code being
+    /// converted, within the current function body; a `return` there would
+    /// only leave the IIFE
+    value_iife_depth: usize,
+    /// The comments of generated nodes; a synthetic span position `n`
+    /// indexes entry `n - 1`. Emitting with this state hands them to the
+    /// emitter and drops them.
+    generated_comments: Vec<Vec<GeneratedComment>>,
+    /// Method name -> JavaScript mapping, consulted for every method call
+    method_mappings: HashMap<String, MappingFn>,
+    /// Macro name (without the `!`) -> handler, consulted for every macro
+    macro_handlers: HashMap<String, MacroHandler>,
+}
+
+/// Builds the JavaScript for a method call from the converted receiver and
+/// arguments. The original Rust call is passed along for mappings that need
+/// to look at the receiver expression, the turbofish or the method name.
+pub type MappingFn = Rc<
+    dyn Fn(
+        &mut TranspilerState,
+        &syn::ExprMethodCall,
+        js::Expr,
+        Vec<js::Expr>,
+    ) -> Result<js::Expr, String>,
+>;
+
+/// Builds the JavaScript for a macro invocation. The handler gets the whole
+/// macro and parses its tokens itself.
+pub type MacroHandler = Rc<dyn Fn(&mut TranspilerState, &syn::Macro) -> Result<js::Expr, String>>;
+
+/// The receiver form of a method: `self`, `&self` or `&mut self`
+#[derive(Copy, Eq, PartialEq, Clone, Debug)]
+pub enum ReceiverKind {
+    /// `self` / `mut self` (and typed forms like `self: Box<Self>`): Rust
+    /// moves the value, JS keeps the instance usable
+    Value,
+    /// `&self` or `self: &Self`
+    Ref,
+    /// `&mut self` or `self: &mut Self`
+    RefMut,
+}
+
+impl ReceiverKind {
+    pub fn of(receiver: &syn::Receiver) -> Self {
+        match (&receiver.reference, &receiver.mutability) {
+            // The typed form says it in the type: `self: &mut Self`
+            (None, _) if receiver.colon_token.is_some() => match &*receiver.ty {
+                Type::Reference(reference) if reference.mutability.is_some() => {
+                    ReceiverKind::RefMut
+                }
+                Type::Reference(_) => ReceiverKind::Ref,
+                _ => ReceiverKind::Value,
+            },
+            (None, _) => ReceiverKind::Value,
+            (Some(_), None) => ReceiverKind::Ref,
+            (Some(_), Some(_)) => ReceiverKind::RefMut,
+        }
+    }
 }
 
 #[derive(Copy, Eq, PartialEq, Clone, Debug)]
@@ -143,14 +227,66 @@ impl TranspilerState {
         TranspilerState {
             symbol_table: HashMap::new(),
             scope_stack: vec![HashMap::new()], // Start with global scope
+            type_scopes: vec![HashMap::new()],
             errors: Vec::new(),
             warnings: Vec::new(),
             temp_var_counter: 0,
             current_struct_name: None,
             is_in_static_method: false,
+            current_receiver: None,
+            bigint_integers: false,
+            doc_comments: false,
+            print_to_stdout: false,
+            in_wide_int: false,
+            enum_methods: None,
+            enum_types: HashMap::new(),
+            tuple_structs: HashSet::new(),
+            value_iife_depth: 0,
+            generated_comments: Vec::new(),
+            method_mappings: default_method_mappings(),
+            macro_handlers: default_macro_handlers(),
         }
     }
 
+    /// Register how calls to the method `name` are transpiled, replacing any
+    /// existing mapping for it (including the built-in ones).
+    pub fn register_method_mapping<F>(&mut self, name: &str, mapping: F)
+    where
+        F: Fn(
+                &mut TranspilerState,
+                &syn::ExprMethodCall,
+                js::Expr,
+                Vec<js::Expr>,
+            ) -> Result<js::Expr, String>
+            + 'static,
+    {
+        self.method_mappings.insert(name.to_string(), Rc::new(mapping));
+    }
+
+    /// The mapping registered for the method `name`, if any
+    pub fn method_mapping(&self, name: &str) -> Option<MappingFn> {
+        self.method_mappings.get(name).cloned()
+    }
+
+    /// Register how invocations of the macro `name!` are transpiled, replacing
+    /// any existing handler for it (including the built-in ones).
+    pub fn register_macro_handler<F>(&mut self, name: &str, handler: F)
+    where
+        F: Fn(&mut TranspilerState, &syn::Macro) -> Result<js::Expr, String> + 'static,
+    {
+        self.macro_handlers.insert(name.to_string(), Rc::new(handler));
+    }
+
+    /// The handler registered for the macro `name!`, if any
+    pub fn macro_handler(&self, name: &str) -> Option<MacroHandler> {
+        self.macro_handlers.get(name).cloned()
+    }
+
+    /// Names of all macros that currently have a handler
+    pub fn registered_macro_names(&self) -> Vec<String> {
+        self.macro_handlers.keys().cloned().collect()
+    }
+
     /// Set the current struct name for Self resolution
     pub fn set_current_struct_name(&mut self, name: Option<String>) {
         self.current_struct_name = name;
@@ -170,6 +306,96 @@ impl TranspilerState {
     pub fn is_in_static_method(&self) -> bool {
         self.is_in_static_method
     }
+
+    /// Set how the method being transpiled takes `self`
+    pub fn set_current_receiver(&mut self, receiver: Option<ReceiverKind>) {
+        self.current_receiver = receiver;
+    }
+
+    /// How the method being transpiled takes `self`; `None` outside methods
+    /// and in static ones. Every form becomes `this` (or an enum method's
+    /// `self` parameter), so nothing in the output depends on it yet.
+    pub fn current_receiver(&self) -> Option<ReceiverKind> {
+        self.current_receiver
+    }
+
+    /// Emit 64- and 128-bit integer literals and arithmetic as BigInt (see
+    /// `CodegenOptions::bigint_integers`)
+    pub fn set_bigint_integers(&mut self, enabled: bool) {
+        self.bigint_integers = enabled;
+    }
+
+    /// Keep Rust doc comments as leading JS comments (see
+    /// `CodegenOptions::doc_comments`)
+    pub fn set_doc_comments(&mut self, enabled: bool) {
+        self.doc_comments = enabled;
+    }
+
+    /// Declare `name` as an enum, so its impls get enum methods even when no
+    /// variant path in them says so (`format!("{:?}", self)`, say)
+    pub fn register_enum(&mut self, name: &str) {
+        self.enum_types.entry(name.to_string()).or_default();
+    }
+
+    /// Declare `name` as a tuple struct, so `Name(a, b)` becomes
+    /// `new Name(a, b)`; other capitalized callees (glob-imported enum
+    /// variants, say) stay plain calls
+    pub fn register_tuple_struct(&mut self, name: &str) {
+        self.tuple_structs.insert(name.to_string());
+    }
+
+    /// Emit `print!`/`eprint!` as writes to process.stdout/stderr (see
+    /// `CodegenOptions::print_to_stdout`)
+    pub fn set_print_to_stdout(&mut self, enabled: bool) {
+        self.print_to_stdout = enabled;
+    }
+
+    /// The span for the JS node generated from an item with these
+    /// attributes: one carrying the item's `///` comment when doc comments
+    /// are kept and it has any, else DUMMY_SP
+    fn doc_comment_span(&mut self, attrs: &[syn::Attribute]) -> Span {
+        let mut span = DUMMY_SP;
+        if self.doc_comments
+            && let Some(text) = doc_comment_text(attrs)
+        {
+            attach_comment(self, &mut span, GeneratedComment::own_line_block(&text), false);
+        }
+        span
+    }
+
+    /// Convert `expr` with untyped integer literals emitted as BigInt, when
+    /// the BigInt option is on
+    fn with_wide_int<T>(
+        &mut self,
+        wide: bool,
+        convert: impl FnOnce(&mut Self) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let saved = self.in_wide_int;
+        self.in_wide_int = saved || (wide && self.bigint_integers);
+        let result = convert(self);
+        self.in_wide_int = saved;
+        result
+    }
+
+    /// Convert an expression that becomes an IIFE. Unless `block_action`
+    /// returns its result, the IIFE is a value inside the function body.
+    fn in_value_iife<T>(&mut self, block_action: BlockAction, convert: impl FnOnce(&mut Self) -> T) -> T {
+        let counted = block_action != BlockAction::Return;
+        self.value_iife_depth += usize::from(counted);
+        let result = convert(self);
+        self.value_iife_depth -= usize::from(counted);
+        result
+    }
+
+    /// Convert the body of a function or closure, where `return` leaves
+    /// that body whatever IIFE its definition sits in
+    fn in_function_body<T>(&mut self, convert: impl FnOnce(&mut Self) -> T) -> T {
+        let saved = std::mem::take(&mut self.value_iife_depth);
+        let result = convert(self);
+        self.value_iife_depth = saved;
+        result
+    }
+
     /// Convert Pat to Param for function parameters
     pub fn pat_to_param(&self, pat: js::Pat) -> js::Param {
         js::Param {
@@ -186,11 +412,16 @@ impl TranspilerState {
                     type_ann: None,
                 },
             ))),
+            // Plain, computed (`a[i]`) and chained (`a[i].b[j]`) members
             js::Expr::Member(member) => Ok(js::AssignTarget::Simple(
                 js::SimpleAssignTarget::Member(member),
             )),
+            // `(*r) = v`, `(*self).field = v`: the parens are only grouping
+            js::Expr::Paren(paren) => self.expr_to_assign_target(*paren.expr),
             js::Expr::This(_) => Err("Cannot assign to 'this'".to_string()),
-            _ => panic!("Unsupported assignment target expression: {:?}", &expr),
+            _ => Err("invalid assignment target: only a variable, a field or an indexed \
+                element can be assigned to"
+                .to_string()),
         }
     }
     pub fn mk_ident_name(&self, name: &str) -> js::IdentName {
@@ -199,10 +430,26 @@ impl TranspilerState {
 
     pub fn enter_scope(&mut self) {
         self.scope_stack.push(HashMap::new());
+        self.type_scopes.push(HashMap::new());
     }
 
     pub fn exit_scope(&mut self) {
         self.scope_stack.pop();
+        self.type_scopes.pop();
+    }
+
+    /// Leave a scope whose JS declarations were emitted into the enclosing
+    /// JS scope: its Rust names go out of scope, but its JS names stay taken
+    /// so later declarations don't collide with them
+    fn exit_scope_keeping_js_names(&mut self) {
+        self.type_scopes.pop();
+        let Some(inner) = self.scope_stack.pop() else {
+            return;
+        };
+        if let Some(outer) = self.scope_stack.last_mut() {
+            // `{name}` is not a Rust identifier, so nothing resolves to these
+            outer.extend(inner.into_values().map(|js_name| (format!("{{{js_name}}}"), js_name)));
+        }
     }
 
     pub fn declare_variable(&mut self, rust_name: String, js_name: String, is_mutable: bool) -> String {
@@ -269,6 +516,34 @@ impl TranspilerState {
             .map(|info| info.js_name.clone())
     }
 
+    /// Record the Rust type of `rust_name` in the innermost scope. Passing
+    /// `None` shadows any typed binding of the same name further out.
+    pub fn declare_variable_type(&mut self, rust_name: &str, ty: Option<Type>) {
+        if let Some(current_scope) = self.type_scopes.last_mut() {
+            current_scope.insert(rust_name.to_string(), ty);
+        }
+    }
+
+    /// Record the declared return type of the function whose body is being
+    /// converted; call it in the scope around that body
+    pub fn declare_return_type(&mut self, ty: Option<Type>) {
+        self.declare_variable_type(RETURN_TYPE_KEY, ty);
+    }
+
+    /// The return type of the innermost function being converted, if known
+    pub fn return_type(&self) -> Option<&Type> {
+        self.variable_type(RETURN_TYPE_KEY)
+    }
+
+    /// The declared Rust type of the variable `rust_name`, if it is known
+    pub fn variable_type(&self, rust_name: &str) -> Option<&Type> {
+        self.type_scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(rust_name))
+            .and_then(|ty| ty.as_ref())
+    }
+
     pub fn add_error(&mut self, error_message: String) {
         self.errors.push(error_message);
     }
@@ -311,6 +586,32 @@ impl TranspilerState {
         }))
     }
 
+    /// A double-quoted string literal with control characters, quotes and
+    /// backslashes escaped; everything else (including non-ASCII text) is
+    /// emitted as-is.
+    pub fn mk_str_lit_escaped(&self, value: &str) -> js::Expr {
+        let mut raw = String::with_capacity(value.len() + 2);
+        raw.push('"');
+        for ch in value.chars() {
+            match ch {
+                '\\' => raw.push_str("\\\\"),
+                '"' => raw.push_str("\\\""),
+                '\n' => raw.push_str("\\n"),
+                '\r' => raw.push_str("\\r"),
+                '\t' => raw.push_str("\\t"),
+                '\u{2028}' | '\u{2029}' => raw.push_str(&format!("\\u{:04x}", ch as u32)),
+                c if c.is_control() => raw.push_str(&format!("\\u{:04x}", c as u32)),
+                c => raw.push(c),
+            }
+        }
+        raw.push('"');
+        js::Expr::Lit(js::Lit::Str(js::Str {
+            span: DUMMY_SP,
+            value: value.into(),
+            raw: Some(swc_atoms::Atom::new(raw)),
+        }))
+    }
+
     pub fn mk_str_lit_single_quote(&self, value: &str) -> js::Expr {
         js::Expr::Lit(js::Lit::Str(js::Str {
             span: DUMMY_SP,
@@ -346,7 +647,16 @@ impl TranspilerState {
         js::Expr::This(js::ThisExpr { span: DUMMY_SP })
     }
 
+    /// `obj.prop`. A number literal is parenthesized first, `(5).toString()`,
+    /// so the dot can't be read as part of the number.
     pub fn mk_member_expr(&self, obj: js::Expr, prop: &str) -> js::Expr {
+        let obj = match obj {
+            js::Expr::Lit(js::Lit::Num(_)) => js::Expr::Paren(js::ParenExpr {
+                span: DUMMY_SP,
+                expr: Box::new(obj),
+            }),
+            obj => obj,
+        };
         js::Expr::Member(js::MemberExpr {
             span: DUMMY_SP,
             obj: Box::new(obj),
@@ -375,16 +685,9 @@ impl TranspilerState {
         self.mk_iife_with_this_context(stmts)
     }
 
-    /// `((p1, p2, ...) => body)(arg1, arg2, ...)` - an immediately-invoked
-    /// arrow with an expression body, used for value-level dispatch (e.g.
-    /// Result-aware unwrap) without evaluating the receiver twice.
-    pub fn mk_arrow_iife(
-        &self,
-        params: &[&str],
-        body: js::Expr,
-        args: Vec<js::Expr>,
-    ) -> js::Expr {
-        let arrow = js::ArrowExpr {
+    /// `(p1, p2, ...) => body`, an arrow with an expression body
+    pub fn mk_arrow(&self, params: &[&str], body: js::Expr) -> js::Expr {
+        js::Expr::Arrow(js::ArrowExpr {
             span: DUMMY_SP,
             params: params
                 .iter()
@@ -401,12 +704,23 @@ impl TranspilerState {
             type_params: None,
             return_type: None,
             ctxt: SyntaxContext::empty(),
-        };
+        })
+    }
+
+    /// `((p1, p2, ...) => body)(arg1, arg2, ...)` - an immediately-invoked
+    /// arrow with an expression body, used for value-level dispatch (e.g.
+    /// Result-aware unwrap) without evaluating the receiver twice.
+    pub fn mk_arrow_iife(
+        &self,
+        params: &[&str],
+        body: js::Expr,
+        args: Vec<js::Expr>,
+    ) -> js::Expr {
         js::Expr::Call(js::CallExpr {
             span: DUMMY_SP,
             callee: js::Callee::Expr(Box::new(js::Expr::Paren(js::ParenExpr {
                 span: DUMMY_SP,
-                expr: Box::new(js::Expr::Arrow(arrow)),
+                expr: Box::new(self.mk_arrow(params, body)),
             }))),
             args: args
                 .into_iter()
@@ -561,7 +875,8 @@ impl TranspilerState {
             .into_iter()
             .enumerate()
             .map(|(i, part)| {
-                let escaped_part = part.replace('`', "\\`");
+                // A literal `${` would otherwise start an interpolation
+                let escaped_part = part.replace('`', "\\`").replace("${", "\\${");
                 js::TplElement {
                     span: DUMMY_SP,
                     tail: i == parts_len - 1,
@@ -579,12 +894,147 @@ impl TranspilerState {
     }
 }
 
-/// Generate JavaScript methods for a Rust impl block
+/// The text of the `/** */` JS comment for an item's `///` doc comments,
+/// between its `/*` and `*/`; `None` when it has none
+fn doc_comment_text(attrs: &[syn::Attribute]) -> Option<String> {
+    let mut lines: Vec<String> = attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                path,
+                value: Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(doc), .. }),
+                ..
+            }) if path.is_ident("doc") => Some(doc.value()),
+            _ => None,
+        })
+        .flat_map(|doc| doc.split('\n').map(str::to_string).collect::<Vec<_>>())
+        .map(|line| {
+            let line = line.strip_prefix(' ').unwrap_or(&line).trim_end();
+            // A `*/` in the text would end the JS comment early
+            line.replace("*/", "*\\/")
+        })
+        .collect();
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    let first = lines.iter().position(|line| !line.is_empty())?;
+    let lines = &lines[first..];
+    if let [line] = lines {
+        return Some(format!("* {line} "));
+    }
+    let body: Vec<String> = lines
+        .iter()
+        .map(|line| if line.is_empty() { " *".to_string() } else { format!(" * {line}") })
+        .collect();
+    Some(format!("*\n{}\n ", body.join("\n")))
+}
+
+/// Whether an item, field or statement is part of the JavaScript output.
+/// `#[js_ignore]` drops it, and so does a `#[cfg(..)]` that is false for the
+/// JS target, where `feature = "js"` is on and `target_arch` is `wasm32`
+/// (so `#[cfg(not(feature = "js"))]` marks server-only code). `#[mojes_only]`
+/// code is always kept; Rust-side, that attribute removes it from the native
+/// build instead. A cfg that mentions anything else keeps the item.
+fn included_in_js(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().all(|attr| {
+        if attr.path().is_ident("js_ignore") {
+            return false;
+        }
+        if attr.path().is_ident("cfg")
+            && let Ok(predicate) = attr.parse_args::<syn::Meta>()
+        {
+            return cfg_for_js(&predicate) != Some(false);
+        }
+        true
+    })
+}
+
+/// Evaluates a cfg predicate for the JS target; `None` when it depends on
+/// something the transpiler can't know.
+fn cfg_for_js(predicate: &syn::Meta) -> Option<bool> {
+    match predicate {
+        syn::Meta::NameValue(name_value) => {
+            let Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(value), .. }) = &name_value.value
+            else {
+                return None;
+            };
+            let value = value.value();
+            if name_value.path.is_ident("feature") {
+                (value == "js").then_some(true)
+            } else if name_value.path.is_ident("target_arch") {
+                Some(value == "wasm32")
+            } else {
+                None
+            }
+        }
+        syn::Meta::List(list) => {
+            let nested = list
+                .parse_args_with(Punctuated::<syn::Meta, Comma>::parse_terminated)
+                .ok()?;
+            let values: Vec<Option<bool>> = nested.iter().map(cfg_for_js).collect();
+            if list.path.is_ident("not") {
+                values.first().copied().flatten().map(|v| !v)
+            } else if list.path.is_ident("all") {
+                if values.contains(&Some(false)) {
+                    Some(false)
+                } else if values.iter().all(|v| *v == Some(true)) {
+                    Some(true)
+                } else {
+                    None
+                }
+            } else if list.path.is_ident("any") {
+                if values.contains(&Some(true)) {
+                    Some(true)
+                } else if values.iter().all(|v| *v == Some(false)) {
+                    Some(false)
+                } else {
+                    None
+                }
+            } else {
+                None
+            }
+        }
+        syn::Meta::Path(_) => None,
+    }
+}
+
+/// The outer attributes of a block statement, for `included_in_js`
+fn stmt_attrs(stmt: &Stmt) -> &[syn::Attribute] {
+    match stmt {
+        Stmt::Local(local) => &local.attrs,
+        Stmt::Macro(mac) => &mac.attrs,
+        Stmt::Item(syn::Item::Fn(item_fn)) => &item_fn.attrs,
+        Stmt::Item(syn::Item::Const(item_const)) => &item_const.attrs,
+        Stmt::Expr(expr, _) => match expr {
+            Expr::Block(e) => &e.attrs,
+            Expr::Call(e) => &e.attrs,
+            Expr::MethodCall(e) => &e.attrs,
+            Expr::Macro(e) => &e.attrs,
+            Expr::Assign(e) => &e.attrs,
+            Expr::If(e) => &e.attrs,
+            Expr::ForLoop(e) => &e.attrs,
+            Expr::While(e) => &e.attrs,
+            Expr::Loop(e) => &e.attrs,
+            Expr::Match(e) => &e.attrs,
+            _ => &[],
+        },
+        _ => &[],
+    }
+}
+
+/// Generate JavaScript methods for a Rust impl block. The comments the
+/// transpiler generates (the `// Methods for` header) live in a state that
+/// isn't returned, so the items carry none; `transpile_impl_to_js` keeps them.
 pub fn generate_js_methods_for_impl_with_state(
     input_impl: &ItemImpl,
 ) -> Result<Vec<js::ModuleItem>, String> {
-    let mut state = TranspilerState::new();
+    impl_to_module_items(input_impl, &mut TranspilerState::new())
+}
 
+fn impl_to_module_items(
+    input_impl: &ItemImpl,
+    state: &mut TranspilerState,
+) -> Result<Vec<js::ModuleItem>, String> {
     let struct_name = if let syn::Type::Path(type_path) = &*input_impl.self_ty {
         if let Some(segment) = type_path.path.segments.last() {
             segment.ident.to_string()
@@ -600,140 +1050,505 @@ pub fn generate_js_methods_for_impl_with_state(
 
     let mut js_items = Vec::new();
 
-    // Add header comment for the methods
-    let header_comment = format!("// Methods for {}", struct_name);
-    let comment_stmt = js::Stmt::Expr(js::ExprStmt {
-        span: DUMMY_SP,
-        expr: Box::new(js::Expr::Ident(js::Ident::new(
-            header_comment.into(),
-            DUMMY_SP,
-            SyntaxContext::empty(),
-        ))),
-    });
-    js_items.push(js::ModuleItem::Stmt(comment_stmt));
+    let trait_name = input_impl
+        .trait_
+        .as_ref()
+        .and_then(|(_, path, _)| path.segments.last())
+        .map(|segment| segment.ident.to_string());
+
+    let is_enum = state.enum_types.contains_key(&struct_name) || impl_is_for_enum(input_impl, &struct_name);
+    if is_enum {
+        let instance_methods: HashMap<String, String> = input_impl
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                ImplItem::Fn(method) if method.sig.receiver().is_some() && included_in_js(&method.attrs) => {
+                    let js_name = trait_method_js_name(trait_name.as_deref(), &method.sig.ident);
+                    Some((method.sig.ident.to_string(), js_name))
+                }
+                _ => None,
+            })
+            .collect();
+        state
+            .enum_types
+            .entry(struct_name.clone())
+            .or_default()
+            .extend(instance_methods.clone());
+        state.enum_methods = Some(instance_methods);
+    }
 
     for item in &input_impl.items {
-        if let ImplItem::Fn(method) = item {
-            match generate_js_method(&struct_name, method, &mut state) {
-                Ok(method_item) => js_items.push(method_item),
-                Err(e) => state.add_error(e),
+        match item {
+            ImplItem::Fn(method) if !included_in_js(&method.attrs) => {}
+            ImplItem::Fn(method) => {
+                let method_name = trait_method_js_name(trait_name.as_deref(), &method.sig.ident);
+                match generate_js_method(&struct_name, &method_name, method, state) {
+                    Ok(method_item) => js_items.push(method_item),
+                    Err(e) => state.add_error(e),
+                }
+            }
+            ImplItem::Const(item_const) if !included_in_js(&item_const.attrs) => {}
+            ImplItem::Const(item_const) => {
+                match generate_associated_const(&struct_name, item_const, state) {
+                    Ok(const_item) => js_items.push(const_item),
+                    Err(e) => state.add_error(e),
+                }
+            }
+            // Associated types (`type Item = u32;`) only matter to rustc
+            ImplItem::Type(_) => {}
+            _ => {}
+        }
+    }
+
+    // A Display impl or an inherent `to_string(&self)` also becomes the
+    // class's toString(), so `${value}` and `.to_string()` (which maps to
+    // `.toString()`) use it
+    let is_display = trait_name.as_deref() == Some("Display");
+    let to_string_source = input_impl.items.iter().find_map(|item| match item {
+        ImplItem::Fn(method) if included_in_js(&method.attrs) => {
+            let name = method.sig.ident.to_string();
+            let has_self = matches!(method.sig.inputs.first(), Some(FnArg::Receiver(_)));
+            let arity = method.sig.inputs.len();
+            match name.as_str() {
+                "fmt" if is_display && has_self && arity == 2 => Some(true),
+                "to_string" if has_self && arity == 1 => Some(false),
+                _ => None,
             }
         }
+        _ => None,
+    });
+    if let Some(via_fmt) = to_string_source
+        && !is_enum
+    {
+        js_items.push(generate_to_string_method(&struct_name, via_fmt, state)?);
+    }
+
+    // An Iterator impl makes instances iterable with for...of
+    let has_next = input_impl.items.iter().any(|item| {
+        matches!(item, ImplItem::Fn(method) if method.sig.ident == "next"
+            && matches!(method.sig.inputs.first(), Some(FnArg::Receiver(_)))
+            && included_in_js(&method.attrs))
+    });
+    if trait_name.as_deref() == Some("Iterator") && has_next && !is_enum {
+        js_items.push(generate_symbol_iterator_method(&struct_name, state)?);
     }
+    state.enum_methods = None;
 
     if state.has_errors() {
         return Err(format!("Transpilation errors: {:?}", state.get_errors()));
     }
 
+    // A header comment leads the methods; an impl without any gets an empty
+    // statement to carry it
+    let header_comment = match &trait_name {
+        Some(trait_name) => format!("Methods for {} (impl {})", struct_name, trait_name),
+        None => format!("Methods for {}", struct_name),
+    };
+    if js_items.is_empty() {
+        js_items.push(js::ModuleItem::Stmt(js::Stmt::Empty(js::EmptyStmt { span: DUMMY_SP })));
+    }
+    if let js::ModuleItem::Stmt(first) = &mut js_items[0]
+        && let Some(span) = stmt_span_mut(first)
+    {
+        attach_comment(state, span, GeneratedComment::line(&header_comment), true);
+    }
+
     Ok(js_items)
 }
 
-/// Generate JavaScript method for a single Rust method
-fn generate_js_method(
-    struct_name: &str,
-    method: &syn::ImplItemFn,
-    state: &mut TranspilerState,
-) -> Result<js::ModuleItem, String> {
-    let method_name = method.sig.ident.to_string();
-    let sig = &method.sig;
-
-    // Check if this is a static method (no self parameter)
-    let is_static = !sig
-        .inputs
-        .iter()
-        .any(|arg| matches!(arg, FnArg::Receiver(_)));
+/// Whether an impl block is for an enum rather than a struct. The impl
+/// doesn't say, so this looks for a path naming a variant of the type:
+/// `Self::Red` or `Color::Rgb(..)` in a `match` or `if let`, or a unit
+/// variant such as `Color::Red` used as a value. SCREAMING_CASE names are
+/// associated consts, which structs have too.
+fn impl_is_for_enum(input_impl: &ItemImpl, type_name: &str) -> bool {
+    use syn::visit::Visit;
+
+    struct VariantPatterns<'a> {
+        type_name: &'a str,
+        found: bool,
+    }
 
-    // Extract non-self parameters (don't register in scope yet - will be done in function body)
-    let params: Vec<js::Pat> = sig
-        .inputs
-        .iter()
-        .filter_map(|arg| {
-            match arg {
-                FnArg::Receiver(_) => None, // Skip self
-                FnArg::Typed(pat_type) => {
-                    if let Pat::Ident(pat_ident) = &*pat_type.pat {
-                        let param_name = pat_ident.ident.to_string();
-                        let js_param_name = escape_js_identifier(&param_name);
-                        
-                        Some(js::Pat::Ident(js::BindingIdent {
-                            id: state.mk_ident(&js_param_name),
-                            type_ann: None,
-                        }))
-                    } else {
-                        None
-                    }
+    impl VariantPatterns<'_> {
+        fn check(&mut self, path: &syn::Path) {
+            if path.segments.len() == 2
+                && (path.segments[0].ident == "Self" || path.segments[0].ident == self.type_name)
+            {
+                let variant = path.segments[1].ident.to_string();
+                if variant.starts_with(|c: char| c.is_ascii_uppercase())
+                    && variant.chars().any(|c| c.is_ascii_lowercase())
+                {
+                    self.found = true;
                 }
             }
-        })
-        .collect();
+        }
+    }
 
-    // Collect parameter information for scope registration
-    let param_info: Vec<(String, String)> = sig
-        .inputs
-        .iter()
-        .filter_map(|arg| {
-            match arg {
-                FnArg::Receiver(_) => None,
-                FnArg::Typed(pat_type) => {
-                    if let Pat::Ident(pat_ident) = &*pat_type.pat {
-                        let param_name = pat_ident.ident.to_string();
-                        let js_param_name = escape_js_identifier(&param_name);
-                        Some((param_name, js_param_name))
-                    } else {
-                        None
-                    }
-                }
+    impl<'ast> Visit<'ast> for VariantPatterns<'_> {
+        fn visit_pat(&mut self, pat: &'ast Pat) {
+            match pat {
+                Pat::TupleStruct(p) => self.check(&p.path),
+                Pat::Struct(p) => self.check(&p.path),
+                _ => {}
             }
-        })
-        .collect();
+            syn::visit::visit_pat(self, pat);
+        }
 
-    // Set static method context before converting method body
-    state.set_in_static_method(is_static);
-    // Convert method body to JavaScript with parameter registration
-    let body_stmts = rust_block_to_js_with_params_and_state(BlockAction::Return, &method.block, &param_info, state)?;
-    // Reset static method context after conversion
-    state.set_in_static_method(false);
-    let body = js::BlockStmt {
-        span: DUMMY_SP,
-        stmts: body_stmts,
-        ctxt: SyntaxContext::empty(),
+        // Unit variant patterns are path expressions as well
+        fn visit_expr_path(&mut self, expr: &'ast syn::ExprPath) {
+            self.check(&expr.path);
+        }
+    }
+
+    let mut visitor = VariantPatterns {
+        type_name,
+        found: false,
     };
+    visitor.visit_item_impl(input_impl);
+    visitor.found
+}
 
+/// `Struct.prototype[Symbol.iterator]` for an `impl Iterator`: the JS
+/// iterator's `next()` wraps the transpiled Rust `next`, whose None (null)
+/// ends the iteration:
+/// `function() { return { next: () => { const v = this.next();
+///     return v === null || v === undefined ? { value: undefined, done: true }
+///         : { value: v, done: false }; } }; }`
+fn generate_symbol_iterator_method(
+    struct_name: &str,
+    state: &mut TranspilerState,
+) -> Result<js::ModuleItem, String> {
+    let step = |value: js::Expr, done: bool| {
+        js::Expr::Object(js::ObjectLit {
+            span: DUMMY_SP,
+            props: [("value", value), ("done", state.mk_bool_lit(done))]
+                .into_iter()
+                .map(|(key, value)| {
+                    js::PropOrSpread::Prop(Box::new(js::Prop::KeyValue(js::KeyValueProp {
+                        key: js::PropName::Ident(state.mk_ident_name(key)),
+                        value: Box::new(value),
+                    })))
+                })
+                .collect(),
+        })
+    };
+    let rust_next = state.mk_call_expr(state.mk_member_expr(state.mk_this_expr(), "next"), vec![]);
+    let result = js::Expr::Cond(js::CondExpr {
+        span: DUMMY_SP,
+        test: Box::new(state.mk_nullish_check("v")),
+        cons: Box::new(step(state.mk_undefined(), true)),
+        alt: Box::new(step(js::Expr::Ident(state.mk_ident("v")), false)),
+    });
+    let js_next = js::Expr::Arrow(js::ArrowExpr {
+        span: DUMMY_SP,
+        params: vec![],
+        body: Box::new(js::BlockStmtOrExpr::BlockStmt(js::BlockStmt {
+            span: DUMMY_SP,
+            stmts: vec![
+                state.mk_var_decl("v", Some(rust_next), true),
+                state.mk_return_stmt(Some(result)),
+            ],
+            ctxt: SyntaxContext::empty(),
+        })),
+        is_async: false,
+        is_generator: false,
+        type_params: None,
+        return_type: None,
+        ctxt: SyntaxContext::empty(),
+    });
+    let iterator = js::Expr::Object(js::ObjectLit {
+        span: DUMMY_SP,
+        props: vec![js::PropOrSpread::Prop(Box::new(js::Prop::KeyValue(
+            js::KeyValueProp {
+                key: js::PropName::Ident(state.mk_ident_name("next")),
+                value: Box::new(js_next),
+            },
+        )))],
+    });
     let function = js::Function {
-        params: params.into_iter().map(|p| state.pat_to_param(p)).collect(),
+        params: vec![],
         decorators: vec![],
         span: DUMMY_SP,
-        body: Some(body),
+        body: Some(js::BlockStmt {
+            span: DUMMY_SP,
+            stmts: vec![state.mk_return_stmt(Some(iterator))],
+            ctxt: SyntaxContext::empty(),
+        }),
         is_generator: false,
-        is_async: method.sig.asyncness.is_some(),
+        is_async: false,
         type_params: None,
         return_type: None,
         ctxt: SyntaxContext::empty(),
     };
-
-    // Create the assignment statement
-    let target = if is_static {
-        // Static method: StructName.methodName
-        state.mk_member_expr(js::Expr::Ident(state.mk_ident(struct_name)), &method_name)
-    } else {
-        // Instance method: StructName.prototype.methodName
-        let prototype =
-            state.mk_member_expr(js::Expr::Ident(state.mk_ident(struct_name)), "prototype");
-        state.mk_member_expr(prototype, &method_name)
-    };
-
-    let assignment = js::Expr::Assign(js::AssignExpr {
+    let prototype = state.mk_member_expr(js::Expr::Ident(state.mk_ident(struct_name)), "prototype");
+    let target = js::Expr::Member(js::MemberExpr {
         span: DUMMY_SP,
-        op: js::AssignOp::Assign,
+        obj: Box::new(prototype),
+        prop: js::MemberProp::Computed(js::ComputedPropName {
+            span: DUMMY_SP,
+            expr: Box::new(state.mk_member_expr(
+                js::Expr::Ident(state.mk_ident("Symbol")),
+                "iterator",
+            )),
+        }),
+    });
+    let assignment = js::Expr::Assign(js::AssignExpr {
+        span: DUMMY_SP,
+        op: js::AssignOp::Assign,
         left: state.expr_to_assign_target(target)?,
         right: Box::new(js::Expr::Fn(js::FnExpr {
             ident: None,
             function: Box::new(function),
         })),
     });
+    Ok(js::ModuleItem::Stmt(state.mk_expr_stmt(assignment)))
+}
 
-    Ok(js::ModuleItem::Stmt(js::Stmt::Expr(js::ExprStmt {
+/// `Struct.NAME = value;` for an associated const, so `Self::NAME` and
+/// `Struct::NAME` (which become `Struct.NAME`) find it on the class.
+fn generate_associated_const(
+    struct_name: &str,
+    item_const: &syn::ImplItemConst,
+    state: &mut TranspilerState,
+) -> Result<js::ModuleItem, String> {
+    let value = rust_expr_to_js_with_action_and_state(
+        BlockAction::NoReturn,
+        &item_const.expr,
+        state,
+    )?;
+    let target = state.mk_member_expr(
+        js::Expr::Ident(state.mk_ident(struct_name)),
+        &item_const.ident.to_string(),
+    );
+    let assignment = js::Expr::Assign(js::AssignExpr {
+        span: DUMMY_SP,
+        op: js::AssignOp::Assign,
+        left: state.expr_to_assign_target(target)?,
+        right: Box::new(value),
+    });
+    Ok(js::ModuleItem::Stmt(state.mk_expr_stmt(assignment)))
+}
+
+/// `Struct.prototype.toString = function() { ... }` delegating to the
+/// transpiled `to_string`, or for a Display impl to `fmt` with a formatter
+/// whose `write_str` pushes each piece onto an array that is then joined.
+fn generate_to_string_method(
+    struct_name: &str,
+    via_fmt: bool,
+    state: &mut TranspilerState,
+) -> Result<js::ModuleItem, String> {
+    let this_call = |state: &TranspilerState, method: &str, args| {
+        state.mk_call_expr(state.mk_member_expr(state.mk_this_expr(), method), args)
+    };
+    let stmts = if via_fmt {
+        let out = || js::Expr::Ident(state.mk_ident("out"));
+        let push = state.mk_call_expr(
+            state.mk_member_expr(out(), "push"),
+            vec![js::Expr::Ident(state.mk_ident("s"))],
+        );
+        let ok_unit = js::Expr::Object(js::ObjectLit {
+            span: DUMMY_SP,
+            props: vec![js::PropOrSpread::Prop(Box::new(js::Prop::KeyValue(
+                js::KeyValueProp {
+                    key: js::PropName::Ident(state.mk_ident_name("ok")),
+                    value: Box::new(state.mk_null_lit()),
+                },
+            )))],
+        });
+        let write_str = js::Expr::Arrow(js::ArrowExpr {
+            span: DUMMY_SP,
+            params: vec![js::Pat::Ident(js::BindingIdent {
+                id: state.mk_ident("s"),
+                type_ann: None,
+            })],
+            body: Box::new(js::BlockStmtOrExpr::BlockStmt(js::BlockStmt {
+                span: DUMMY_SP,
+                stmts: vec![state.mk_expr_stmt(push), state.mk_return_stmt(Some(ok_unit))],
+                ctxt: SyntaxContext::empty(),
+            })),
+            is_async: false,
+            is_generator: false,
+            type_params: None,
+            return_type: None,
+            ctxt: SyntaxContext::empty(),
+        });
+        let formatter = js::Expr::Object(js::ObjectLit {
+            span: DUMMY_SP,
+            props: vec![js::PropOrSpread::Prop(Box::new(js::Prop::KeyValue(
+                js::KeyValueProp {
+                    key: js::PropName::Ident(state.mk_ident_name("write_str")),
+                    value: Box::new(write_str),
+                },
+            )))],
+        });
+        vec![
+            state.mk_var_decl(
+                "out",
+                Some(js::Expr::Array(js::ArrayLit {
+                    span: DUMMY_SP,
+                    elems: vec![],
+                })),
+                true,
+            ),
+            state.mk_expr_stmt(this_call(state, "fmt", vec![formatter])),
+            state.mk_return_stmt(Some(state.mk_call_expr(
+                state.mk_member_expr(out(), "join"),
+                vec![state.mk_str_lit("")],
+            ))),
+        ]
+    } else {
+        vec![state.mk_return_stmt(Some(this_call(state, "to_string", vec![])))]
+    };
+
+    let function = js::Function {
+        params: vec![],
+        decorators: vec![],
+        span: DUMMY_SP,
+        body: Some(js::BlockStmt {
+            span: DUMMY_SP,
+            stmts,
+            ctxt: SyntaxContext::empty(),
+        }),
+        is_generator: false,
+        is_async: false,
+        type_params: None,
+        return_type: None,
+        ctxt: SyntaxContext::empty(),
+    };
+    let prototype = state.mk_member_expr(js::Expr::Ident(state.mk_ident(struct_name)), "prototype");
+    let assignment = js::Expr::Assign(js::AssignExpr {
+        span: DUMMY_SP,
+        op: js::AssignOp::Assign,
+        left: state.expr_to_assign_target(state.mk_member_expr(prototype, "toString"))?,
+        right: Box::new(js::Expr::Fn(js::FnExpr {
+            ident: None,
+            function: Box::new(function),
+        })),
+    });
+    Ok(js::ModuleItem::Stmt(state.mk_expr_stmt(assignment)))
+}
+
+/// The prototype name for a method of an impl block. Trait methods keep
+/// their own name and share the prototype with inherent ones, except `fmt`
+/// from the formatting traits other than Display: a type usually implements
+/// several of them, so `Debug::fmt` becomes `fmt_debug`, `LowerHex::fmt`
+/// `fmt_lower_hex`, etc., leaving `fmt` (and toString) to Display.
+fn trait_method_js_name(trait_name: Option<&str>, method: &syn::Ident) -> String {
+    let method_name = method.to_string();
+    match trait_name {
+        Some(
+            trait_name @ ("Debug" | "LowerHex" | "UpperHex" | "Binary" | "Octal" | "LowerExp"
+            | "UpperExp" | "Pointer"),
+        ) if method_name == "fmt" => {
+            let mut snake = String::new();
+            for ch in trait_name.chars() {
+                if ch.is_ascii_uppercase() {
+                    snake.push('_');
+                }
+                snake.push(ch.to_ascii_lowercase());
+            }
+            format!("fmt{}", snake)
+        }
+        _ => method_name,
+    }
+}
+
+/// Generate JavaScript method for a single Rust method
+fn generate_js_method(
+    struct_name: &str,
+    method_name: &str,
+    method: &syn::ImplItemFn,
+    state: &mut TranspilerState,
+) -> Result<js::ModuleItem, String> {
+    let sig = &method.sig;
+
+    // Check if this is a static method (no self parameter)
+    let receiver = sig.receiver().map(ReceiverKind::of);
+    let is_static = receiver.is_none();
+
+    // A method consuming `self` without handing it back (unlike a builder's
+    // `-> Self`) can still be called again in JS
+    let returns_self = match &sig.output {
+        syn::ReturnType::Type(_, ty) => {
+            matches!(&**ty, Type::Path(p) if p.path.is_ident("Self") || p.path.is_ident(struct_name))
+        }
+        syn::ReturnType::Default => false,
+    };
+    if receiver == Some(ReceiverKind::RefMut) && state.enum_methods.is_some() {
+        state.add_warning(format!(
+            "`{}::{}` takes `&mut self`; assigning to `*self` only rebinds the JavaScript parameter",
+            struct_name, sig.ident
+        ));
+    } else if receiver == Some(ReceiverKind::Value) && !returns_self {
+        state.add_warning(format!(
+            "`{}::{}` takes `self` by value; the JavaScript instance stays usable after the call",
+            struct_name, sig.ident
+        ));
+    }
+
+    // Set static method context before converting method body
+    state.set_in_static_method(is_static);
+    state.set_current_receiver(receiver);
+    // The parameters and their types live in a scope around the body, and
+    // the signature uses the names they were declared under there
+    state.enter_scope();
+    declare_param_types(sig, state);
+    let mut params = js_params_for_signature(sig, state);
+    if state.enum_methods.is_some() && !is_static {
+        params.insert(0, js::Pat::Ident(js::BindingIdent {
+            id: state.mk_ident("self"),
+            type_ann: None,
+        }));
+    }
+    let body_stmts = state.in_function_body(|state| {
+        rust_block_to_js_with_params_and_state(BlockAction::Return, &method.block, &[], state)
+    });
+    state.exit_scope();
+    let body_stmts = body_stmts?;
+    // Reset static method context after conversion
+    state.set_in_static_method(false);
+    state.set_current_receiver(None);
+    let body = js::BlockStmt {
+        span: DUMMY_SP,
+        stmts: body_stmts,
+        ctxt: SyntaxContext::empty(),
+    };
+
+    let function = js::Function {
+        params: params.into_iter().map(|p| state.pat_to_param(p)).collect(),
+        decorators: vec![],
+        span: DUMMY_SP,
+        body: Some(body),
+        is_generator: false,
+        is_async: method.sig.asyncness.is_some(),
+        type_params: None,
+        return_type: None,
+        ctxt: SyntaxContext::empty(),
+    };
+
+    // Create the assignment statement
+    let target = if is_static || state.enum_methods.is_some() {
+        // Static method, or any method of an enum: StructName.methodName
+        state.mk_member_expr(js::Expr::Ident(state.mk_ident(struct_name)), method_name)
+    } else {
+        // Instance method: StructName.prototype.methodName
+        let prototype =
+            state.mk_member_expr(js::Expr::Ident(state.mk_ident(struct_name)), "prototype");
+        state.mk_member_expr(prototype, method_name)
+    };
+
+    let assignment = js::Expr::Assign(js::AssignExpr {
         span: DUMMY_SP,
+        op: js::AssignOp::Assign,
+        left: state.expr_to_assign_target(target)?,
+        right: Box::new(js::Expr::Fn(js::FnExpr {
+            ident: None,
+            function: Box::new(function),
+        })),
+    });
+
+    Ok(js::ModuleItem::Stmt(js::Stmt::Expr(js::ExprStmt {
+        span: state.doc_comment_span(&method.attrs),
         expr: Box::new(assignment),
     })))
 }
@@ -900,6 +1715,15 @@ fn handle_if_let_as_stmt(
     if_expr: &syn::ExprIf,
     state: &mut TranspilerState,
 ) -> Result<Option<js::Stmt>, String> {
+    if let Some(conditions) = let_chain_conditions(&if_expr.cond) {
+        return Ok(Some(convert_let_chain_to_stmt(
+            block_action,
+            &conditions,
+            if_expr,
+            state,
+        )?));
+    }
+
     // Check if this is an "if let" expression by examining the condition
     if let Expr::Let(expr_let) = &*if_expr.cond {
         let pat = &expr_let.pat;
@@ -933,6 +1757,143 @@ fn handle_if_let_as_stmt(
     Ok(None)
 }
 
+/// The operands of an `&&` chain in an `if` condition, left to right, when
+/// at least one of them is a `let` (`if let Some(a) = x && let Some(b) = y`)
+fn let_chain_conditions(cond: &Expr) -> Option<Vec<&Expr>> {
+    fn flatten<'a>(expr: &'a Expr, out: &mut Vec<&'a Expr>) {
+        match expr {
+            Expr::Binary(bin) if matches!(bin.op, syn::BinOp::And(_)) => {
+                flatten(&bin.left, out);
+                flatten(&bin.right, out);
+            }
+            _ => out.push(expr),
+        }
+    }
+    if !matches!(cond, Expr::Binary(bin) if matches!(bin.op, syn::BinOp::And(_))) {
+        return None;
+    }
+    let mut conditions = Vec::new();
+    flatten(cond, &mut conditions);
+    conditions
+        .iter()
+        .any(|c| matches!(c, Expr::Let(_)))
+        .then_some(conditions)
+}
+
+/// Convert an `if` whose condition is a let-chain into nested `if`s, one per
+/// operand, each `let` caching its value in a temp and binding its pattern
+/// for everything after it. A flag set in the innermost branch decides
+/// whether the `else` runs, so it is emitted only once:
+///
+/// ```js
+/// { let _temp1 = false; const _temp2 = x;
+///   if (_temp2 !== null && _temp2 !== undefined) { const a = _temp2; ...
+///       _temp1 = true; then } ...
+///   if (!_temp1) { else } }
+/// ```
+fn convert_let_chain_to_stmt(
+    block_action: BlockAction,
+    conditions: &[&Expr],
+    if_expr: &syn::ExprIf,
+    state: &mut TranspilerState,
+) -> Result<js::Stmt, String> {
+    let matched_var = if_expr
+        .else_branch
+        .is_some()
+        .then(|| state.generate_temp_var());
+
+    state.enter_scope();
+    // (statements before the test, the test, bindings inside the branch)
+    let mut levels = Vec::new();
+    for condition in conditions {
+        match condition {
+            Expr::Let(expr_let) => {
+                let matched_expr = rust_expr_to_js_with_state(&expr_let.expr, state)?;
+                let temp_var = state.generate_temp_var();
+                let temp_decl = state.mk_var_decl(&temp_var, Some(matched_expr), true);
+                let (test, bindings) = handle_pattern_binding(&expr_let.pat, &temp_var, state)?;
+                levels.push((vec![temp_decl], test, bindings));
+            }
+            _ => {
+                let test = rust_expr_to_js_with_state(condition, state)?;
+                levels.push((Vec::new(), test, Vec::new()));
+            }
+        }
+    }
+    let mut body = Vec::new();
+    if let Some(matched_var) = &matched_var {
+        body.push(state.mk_expr_stmt(js::Expr::Assign(js::AssignExpr {
+            span: DUMMY_SP,
+            op: js::AssignOp::Assign,
+            left: js::AssignTarget::Simple(js::SimpleAssignTarget::Ident(
+                state.mk_ident(matched_var).into(),
+            )),
+            right: Box::new(state.mk_bool_lit(true)),
+        })));
+    }
+    body.extend(rust_block_to_js_with_state(
+        block_action,
+        &if_expr.then_branch,
+        state,
+    )?);
+    state.exit_scope();
+
+    for (mut prefix, test, mut bindings) in levels.into_iter().rev() {
+        bindings.extend(body);
+        prefix.push(js::Stmt::If(js::IfStmt {
+            span: DUMMY_SP,
+            test: Box::new(test),
+            cons: Box::new(js::Stmt::Block(js::BlockStmt {
+                span: DUMMY_SP,
+                stmts: bindings,
+                ctxt: SyntaxContext::empty(),
+            })),
+            alt: None,
+        }));
+        body = prefix;
+    }
+
+    let mut stmts = Vec::new();
+    if let (Some(matched_var), Some((_, else_branch))) = (&matched_var, &if_expr.else_branch) {
+        stmts.push(state.mk_var_decl(matched_var, Some(state.mk_bool_lit(false)), false));
+        stmts.extend(body);
+        let else_stmt = match &**else_branch {
+            Expr::Block(else_block) => js::Stmt::Block(js::BlockStmt {
+                span: DUMMY_SP,
+                stmts: rust_block_to_js_with_state(block_action, &else_block.block, state)?,
+                ctxt: SyntaxContext::empty(),
+            }),
+            Expr::If(nested_if) => convert_if_to_stmt(block_action, nested_if, state)?,
+            _ => {
+                let else_expr = rust_expr_to_js_with_state(else_branch, state)?;
+                js::Stmt::Block(js::BlockStmt {
+                    span: DUMMY_SP,
+                    stmts: vec![state.mk_expr_stmt(else_expr)],
+                    ctxt: SyntaxContext::empty(),
+                })
+            }
+        };
+        stmts.push(js::Stmt::If(js::IfStmt {
+            span: DUMMY_SP,
+            test: Box::new(js::Expr::Unary(js::UnaryExpr {
+                span: DUMMY_SP,
+                op: js::UnaryOp::Bang,
+                arg: Box::new(js::Expr::Ident(state.mk_ident(matched_var))),
+            })),
+            cons: Box::new(else_stmt),
+            alt: None,
+        }));
+    } else {
+        stmts.extend(body);
+    }
+
+    Ok(js::Stmt::Block(js::BlockStmt {
+        span: DUMMY_SP,
+        stmts,
+        ctxt: SyntaxContext::empty(),
+    }))
+}
+
 /// Convert "if let Some(x) = expr" to optimized JavaScript statement with caching
 fn convert_if_let_some_to_stmt(
     block_action: BlockAction,
@@ -976,10 +1937,10 @@ fn convert_if_let_some_to_stmt(
             Pat::Ident(pat_ident) => {
                 let var_name = pat_ident.ident.to_string();
                 let js_var_name = escape_js_identifier(&var_name);
-                let unique_js_var_name = state.declare_variable(var_name, js_var_name, false);
+                let unique_js_var_name = state.declare_variable(var_name, js_var_name, pat_ident.mutability.is_some());
 
                 // Add: const x = temp_var; (use cached result)
-                consequent_stmts.push(state.mk_var_decl(&unique_js_var_name, Some(temp_var_ref3), true));
+                consequent_stmts.push(state.mk_var_decl(&unique_js_var_name, Some(temp_var_ref3), pat_ident.mutability.is_none()));
             }
             Pat::Tuple(pat_tuple) => {
                 // Handle tuple destructuring: Some((a, b)) pattern
@@ -989,7 +1950,7 @@ fn convert_if_let_some_to_stmt(
                     if let Pat::Ident(pat_ident) = elem_pat {
                         let var_name = pat_ident.ident.to_string();
                         let js_var_name = escape_js_identifier(&var_name);
-                        let unique_js_var_name = state.declare_variable(var_name.clone(), js_var_name, false);
+                        let unique_js_var_name = state.declare_variable(var_name.clone(), js_var_name, pat_ident.mutability.is_some());
                         
                         // Create: const var = temp_var[index];
                         let array_access = js::Expr::Member(js::MemberExpr {
@@ -1001,7 +1962,7 @@ fn convert_if_let_some_to_stmt(
                             }),
                         });
                         
-                        consequent_stmts.push(state.mk_var_decl(&unique_js_var_name, Some(array_access), true));
+                        consequent_stmts.push(state.mk_var_decl(&unique_js_var_name, Some(array_access), pat_ident.mutability.is_none()));
                     }
                 }
             }
@@ -1141,10 +2102,14 @@ fn convert_generic_if_let_to_stmt(
 
 /// Modified handle_if_expr that reuses the core logic
 fn handle_if_expr(
-    block_action: BlockAction,
     if_expr: &syn::ExprIf,
     state: &mut TranspilerState,
 ) -> Result<js::Expr, String> {
+    // An if in value position always yields its branch values, whatever the
+    // enclosing block does with its own tail: the branches must return (or
+    // assign _rust_retval), never just evaluate their tail expression.
+    let block_action = BlockAction::Return;
+
     // Check if this is an if-let expression that should be handled specially
     if let Some(if_let_expr) = handle_if_let_as_expr(block_action, if_expr, state)? {
         return Ok(if_let_expr);
@@ -1165,49 +2130,210 @@ fn handle_if_expr(
     
     // Wrap in IIFE with retval declaration, if statement, and return
     let stmts = vec![retval_decl, if_stmt, return_stmt];
-    
+
     Ok(state.mk_iife(stmts))
 }
 
-/// Handle if-let expressions that need to return values (expression context)
-fn handle_if_let_as_expr(
+/// Convert the initializer of a `let` to JavaScript. An `if` or `match`
+/// whose branches are all single expressions becomes a ternary
+/// (`const x = c ? 1 : 2;`); everything else takes the regular path.
+fn local_init_to_js(
     block_action: BlockAction,
-    if_expr: &syn::ExprIf,
+    init: &Expr,
     state: &mut TranspilerState,
-) -> Result<Option<js::Expr>, String> {
-    if let Expr::Let(expr_let) = &*if_expr.cond {
-        let pat = &expr_let.pat;
-        let init_expr = &expr_let.expr;
+) -> Result<js::Expr, String> {
+    let ternary = match init {
+        Expr::If(if_expr) => try_convert_if_to_ternary(if_expr, state)?,
+        Expr::Match(match_expr) => try_convert_match_to_ternary(match_expr, state)?,
+        _ => None,
+    };
+    match ternary {
+        Some(expr) => Ok(expr),
+        // The initializer is a value, whatever the enclosing block returns
+        None => state.in_value_iife(BlockAction::NoReturn, |state| {
+            rust_expr_to_js_with_action_and_state(block_action, init, state)
+        }),
+    }
+}
 
-        // Handle "if let Some(x) = ..." pattern in expression context
-        if let Pat::TupleStruct(tuple_struct) = &**pat {
-            if let Some(last_segment) = tuple_struct.path.segments.last() {
-                if last_segment.ident == "Some" && !tuple_struct.elems.is_empty() {
-                    return Ok(Some(convert_if_let_some_to_expr(
-                        block_action,
-                        tuple_struct,
-                        init_expr,
-                        if_expr,
-                        state,
-                    )?));
-                }
+/// The lone tail expression of a branch, if the branch is nothing more than
+/// that. Control flow (`return`, `break`, ...) has no expression form, so a
+/// branch ending in it does not qualify.
+fn single_branch_expr(expr: &Expr) -> Option<&Expr> {
+    let inner = match expr {
+        Expr::Block(block_expr) if block_expr.label.is_none() => {
+            match block_expr.block.stmts.as_slice() {
+                [Stmt::Expr(tail, None)] => tail,
+                _ => return None,
             }
         }
+        _ => expr,
+    };
+    match inner {
+        Expr::Return(_) | Expr::Break(_) | Expr::Continue(_) | Expr::Let(_) => None,
+        Expr::Block(_) => single_branch_expr(inner),
+        _ => Some(inner),
     }
+}
 
-    Ok(None)
+/// Whether an if/else-if chain can be written as nested ternaries: every
+/// condition is a plain boolean (no `if let`), there is a final `else`, and
+/// every branch is a single expression.
+fn if_is_ternary_candidate(if_expr: &syn::ExprIf) -> bool {
+    if matches!(&*if_expr.cond, Expr::Let(_)) || let_chain_conditions(&if_expr.cond).is_some() {
+        return false;
+    }
+    let then_ok = matches!(
+        if_expr.then_branch.stmts.as_slice(),
+        [Stmt::Expr(tail, None)] if single_branch_expr(tail).is_some()
+    );
+    let else_ok = match &if_expr.else_branch {
+        Some((_, else_branch)) => match &**else_branch {
+            Expr::If(nested_if) => if_is_ternary_candidate(nested_if),
+            other => single_branch_expr(other).is_some(),
+        },
+        None => false,
+    };
+    then_ok && else_ok
 }
 
-/// Convert "if let Some(x) = expr" to IIFE expression (like legacy version)
-fn convert_if_let_some_to_expr(
-    block_action: BlockAction,
-    tuple_struct: &syn::PatTupleStruct,
-    init_expr: &Expr,
+fn try_convert_if_to_ternary(
     if_expr: &syn::ExprIf,
     state: &mut TranspilerState,
-) -> Result<js::Expr, String> {
-    let matched_expr = rust_expr_to_js_with_state(init_expr, state)?;
-    let temp_var = "_temp";
+) -> Result<Option<js::Expr>, String> {
+    if !if_is_ternary_candidate(if_expr) {
+        return Ok(None);
+    }
+    convert_if_to_ternary(if_expr, state).map(Some)
+}
+
+fn convert_if_to_ternary(
+    if_expr: &syn::ExprIf,
+    state: &mut TranspilerState,
+) -> Result<js::Expr, String> {
+    let test = rust_expr_to_js_with_state(&if_expr.cond, state)?;
+    let then_tail = match if_expr.then_branch.stmts.as_slice() {
+        [Stmt::Expr(tail, None)] => single_branch_expr(tail),
+        _ => None,
+    }
+    .ok_or_else(|| "if branch is not a single expression".to_string())?;
+    let cons = rust_expr_to_js_with_state(then_tail, state)?;
+
+    let alt = match &if_expr.else_branch {
+        Some((_, else_branch)) => match &**else_branch {
+            Expr::If(nested_if) => convert_if_to_ternary(nested_if, state)?,
+            other => {
+                let else_tail = single_branch_expr(other)
+                    .ok_or_else(|| "else branch is not a single expression".to_string())?;
+                rust_expr_to_js_with_state(else_tail, state)?
+            }
+        },
+        None => return Err("if expression without else has no value".to_string()),
+    };
+
+    Ok(js::Expr::Cond(js::CondExpr {
+        span: DUMMY_SP,
+        test: Box::new(test),
+        cons: Box::new(cons),
+        alt: Box::new(alt),
+    }))
+}
+
+/// Whether a match arm pattern can be tested without binding anything.
+fn pattern_binds_nothing(pat: &Pat) -> bool {
+    match pat {
+        Pat::Lit(_) | Pat::Wild(_) | Pat::Path(_) | Pat::Range(_) => true,
+        Pat::Or(or_pat) => or_pat.cases.iter().all(pattern_binds_nothing),
+        _ => false,
+    }
+}
+
+/// A `match` on a plain variable whose arms bind nothing and whose bodies
+/// are single expressions becomes a ternary chain testing the variable
+/// directly. The last arm is the final `else`, relying on Rust's
+/// exhaustiveness check.
+fn try_convert_match_to_ternary(
+    match_expr: &syn::ExprMatch,
+    state: &mut TranspilerState,
+) -> Result<Option<js::Expr>, String> {
+    let scrutinee_is_var = matches!(
+        &*match_expr.expr,
+        Expr::Path(path) if path.qself.is_none() && path.path.get_ident().is_some()
+    );
+    let arms_ok = !match_expr.arms.is_empty()
+        && match_expr.arms.iter().all(|arm| {
+            arm.guard.is_none()
+                && pattern_binds_nothing(&arm.pat)
+                && single_branch_expr(&arm.body).is_some()
+        });
+    if !scrutinee_is_var || !arms_ok {
+        return Ok(None);
+    }
+
+    let match_var = match rust_expr_to_js_with_state(&match_expr.expr, state)? {
+        js::Expr::Ident(ident) => ident.sym.to_string(),
+        _ => return Ok(None),
+    };
+
+    let (last_arm, leading_arms) = match_expr.arms.split_last().unwrap();
+    let mut branches = Vec::new();
+    for arm in leading_arms {
+        let (test, _) = handle_pattern_binding(&arm.pat, &match_var, state)?;
+        let cons = rust_expr_to_js_with_state(single_branch_expr(&arm.body).unwrap(), state)?;
+        branches.push((test, cons));
+    }
+    let mut result =
+        rust_expr_to_js_with_state(single_branch_expr(&last_arm.body).unwrap(), state)?;
+    for (test, cons) in branches.into_iter().rev() {
+        result = js::Expr::Cond(js::CondExpr {
+            span: DUMMY_SP,
+            test: Box::new(test),
+            cons: Box::new(cons),
+            alt: Box::new(result),
+        });
+    }
+    Ok(Some(result))
+}
+
+/// Handle if-let expressions that need to return values (expression context)
+fn handle_if_let_as_expr(
+    block_action: BlockAction,
+    if_expr: &syn::ExprIf,
+    state: &mut TranspilerState,
+) -> Result<Option<js::Expr>, String> {
+    if let Expr::Let(expr_let) = &*if_expr.cond {
+        let pat = &expr_let.pat;
+        let init_expr = &expr_let.expr;
+
+        // Handle "if let Some(x) = ..." pattern in expression context
+        if let Pat::TupleStruct(tuple_struct) = &**pat {
+            if let Some(last_segment) = tuple_struct.path.segments.last() {
+                if last_segment.ident == "Some" && !tuple_struct.elems.is_empty() {
+                    return Ok(Some(convert_if_let_some_to_expr(
+                        block_action,
+                        tuple_struct,
+                        init_expr,
+                        if_expr,
+                        state,
+                    )?));
+                }
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Convert "if let Some(x) = expr" to IIFE expression (like legacy version)
+fn convert_if_let_some_to_expr(
+    block_action: BlockAction,
+    tuple_struct: &syn::PatTupleStruct,
+    init_expr: &Expr,
+    if_expr: &syn::ExprIf,
+    state: &mut TranspilerState,
+) -> Result<js::Expr, String> {
+    let matched_expr = rust_expr_to_js_with_state(init_expr, state)?;
+    let temp_var = "_temp";
 
     let mut stmts = vec![state.mk_var_decl(temp_var, Some(matched_expr), true)];
 
@@ -1230,12 +2356,12 @@ fn convert_if_let_some_to_expr(
         if let Pat::Ident(pat_ident) = inner_pat {
             let var_name = pat_ident.ident.to_string();
             let js_var_name = escape_js_identifier(&var_name);
-            let js_var_name = state.declare_variable(var_name, js_var_name.clone(), false);
+            let js_var_name = state.declare_variable(var_name, js_var_name.clone(), pat_ident.mutability.is_some());
 
             then_stmts.push(state.mk_var_decl(
                 &js_var_name,
                 Some(js::Expr::Ident(state.mk_ident(temp_var))),
-                true,
+                pat_ident.mutability.is_none(),
             ));
         }
     }
@@ -1323,20 +2449,26 @@ pub fn rust_block_to_js_with_params_and_state(
     }
 
     for stmt in &block.stmts {
+        if !included_in_js(stmt_attrs(stmt)) {
+            continue;
+        }
         // Wrap each statement so any transpile error names the exact Rust
         // source that failed ("in `<stmt>`: <reason>").
         let stmt_ctx = (|| -> Result<(), String> {
         match stmt {
             Stmt::Local(local) => {
                 debug_print!("DEBUG BLOCK LOCAL: {:?}", &local);
-                let js_stmt = handle_local_statement(block_action, local, state)?;
-                js_stmts.push(js_stmt);
+                js_stmts.extend(handle_local_with_try(block_action, local, state)?);
             }
             Stmt::Item(item) => match item {
                 syn::Item::Fn(item_fn) => {
                     let js_stmt = handle_function_definition(item_fn, state)?;
                     js_stmts.push(js_stmt);
                 }
+                syn::Item::Const(item_const) => {
+                    let js_stmt = handle_const_item(item_const, state)?;
+                    js_stmts.push(js_stmt);
+                }
                 syn::Item::Struct(_) => {
                     return Err("nested `struct` definitions inside a function body are not \
                         supported; move the struct to module scope and annotate it with \
@@ -1382,6 +2514,27 @@ pub fn rust_block_to_js_with_params_and_state(
                         debug_print!("DEBUG IFIN BLOCK: {:?}", if_stmt);
                         js_stmts.push(if_stmt);
                     }
+                    Expr::Match(match_expr)
+                        if semi.is_some()
+                            || block_action == BlockAction::NoReturn
+                            || stmt != block.stmts.last().unwrap() =>
+                    {
+                        // Its value is unused, so the arms run as plain
+                        // statements rather than returning from an IIFE
+                        js_stmts.push(convert_match_to_stmt(match_expr, state)?);
+                    }
+                    Expr::Block(block_expr) if block_expr.label.is_none() => {
+                        // A bare `{ ... }` statement is a lexical JS block, not
+                        // an IIFE, so a `return` inside it leaves the function
+                        let is_tail = semi.is_none() && stmt == block.stmts.last().unwrap();
+                        let inner_action = if is_tail { block_action } else { BlockAction::NoReturn };
+                        let stmts = rust_block_to_js_with_state(inner_action, &block_expr.block, state)?;
+                        js_stmts.push(js::Stmt::Block(js::BlockStmt {
+                            span: DUMMY_SP,
+                            stmts,
+                            ctxt: SyntaxContext::empty(),
+                        }));
+                    }
                     Expr::Break(break_expr) => {
                         // Generate break statement directly
                         if let Some(value) = &break_expr.expr {
@@ -1467,20 +2620,26 @@ pub fn rust_block_to_js_with_params_and_retval(
     }
 
     for stmt in &block.stmts {
+        if !included_in_js(stmt_attrs(stmt)) {
+            continue;
+        }
         // Wrap each statement so any transpile error names the exact Rust
         // source that failed ("in `<stmt>`: <reason>").
         let stmt_ctx = (|| -> Result<(), String> {
         match stmt {
             Stmt::Local(local) => {
                 debug_print!("DEBUG BLOCK LOCAL: {:?}", &local);
-                let js_stmt = handle_local_statement(block_action, local, state)?;
-                js_stmts.push(js_stmt);
+                js_stmts.extend(handle_local_with_try(block_action, local, state)?);
             }
             Stmt::Item(item) => match item {
                 syn::Item::Fn(item_fn) => {
                     let js_stmt = handle_function_definition(item_fn, state)?;
                     js_stmts.push(js_stmt);
                 }
+                syn::Item::Const(item_const) => {
+                    let js_stmt = handle_const_item(item_const, state)?;
+                    js_stmts.push(js_stmt);
+                }
                 syn::Item::Struct(_) => {
                     return Err("nested `struct` definitions inside a function body are not \
                         supported; move the struct to module scope and annotate it with \
@@ -1530,6 +2689,26 @@ pub fn rust_block_to_js_with_params_and_retval(
                         debug_print!("DEBUG IFIN BLOCK: {:?}", if_stmt);
                         js_stmts.push(if_stmt);
                     }
+                    Expr::Match(match_expr)
+                        if semi.is_some() || stmt != block.stmts.last().unwrap() =>
+                    {
+                        // Only a tail match feeds the retval
+                        js_stmts.push(convert_match_to_stmt(match_expr, state)?);
+                    }
+                    Expr::Block(block_expr) if block_expr.label.is_none() => {
+                        // Lexical block; as the tail it feeds the retval
+                        let is_tail = semi.is_none() && stmt == block.stmts.last().unwrap();
+                        let stmts = if is_tail {
+                            rust_block_to_js_with_retval(block_action, &block_expr.block, retval_var, state)?
+                        } else {
+                            rust_block_to_js_with_state(BlockAction::NoReturn, &block_expr.block, state)?
+                        };
+                        js_stmts.push(js::Stmt::Block(js::BlockStmt {
+                            span: DUMMY_SP,
+                            stmts,
+                            ctxt: SyntaxContext::empty(),
+                        }));
+                    }
                     Expr::Break(break_expr) => {
                         // Generate break statement directly
                         if let Some(value) = &break_expr.expr {
@@ -1604,61 +2783,106 @@ pub fn rust_block_to_js_with_params_and_retval(
     Ok(js_stmts)
 }
 
-/// Handle function definitions inside blocks
-fn handle_function_definition(
-    item_fn: &syn::ItemFn,
+/// `const N: usize = 4;` inside a function body -> `const N = 4;`, so later
+/// uses such as the count in `[0; N]` resolve to it
+fn handle_const_item(
+    item_const: &syn::ItemConst,
     state: &mut TranspilerState,
 ) -> Result<js::Stmt, String> {
-    let func_name = item_fn.sig.ident.to_string();
-    let js_func_name = escape_js_identifier(&func_name);
+    let const_name = item_const.ident.to_string();
+    let js_const_name = escape_js_identifier(&const_name);
+    let init_expr = rust_expr_to_js_with_state(&item_const.expr, state)?;
 
-    // Convert parameters (don't register in scope yet - will be done in function body)
-    let params: Vec<js::Pat> = item_fn
-        .sig
+    state.declare_variable_type(&const_name, Some((*item_const.ty).clone()));
+    let unique_js_name = state.declare_variable(const_name, js_const_name, false);
+    Ok(state.mk_var_decl(&unique_js_name, Some(init_expr), true))
+}
+
+/// JS parameters for a function's simple `name: Type` inputs (self is
+/// skipped), each declared in the current scope and named as declared there.
+/// Trailing `Option<T>` parameters get `= null` so JS callers can leave
+/// them out.
+fn js_params_for_signature(sig: &syn::Signature, state: &mut TranspilerState) -> Vec<js::Pat> {
+    let typed: Vec<&syn::PatType> = sig
         .inputs
         .iter()
-        .filter_map(|arg| {
-            match arg {
-                syn::FnArg::Receiver(_) => None, // Skip self parameters in nested functions
-                syn::FnArg::Typed(pat_type) => {
-                    if let syn::Pat::Ident(pat_ident) = &*pat_type.pat {
-                        let param_name = pat_ident.ident.to_string();
-                        let js_param_name = escape_js_identifier(&param_name);
-                        Some(js::Pat::Ident(js::BindingIdent {
-                            id: state.mk_ident(&js_param_name),
-                            type_ann: None,
-                        }))
-                    } else {
-                        None
-                    }
-                }
-            }
+        .filter_map(|arg| match arg {
+            FnArg::Receiver(_) => None,
+            FnArg::Typed(pat_type) => Some(pat_type),
         })
         .collect();
+    let first_optional = typed
+        .iter()
+        .rposition(|pat_type| wrapped_type_arg(&pat_type.ty, "Option").is_none())
+        .map_or(0, |i| i + 1);
 
-    // Collect parameter information for scope registration
-    let param_info: Vec<(String, String)> = item_fn
-        .sig
-        .inputs
+    typed
         .iter()
-        .filter_map(|arg| {
-            match arg {
-                syn::FnArg::Receiver(_) => None,
-                syn::FnArg::Typed(pat_type) => {
-                    if let syn::Pat::Ident(pat_ident) = &*pat_type.pat {
-                        let param_name = pat_ident.ident.to_string();
-                        let js_param_name = escape_js_identifier(&param_name);
-                        Some((param_name, js_param_name))
-                    } else {
-                        None
-                    }
-                }
+        .enumerate()
+        .filter_map(|(i, pat_type)| {
+            let Pat::Ident(pat_ident) = &*pat_type.pat else {
+                return None;
+            };
+            let rust_name = pat_ident.ident.to_string();
+            let js_param_name = escape_js_identifier(&rust_name);
+            state.declare_variable_type(&rust_name, Some((*pat_type.ty).clone()));
+            let js_param_name = state.declare_variable(rust_name, js_param_name, false);
+            let binding = js::Pat::Ident(js::BindingIdent {
+                id: state.mk_ident(&js_param_name),
+                type_ann: None,
+            });
+            if i >= first_optional {
+                Some(js::Pat::Assign(js::AssignPat {
+                    span: DUMMY_SP,
+                    left: Box::new(binding),
+                    right: Box::new(state.mk_null_lit()),
+                }))
+            } else {
+                Some(binding)
             }
         })
-        .collect();
+        .collect()
+}
+
+/// `type_scopes` key for the enclosing function's return type; not a valid
+/// Rust identifier, so no variable can shadow it
+const RETURN_TYPE_KEY: &str = "->";
+
+/// Record the declared types of a function's simple `name: Type` parameters
+/// and its return type
+fn declare_param_types(sig: &syn::Signature, state: &mut TranspilerState) {
+    for arg in &sig.inputs {
+        if let FnArg::Typed(pat_type) = arg
+            && let Pat::Ident(pat_ident) = &*pat_type.pat
+        {
+            state.declare_variable_type(&pat_ident.ident.to_string(), Some((*pat_type.ty).clone()));
+        }
+    }
+    state.declare_return_type(match &sig.output {
+        syn::ReturnType::Type(_, ty) => Some((**ty).clone()),
+        syn::ReturnType::Default => None,
+    });
+}
+
+/// Handle function definitions inside blocks
+fn handle_function_definition(
+    item_fn: &syn::ItemFn,
+    state: &mut TranspilerState,
+) -> Result<js::Stmt, String> {
+    let func_name = item_fn.sig.ident.to_string();
+    let js_func_name = escape_js_identifier(&func_name);
 
-    // Convert function body with parameter registration
-    let body_stmts = rust_block_to_js_with_params_and_state(BlockAction::Return, &item_fn.block, &param_info, state)?;
+    // Declare the parameters in the function's scope; the signature uses the
+    // names they got there, so a parameter shadowing an outer variable is
+    // renamed consistently in both
+    state.enter_scope();
+    declare_param_types(&item_fn.sig, state);
+    let params = js_params_for_signature(&item_fn.sig, state);
+    let body_stmts = state.in_function_body(|state| {
+        rust_block_to_js_with_params_and_state(BlockAction::Return, &item_fn.block, &[], state)
+    });
+    state.exit_scope();
+    let body_stmts = body_stmts?;
 
     let function_body = js::BlockStmt {
         span: DUMMY_SP,
@@ -1669,7 +2893,7 @@ fn handle_function_definition(
     let function = js::Function {
         params: params.into_iter().map(|p| state.pat_to_param(p)).collect(),
         decorators: vec![],
-        span: DUMMY_SP,
+        span: state.doc_comment_span(&item_fn.attrs),
         body: Some(function_body),
         is_generator: false,
         is_async: item_fn.sig.asyncness.is_some(),
@@ -1705,21 +2929,40 @@ pub fn rust_expr_to_js_with_action_and_state(
     state: &mut TranspilerState,
 ) -> Result<js::Expr, String> {
     debug_print!("DEBUG EXPR: {:?}, {:?}", block_action, &expr);
+    // Only the operands of the arithmetic itself are BigInt; call arguments,
+    // indices and the like inside it stay plain numbers
+    if state.in_wide_int
+        && !matches!(
+            expr,
+            Expr::Lit(_) | Expr::Binary(_) | Expr::Paren(_) | Expr::Group(_) | Expr::Unary(_)
+        )
+    {
+        state.in_wide_int = false;
+        let result = rust_expr_to_js_with_action_and_state(block_action, expr, state);
+        state.in_wide_int = true;
+        return result;
+    }
     match expr {
         // Handle literals
         Expr::Lit(lit) => match &lit.lit {
-            syn::Lit::Str(s) => {
-                let value = s.value();
-                // Properly escape the string for JavaScript
-                let escaped = value
-                    .replace('\\', "\\\\")
-                    .replace('"', "\\\"")
-                    .replace('\n', "\\n")
-                    .replace('\t', "\\t")
-                    .replace('\r', "\\r");
-                Ok(state.mk_str_lit(&escaped))
+            // Escape the literal's value (not its source text), so raw strings
+            // keep their backslashes and nothing is escaped twice
+            syn::Lit::Str(s) => Ok(state.mk_str_lit_escaped(&s.value())),
+
+            syn::Lit::Int(i)
+                if state.bigint_integers
+                    && (state.in_wide_int || is_wide_int_name(i.suffix())) =>
+            {
+                let value = i
+                    .base10_digits()
+                    .parse::<js::BigIntValue>()
+                    .map_err(|e| format!("Failed to parse integer: {}", e))?;
+                Ok(js::Expr::Lit(js::Lit::BigInt(js::BigInt {
+                    span: DUMMY_SP,
+                    value: Box::new(value),
+                    raw: None,
+                })))
             }
-
             syn::Lit::Int(i) => {
                 let value = i
                     .base10_parse::<f64>()
@@ -1755,6 +2998,15 @@ pub fn rust_expr_to_js_with_action_and_state(
                 if type_name == "Option" && member == "None" {
                     return Ok(state.mk_null_lit());
                 }
+                // An Ordering is the number a JS comparator returns
+                if type_name == "Ordering" {
+                    match member.as_str() {
+                        "Less" => return Ok(state.mk_num_lit(-1.0)),
+                        "Equal" => return Ok(state.mk_num_lit(0.0)),
+                        "Greater" => return Ok(state.mk_num_lit(1.0)),
+                        _ => {}
+                    }
+                }
                 let base = if type_name == "Self" {
                     state
                         .get_current_struct_name()
@@ -1773,6 +3025,9 @@ pub fn rust_expr_to_js_with_action_and_state(
                 let ident_str = last_segment.ident.to_string();
 
                 match ident_str.as_str() {
+                    "self" if state.enum_methods.is_some() => {
+                        Ok(js::Expr::Ident(state.mk_ident("self")))
+                    }
                     "self" => Ok(state.mk_this_expr()),
                     "Self" => {
                         // A bare `Self` in expression position constructs the
@@ -1808,8 +3063,14 @@ pub fn rust_expr_to_js_with_action_and_state(
 
         // Handle binary operations
         Expr::Binary(bin) => {
-            let left = rust_expr_to_js_with_state(&bin.left, state)?;
-            let right = rust_expr_to_js_with_state(&bin.right, state)?;
+            let wide = !matches!(bin.op, syn::BinOp::And(_) | syn::BinOp::Or(_))
+                && (is_wide_int_expr(&bin.left, state) || is_wide_int_expr(&bin.right, state));
+            let (left, right) = state.with_wide_int(wide, |state| {
+                Ok((
+                    rust_expr_to_js_with_state(&bin.left, state)?,
+                    rust_expr_to_js_with_state(&bin.right, state)?,
+                ))
+            })?;
 
             let js_op = match &bin.op {
                 syn::BinOp::AddAssign(_) => Some(js::AssignOp::AddAssign),
@@ -1909,22 +3170,14 @@ pub fn rust_expr_to_js_with_action_and_state(
                     Ok(state.mk_member_expr(base, clean_member_name))
                 }
                 syn::Member::Unnamed(index) => {
-                    // Tuple field access: obj[0]
-                    let index_expr = state.mk_num_lit(index.index as f64);
-                    Ok(js::Expr::Member(js::MemberExpr {
-                        span: DUMMY_SP,
-                        obj: Box::new(base),
-                        prop: js::MemberProp::Computed(js::ComputedPropName {
-                            span: DUMMY_SP,
-                            expr: Box::new(index_expr),
-                        }),
-                    }))
+                    // Tuple (and tuple struct) field access: obj[0]
+                    Ok(tuple_field_expr(state, base, index.index as usize))
                 }
             }
         }
 
         // Handle if expressions
-        Expr::If(if_expr) => handle_if_expr(block_action, if_expr, state),
+        Expr::If(if_expr) => state.in_value_iife(block_action, |state| handle_if_expr(if_expr, state)),
 
         // Handle block expressions
         Expr::Block(block_expr) => {
@@ -1954,7 +3207,9 @@ pub fn rust_expr_to_js_with_action_and_state(
                 }
                 _ => {
                     // For non-value contexts, use the original approach
-                    let stmts = rust_block_to_js_with_state(block_action, &block_expr.block, state)?;
+                    let stmts = state.in_value_iife(block_action, |state| {
+                        rust_block_to_js_with_state(block_action, &block_expr.block, state)
+                    })?;
                     Ok(state.mk_iife(stmts))
                 }
             }
@@ -1987,6 +3242,9 @@ pub fn rust_expr_to_js_with_action_and_state(
         // Handle index expressions
         Expr::Index(index) => {
             let obj = rust_expr_to_js_with_state(&index.expr, state)?;
+            if let Expr::Range(range_expr) = &*index.index {
+                return handle_slice_index(obj, range_expr, state);
+            }
             let prop = rust_expr_to_js_with_state(&index.index, state)?;
 
             Ok(js::Expr::Member(js::MemberExpr {
@@ -2002,7 +3260,10 @@ pub fn rust_expr_to_js_with_action_and_state(
         // Handle assignments
         Expr::Assign(assign) => {
             let left = rust_expr_to_js_with_state(&assign.left, state)?;
-            let right = rust_expr_to_js_with_state(&assign.right, state)?;
+            let wide = is_wide_int_expr(&assign.left, state);
+            let right = state.with_wide_int(wide, |state| {
+                rust_expr_to_js_with_state(&assign.right, state)
+            })?;
 
             Ok(js::Expr::Assign(js::AssignExpr {
                 span: DUMMY_SP,
@@ -2031,10 +3292,10 @@ pub fn rust_expr_to_js_with_action_and_state(
         Expr::Struct(struct_expr) => handle_struct_expr(struct_expr, state),
 
         // Handle for loops
-        Expr::ForLoop(for_expr) => handle_for_expr(for_expr, state),
+        Expr::ForLoop(for_expr) => state.in_value_iife(block_action, |state| handle_for_expr(for_expr, state)),
 
         // Handle match expressions
-        Expr::Match(match_expr) => handle_match_expr(match_expr, state),
+        Expr::Match(match_expr) => state.in_value_iife(block_action, |state| handle_match_expr(match_expr, state)),
 
         Expr::Paren(paren) => handle_paren_expr(paren, state),
 
@@ -2088,9 +3349,9 @@ pub fn rust_expr_to_js_with_action_and_state(
         }
 
         // Handle additional expression types
-        Expr::While(while_expr) => handle_while_expr(while_expr, state),
+        Expr::While(while_expr) => state.in_value_iife(block_action, |state| handle_while_expr(while_expr, state)),
         Expr::ForLoop(for_expr) => handle_for_expr(for_expr, state),
-        Expr::Loop(loop_expr) => handle_loop_expr(loop_expr, state),
+        Expr::Loop(loop_expr) => state.in_value_iife(block_action, |state| handle_loop_expr(loop_expr, state)),
 
         Expr::Match(match_expr) => handle_match_expr(match_expr, state),
         Expr::Closure(closure) => handle_closure_expr(closure, state),
@@ -2150,32 +3411,19 @@ pub fn rust_expr_to_js_with_action_and_state(
                 Type::Path(type_path) => {
                     if let Some(segment) = type_path.path.segments.last() {
                         match segment.ident.to_string().as_str() {
+                            name if state.bigint_integers && is_wide_int_name(name) => "BigInt",
                             "i8" | "i16" | "i32" | "i64" | "isize" | "u8" | "u16" | "u32"
-                            | "u64" | "usize" | "f32" | "f64" => "Number",
+                            | "u64" | "u128" | "i128" | "usize" | "f32" | "f64" => "Number",
                             "String" | "str" => "String",
                             "bool" => "Boolean",
                             _ => {
-                                // For unknown types, add a comment
-                                return Ok(js::Expr::Ident(js::Ident::new(
-                                    format!(
-                                        "{} /* was {} as {} in Rust */",
-                                        match ast_to_code_trimmed(&[js::ModuleItem::Stmt(
-                                            js::Stmt::Expr(js::ExprStmt {
-                                                span: DUMMY_SP,
-                                                expr: Box::new(inner_expr.clone()),
-                                            })
-                                        )]) {
-                                            Ok(code) =>
-                                                code.trim_end_matches(';').trim().to_string(),
-                                            Err(_) => "expr".to_string(),
-                                        },
-                                        "expr",
-                                        format_rust_type(&cast_expr.ty)
-                                    )
-                                    .into(),
-                                    DUMMY_SP,
-                                    SyntaxContext::empty(),
-                                )));
+                                // A user type has no conversion function: the
+                                // value is used as it is
+                                state.add_warning(format!(
+                                    "`{}` has no JavaScript conversion; the value is used unchanged",
+                                    source_snippet(cast_expr)
+                                ));
+                                return Ok(inner_expr);
                             }
                         }
                     } else {
@@ -2238,12 +3486,15 @@ pub fn rust_expr_to_js_with_action_and_state(
                     token_string
                 ));
 
-                // Return as a comment expression
-                Ok(js::Expr::Ident(js::Ident::new(
-                    format!("/* verbatim: {} */", token_string.trim()).into(),
-                    DUMMY_SP,
-                    SyntaxContext::empty(),
-                )))
+                // `undefined`, commented with what it stands for
+                let mut span = DUMMY_SP;
+                attach_comment(
+                    state,
+                    &mut span,
+                    GeneratedComment::block(&format!("verbatim: {}", token_string.trim())),
+                    false,
+                );
+                Ok(js::Expr::Ident(js::Ident::new("undefined".into(), span, SyntaxContext::empty())))
             }
         }
 
@@ -2323,8 +3574,20 @@ pub fn rust_expr_to_js_with_action_and_state(
                     })),
                 });
 
+                // Awaited, so the expression is the value rather than a promise
                 let call_expr = state.mk_member_expr(wrapped_fn, "call");
-                Ok(state.mk_call_expr(call_expr, vec![state.mk_this_expr()]))
+                Ok(js::Expr::Await(js::AwaitExpr {
+                    span: DUMMY_SP,
+                    arg: Box::new(state.mk_call_expr(call_expr, vec![state.mk_this_expr()])),
+                }))
+            } else if is_option_try(state, try_expr) {
+                // An expression can't return from the function it is in, and
+                // going on with the null would change what the code computes
+                Err(format!(
+                    "`{}`: `?` on an Option can only return early as `let x = opt?;`; \
+                     bind the value with a `let` first",
+                    source_snippet(try_expr)
+                ))
             } else {
                 let inner = rust_expr_to_js_with_action_and_state(block_action, &try_expr.expr, state)?;
                 // Generate an IIFE that handles the try operation
@@ -2424,14 +3687,69 @@ pub fn handle_paren_expr(
 
 // Continuation of lib.rs - Helper functions
 
+/// The enum object and JS method name for calling `method_name` on
+/// `receiver`, when that is a method of an enum impl: `self` inside the
+/// impl, or a variable declared as a known enum (or as `Self` in its impl)
+fn enum_method_target(state: &TranspilerState, receiver: &Expr, method_name: &str) -> Option<(String, String)> {
+    if let Expr::Path(path) = receiver
+        && path.path.is_ident("self")
+    {
+        let js_name = state.enum_methods.as_ref()?.get(method_name)?;
+        return Some((state.get_current_struct_name()?.to_string(), js_name.clone()));
+    }
+    let mut ty = known_receiver_type(state, receiver)?;
+    while let Type::Reference(type_ref) = ty {
+        ty = &type_ref.elem;
+    }
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let mut name = type_path.path.segments.last()?.ident.to_string();
+    if name == "Self" {
+        name = state.get_current_struct_name()?.to_string();
+    }
+    let js_name = state.enum_types.get(&name)?.get(method_name)?;
+    Some((name, js_name.clone()))
+}
+
 /// Handle method calls
 fn handle_method_call(
     method_call: &syn::ExprMethodCall,
     state: &mut TranspilerState,
 ) -> Result<js::Expr, String> {
+    // successors(init, f).take(n) must be seen as a whole: the receiver on
+    // its own is an infinite sequence with no JS counterpart.
+    if method_call.method == "take"
+        && method_call.args.len() == 1
+        && let Expr::Call(call) = &*method_call.receiver
+        && is_successors_call(call)
+    {
+        return handle_successors_take(call, &method_call.args[0], state);
+    }
+
     let receiver = rust_expr_to_js_with_state(&method_call.receiver, state)?;
     let method_name = method_call.method.to_string();
 
+    // Enum values have no methods of their own, so a method of an enum impl
+    // is called on the enum object: `Enum.other(self)` inside the impl,
+    // `Shape.area(s)` for a receiver declared as `Shape`
+    if let Some((enum_name, js_name)) = enum_method_target(state, &method_call.receiver, &method_name) {
+        let callee = state.mk_member_expr(js::Expr::Ident(state.mk_ident(&enum_name)), &js_name);
+        let mut js_args = vec![receiver];
+        for arg in &method_call.args {
+            js_args.push(rust_expr_to_js_with_state(arg, state)?);
+        }
+        return Ok(state.mk_call_expr(callee, js_args));
+    }
+
+    // drain(..) takes a range that is never materialized, and `..` alone
+    // has no value form, so it bypasses the argument conversion below
+    if method_name == "drain"
+        && let [Expr::Range(range)] = method_call.args.iter().collect::<Vec<_>>()[..]
+    {
+        return drain_to_splice(state, receiver, range);
+    }
+
     // Convert arguments
     let args: Result<Vec<_>, _> = method_call
         .args
@@ -2440,875 +3758,2997 @@ fn handle_method_call(
         .collect();
     let js_args = args?;
 
-    // Handle special method mappings
-    match method_name.as_str() {
-        "len" | "count" => {
-            // Use IIFE to evaluate receiver once and handle both arrays/strings and objects
-            // ((obj) => obj.length !== undefined ? obj.length : Object.keys(obj).length)(receiver)
-            
-            // Create parameter for the IIFE
-            let obj_param = js::Pat::Ident(js::BindingIdent {
-                id: state.mk_ident("obj"),
-                type_ann: None,
-            });
-            
-            // Create obj.length access
-            let length_access = state.mk_member_expr(js::Expr::Ident(state.mk_ident("obj")), "length");
-            
-            // Create undefined check: obj.length !== undefined
-            let undefined_check = state.mk_binary_expr(
-                length_access.clone(),
-                js::BinaryOp::NotEqEq,
-                state.mk_undefined()
-            );
-            
-            // Create Object.keys(obj).length for objects
-            let object_keys = state.mk_call_expr(
-                state.mk_member_expr(js::Expr::Ident(state.mk_ident("Object")), "keys"),
-                vec![js::Expr::Ident(state.mk_ident("obj"))]
-            );
-            let object_keys_length = state.mk_member_expr(object_keys, "length");
-            
-            // Create conditional expression
-            let conditional = js::Expr::Cond(js::CondExpr {
-                span: DUMMY_SP,
-                test: Box::new(undefined_check),
-                cons: Box::new(length_access),
-                alt: Box::new(object_keys_length),
-            });
-            
-            // Create IIFE: (obj) => conditional
-            let iife = js::ArrowExpr {
-                span: DUMMY_SP,
-                params: vec![obj_param],
-                body: Box::new(js::BlockStmtOrExpr::Expr(Box::new(conditional))),
-                is_async: false,
-                is_generator: false,
-                type_params: None,
-                return_type: None,
-                ctxt: SyntaxContext::empty(),
-            };
-            
-            // Call the IIFE with receiver: ((obj) => ...)(receiver)
-            Ok(js::Expr::Call(js::CallExpr {
+    // The same names on anything else (Vec::reverse, Promise::then) are
+    // left to the table below
+    let ordering_arity = match method_name.as_str() {
+        "reverse" => Some(0),
+        "then" | "then_with" => Some(1),
+        _ => None,
+    };
+    if ordering_arity == Some(js_args.len()) && expr_is_ordering(state, &method_call.receiver) {
+        return Ok(ordering_method(state, &method_name, receiver, js_args));
+    }
+
+    // Well-known methods are looked up in the mapping table; anything else
+    // is emitted as a plain method call
+    let call_expr = match state.method_mapping(&method_name) {
+        Some(mapping) => mapping(state, method_call, receiver, js_args)?,
+        None => plain_method_call(state, method_call, receiver, js_args),
+    };
+    Ok(mark_spread_args(call_expr, &method_call.args))
+}
+
+/// The built-in method mappings every `TranspilerState` starts with.
+fn default_method_mappings() -> HashMap<String, MappingFn> {
+    let mut mappings: HashMap<String, MappingFn> = HashMap::new();
+
+    // Methods that only change name on the way to JavaScript
+    for (rust_name, js_name) in [
+        ("to_string", "toString"),
+        ("to_uppercase", "toUpperCase"),
+        ("to_lowercase", "toLowerCase"),
+        ("trim_start", "trimStart"),
+        ("trim_end", "trimEnd"),
+        ("starts_with", "startsWith"),
+        ("ends_with", "endsWith"),
+        ("flat_map", "flatMap"),
+    ] {
+        mappings.insert(
+            rust_name.to_string(),
+            Rc::new(move |state: &mut TranspilerState, _: &syn::ExprMethodCall, receiver, args| {
+                Ok(state.mk_call_expr(state.mk_member_expr(receiver, js_name), args))
+            }),
+        );
+    }
+
+    // Numeric methods become the matching Math function with the receiver as
+    // its first argument: `x.abs()` -> `Math.abs(x)`, `a.max(b)` ->
+    // `Math.max(a, b)`. The argument count must match, so the zero-argument
+    // Iterator::min()/max() keep their plain method call. A receiver that
+    // already is the JS `Math` object is left alone as well, and so is one
+    // known not to be a number (a struct's own `max`, a String).
+    for (rust_name, math_fn, arity) in [
+        ("abs", "abs", 0),
+        ("sqrt", "sqrt", 0),
+        ("cbrt", "cbrt", 0),
+        ("floor", "floor", 0),
+        ("ceil", "ceil", 0),
+        ("round", "round", 0),
+        ("trunc", "trunc", 0),
+        ("signum", "sign", 0),
+        ("exp", "exp", 0),
+        ("ln", "log", 0),
+        ("log10", "log10", 0),
+        ("log2", "log2", 0),
+        ("sin", "sin", 0),
+        ("cos", "cos", 0),
+        ("tan", "tan", 0),
+        ("atan2", "atan2", 1),
+        ("hypot", "hypot", 1),
+        ("pow", "pow", 1),
+        ("powi", "pow", 1),
+        ("powf", "pow", 1),
+        ("min", "min", 1),
+        ("max", "max", 1),
+    ] {
+        mappings.insert(
+            rust_name.to_string(),
+            Rc::new(move |state: &mut TranspilerState, method_call: &syn::ExprMethodCall, receiver, args| {
+                let on_math = matches!(&*method_call.receiver, Expr::Path(p) if p.path.is_ident("Math"));
+                let not_number = receiver_js_type(state, &method_call.receiver)
+                    .is_some_and(|js_type| js_type != "number");
+                if args.len() != arity || on_math || not_number {
+                    return Ok(plain_method_call(state, method_call, receiver, args));
+                }
+                let math = state.mk_member_expr(js::Expr::Ident(state.mk_ident("Math")), math_fn);
+                Ok(state.mk_call_expr(math, std::iter::once(receiver).chain(args).collect()))
+            }),
+        );
+    }
+
+    mappings.insert("cmp".to_string(), Rc::new(map_cmp));
+    mappings.insert("partial_cmp".to_string(), Rc::new(map_cmp));
+    for name in ["sort_by", "sort_unstable_by", "sort_by_key", "sort_unstable_by_key", "sort_by_cached_key"] {
+        mappings.insert(name.to_string(), Rc::new(map_sort_by));
+    }
+    for name in ["min_by", "max_by", "min_by_key", "max_by_key"] {
+        mappings.insert(name.to_string(), Rc::new(map_min_max_by));
+    }
+    mappings.insert("take_while".to_string(), Rc::new(map_take_skip_while));
+    mappings.insert("skip_while".to_string(), Rc::new(map_take_skip_while));
+    mappings.insert("clamp".to_string(), Rc::new(map_clamp));
+    mappings.insert("len".to_string(), Rc::new(map_len));
+    mappings.insert("count".to_string(), Rc::new(map_len));
+    mappings.insert("clone".to_string(), Rc::new(map_clone));
+    mappings.insert("copied".to_string(), Rc::new(map_clone));
+    mappings.insert("cloned".to_string(), Rc::new(map_clone));
+    mappings.insert("sum".to_string(), Rc::new(map_fold_arithmetic));
+    mappings.insert("product".to_string(), Rc::new(map_fold_arithmetic));
+    mappings.insert("borrow".to_string(), Rc::new(map_unwrap_cell));
+    mappings.insert("borrow_mut".to_string(), Rc::new(map_unwrap_cell));
+    mappings.insert("lock".to_string(), Rc::new(map_unwrap_cell));
+    mappings.insert("as_str".to_string(), Rc::new(map_as_str));
+    mappings.insert("as_bytes".to_string(), Rc::new(map_as_bytes));
+    mappings.insert("to_vec".to_string(), Rc::new(map_to_vec));
+    mappings.insert("parse".to_string(), Rc::new(map_parse));
+    mappings.insert("is_empty".to_string(), Rc::new(map_is_empty));
+    mappings.insert("remove".to_string(), Rc::new(map_remove));
+    mappings.insert("insert".to_string(), Rc::new(map_insert));
+    mappings.insert("get".to_string(), Rc::new(map_get));
+    mappings.insert("keys".to_string(), Rc::new(map_keys));
+    mappings.insert("iter".to_string(), Rc::new(map_iter));
+    mappings.insert("into_iter".to_string(), Rc::new(map_iter));
+    mappings.insert("retain".to_string(), Rc::new(map_retain));
+    mappings.insert("dedup".to_string(), Rc::new(map_retain));
+    mappings.insert("collect".to_string(), Rc::new(map_collect));
+    mappings.insert("chars".to_string(), Rc::new(map_chars));
+    mappings.insert("rev".to_string(), Rc::new(map_rev));
+    mappings.insert("step_by".to_string(), Rc::new(map_step_by));
+    mappings.insert("repeat".to_string(), Rc::new(map_repeat));
+    mappings.insert("concat".to_string(), Rc::new(map_concat));
+    mappings.insert("join".to_string(), Rc::new(map_join));
+    mappings.insert("extend_from_slice".to_string(), Rc::new(map_extend_from_slice));
+    mappings.insert("is_some".to_string(), Rc::new(map_is_some));
+    mappings.insert("is_none".to_string(), Rc::new(map_is_none));
+    mappings.insert("contains".to_string(), Rc::new(map_contains));
+    mappings.insert("contains_key".to_string(), Rc::new(map_contains_key));
+    mappings.insert("unwrap".to_string(), Rc::new(map_unwrap));
+    mappings.insert("unwrap_or".to_string(), Rc::new(map_unwrap_or));
+    mappings.insert("unwrap_or_else".to_string(), Rc::new(map_unwrap_or));
+    mappings.insert("unwrap_or_default".to_string(), Rc::new(map_unwrap_or));
+    mappings.insert("is_ok".to_string(), Rc::new(map_is_ok));
+    mappings.insert("is_err".to_string(), Rc::new(map_is_ok));
+    mappings.insert("ok".to_string(), Rc::new(map_ok));
+    mappings.insert("filter".to_string(), Rc::new(map_filter));
+    mappings.insert("or".to_string(), Rc::new(map_option_combinator));
+    mappings.insert("and".to_string(), Rc::new(map_option_combinator));
+    mappings.insert("xor".to_string(), Rc::new(map_option_combinator));
+    mappings.insert("ok_or".to_string(), Rc::new(map_ok_or));
+    mappings.insert("ok_or_else".to_string(), Rc::new(map_ok_or));
+    mappings.insert("split_whitespace".to_string(), Rc::new(map_split_whitespace));
+    mappings.insert("splitn".to_string(), Rc::new(map_splitn));
+    mappings.insert("rsplit".to_string(), Rc::new(map_rsplit));
+    mappings.insert("map_or".to_string(), Rc::new(map_map_or));
+    mappings.insert("map_or_else".to_string(), Rc::new(map_map_or));
+
+    mappings
+}
+
+/// `receiver.method(args)` with the Rust method name unchanged - what a
+/// method without a mapping becomes, and what a mapping falls back to when
+/// the call does not have the shape it handles.
+fn plain_method_call(
+    state: &TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> js::Expr {
+    state.mk_call_expr(
+        state.mk_member_expr(receiver, &method_call.method.to_string()),
+        js_args,
+    )
+}
+
+/// `v.repeat(n)` on a Vec concatenates n copies:
+/// `((v, n) => Array.from({length: n}).flatMap(() => v))(receiver, n)`.
+/// A string keeps its own `repeat`.
+fn map_repeat(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    if !receiver_is_array(state, method_call) {
+        return Ok(plain_method_call(state, method_call, receiver, js_args));
+    }
+    let Ok([count]) = <[js::Expr; 1]>::try_from(js_args) else {
+        return Err("repeat() expects exactly one argument".to_string());
+    };
+    let items = state.generate_temp_var();
+    let n = state.generate_temp_var();
+    let length_obj = js::Expr::Object(js::ObjectLit {
+        span: DUMMY_SP,
+        props: vec![js::PropOrSpread::Prop(Box::new(js::Prop::KeyValue(
+            js::KeyValueProp {
+                key: js::PropName::Ident(state.mk_ident_name("length")),
+                value: Box::new(js::Expr::Ident(state.mk_ident(&n))),
+            },
+        )))],
+    });
+    let copies = state.mk_call_expr(
+        state.mk_member_expr(js::Expr::Ident(state.mk_ident("Array")), "from"),
+        vec![length_obj],
+    );
+    let body = state.mk_call_expr(
+        state.mk_member_expr(copies, "flatMap"),
+        vec![state.mk_arrow(&[], js::Expr::Ident(state.mk_ident(&items)))],
+    );
+    Ok(state.mk_arrow_iife(&[&items, &n], body, vec![receiver, count]))
+}
+
+/// `[a, b].concat()` joins a slice of slices into one Vec, or a slice of
+/// strings into one string:
+/// `((v) => v.length > 0 && typeof v[0] === "string" ? v.join("") : [].concat(...v))(receiver)`.
+/// With an argument it already is the JS `a.concat(b)`.
+fn map_concat(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    if !js_args.is_empty() {
+        return Ok(plain_method_call(state, method_call, receiver, js_args));
+    }
+    let parts = state.generate_temp_var();
+    let v = || js::Expr::Ident(state.mk_ident(&parts));
+    let first_is_string = state.mk_binary_expr(
+        js::Expr::Unary(js::UnaryExpr {
+            span: DUMMY_SP,
+            op: js::UnaryOp::TypeOf,
+            arg: Box::new(js::Expr::Member(js::MemberExpr {
                 span: DUMMY_SP,
-                callee: js::Callee::Expr(Box::new(js::Expr::Paren(js::ParenExpr {
-                    span: DUMMY_SP,
-                    expr: Box::new(js::Expr::Arrow(iife)),
-                }))),
-                args: vec![js::ExprOrSpread {
-                    spread: None,
-                    expr: Box::new(receiver),
-                }],
-                type_args: None,
-                ctxt: SyntaxContext::empty(),
-            }))
-        }
-        "clone" => {
-            // .clone() is typically a no-op in JavaScript for primitives
-            Ok(receiver)
-        }
-        "as_str" => {
-            // Convert .as_str() to JavaScript string conversion: String(receiver)
-            Ok(state.mk_call_expr(
-                js::Expr::Ident(state.mk_ident("String")),
-                vec![receiver]
-            ))
-        }
-        // str.parse::<N>() -> a Result-shaped value ({ok: n} / {error: msg}),
-        // the same representation Ok()/Err() transpile to, so `match
-        // s.parse() { Ok(n) => .., Err(e) => .. }`, if-let, .unwrap() and
-        // .unwrap_or() all behave. Zero-arg only, so JSON.parse(text) keeps
-        // its native meaning. Emitted JS:
-        //   ((s) => ((v) => Number.isNaN(v)
-        //       ? { error: "invalid number: " + s } : { ok: v }
-        //     )((typeof s === "string" && s.trim() === "") ? NaN : Number(s))
-        //   )(receiver)
-        "parse" if js_args.is_empty() => {
-            let s = || js::Expr::Ident(state.mk_ident("s"));
-            let v = || js::Expr::Ident(state.mk_ident("v"));
-            let mk_obj = |field: &str, value: js::Expr| {
-                js::Expr::Object(js::ObjectLit {
+                obj: Box::new(v()),
+                prop: js::MemberProp::Computed(js::ComputedPropName {
                     span: DUMMY_SP,
-                    props: vec![js::PropOrSpread::Prop(Box::new(js::Prop::KeyValue(
-                        js::KeyValueProp {
-                            key: js::PropName::Ident(state.mk_ident_name(field)),
-                            value: Box::new(value),
-                        },
-                    )))],
-                })
-            };
-            // Number.isNaN(v) ? {error: "invalid number: " + s} : {ok: v}
-            let inner_body = js::Expr::Cond(js::CondExpr {
+                    expr: Box::new(state.mk_num_lit(0.0)),
+                }),
+            })),
+        }),
+        js::BinaryOp::EqEqEq,
+        state.mk_str_lit("string"),
+    );
+    let joins_strings = state.mk_binary_expr(
+        state.mk_binary_expr(
+            state.mk_member_expr(v(), "length"),
+            js::BinaryOp::Gt,
+            state.mk_num_lit(0.0),
+        ),
+        js::BinaryOp::LogicalAnd,
+        first_is_string,
+    );
+    let flattened = js::Expr::Call(js::CallExpr {
+        span: DUMMY_SP,
+        callee: js::Callee::Expr(Box::new(state.mk_member_expr(
+            js::Expr::Array(js::ArrayLit {
                 span: DUMMY_SP,
-                test: Box::new(state.mk_call_expr(
-                    state.mk_member_expr(
-                        js::Expr::Ident(state.mk_ident("Number")),
-                        "isNaN",
-                    ),
-                    vec![v()],
-                )),
-                cons: Box::new(mk_obj(
-                    "error",
-                    state.mk_binary_expr(
-                        state.mk_str_lit("invalid number: "),
-                        js::BinaryOp::Add,
-                        s(),
-                    ),
-                )),
-                alt: Box::new(mk_obj("ok", v())),
-            });
-            // (typeof s === "string" && s.trim() === "") ? NaN : Number(s)
-            // (Number("") is 0, but Rust's "".parse() is an error)
-            let is_blank_string = state.mk_binary_expr(
-                state.mk_binary_expr(
-                    js::Expr::Unary(js::UnaryExpr {
-                        span: DUMMY_SP,
-                        op: js::UnaryOp::TypeOf,
-                        arg: Box::new(s()),
-                    }),
-                    js::BinaryOp::EqEqEq,
-                    state.mk_str_lit("string"),
-                ),
-                js::BinaryOp::LogicalAnd,
-                state.mk_binary_expr(
-                    state.mk_call_expr(state.mk_member_expr(s(), "trim"), vec![]),
-                    js::BinaryOp::EqEqEq,
-                    state.mk_str_lit(""),
-                ),
-            );
-            let v_value = js::Expr::Cond(js::CondExpr {
+                elems: vec![],
+            }),
+            "concat",
+        ))),
+        args: vec![js::ExprOrSpread {
+            spread: Some(DUMMY_SP),
+            expr: Box::new(v()),
+        }],
+        type_args: None,
+        ctxt: SyntaxContext::empty(),
+    });
+    let body = js::Expr::Cond(js::CondExpr {
+        span: DUMMY_SP,
+        test: Box::new(joins_strings),
+        cons: Box::new(state.mk_call_expr(state.mk_member_expr(v(), "join"), vec![state.mk_str_lit("")])),
+        alt: Box::new(flattened),
+    });
+    Ok(state.mk_arrow_iife(&[&parts], body, vec![receiver]))
+}
+
+/// `v.join(sep)` is the JS string join, except on a binding known to hold
+/// slices of slices, where Rust puts the separator between the inner ones:
+/// `((v, sep) => v.flatMap((x, i) => i === 0 ? x : [].concat(sep, x)))(receiver, sep)`
+fn map_join(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    let is_nested = known_receiver_type(state, &method_call.receiver)
+        .and_then(sequence_element_type)
+        .is_some_and(|elem| sequence_element_type(elem).is_some());
+    if !is_nested {
+        return Ok(plain_method_call(state, method_call, receiver, js_args));
+    }
+    let Ok([separator]) = <[js::Expr; 1]>::try_from(js_args) else {
+        return Err("join() expects exactly one argument".to_string());
+    };
+    let parts = state.generate_temp_var();
+    let sep = state.generate_temp_var();
+    let empty_concat = |state: &TranspilerState, args: Vec<js::Expr>| {
+        state.mk_call_expr(
+            state.mk_member_expr(
+                js::Expr::Array(js::ArrayLit {
+                    span: DUMMY_SP,
+                    elems: vec![],
+                }),
+                "concat",
+            ),
+            args,
+        )
+    };
+    let piece = js::Expr::Cond(js::CondExpr {
+        span: DUMMY_SP,
+        test: Box::new(state.mk_binary_expr(
+            js::Expr::Ident(state.mk_ident("i")),
+            js::BinaryOp::EqEqEq,
+            state.mk_num_lit(0.0),
+        )),
+        cons: Box::new(js::Expr::Ident(state.mk_ident("x"))),
+        alt: Box::new(empty_concat(
+            state,
+            vec![js::Expr::Ident(state.mk_ident(&sep)), js::Expr::Ident(state.mk_ident("x"))],
+        )),
+    });
+    let body = state.mk_call_expr(
+        state.mk_member_expr(js::Expr::Ident(state.mk_ident(&parts)), "flatMap"),
+        vec![state.mk_arrow(&["x", "i"], piece)],
+    );
+    Ok(state.mk_arrow_iife(&[&parts, &sep], body, vec![receiver, separator]))
+}
+
+/// The element type of a Vec, slice or array type (or a reference to one)
+fn sequence_element_type(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Reference(type_ref) => sequence_element_type(&type_ref.elem),
+        Type::Slice(slice) => Some(&slice.elem),
+        Type::Array(array) => Some(&array.elem),
+        ty => wrapped_type_arg(ty, "Vec"),
+    }
+}
+
+/// `v.extend_from_slice(&other)` -> `v.push(...other)`
+fn map_extend_from_slice(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    let Ok([other]) = <[js::Expr; 1]>::try_from(js_args) else {
+        return Err(format!("{}() expects exactly one argument", method_call.method));
+    };
+    Ok(push_spread(state, receiver, other))
+}
+
+/// `target.push(...items)`
+fn push_spread(state: &TranspilerState, target: js::Expr, items: js::Expr) -> js::Expr {
+    js::Expr::Call(js::CallExpr {
+        span: DUMMY_SP,
+        callee: js::Callee::Expr(Box::new(state.mk_member_expr(target, "push"))),
+        args: vec![js::ExprOrSpread {
+            spread: Some(DUMMY_SP),
+            expr: Box::new(items),
+        }],
+        type_args: None,
+        ctxt: SyntaxContext::empty(),
+    })
+}
+
+/// `a.cmp(&b)` -> `((x, y)=>x < y ? -1 : x > y ? 1 : 0)(a, b)`: the
+/// Ordering as the number a JS sort comparator expects, for a number or
+/// string receiver. A receiver of another known type keeps its own `cmp`;
+/// when the type isn't known (say a sort closure's parameters) a `cmp`
+/// method on the value wins at run time.
+fn map_cmp(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    let primitive = receiver_is_primitive(state, &method_call.receiver);
+    if js_args.len() != 1 || primitive == Some(false) {
+        return Ok(plain_method_call(state, method_call, receiver, js_args));
+    }
+    let other = js_args.into_iter().next().unwrap();
+    if primitive == Some(true) {
+        return Ok(compare_values(state, receiver, other));
+    }
+    let (lhs, rhs) = (state.generate_temp_var(), state.generate_temp_var());
+    let var = |name: &str| js::Expr::Ident(state.mk_ident(name));
+    let own_cmp = state.mk_member_expr(var(&lhs), &method_call.method.to_string());
+    let has_cmp = state.mk_binary_expr(
+        state.mk_binary_expr(var(&lhs), js::BinaryOp::NotEq, state.mk_null_lit()),
+        js::BinaryOp::LogicalAnd,
+        state.mk_binary_expr(
+            js::Expr::Unary(js::UnaryExpr {
                 span: DUMMY_SP,
-                test: Box::new(is_blank_string),
-                cons: Box::new(js::Expr::Ident(state.mk_ident("NaN"))),
-                alt: Box::new(state.mk_call_expr(
-                    js::Expr::Ident(state.mk_ident("Number")),
-                    vec![s()],
-                )),
-            });
-            let inner = state.mk_arrow_iife(&["v"], inner_body, vec![v_value]);
-            Ok(state.mk_arrow_iife(&["s"], inner, vec![receiver]))
-        }
-        "push" => Ok(state.mk_call_expr(state.mk_member_expr(receiver, "push"), js_args)),
-        "pop" => Ok(state.mk_call_expr(state.mk_member_expr(receiver, "pop"), js_args)),
-        "contains" => Ok(state.mk_call_expr(state.mk_member_expr(receiver, "includes"), js_args)),
-        "to_string" => Ok(state.mk_call_expr(state.mk_member_expr(receiver, "toString"), js_args)),
-        "to_uppercase" => {
-            Ok(state.mk_call_expr(state.mk_member_expr(receiver, "toUpperCase"), js_args))
-        }
-        "to_lowercase" => {
-            Ok(state.mk_call_expr(state.mk_member_expr(receiver, "toLowerCase"), js_args))
-        }
-        "trim" => Ok(state.mk_call_expr(state.mk_member_expr(receiver, "trim"), js_args)),
-        "trim_start" => {
-            Ok(state.mk_call_expr(state.mk_member_expr(receiver, "trimStart"), js_args))
-        }
-        "trim_end" => Ok(state.mk_call_expr(state.mk_member_expr(receiver, "trimEnd"), js_args)),
-        "is_empty" => {
-            // Convert .is_empty() to .length === 0
-            Ok(state.mk_binary_expr(
-                state.mk_member_expr(receiver, "length"),
-                js::BinaryOp::EqEqEq,
-                state.mk_num_lit(0.0)
-            ))
+                op: js::UnaryOp::TypeOf,
+                arg: Box::new(own_cmp.clone()),
+            }),
+            js::BinaryOp::EqEqEq,
+            state.mk_str_lit("function"),
+        ),
+    );
+    let body = js::Expr::Cond(js::CondExpr {
+        span: DUMMY_SP,
+        test: Box::new(has_cmp),
+        cons: Box::new(state.mk_call_expr(own_cmp, vec![var(&rhs)])),
+        alt: Box::new(compare_values(state, var(&lhs), var(&rhs))),
+    });
+    Ok(state.mk_arrow_iife(&[&lhs, &rhs], body, vec![receiver, other]))
+}
+
+/// Whether an expression is an `Ordering`, which is a -1/0/1 number in JS:
+/// an `Ordering::Less`-style path, a `cmp` call, an Ordering method on one,
+/// or a variable declared as `Ordering`
+fn expr_is_ordering(state: &TranspilerState, expr: &Expr) -> bool {
+    match expr {
+        Expr::Paren(paren) => expr_is_ordering(state, &paren.expr),
+        Expr::Path(path) if path.path.segments.len() >= 2 => {
+            path.path.segments[path.path.segments.len() - 2].ident == "Ordering"
         }
-        "remove" => {
-            // Universal remove: use splice for arrays, delete for objects/HashMaps
-            // ((obj, key) => obj.splice ? obj.splice(key, 1)[0] : (delete obj[key] ? undefined : undefined))(receiver, key)
-            if js_args.len() == 1 {
-                // Create parameters for the IIFE
-                let obj_param = js::Pat::Ident(js::BindingIdent {
-                    id: state.mk_ident("obj"),
-                    type_ann: None,
-                });
-                let key_param = js::Pat::Ident(js::BindingIdent {
-                    id: state.mk_ident("key"),
-                    type_ann: None,
-                });
-                
-                // Check if obj.splice exists (array)
-                let splice_check = state.mk_member_expr(js::Expr::Ident(state.mk_ident("obj")), "splice");
-                
-                // Array case: obj.splice(key, 1)[0]
-                let array_remove = js::Expr::Member(js::MemberExpr {
-                    span: DUMMY_SP,
-                    obj: Box::new(state.mk_call_expr(
-                        splice_check.clone(),
-                        vec![
-                            js::Expr::Ident(state.mk_ident("key")),
-                            state.mk_num_lit(1.0),
-                        ],
-                    )),
-                    prop: js::MemberProp::Computed(js::ComputedPropName {
-                        span: DUMMY_SP,
-                        expr: Box::new(state.mk_num_lit(0.0)),
-                    }),
-                });
-                
-                // Object case: delete obj[key] (returns true/false, but we need the old value)
-                // For HashMap compatibility, we should: let oldVal = obj[key]; delete obj[key]; return oldVal;
-                // But for simplicity in IIFE: (function(){let v=obj[key]; delete obj[key]; return v;})()
-                let obj_remove = js::Expr::Call(js::CallExpr {
-                    span: DUMMY_SP,
-                    callee: js::Callee::Expr(Box::new(js::Expr::Paren(js::ParenExpr {
-                        span: DUMMY_SP,
-                        expr: Box::new(js::Expr::Arrow(js::ArrowExpr {
-                            span: DUMMY_SP,
-                            params: vec![],
-                            body: Box::new(js::BlockStmtOrExpr::BlockStmt(js::BlockStmt {
-                                span: DUMMY_SP,
-                                stmts: vec![
-                                    // let v = obj[key];
-                                    js::Stmt::Decl(js::Decl::Var(Box::new(js::VarDecl {
-                                        span: DUMMY_SP,
-                                        kind: js::VarDeclKind::Let,
-                                        declare: false,
-                                        decls: vec![js::VarDeclarator {
-                                            span: DUMMY_SP,
-                                            name: js::Pat::Ident(js::BindingIdent {
-                                                id: state.mk_ident("v"),
-                                                type_ann: None,
-                                            }),
-                                            init: Some(Box::new(js::Expr::Member(js::MemberExpr {
-                                                span: DUMMY_SP,
-                                                obj: Box::new(js::Expr::Ident(state.mk_ident("obj"))),
-                                                prop: js::MemberProp::Computed(js::ComputedPropName {
-                                                    span: DUMMY_SP,
-                                                    expr: Box::new(js::Expr::Ident(state.mk_ident("key"))),
-                                                }),
-                                            }))),
-                                            definite: false,
-                                        }],
-                                        ctxt: SyntaxContext::empty(),
-                                    }))),
-                                    // delete obj[key];
-                                    js::Stmt::Expr(js::ExprStmt {
-                                        span: DUMMY_SP,
-                                        expr: Box::new(js::Expr::Unary(js::UnaryExpr {
-                                            span: DUMMY_SP,
-                                            op: js::UnaryOp::Delete,
-                                            arg: Box::new(js::Expr::Member(js::MemberExpr {
-                                                span: DUMMY_SP,
-                                                obj: Box::new(js::Expr::Ident(state.mk_ident("obj"))),
-                                                prop: js::MemberProp::Computed(js::ComputedPropName {
-                                                    span: DUMMY_SP,
-                                                    expr: Box::new(js::Expr::Ident(state.mk_ident("key"))),
-                                                }),
-                                            })),
-                                        })),
-                                    }),
-                                    // return v;
-                                    js::Stmt::Return(js::ReturnStmt {
-                                        span: DUMMY_SP,
-                                        arg: Some(Box::new(js::Expr::Ident(state.mk_ident("v")))),
-                                    }),
-                                ],
-                                ctxt: SyntaxContext::empty(),
-                            })),
-                            is_async: false,
-                            is_generator: false,
-                            ctxt: SyntaxContext::empty(),
-                            return_type: None,
-                            type_params: None,
-                        })),
-                    }))),
-                    args: vec![],
-                    type_args: None,
-                    ctxt: SyntaxContext::empty(),
-                });
-                
-                // Conditional: obj.splice ? array_remove : obj_remove
-                let conditional = js::Expr::Cond(js::CondExpr {
-                    span: DUMMY_SP,
-                    test: Box::new(splice_check),
-                    cons: Box::new(array_remove),
-                    alt: Box::new(obj_remove),
-                });
-                
-                // Create IIFE: (obj, key) => conditional
-                let iife = js::ArrowExpr {
-                    span: DUMMY_SP,
-                    params: vec![obj_param, key_param],
-                    body: Box::new(js::BlockStmtOrExpr::Expr(Box::new(conditional))),
-                    is_async: false,
-                    is_generator: false,
-                    ctxt: SyntaxContext::empty(),
-                    return_type: None,
-                    type_params: None,
-                };
-                
-                // Call the IIFE with receiver and key: ((obj, key) => ...)(receiver, key)
-                Ok(js::Expr::Call(js::CallExpr {
-                    span: DUMMY_SP,
-                    callee: js::Callee::Expr(Box::new(js::Expr::Paren(js::ParenExpr {
-                        span: DUMMY_SP,
-                        expr: Box::new(js::Expr::Arrow(iife)),
-                    }))),
-                    args: vec![
-                        js::ExprOrSpread {
-                            spread: None,
-                            expr: Box::new(receiver),
-                        },
-                        js::ExprOrSpread {
-                            spread: None,
-                            expr: Box::new(js_args[0].clone()),
-                        },
-                    ],
-                    type_args: None,
-                    ctxt: SyntaxContext::empty(),
-                }))
-            } else if js_args.is_empty() {
-                // Zero-argument remove(): DOM Element.remove() - removes the
-                // element from the document. Pass through as a method call.
-                Ok(state.mk_call_expr(state.mk_member_expr(receiver, "remove"), vec![]))
-            } else {
-                Err("remove() expects exactly one argument".to_string())
-            }
+        Expr::MethodCall(call) => match call.method.to_string().as_str() {
+            "cmp" => call.args.len() == 1,
+            "reverse" | "then" | "then_with" => expr_is_ordering(state, &call.receiver),
+            _ => false,
+        },
+        _ => known_receiver_type(state, expr).is_some_and(|ty| {
+            matches!(ty, Type::Path(type_path)
+                if type_path.path.segments.last().is_some_and(|s| s.ident == "Ordering"))
+        }),
+    }
+}
+
+/// Ordering methods on the -1/0/1 number: `o.reverse()` ->
+/// `((o)=>-o)(o)`, `o.then(p)` -> `((o, p)=>o || p)(o, p)`,
+/// `o.then_with(f)` -> `((o, f)=>o || f())(o, f)`
+fn ordering_method(
+    state: &mut TranspilerState,
+    method_name: &str,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> js::Expr {
+    let var = |name: &str| js::Expr::Ident(state.mk_ident(name));
+    match method_name {
+        "reverse" => {
+            let negated = js::Expr::Unary(js::UnaryExpr {
+                span: DUMMY_SP,
+                op: js::UnaryOp::Minus,
+                arg: Box::new(var("o")),
+            });
+            state.mk_arrow_iife(&["o"], negated, vec![receiver])
         }
-        "insert" => {
-            // Universal insert: use splice for arrays, property assignment for objects/HashMaps
-            // ((obj, key, val) => obj.splice ? obj.splice(key, 0, val) : (obj[key] = val))(receiver, key, value)
-            if js_args.len() == 2 {
-                // Create parameters for the IIFE
-                let obj_param = js::Pat::Ident(js::BindingIdent {
-                    id: state.mk_ident("obj"),
-                    type_ann: None,
-                });
-                let key_param = js::Pat::Ident(js::BindingIdent {
-                    id: state.mk_ident("key"),
-                    type_ann: None,
-                });
-                let val_param = js::Pat::Ident(js::BindingIdent {
-                    id: state.mk_ident("val"),
-                    type_ann: None,
-                });
-                
-                // Check if obj.splice exists (array)
-                let splice_check = state.mk_member_expr(js::Expr::Ident(state.mk_ident("obj")), "splice");
-                
-                // Array case: obj.splice(key, 0, val)
-                let array_insert = state.mk_call_expr(
-                    splice_check.clone(),
-                    vec![
-                        js::Expr::Ident(state.mk_ident("key")),
-                        state.mk_num_lit(0.0),
-                        js::Expr::Ident(state.mk_ident("val")),
-                    ],
-                );
-                
-                // Object case: obj[key] = val
-                let obj_assignment = js::Expr::Assign(js::AssignExpr {
-                    span: DUMMY_SP,
-                    op: js::AssignOp::Assign,
-                    left: js::AssignTarget::Simple(js::SimpleAssignTarget::Member(js::MemberExpr {
-                        span: DUMMY_SP,
-                        obj: Box::new(js::Expr::Ident(state.mk_ident("obj"))),
-                        prop: js::MemberProp::Computed(js::ComputedPropName {
-                            span: DUMMY_SP,
-                            expr: Box::new(js::Expr::Ident(state.mk_ident("key"))),
-                        }),
-                    })),
-                    right: Box::new(js::Expr::Ident(state.mk_ident("val"))),
-                });
-                
-                // Conditional: obj.splice ? array_insert : obj_assignment
-                let conditional = js::Expr::Cond(js::CondExpr {
-                    span: DUMMY_SP,
-                    test: Box::new(splice_check),
-                    cons: Box::new(array_insert),
-                    alt: Box::new(obj_assignment),
-                });
-                
-                // Create IIFE: (obj, key, val) => conditional
-                let iife = js::ArrowExpr {
-                    span: DUMMY_SP,
-                    params: vec![obj_param, key_param, val_param],
-                    body: Box::new(js::BlockStmtOrExpr::Expr(Box::new(conditional))),
-                    is_async: false,
-                    is_generator: false,
-                    type_params: None,
-                    return_type: None,
-                    ctxt: SyntaxContext::empty(),
-                };
-                
-                // Call the IIFE with arguments: ((obj, key, val) => ...)(receiver, key, value)
-                Ok(js::Expr::Call(js::CallExpr {
-                    span: DUMMY_SP,
-                    callee: js::Callee::Expr(Box::new(js::Expr::Paren(js::ParenExpr {
-                        span: DUMMY_SP,
-                        expr: Box::new(js::Expr::Arrow(iife)),
-                    }))),
-                    args: vec![
-                        js::ExprOrSpread {
-                            spread: None,
-                            expr: Box::new(receiver),
-                        },
-                        js::ExprOrSpread {
-                            spread: None,
-                            expr: Box::new(js_args[0].clone()),
-                        },
-                        js::ExprOrSpread {
-                            spread: None,
-                            expr: Box::new(js_args[1].clone()),
-                        },
-                    ],
-                    type_args: None,
-                    ctxt: SyntaxContext::empty(),
-                }))
-            } else {
-                Err("insert() expects exactly two arguments".to_string())
-            }
+        "then" => {
+            let body = state.mk_binary_expr(var("o"), js::BinaryOp::LogicalOr, var("p"));
+            state.mk_arrow_iife(&["o", "p"], body, std::iter::once(receiver).chain(js_args).collect())
         }
-        "get" => {
-            // Universal get: use .get() for Map objects, bracket notation for objects/HashMaps
-            // ((obj, key) => obj.get && typeof obj.get === 'function' ? obj.get(key) : obj[key])(receiver, key)
-            if js_args.len() == 1 {
-                // Create parameters for the IIFE
-                let obj_param = js::Pat::Ident(js::BindingIdent {
-                    id: state.mk_ident("obj"),
-                    type_ann: None,
-                });
-                let key_param = js::Pat::Ident(js::BindingIdent {
-                    id: state.mk_ident("key"),
-                    type_ann: None,
-                });
-                
-                // Check if obj.get exists and is a function (Map objects)
-                let get_exists = state.mk_member_expr(js::Expr::Ident(state.mk_ident("obj")), "get");
-                let typeof_get = js::Expr::Unary(js::UnaryExpr {
-                    span: DUMMY_SP,
-                    op: js::UnaryOp::TypeOf,
-                    arg: Box::new(state.mk_member_expr(js::Expr::Ident(state.mk_ident("obj")), "get")),
-                });
-                let is_function = state.mk_binary_expr(
-                    typeof_get,
-                    js::BinaryOp::EqEqEq,
-                    state.mk_str_lit("function")
-                );
-                let get_check = state.mk_binary_expr(
-                    get_exists,
-                    js::BinaryOp::LogicalAnd,
-                    is_function
-                );
-                
-                // Map case: obj.get(key) 
-                let map_get = state.mk_call_expr(
-                    state.mk_member_expr(js::Expr::Ident(state.mk_ident("obj")), "get"),
-                    vec![js::Expr::Ident(state.mk_ident("key"))],
-                );
-                
-                // Object case: obj[key]
-                let obj_get = js::Expr::Member(js::MemberExpr {
-                    span: DUMMY_SP,
-                    obj: Box::new(js::Expr::Ident(state.mk_ident("obj"))),
-                    prop: js::MemberProp::Computed(js::ComputedPropName {
-                        span: DUMMY_SP,
-                        expr: Box::new(js::Expr::Ident(state.mk_ident("key"))),
-                    }),
-                });
-                
-                // Conditional: obj.get && typeof obj.get === 'function' ? obj.get(key) : obj[key]
-                let conditional = js::Expr::Cond(js::CondExpr {
-                    span: DUMMY_SP,
-                    test: Box::new(get_check),
-                    cons: Box::new(map_get),
-                    alt: Box::new(obj_get),
-                });
-                
-                // Create IIFE: (obj, key) => conditional
-                let iife = js::ArrowExpr {
-                    span: DUMMY_SP,
-                    params: vec![obj_param, key_param],
-                    body: Box::new(js::BlockStmtOrExpr::Expr(Box::new(conditional))),
-                    is_async: false,
-                    is_generator: false,
-                    ctxt: SyntaxContext::empty(),
-                    return_type: None,
-                    type_params: None,
-                };
-                
-                // Call the IIFE with receiver and key: ((obj, key) => ...)(receiver, key)
-                Ok(js::Expr::Call(js::CallExpr {
-                    span: DUMMY_SP,
-                    callee: js::Callee::Expr(Box::new(js::Expr::Paren(js::ParenExpr {
-                        span: DUMMY_SP,
-                        expr: Box::new(js::Expr::Arrow(iife)),
-                    }))),
-                    args: vec![
-                        js::ExprOrSpread {
-                            spread: None,
-                            expr: Box::new(receiver),
-                        },
-                        js::ExprOrSpread {
-                            spread: None,
-                            expr: Box::new(js_args[0].clone()),
-                        },
-                    ],
-                    type_args: None,
-                    ctxt: SyntaxContext::empty(),
-                }))
-            } else {
-                Err("get() expects exactly one argument".to_string())
-            }
+        _ => {
+            let next = state.mk_call_expr(var("f"), vec![]);
+            let body = state.mk_binary_expr(var("o"), js::BinaryOp::LogicalOr, next);
+            state.mk_arrow_iife(&["o", "f"], body, std::iter::once(receiver).chain(js_args).collect())
         }
+    }
+}
 
-        "keys" => {
-            // HashMap.keys() -> Object.keys(HashMap)
-            // For JavaScript objects, we need to use Object.keys()
-            if js_args.is_empty() {
-                Ok(state.mk_call_expr(
-                    state.mk_member_expr(js::Expr::Ident(state.mk_ident("Object")), "keys"),
-                    vec![receiver]
-                ))
-            } else {
-                Err("keys() expects no arguments".to_string())
-            }
+/// The JS type of a receiver: "number", "string" (chars too) or "boolean"
+/// for a literal or a variable declared as one, what `format_rust_type`
+/// says for a variable of any other declared type, `None` when nothing is
+/// known
+fn receiver_js_type(state: &TranspilerState, receiver: &Expr) -> Option<String> {
+    match receiver {
+        Expr::Lit(lit) => {
+            return Some(
+                match lit.lit {
+                    syn::Lit::Int(_) | syn::Lit::Float(_) => "number",
+                    syn::Lit::Bool(_) => "boolean",
+                    _ => "string",
+                }
+                .to_string(),
+            );
         }
+        Expr::Reference(reference) => return receiver_js_type(state, &reference.expr),
+        Expr::Paren(paren) => return receiver_js_type(state, &paren.expr),
+        Expr::Unary(unary) if matches!(unary.op, syn::UnOp::Neg(_)) => {
+            return receiver_js_type(state, &unary.expr);
+        }
+        _ => {}
+    }
+    let mut ty = known_receiver_type(state, receiver)?;
+    while let Type::Reference(type_ref) = ty {
+        ty = &type_ref.elem;
+    }
+    let name = match ty {
+        Type::Path(type_path) => type_path.path.segments.last().map(|s| s.ident.to_string()),
+        _ => None,
+    };
+    Some(match name.as_deref() {
+        Some("char") => "string".to_string(),
+        Some(name) if is_integer_type_name(name) => "number".to_string(),
+        _ => format_rust_type(ty),
+    })
+}
 
-        "starts_with" => {
-            Ok(state.mk_call_expr(state.mk_member_expr(receiver, "startsWith"), js_args))
-        }
-        "ends_with" => Ok(state.mk_call_expr(state.mk_member_expr(receiver, "endsWith"), js_args)),
-        "replace" => Ok(state.mk_call_expr(state.mk_member_expr(receiver, "replace"), js_args)),
-        "split" => Ok(state.mk_call_expr(state.mk_member_expr(receiver, "split"), js_args)),
-        "join" => Ok(state.mk_call_expr(state.mk_member_expr(receiver, "join"), js_args)),
-        "map" => Ok(state.mk_call_expr(state.mk_member_expr(receiver, "map"), js_args)),
-        "filter" => Ok(state.mk_call_expr(state.mk_member_expr(receiver, "filter"), js_args)),
-        "find" => Ok(state.mk_call_expr(state.mk_member_expr(receiver, "find"), js_args)),
-        "iter" => {
-            // .iter() is typically a no-op in JavaScript
-            Ok(receiver)
-        }
-        "collect" => {
-            // .collect() is typically a no-op in JavaScript
-            Ok(receiver)
-        }
-        "is_some" => {
-            // Check if receiver is a function call to avoid duplication
-            if matches!(method_call.receiver.as_ref(), syn::Expr::Call(_)) {
-                // Create IIFE to cache function call result: (val => val !== null && val !== undefined)(function_call())
-                let param = js::Pat::Ident(js::BindingIdent {
-                    id: state.mk_ident("val"),
-                    type_ann: None,
-                });
-                
-                let null_check = state.mk_binary_expr(
-                    js::Expr::Ident(state.mk_ident("val")), 
-                    js::BinaryOp::NotEqEq, 
-                    state.mk_null_lit()
-                );
-                let undefined_check = state.mk_binary_expr(
-                    js::Expr::Ident(state.mk_ident("val")), 
-                    js::BinaryOp::NotEqEq, 
-                    state.mk_undefined()
-                );
-                let condition = state.mk_binary_expr(null_check, js::BinaryOp::LogicalAnd, undefined_check);
-                
-                let iife = js::ArrowExpr {
+/// Whether a receiver is a number, string, char or bool: `Some(true)` for
+/// a literal or a variable declared as one, `Some(false)` for a variable of
+/// any other declared type, `None` when nothing is known
+fn receiver_is_primitive(state: &TranspilerState, receiver: &Expr) -> Option<bool> {
+    receiver_js_type(state, receiver)
+        .map(|js_type| matches!(js_type.as_str(), "number" | "string" | "boolean"))
+}
+
+/// The `cmp` comparison of two values, -1, 0 or 1
+fn compare_values(state: &mut TranspilerState, a: js::Expr, b: js::Expr) -> js::Expr {
+    let lhs = state.generate_temp_var();
+    let rhs = state.generate_temp_var();
+    let var = |name: &str| js::Expr::Ident(state.mk_ident(name));
+    let ordering = js::Expr::Cond(js::CondExpr {
+        span: DUMMY_SP,
+        test: Box::new(state.mk_binary_expr(var(&lhs), js::BinaryOp::Lt, var(&rhs))),
+        cons: Box::new(state.mk_num_lit(-1.0)),
+        alt: Box::new(js::Expr::Cond(js::CondExpr {
+            span: DUMMY_SP,
+            test: Box::new(state.mk_binary_expr(var(&lhs), js::BinaryOp::Gt, var(&rhs))),
+            cons: Box::new(state.mk_num_lit(1.0)),
+            alt: Box::new(state.mk_num_lit(0.0)),
+        })),
+    });
+    state.mk_arrow_iife(&[&lhs, &rhs], ordering, vec![a, b])
+}
+
+/// `v.sort_by(f)` -> `v.sort(f)`, the closure already returning the
+/// comparator number through `cmp`. `v.sort_by_key(f)` compares the keys
+/// the same way, so string keys sort too:
+/// `((key)=>v.sort((a, b)=>cmp(key(a), key(b))))(f)`
+fn map_sort_by(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    let method = method_call.method.to_string();
+    let Ok([f]) = <[js::Expr; 1]>::try_from(js_args) else {
+        return Err(format!("{method}() expects exactly one argument"));
+    };
+    let sort = state.mk_member_expr(receiver, "sort");
+    if !method.ends_with("_key") {
+        return Ok(state.mk_call_expr(sort, vec![f]));
+    }
+    let key = state.generate_temp_var();
+    let a = state.generate_temp_var();
+    let b = state.generate_temp_var();
+    let key_of = |state: &TranspilerState, item: &str| {
+        state.mk_call_expr(
+            js::Expr::Ident(state.mk_ident(&key)),
+            vec![js::Expr::Ident(state.mk_ident(item))],
+        )
+    };
+    let (key_a, key_b) = (key_of(state, &a), key_of(state, &b));
+    let ordering = compare_values(state, key_a, key_b);
+    let comparator = state.mk_arrow(&[&a, &b], ordering);
+    let sorted = state.mk_call_expr(sort, vec![comparator]);
+    Ok(state.mk_arrow_iife(&[&key], sorted, vec![f]))
+}
+
+/// `min_by_key(f)`, `max_by_key(f)`, `min_by(cmp)` and `max_by(cmp)`
+/// reduce to the chosen element, or null for an empty array:
+/// `((items, f)=>items.length === 0 ? null : items.reduce((best, x)=>..))(v, f)`.
+/// Like Rust, ties keep the first minimum and the last maximum.
+fn map_min_max_by(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    let method = method_call.method.to_string();
+    let Ok([f]) = <[js::Expr; 1]>::try_from(js_args) else {
+        return Err(format!("{method}() expects exactly one argument"));
+    };
+    let [items, func, best, x] = [(); 4].map(|_| state.generate_temp_var());
+    let var = |state: &TranspilerState, name: &str| js::Expr::Ident(state.mk_ident(name));
+    let call_f = |state: &TranspilerState, args: Vec<js::Expr>| {
+        state.mk_call_expr(var(state, &func), args)
+    };
+    let ordering = if method.ends_with("_key") {
+        let (key_x, key_best) = (
+            call_f(state, vec![var(state, &x)]),
+            call_f(state, vec![var(state, &best)]),
+        );
+        compare_values(state, key_x, key_best)
+    } else {
+        call_f(state, vec![var(state, &x), var(state, &best)])
+    };
+    let op = if method.starts_with("min") {
+        js::BinaryOp::Lt
+    } else {
+        js::BinaryOp::GtEq
+    };
+    let pick = js::Expr::Cond(js::CondExpr {
+        span: DUMMY_SP,
+        test: Box::new(state.mk_binary_expr(ordering, op, state.mk_num_lit(0.0))),
+        cons: Box::new(var(state, &x)),
+        alt: Box::new(var(state, &best)),
+    });
+    let reducer = state.mk_arrow(&[&best, &x], pick);
+    let reduced = state.mk_call_expr(state.mk_member_expr(var(state, &items), "reduce"), vec![reducer]);
+    let is_empty = state.mk_binary_expr(
+        state.mk_member_expr(var(state, &items), "length"),
+        js::BinaryOp::EqEqEq,
+        state.mk_num_lit(0.0),
+    );
+    let chosen = js::Expr::Cond(js::CondExpr {
+        span: DUMMY_SP,
+        test: Box::new(is_empty),
+        cons: Box::new(state.mk_null_lit()),
+        alt: Box::new(reduced),
+    });
+    Ok(state.mk_arrow_iife(&[&items, &func], chosen, vec![receiver, f]))
+}
+
+/// `take_while(f)` / `skip_while(f)`: a loop finds the end of the leading
+/// run of elements satisfying `f`, and the array before (or after) it is
+/// the result.
+fn map_take_skip_while(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    let method = method_call.method.to_string();
+    let Ok([f]) = <[js::Expr; 1]>::try_from(js_args) else {
+        return Err(format!("{method}() expects exactly one argument"));
+    };
+    let [items, pred, end] = [(); 3].map(|_| state.generate_temp_var());
+    let var = |state: &TranspilerState, name: &str| js::Expr::Ident(state.mk_ident(name));
+
+    // while (_end < _items.length && _pred(_items[_end])) _end++;
+    let current = js::Expr::Member(js::MemberExpr {
+        span: DUMMY_SP,
+        obj: Box::new(var(state, &items)),
+        prop: js::MemberProp::Computed(js::ComputedPropName {
+            span: DUMMY_SP,
+            expr: Box::new(var(state, &end)),
+        }),
+    });
+    let test = state.mk_binary_expr(
+        state.mk_binary_expr(
+            var(state, &end),
+            js::BinaryOp::Lt,
+            state.mk_member_expr(var(state, &items), "length"),
+        ),
+        js::BinaryOp::LogicalAnd,
+        state.mk_call_expr(var(state, &pred), vec![current]),
+    );
+    let advance = state.mk_expr_stmt(js::Expr::Update(js::UpdateExpr {
+        span: DUMMY_SP,
+        op: js::UpdateOp::PlusPlus,
+        prefix: false,
+        arg: Box::new(var(state, &end)),
+    }));
+    let while_stmt = js::Stmt::While(js::WhileStmt {
+        span: DUMMY_SP,
+        test: Box::new(test),
+        body: Box::new(advance),
+    });
+
+    let slice_args = if method == "take_while" {
+        vec![state.mk_num_lit(0.0), var(state, &end)]
+    } else {
+        vec![var(state, &end)]
+    };
+    let result = state.mk_call_expr(state.mk_member_expr(var(state, &items), "slice"), slice_args);
+    let stmts = vec![
+        state.mk_var_decl(&items, Some(receiver), true),
+        state.mk_var_decl(&pred, Some(f), true),
+        state.mk_var_decl(&end, Some(state.mk_num_lit(0.0)), false),
+        while_stmt,
+        state.mk_return_stmt(Some(result)),
+    ];
+    Ok(state.mk_iife(stmts))
+}
+
+/// `x.clamp(lo, hi)` -> `Math.min(Math.max(x, lo), hi)`
+fn map_clamp(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    if js_args.len() != 2 || receiver_is_primitive(state, &method_call.receiver) == Some(false) {
+        return Ok(plain_method_call(state, method_call, receiver, js_args));
+    }
+    let [lo, hi] = <[js::Expr; 2]>::try_from(js_args).unwrap();
+    let math = |f: &str| state.mk_member_expr(js::Expr::Ident(state.mk_ident("Math")), f);
+    let at_least_lo = state.mk_call_expr(math("max"), vec![receiver, lo]);
+    Ok(state.mk_call_expr(math("min"), vec![at_least_lo, hi]))
+}
+
+fn map_len(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    if receiver_is_set(state, method_call) {
+        return Ok(set_method_call(state, method_call, receiver, js_args));
+    }
+    // Use IIFE to evaluate receiver once and handle both arrays/strings and objects
+    // ((obj) => obj.length !== undefined ? obj.length : Object.keys(obj).length)(receiver)
+    // The parameter gets a fresh temp name so it never reads like (or, through
+    // a closure in the argument, captures) a user variable called `obj`
+    let obj = state.generate_temp_var();
+
+    // Create parameter for the IIFE
+    let obj_param = js::Pat::Ident(js::BindingIdent {
+        id: state.mk_ident(&obj),
+        type_ann: None,
+    });
+    
+    // Create obj.length access
+    let length_access = state.mk_member_expr(js::Expr::Ident(state.mk_ident(&obj)), "length");
+    
+    // Create undefined check: obj.length !== undefined
+    let undefined_check = state.mk_binary_expr(
+        length_access.clone(),
+        js::BinaryOp::NotEqEq,
+        state.mk_undefined()
+    );
+    
+    // Create Object.keys(obj).length for objects
+    let object_keys = state.mk_call_expr(
+        state.mk_member_expr(js::Expr::Ident(state.mk_ident("Object")), "keys"),
+        vec![js::Expr::Ident(state.mk_ident(&obj))]
+    );
+    let object_keys_length = state.mk_member_expr(object_keys, "length");
+    
+    // Create conditional expression
+    let conditional = js::Expr::Cond(js::CondExpr {
+        span: DUMMY_SP,
+        test: Box::new(undefined_check),
+        cons: Box::new(length_access),
+        alt: Box::new(object_keys_length),
+    });
+    
+    // Create IIFE: (obj) => conditional
+    let iife = js::ArrowExpr {
+        span: DUMMY_SP,
+        params: vec![obj_param],
+        body: Box::new(js::BlockStmtOrExpr::Expr(Box::new(conditional))),
+        is_async: false,
+        is_generator: false,
+        type_params: None,
+        return_type: None,
+        ctxt: SyntaxContext::empty(),
+    };
+    
+    // Call the IIFE with receiver: ((obj) => ...)(receiver)
+    Ok(js::Expr::Call(js::CallExpr {
+        span: DUMMY_SP,
+        callee: js::Callee::Expr(Box::new(js::Expr::Paren(js::ParenExpr {
+            span: DUMMY_SP,
+            expr: Box::new(js::Expr::Arrow(iife)),
+        }))),
+        args: vec![js::ExprOrSpread {
+            spread: None,
+            expr: Box::new(receiver),
+        }],
+        type_args: None,
+        ctxt: SyntaxContext::empty(),
+    }))
+}
+
+fn map_clone(
+    _state: &mut TranspilerState,
+    _method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    _js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    // .clone() is typically a no-op in JavaScript for primitives
+    Ok(receiver)
+}
+
+/// `v.to_vec()` -> `Array.from(v)`, a copy that is a plain array even when
+/// `v` is a typed array. A receiver known not to be a Vec, array or slice
+/// keeps its own `to_vec`.
+fn map_to_vec(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    let other_type = known_receiver_type(state, &method_call.receiver).is_some_and(|ty| {
+        let ty = match ty {
+            Type::Reference(type_ref) => &*type_ref.elem,
+            ty => ty,
+        };
+        !matches!(ty, Type::Slice(_)) && format_rust_type(ty) != "Array"
+    });
+    if !js_args.is_empty() || other_type {
+        return Ok(plain_method_call(state, method_call, receiver, js_args));
+    }
+    let array_from = state.mk_member_expr(js::Expr::Ident(state.mk_ident("Array")), "from");
+    Ok(state.mk_call_expr(array_from, vec![receiver]))
+}
+
+/// `it.sum()` -> `it.reduce((a, b)=>a + b, 0)`, and `product()` likewise
+/// with `*` and `1`
+fn map_fold_arithmetic(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    if !js_args.is_empty() {
+        return Ok(plain_method_call(state, method_call, receiver, js_args));
+    }
+    let (op, identity) = if method_call.method == "sum" {
+        (js::BinaryOp::Add, 0.0)
+    } else {
+        (js::BinaryOp::Mul, 1.0)
+    };
+    let acc = state.generate_temp_var();
+    let item = state.generate_temp_var();
+    let step = state.mk_binary_expr(
+        js::Expr::Ident(state.mk_ident(&acc)),
+        op,
+        js::Expr::Ident(state.mk_ident(&item)),
+    );
+    let reducer = state.mk_arrow(&[&acc, &item], step);
+    Ok(state.mk_call_expr(
+        state.mk_member_expr(receiver, "reduce"),
+        vec![reducer, state.mk_num_lit(identity)],
+    ))
+}
+
+fn map_unwrap_cell(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    if !js_args.is_empty() {
+        return Ok(plain_method_call(state, method_call, receiver, js_args));
+    }
+    // RefCell::new / Mutex::new are transparent, so .borrow(), .borrow_mut()
+    // and .lock() hand back the value itself (a following .unwrap() on the
+    // lock result passes a plain value straight through)
+    Ok(receiver)
+}
+
+fn map_as_str(
+    state: &mut TranspilerState,
+    _method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    _js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    // Convert .as_str() to JavaScript string conversion: String(receiver)
+    Ok(state.mk_call_expr(
+        js::Expr::Ident(state.mk_ident("String")),
+        vec![receiver]
+    ))
+}
+
+// str.parse::<N>() -> a Result-shaped value ({ok: n} / {error: msg}),
+// the same representation Ok()/Err() transpile to, so `match
+// s.parse() { Ok(n) => .., Err(e) => .. }`, if-let, .unwrap() and
+// .unwrap_or() all behave. Zero-arg only, so JSON.parse(text) keeps
+// its native meaning. Emitted JS:
+//   ((s) => ((v) => Number.isNaN(v)
+//       ? { error: "invalid number: " + s } : { ok: v }
+//     )((typeof s === "string" && s.trim() === "") ? NaN : Number(s))
+//   )(receiver)
+fn map_parse(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    if !js_args.is_empty() {
+        return Ok(plain_method_call(state, method_call, receiver, js_args));
+    }
+    let target = turbofish_type_name(method_call);
+    match target.as_deref() {
+        // Parsing into a String can't fail
+        Some("String") => return Ok(result_object(state, "ok", receiver)),
+        Some("bool") => return Ok(parse_bool(state, receiver)),
+        _ => {}
+    }
+    // Integer targets also reject fractions ("1.5".parse::<i32>() is an Err)
+    let number_check = match target.as_deref() {
+        Some(name) if is_integer_type_name(name) => "isInteger",
+        _ => "isNaN",
+    };
+    let s = || js::Expr::Ident(state.mk_ident("s"));
+    let v = || js::Expr::Ident(state.mk_ident("v"));
+    let mk_obj = |field: &str, value: js::Expr| {
+        js::Expr::Object(js::ObjectLit {
+            span: DUMMY_SP,
+            props: vec![js::PropOrSpread::Prop(Box::new(js::Prop::KeyValue(
+                js::KeyValueProp {
+                    key: js::PropName::Ident(state.mk_ident_name(field)),
+                    value: Box::new(value),
+                },
+            )))],
+        })
+    };
+    // Number.isNaN(v) ? {error: "invalid number: " + s} : {ok: v}, or
+    // !Number.isInteger(v) ? ... for an integer type
+    let check = state.mk_call_expr(
+        state.mk_member_expr(js::Expr::Ident(state.mk_ident("Number")), number_check),
+        vec![v()],
+    );
+    let is_invalid = if number_check == "isInteger" {
+        js::Expr::Unary(js::UnaryExpr {
+            span: DUMMY_SP,
+            op: js::UnaryOp::Bang,
+            arg: Box::new(check),
+        })
+    } else {
+        check
+    };
+    let inner_body = js::Expr::Cond(js::CondExpr {
+        span: DUMMY_SP,
+        test: Box::new(is_invalid),
+        cons: Box::new(mk_obj(
+            "error",
+            state.mk_binary_expr(
+                state.mk_str_lit("invalid number: "),
+                js::BinaryOp::Add,
+                s(),
+            ),
+        )),
+        alt: Box::new(mk_obj("ok", v())),
+    });
+    // (typeof s === "string" && s.trim() === "") ? NaN : Number(s)
+    // (Number("") is 0, but Rust's "".parse() is an error)
+    let is_blank_string = state.mk_binary_expr(
+        state.mk_binary_expr(
+            js::Expr::Unary(js::UnaryExpr {
+                span: DUMMY_SP,
+                op: js::UnaryOp::TypeOf,
+                arg: Box::new(s()),
+            }),
+            js::BinaryOp::EqEqEq,
+            state.mk_str_lit("string"),
+        ),
+        js::BinaryOp::LogicalAnd,
+        state.mk_binary_expr(
+            state.mk_call_expr(state.mk_member_expr(s(), "trim"), vec![]),
+            js::BinaryOp::EqEqEq,
+            state.mk_str_lit(""),
+        ),
+    );
+    let v_value = js::Expr::Cond(js::CondExpr {
+        span: DUMMY_SP,
+        test: Box::new(is_blank_string),
+        cons: Box::new(js::Expr::Ident(state.mk_ident("NaN"))),
+        alt: Box::new(state.mk_call_expr(
+            js::Expr::Ident(state.mk_ident("Number")),
+            vec![s()],
+        )),
+    });
+    let inner = state.mk_arrow_iife(&["v"], inner_body, vec![v_value]);
+    Ok(state.mk_arrow_iife(&["s"], inner, vec![receiver]))
+}
+
+/// `s.parse::<bool>()`:
+/// `((s) => s === "true" || s === "false" ? {ok: s === "true"} : {error: "invalid bool: " + s})(receiver)`
+fn parse_bool(state: &TranspilerState, receiver: js::Expr) -> js::Expr {
+    let s = || js::Expr::Ident(state.mk_ident("s"));
+    let is = |text: &str| state.mk_binary_expr(s(), js::BinaryOp::EqEqEq, state.mk_str_lit(text));
+    let body = js::Expr::Cond(js::CondExpr {
+        span: DUMMY_SP,
+        test: Box::new(state.mk_binary_expr(is("true"), js::BinaryOp::LogicalOr, is("false"))),
+        cons: Box::new(result_object(state, "ok", is("true"))),
+        alt: Box::new(result_object(
+            state,
+            "error",
+            state.mk_binary_expr(state.mk_str_lit("invalid bool: "), js::BinaryOp::Add, s()),
+        )),
+    });
+    state.mk_arrow_iife(&["s"], body, vec![receiver])
+}
+
+fn map_is_empty(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    if receiver_is_set(state, method_call) {
+        return Ok(set_method_call(state, method_call, receiver, js_args));
+    }
+    // Convert .is_empty() to .length === 0
+    Ok(state.mk_binary_expr(
+        state.mk_member_expr(receiver, "length"),
+        js::BinaryOp::EqEqEq,
+        state.mk_num_lit(0.0)
+    ))
+}
+
+fn map_remove(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    if receiver_is_set(state, method_call) {
+        return Ok(set_method_call(state, method_call, receiver, js_args));
+    }
+    // Universal remove: use splice for arrays, delete for objects/HashMaps
+    // ((obj, key) => obj.splice ? obj.splice(key, 1)[0] : (delete obj[key] ? undefined : undefined))(receiver, key)
+    if js_args.len() == 1 {
+        // Create parameters for the IIFE
+        let obj_param = js::Pat::Ident(js::BindingIdent {
+            id: state.mk_ident("obj"),
+            type_ann: None,
+        });
+        let key_param = js::Pat::Ident(js::BindingIdent {
+            id: state.mk_ident("key"),
+            type_ann: None,
+        });
+        
+        // Check if obj.splice exists (array)
+        let splice_check = state.mk_member_expr(js::Expr::Ident(state.mk_ident("obj")), "splice");
+        
+        // Array case: obj.splice(key, 1)[0]
+        let array_remove = js::Expr::Member(js::MemberExpr {
+            span: DUMMY_SP,
+            obj: Box::new(state.mk_call_expr(
+                splice_check.clone(),
+                vec![
+                    js::Expr::Ident(state.mk_ident("key")),
+                    state.mk_num_lit(1.0),
+                ],
+            )),
+            prop: js::MemberProp::Computed(js::ComputedPropName {
+                span: DUMMY_SP,
+                expr: Box::new(state.mk_num_lit(0.0)),
+            }),
+        });
+        
+        // Object case: delete obj[key] (returns true/false, but we need the old value)
+        // For HashMap compatibility, we should: let oldVal = obj[key]; delete obj[key]; return oldVal;
+        // But for simplicity in IIFE: (function(){let v=obj[key]; delete obj[key]; return v;})()
+        let obj_remove = js::Expr::Call(js::CallExpr {
+            span: DUMMY_SP,
+            callee: js::Callee::Expr(Box::new(js::Expr::Paren(js::ParenExpr {
+                span: DUMMY_SP,
+                expr: Box::new(js::Expr::Arrow(js::ArrowExpr {
                     span: DUMMY_SP,
-                    params: vec![param],
-                    body: Box::new(js::BlockStmtOrExpr::Expr(Box::new(condition))),
+                    params: vec![],
+                    body: Box::new(js::BlockStmtOrExpr::BlockStmt(js::BlockStmt {
+                        span: DUMMY_SP,
+                        stmts: vec![
+                            // let v = obj[key];
+                            js::Stmt::Decl(js::Decl::Var(Box::new(js::VarDecl {
+                                span: DUMMY_SP,
+                                kind: js::VarDeclKind::Let,
+                                declare: false,
+                                decls: vec![js::VarDeclarator {
+                                    span: DUMMY_SP,
+                                    name: js::Pat::Ident(js::BindingIdent {
+                                        id: state.mk_ident("v"),
+                                        type_ann: None,
+                                    }),
+                                    init: Some(Box::new(js::Expr::Member(js::MemberExpr {
+                                        span: DUMMY_SP,
+                                        obj: Box::new(js::Expr::Ident(state.mk_ident("obj"))),
+                                        prop: js::MemberProp::Computed(js::ComputedPropName {
+                                            span: DUMMY_SP,
+                                            expr: Box::new(js::Expr::Ident(state.mk_ident("key"))),
+                                        }),
+                                    }))),
+                                    definite: false,
+                                }],
+                                ctxt: SyntaxContext::empty(),
+                            }))),
+                            // delete obj[key];
+                            js::Stmt::Expr(js::ExprStmt {
+                                span: DUMMY_SP,
+                                expr: Box::new(js::Expr::Unary(js::UnaryExpr {
+                                    span: DUMMY_SP,
+                                    op: js::UnaryOp::Delete,
+                                    arg: Box::new(js::Expr::Member(js::MemberExpr {
+                                        span: DUMMY_SP,
+                                        obj: Box::new(js::Expr::Ident(state.mk_ident("obj"))),
+                                        prop: js::MemberProp::Computed(js::ComputedPropName {
+                                            span: DUMMY_SP,
+                                            expr: Box::new(js::Expr::Ident(state.mk_ident("key"))),
+                                        }),
+                                    })),
+                                })),
+                            }),
+                            // return v;
+                            js::Stmt::Return(js::ReturnStmt {
+                                span: DUMMY_SP,
+                                arg: Some(Box::new(js::Expr::Ident(state.mk_ident("v")))),
+                            }),
+                        ],
+                        ctxt: SyntaxContext::empty(),
+                    })),
                     is_async: false,
                     is_generator: false,
-                    type_params: None,
-                    return_type: None,
-                    ctxt: SyntaxContext::empty(),
-                };
-                
-                Ok(js::Expr::Call(js::CallExpr {
-                    span: DUMMY_SP,
-                    callee: js::Callee::Expr(Box::new(js::Expr::Paren(js::ParenExpr {
-                        span: DUMMY_SP,
-                        expr: Box::new(js::Expr::Arrow(iife)),
-                    }))),
-                    args: vec![js::ExprOrSpread {
-                        spread: None,
-                        expr: Box::new(receiver),
-                    }],
-                    type_args: None,
                     ctxt: SyntaxContext::empty(),
-                }))
-            } else {
-                // Option::is_some() -> value !== null && value !== undefined
-                let null_check =
-                    state.mk_binary_expr(receiver.clone(), js::BinaryOp::NotEqEq, state.mk_null_lit());
-                let undefined_check =
-                    state.mk_binary_expr(receiver, js::BinaryOp::NotEqEq, state.mk_undefined());
-                Ok(state.mk_binary_expr(null_check, js::BinaryOp::LogicalAnd, undefined_check))
-            }
+                    return_type: None,
+                    type_params: None,
+                })),
+            }))),
+            args: vec![],
+            type_args: None,
+            ctxt: SyntaxContext::empty(),
+        });
+        
+        // Conditional: obj.splice ? array_remove : obj_remove
+        let conditional = js::Expr::Cond(js::CondExpr {
+            span: DUMMY_SP,
+            test: Box::new(splice_check),
+            cons: Box::new(array_remove),
+            alt: Box::new(obj_remove),
+        });
+        
+        // Create IIFE: (obj, key) => conditional
+        let iife = js::ArrowExpr {
+            span: DUMMY_SP,
+            params: vec![obj_param, key_param],
+            body: Box::new(js::BlockStmtOrExpr::Expr(Box::new(conditional))),
+            is_async: false,
+            is_generator: false,
+            ctxt: SyntaxContext::empty(),
+            return_type: None,
+            type_params: None,
+        };
+        
+        // Call the IIFE with receiver and key: ((obj, key) => ...)(receiver, key)
+        Ok(js::Expr::Call(js::CallExpr {
+            span: DUMMY_SP,
+            callee: js::Callee::Expr(Box::new(js::Expr::Paren(js::ParenExpr {
+                span: DUMMY_SP,
+                expr: Box::new(js::Expr::Arrow(iife)),
+            }))),
+            args: vec![
+                js::ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(receiver),
+                },
+                js::ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(js_args[0].clone()),
+                },
+            ],
+            type_args: None,
+            ctxt: SyntaxContext::empty(),
+        }))
+    } else if js_args.is_empty() {
+        // Zero-argument remove(): DOM Element.remove() - removes the
+        // element from the document. Pass through as a method call.
+        Ok(state.mk_call_expr(state.mk_member_expr(receiver, "remove"), vec![]))
+    } else {
+        Err("remove() expects exactly one argument".to_string())
+    }
+}
+
+fn map_insert(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    if receiver_is_set(state, method_call) {
+        return Ok(set_method_call(state, method_call, receiver, js_args));
+    }
+    // Universal insert: use splice for arrays, property assignment for objects/HashMaps
+    // ((obj, key, val) => obj.splice ? obj.splice(key, 0, val) : (obj[key] = val))(receiver, key, value)
+    if js_args.len() == 2 {
+        // Fresh temp names for the parameters, as in map_len
+        let (obj, key, val) = (
+            state.generate_temp_var(),
+            state.generate_temp_var(),
+            state.generate_temp_var(),
+        );
+
+        // Create parameters for the IIFE
+        let obj_param = js::Pat::Ident(js::BindingIdent {
+            id: state.mk_ident(&obj),
+            type_ann: None,
+        });
+        let key_param = js::Pat::Ident(js::BindingIdent {
+            id: state.mk_ident(&key),
+            type_ann: None,
+        });
+        let val_param = js::Pat::Ident(js::BindingIdent {
+            id: state.mk_ident(&val),
+            type_ann: None,
+        });
+        
+        // Check if obj.splice exists (array)
+        let splice_check = state.mk_member_expr(js::Expr::Ident(state.mk_ident(&obj)), "splice");
+        
+        // Array case: obj.splice(key, 0, val)
+        let array_insert = state.mk_call_expr(
+            splice_check.clone(),
+            vec![
+                js::Expr::Ident(state.mk_ident(&key)),
+                state.mk_num_lit(0.0),
+                js::Expr::Ident(state.mk_ident(&val)),
+            ],
+        );
+        
+        // Object case: obj[key] = val
+        let obj_assignment = js::Expr::Assign(js::AssignExpr {
+            span: DUMMY_SP,
+            op: js::AssignOp::Assign,
+            left: js::AssignTarget::Simple(js::SimpleAssignTarget::Member(js::MemberExpr {
+                span: DUMMY_SP,
+                obj: Box::new(js::Expr::Ident(state.mk_ident(&obj))),
+                prop: js::MemberProp::Computed(js::ComputedPropName {
+                    span: DUMMY_SP,
+                    expr: Box::new(js::Expr::Ident(state.mk_ident(&key))),
+                }),
+            })),
+            right: Box::new(js::Expr::Ident(state.mk_ident(&val))),
+        });
+        
+        // Conditional: obj.splice ? array_insert : obj_assignment
+        let conditional = js::Expr::Cond(js::CondExpr {
+            span: DUMMY_SP,
+            test: Box::new(splice_check),
+            cons: Box::new(array_insert),
+            alt: Box::new(obj_assignment),
+        });
+        
+        // Create IIFE: (obj, key, val) => conditional
+        let iife = js::ArrowExpr {
+            span: DUMMY_SP,
+            params: vec![obj_param, key_param, val_param],
+            body: Box::new(js::BlockStmtOrExpr::Expr(Box::new(conditional))),
+            is_async: false,
+            is_generator: false,
+            type_params: None,
+            return_type: None,
+            ctxt: SyntaxContext::empty(),
+        };
+        
+        // Call the IIFE with arguments: ((obj, key, val) => ...)(receiver, key, value)
+        Ok(js::Expr::Call(js::CallExpr {
+            span: DUMMY_SP,
+            callee: js::Callee::Expr(Box::new(js::Expr::Paren(js::ParenExpr {
+                span: DUMMY_SP,
+                expr: Box::new(js::Expr::Arrow(iife)),
+            }))),
+            args: vec![
+                js::ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(receiver),
+                },
+                js::ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(js_args[0].clone()),
+                },
+                js::ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(js_args[1].clone()),
+                },
+            ],
+            type_args: None,
+            ctxt: SyntaxContext::empty(),
+        }))
+    } else {
+        Err("insert() expects exactly two arguments".to_string())
+    }
+}
+
+/// `index < array.length ? array[index] : null` - Vec::get's Option result
+fn bounds_checked_index(state: &TranspilerState, array: &str, index: &str) -> js::Expr {
+    let element = js::Expr::Member(js::MemberExpr {
+        span: DUMMY_SP,
+        obj: Box::new(js::Expr::Ident(state.mk_ident(array))),
+        prop: js::MemberProp::Computed(js::ComputedPropName {
+            span: DUMMY_SP,
+            expr: Box::new(js::Expr::Ident(state.mk_ident(index))),
+        }),
+    });
+    js::Expr::Cond(js::CondExpr {
+        span: DUMMY_SP,
+        test: Box::new(state.mk_binary_expr(
+            js::Expr::Ident(state.mk_ident(index)),
+            js::BinaryOp::Lt,
+            state.mk_member_expr(js::Expr::Ident(state.mk_ident(array)), "length"),
+        )),
+        cons: Box::new(element),
+        alt: Box::new(state.mk_null_lit()),
+    })
+}
+
+fn map_get(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    // A receiver known to be a Vec or slice only needs the bounds check:
+    // ((v, i) => i < v.length ? v[i] : null)(receiver, index)
+    if js_args.len() == 1 && receiver_is_array(state, method_call) {
+        let array = state.generate_temp_var();
+        let index = state.generate_temp_var();
+        let body = bounds_checked_index(state, &array, &index);
+        return Ok(state.mk_arrow_iife(&[&array, &index], body, vec![receiver, js_args[0].clone()]));
+    }
+    // Universal get: bounds-checked index for arrays, .get() for Map objects,
+    // bracket notation for objects/HashMaps
+    // ((obj, key) => Array.isArray(obj) ? (key < obj.length ? obj[key] : null)
+    //     : obj.get && typeof obj.get === 'function' ? obj.get(key) : obj[key])(receiver, key)
+    if js_args.len() == 1 {
+        // Create parameters for the IIFE
+        let obj_param = js::Pat::Ident(js::BindingIdent {
+            id: state.mk_ident("obj"),
+            type_ann: None,
+        });
+        let key_param = js::Pat::Ident(js::BindingIdent {
+            id: state.mk_ident("key"),
+            type_ann: None,
+        });
+        
+        // Check if obj.get exists and is a function (Map objects)
+        let get_exists = state.mk_member_expr(js::Expr::Ident(state.mk_ident("obj")), "get");
+        let typeof_get = js::Expr::Unary(js::UnaryExpr {
+            span: DUMMY_SP,
+            op: js::UnaryOp::TypeOf,
+            arg: Box::new(state.mk_member_expr(js::Expr::Ident(state.mk_ident("obj")), "get")),
+        });
+        let is_function = state.mk_binary_expr(
+            typeof_get,
+            js::BinaryOp::EqEqEq,
+            state.mk_str_lit("function")
+        );
+        let get_check = state.mk_binary_expr(
+            get_exists,
+            js::BinaryOp::LogicalAnd,
+            is_function
+        );
+        
+        // Map case: obj.get(key) 
+        let map_get = state.mk_call_expr(
+            state.mk_member_expr(js::Expr::Ident(state.mk_ident("obj")), "get"),
+            vec![js::Expr::Ident(state.mk_ident("key"))],
+        );
+        
+        // Object case: obj[key]
+        let obj_get = js::Expr::Member(js::MemberExpr {
+            span: DUMMY_SP,
+            obj: Box::new(js::Expr::Ident(state.mk_ident("obj"))),
+            prop: js::MemberProp::Computed(js::ComputedPropName {
+                span: DUMMY_SP,
+                expr: Box::new(js::Expr::Ident(state.mk_ident("key"))),
+            }),
+        });
+        
+        // Conditional: obj.get && typeof obj.get === 'function' ? obj.get(key) : obj[key]
+        let keyed = js::Expr::Cond(js::CondExpr {
+            span: DUMMY_SP,
+            test: Box::new(get_check),
+            cons: Box::new(map_get),
+            alt: Box::new(obj_get),
+        });
+        let is_array = state.mk_call_expr(
+            state.mk_member_expr(js::Expr::Ident(state.mk_ident("Array")), "isArray"),
+            vec![js::Expr::Ident(state.mk_ident("obj"))],
+        );
+        let conditional = js::Expr::Cond(js::CondExpr {
+            span: DUMMY_SP,
+            test: Box::new(is_array),
+            cons: Box::new(bounds_checked_index(state, "obj", "key")),
+            alt: Box::new(keyed),
+        });
+        
+        // Create IIFE: (obj, key) => conditional
+        let iife = js::ArrowExpr {
+            span: DUMMY_SP,
+            params: vec![obj_param, key_param],
+            body: Box::new(js::BlockStmtOrExpr::Expr(Box::new(conditional))),
+            is_async: false,
+            is_generator: false,
+            ctxt: SyntaxContext::empty(),
+            return_type: None,
+            type_params: None,
+        };
+        
+        // Call the IIFE with receiver and key: ((obj, key) => ...)(receiver, key)
+        Ok(js::Expr::Call(js::CallExpr {
+            span: DUMMY_SP,
+            callee: js::Callee::Expr(Box::new(js::Expr::Paren(js::ParenExpr {
+                span: DUMMY_SP,
+                expr: Box::new(js::Expr::Arrow(iife)),
+            }))),
+            args: vec![
+                js::ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(receiver),
+                },
+                js::ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(js_args[0].clone()),
+                },
+            ],
+            type_args: None,
+            ctxt: SyntaxContext::empty(),
+        }))
+    } else {
+        Err("get() expects exactly one argument".to_string())
+    }
+}
+
+fn map_keys(
+    state: &mut TranspilerState,
+    _method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    // HashMap.keys() -> Object.keys(HashMap)
+    // For JavaScript objects, we need to use Object.keys()
+    if js_args.is_empty() {
+        Ok(state.mk_call_expr(
+            state.mk_member_expr(js::Expr::Ident(state.mk_ident("Object")), "keys"),
+            vec![receiver]
+        ))
+    } else {
+        Err("keys() expects no arguments".to_string())
+    }
+}
+
+fn map_iter(
+    _state: &mut TranspilerState,
+    _method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    _js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    // .iter() is typically a no-op in JavaScript
+    Ok(receiver)
+}
+
+/// `v.drain(range)` -> `v.splice(start, count)`, which removes the elements
+/// in place and returns them: `..` -> `splice(0)`, `a..b` ->
+/// `splice(a, b - a)`, `a..=b` -> `splice(a, b - a + 1)`. A start other
+/// than a literal or variable is used twice, so it is evaluated once into
+/// an IIFE parameter: `((_temp1, _temp2)=>_temp1.splice(_temp2, b - _temp2))(v, a)`
+fn drain_to_splice(
+    state: &mut TranspilerState,
+    receiver: js::Expr,
+    range: &syn::ExprRange,
+) -> Result<js::Expr, String> {
+    let start = match &range.start {
+        Some(start) => rust_expr_to_js_with_state(start, state)?,
+        None => state.mk_num_lit(0.0),
+    };
+    let Some(end) = &range.end else {
+        return Ok(state.mk_call_expr(state.mk_member_expr(receiver, "splice"), vec![start]));
+    };
+    let end = rust_expr_to_js_with_state(end, state)?;
+    let splice = |state: &TranspilerState, receiver: js::Expr, start: js::Expr| {
+        let mut count = match &range.start {
+            Some(_) => state.mk_binary_expr(end.clone(), js::BinaryOp::Sub, start.clone()),
+            None => end.clone(),
+        };
+        if matches!(range.limits, syn::RangeLimits::Closed(_)) {
+            count = state.mk_binary_expr(count, js::BinaryOp::Add, state.mk_num_lit(1.0));
+        }
+        state.mk_call_expr(state.mk_member_expr(receiver, "splice"), vec![start, count])
+    };
+    if matches!(start, js::Expr::Lit(_) | js::Expr::Ident(_)) {
+        return Ok(splice(state, receiver, start));
+    }
+    // The receiver is bound too, so it is still evaluated before the start
+    let vec_var = state.generate_temp_var();
+    let start_var = state.generate_temp_var();
+    let body = splice(
+        state,
+        js::Expr::Ident(state.mk_ident(&vec_var)),
+        js::Expr::Ident(state.mk_ident(&start_var)),
+    );
+    Ok(state.mk_arrow_iife(&[&vec_var, &start_var], body, vec![receiver, start]))
+}
+
+/// `collect()` builds whatever the turbofish names out of the JS array the
+/// iterator chain produced: `String` -> `.join("")`, `HashMap`/`BTreeMap`
+/// -> `Object.fromEntries(..)`, `HashSet`/`BTreeSet` -> `new Set(..)`.
+/// `Vec` and an unknown target keep the array as it is.
+fn map_collect(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    _js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    Ok(match turbofish_type_name(method_call).as_deref() {
+        Some("String") => state.mk_call_expr(
+            state.mk_member_expr(receiver, "join"),
+            vec![state.mk_str_lit("")],
+        ),
+        Some("HashMap") | Some("BTreeMap") => state.mk_call_expr(
+            state.mk_member_expr(js::Expr::Ident(state.mk_ident("Object")), "fromEntries"),
+            vec![receiver],
+        ),
+        Some("HashSet") | Some("BTreeSet") => js::Expr::New(js::NewExpr {
+            span: DUMMY_SP,
+            callee: Box::new(js::Expr::Ident(state.mk_ident("Set"))),
+            args: Some(vec![js::ExprOrSpread {
+                spread: None,
+                expr: Box::new(receiver),
+            }]),
+            type_args: None,
+            ctxt: SyntaxContext::empty(),
+        }),
+        _ => receiver,
+    })
+}
+
+/// The name of the type in a method's turbofish: `String` for
+/// `.collect::<String>()`, `Vec` for `.collect::<Vec<_>>()`
+fn turbofish_type_name(method_call: &syn::ExprMethodCall) -> Option<String> {
+    match method_call.turbofish.as_ref()?.args.first()? {
+        syn::GenericArgument::Type(Type::Path(type_path)) => {
+            type_path.path.segments.last().map(|seg| seg.ident.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// `v.retain(f)` and `v.dedup()` filter the array in place, so other
+/// references to it see the change:
+/// `((a, keep)=>a.splice(0, a.length, ...a.filter(keep)))(v, f)`.
+/// `dedup()` keeps `(x, i)=>i === 0 || x !== a[i - 1]`, which compares
+/// primitives by value and anything else by identity. `retain` on anything
+/// not known to be an array (a HashMap, HashSet or String has its own)
+/// stays a method call.
+fn map_retain(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    let is_dedup = method_call.method == "dedup";
+    if js_args.len() != usize::from(!is_dedup) || !(is_dedup || receiver_is_array(state, method_call)) {
+        return Ok(plain_method_call(state, method_call, receiver, js_args));
+    }
+    let array = state.generate_temp_var();
+    let var = |state: &TranspilerState, name: &str| js::Expr::Ident(state.mk_ident(name));
+    let (params, keep, args) = if is_dedup {
+        let (item, index) = (state.generate_temp_var(), state.generate_temp_var());
+        let previous = js::Expr::Member(js::MemberExpr {
+            span: DUMMY_SP,
+            obj: Box::new(var(state, &array)),
+            prop: js::MemberProp::Computed(js::ComputedPropName {
+                span: DUMMY_SP,
+                expr: Box::new(state.mk_binary_expr(
+                    var(state, &index),
+                    js::BinaryOp::Sub,
+                    state.mk_num_lit(1.0),
+                )),
+            }),
+        });
+        let test = state.mk_binary_expr(
+            state.mk_binary_expr(var(state, &index), js::BinaryOp::EqEqEq, state.mk_num_lit(0.0)),
+            js::BinaryOp::LogicalOr,
+            state.mk_binary_expr(var(state, &item), js::BinaryOp::NotEqEq, previous),
+        );
+        let keep = state.mk_arrow(&[&item, &index], test);
+        (vec![array.clone()], keep, vec![receiver])
+    } else {
+        let keep = state.generate_temp_var();
+        let keep_ref = var(state, &keep);
+        (vec![array.clone(), keep], keep_ref, std::iter::once(receiver).chain(js_args).collect())
+    };
+    let kept = state.mk_call_expr(state.mk_member_expr(var(state, &array), "filter"), vec![keep]);
+    let splice = js::Expr::Call(js::CallExpr {
+        span: DUMMY_SP,
+        callee: js::Callee::Expr(Box::new(state.mk_member_expr(var(state, &array), "splice"))),
+        args: vec![
+            js::ExprOrSpread {
+                spread: None,
+                expr: Box::new(state.mk_num_lit(0.0)),
+            },
+            js::ExprOrSpread {
+                spread: None,
+                expr: Box::new(state.mk_member_expr(var(state, &array), "length")),
+            },
+            js::ExprOrSpread {
+                spread: Some(DUMMY_SP),
+                expr: Box::new(kept),
+            },
+        ],
+        type_args: None,
+        ctxt: SyntaxContext::empty(),
+    });
+    let params: Vec<&str> = params.iter().map(String::as_str).collect();
+    Ok(state.mk_arrow_iife(&params, splice, args))
+}
+
+/// `[...receiver]`: a fresh array of the receiver's elements (for a string,
+/// its code points)
+fn spread_into_array(receiver: js::Expr) -> js::Expr {
+    js::Expr::Array(js::ArrayLit {
+        span: DUMMY_SP,
+        elems: vec![Some(js::ExprOrSpread {
+            spread: Some(DUMMY_SP),
+            expr: Box::new(receiver),
+        })],
+    })
+}
+
+/// `s.chars()` -> `[...s]`, which splits by code point like Rust's chars
+fn map_chars(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    if !js_args.is_empty() {
+        return Ok(plain_method_call(state, method_call, receiver, js_args));
+    }
+    Ok(spread_into_array(receiver))
+}
+
+/// `it.rev()` -> `[...it].reverse()`; copying first keeps `reverse()` from
+/// mutating the collection the iterator came from
+fn map_rev(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    if !js_args.is_empty() {
+        return Ok(plain_method_call(state, method_call, receiver, js_args));
+    }
+    Ok(state.mk_call_expr(
+        state.mk_member_expr(spread_into_array(receiver), "reverse"),
+        vec![],
+    ))
+}
+
+/// `it.step_by(n)` -> `it.filter((_, i)=>i % n === 0)`, keeping every
+/// n-th element of the materialized array, starting with the first
+fn map_step_by(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    let Ok([step]) = <[js::Expr; 1]>::try_from(js_args) else {
+        return Err(format!("{}() expects exactly one argument", method_call.method));
+    };
+    let index = js::Expr::Ident(state.mk_ident("i"));
+    let on_step = state.mk_binary_expr(
+        state.mk_binary_expr(index, js::BinaryOp::Mod, step),
+        js::BinaryOp::EqEqEq,
+        state.mk_num_lit(0.0),
+    );
+    let keep = state.mk_arrow(&["_", "i"], on_step);
+    Ok(state.mk_call_expr(state.mk_member_expr(receiver, "filter"), vec![keep]))
+}
+
+fn map_is_some(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    _js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    // Check if receiver is a function call to avoid duplication
+    if matches!(method_call.receiver.as_ref(), syn::Expr::Call(_)) {
+        // Create IIFE to cache function call result: (val => val !== null && val !== undefined)(function_call())
+        let param = js::Pat::Ident(js::BindingIdent {
+            id: state.mk_ident("val"),
+            type_ann: None,
+        });
+        
+        let null_check = state.mk_binary_expr(
+            js::Expr::Ident(state.mk_ident("val")), 
+            js::BinaryOp::NotEqEq, 
+            state.mk_null_lit()
+        );
+        let undefined_check = state.mk_binary_expr(
+            js::Expr::Ident(state.mk_ident("val")), 
+            js::BinaryOp::NotEqEq, 
+            state.mk_undefined()
+        );
+        let condition = state.mk_binary_expr(null_check, js::BinaryOp::LogicalAnd, undefined_check);
+        
+        let iife = js::ArrowExpr {
+            span: DUMMY_SP,
+            params: vec![param],
+            body: Box::new(js::BlockStmtOrExpr::Expr(Box::new(condition))),
+            is_async: false,
+            is_generator: false,
+            type_params: None,
+            return_type: None,
+            ctxt: SyntaxContext::empty(),
+        };
+        
+        Ok(js::Expr::Call(js::CallExpr {
+            span: DUMMY_SP,
+            callee: js::Callee::Expr(Box::new(js::Expr::Paren(js::ParenExpr {
+                span: DUMMY_SP,
+                expr: Box::new(js::Expr::Arrow(iife)),
+            }))),
+            args: vec![js::ExprOrSpread {
+                spread: None,
+                expr: Box::new(receiver),
+            }],
+            type_args: None,
+            ctxt: SyntaxContext::empty(),
+        }))
+    } else {
+        // Option::is_some() -> value !== null && value !== undefined
+        let null_check =
+            state.mk_binary_expr(receiver.clone(), js::BinaryOp::NotEqEq, state.mk_null_lit());
+        let undefined_check =
+            state.mk_binary_expr(receiver, js::BinaryOp::NotEqEq, state.mk_undefined());
+        Ok(state.mk_binary_expr(null_check, js::BinaryOp::LogicalAnd, undefined_check))
+    }
+}
+
+fn map_is_none(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    _js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    // Check if receiver is a function call to avoid duplication
+    if matches!(method_call.receiver.as_ref(), syn::Expr::Call(_)) {
+        // Create IIFE to cache function call result: (val => val === null || val === undefined)(function_call())
+        let param = js::Pat::Ident(js::BindingIdent {
+            id: state.mk_ident("val"),
+            type_ann: None,
+        });
+        
+        let null_check = state.mk_binary_expr(
+            js::Expr::Ident(state.mk_ident("val")), 
+            js::BinaryOp::EqEqEq, 
+            state.mk_null_lit()
+        );
+        let undefined_check = state.mk_binary_expr(
+            js::Expr::Ident(state.mk_ident("val")), 
+            js::BinaryOp::EqEqEq, 
+            state.mk_undefined()
+        );
+        let condition = state.mk_binary_expr(null_check, js::BinaryOp::LogicalOr, undefined_check);
+        
+        let iife = js::ArrowExpr {
+            span: DUMMY_SP,
+            params: vec![param],
+            body: Box::new(js::BlockStmtOrExpr::Expr(Box::new(condition))),
+            is_async: false,
+            is_generator: false,
+            type_params: None,
+            return_type: None,
+            ctxt: SyntaxContext::empty(),
+        };
+        
+        Ok(js::Expr::Call(js::CallExpr {
+            span: DUMMY_SP,
+            callee: js::Callee::Expr(Box::new(js::Expr::Paren(js::ParenExpr {
+                span: DUMMY_SP,
+                expr: Box::new(js::Expr::Arrow(iife)),
+            }))),
+            args: vec![js::ExprOrSpread {
+                spread: None,
+                expr: Box::new(receiver),
+            }],
+            type_args: None,
+            ctxt: SyntaxContext::empty(),
+        }))
+    } else {
+        // Option::is_none() -> value === null || value === undefined
+        let null_check =
+            state.mk_binary_expr(receiver.clone(), js::BinaryOp::EqEqEq, state.mk_null_lit());
+        let undefined_check =
+            state.mk_binary_expr(receiver, js::BinaryOp::EqEqEq, state.mk_undefined());
+        Ok(state.mk_binary_expr(null_check, js::BinaryOp::LogicalOr, undefined_check))
+    }
+}
+
+fn map_contains(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    if js_args.len() != 1 {
+        return Ok(plain_method_call(state, method_call, receiver, js_args));
+    }
+    // Strings and Vecs (declared, or a string literal receiver) are plain
+    // .includes(); anything else is decided at runtime:
+    // ((r, x) => Array.isArray(r) || typeof r === "string" ? r.includes(x)
+    //     : typeof r.has === "function" ? r.has(x)
+    //     : Object.prototype.hasOwnProperty.call(r, x))(receiver, x)
+    let is_str_literal = matches!(
+        &*method_call.receiver,
+        Expr::Lit(lit) if matches!(lit.lit, syn::Lit::Str(_))
+    );
+    if receiver_is_set(state, method_call) {
+        return Ok(set_method_call(state, method_call, receiver, js_args));
+    }
+    let known_sequence = known_receiver_type(state, &method_call.receiver)
+        .is_some_and(|ty| matches!(format_rust_type(ty).as_str(), "Array" | "string"));
+    if is_str_literal || known_sequence {
+        return Ok(state.mk_call_expr(state.mk_member_expr(receiver, "includes"), js_args));
+    }
+
+    let r = || js::Expr::Ident(state.mk_ident("r"));
+    let x = || js::Expr::Ident(state.mk_ident("x"));
+    let typeof_r = |target: js::Expr, kind: &str| {
+        state.mk_binary_expr(
+            js::Expr::Unary(js::UnaryExpr {
+                span: DUMMY_SP,
+                op: js::UnaryOp::TypeOf,
+                arg: Box::new(target),
+            }),
+            js::BinaryOp::EqEqEq,
+            state.mk_str_lit(kind),
+        )
+    };
+    let is_sequence = state.mk_binary_expr(
+        state.mk_call_expr(
+            state.mk_member_expr(js::Expr::Ident(state.mk_ident("Array")), "isArray"),
+            vec![r()],
+        ),
+        js::BinaryOp::LogicalOr,
+        typeof_r(r(), "string"),
+    );
+    let has_own = state.mk_call_expr(
+        state.mk_member_expr(
+            state.mk_member_expr(
+                state.mk_member_expr(js::Expr::Ident(state.mk_ident("Object")), "prototype"),
+                "hasOwnProperty",
+            ),
+            "call",
+        ),
+        vec![r(), x()],
+    );
+    let body = js::Expr::Cond(js::CondExpr {
+        span: DUMMY_SP,
+        test: Box::new(is_sequence),
+        cons: Box::new(state.mk_call_expr(state.mk_member_expr(r(), "includes"), vec![x()])),
+        alt: Box::new(js::Expr::Cond(js::CondExpr {
+            span: DUMMY_SP,
+            test: Box::new(typeof_r(state.mk_member_expr(r(), "has"), "function")),
+            cons: Box::new(state.mk_call_expr(state.mk_member_expr(r(), "has"), vec![x()])),
+            alt: Box::new(has_own),
+        })),
+    });
+    Ok(state.mk_arrow_iife(&["r", "x"], body, vec![receiver, js_args.into_iter().next().unwrap()]))
+}
+
+fn map_contains_key(
+    state: &mut TranspilerState,
+    _method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    // Universal contains_key: use has() for Maps, hasOwnProperty() for Objects
+    // ((obj, key) => obj && typeof obj.has === 'function' ? obj.has(key) : obj.hasOwnProperty(key))(receiver, key)
+    if js_args.len() == 1 {
+        // Create parameters for the IIFE
+        let obj_param = js::Pat::Ident(js::BindingIdent {
+            id: state.mk_ident("obj"),
+            type_ann: None,
+        });
+        let key_param = js::Pat::Ident(js::BindingIdent {
+            id: state.mk_ident("key"),
+            type_ann: None,
+        });
+        
+        // Create obj && typeof obj.has === 'function' check
+        let obj_exists = js::Expr::Ident(state.mk_ident("obj"));
+        let has_member = state.mk_member_expr(js::Expr::Ident(state.mk_ident("obj")), "has");
+        let typeof_has = js::Expr::Unary(js::UnaryExpr {
+            span: DUMMY_SP,
+            op: js::UnaryOp::TypeOf,
+            arg: Box::new(has_member),
+        });
+        let is_function = state.mk_binary_expr(
+            typeof_has,
+            js::BinaryOp::EqEqEq,
+            state.mk_str_lit("function")
+        );
+        let obj_and_has = state.mk_binary_expr(obj_exists, js::BinaryOp::LogicalAnd, is_function);
+        
+        // Create obj.has(key) call for Maps
+        let map_has = state.mk_call_expr(
+            state.mk_member_expr(js::Expr::Ident(state.mk_ident("obj")), "has"),
+            vec![js::Expr::Ident(state.mk_ident("key"))]
+        );
+        
+        // Create obj.hasOwnProperty(key) call for Objects  
+        let object_has = state.mk_call_expr(
+            state.mk_member_expr(js::Expr::Ident(state.mk_ident("obj")), "hasOwnProperty"),
+            vec![js::Expr::Ident(state.mk_ident("key"))]
+        );
+        
+        // Create conditional expression
+        let conditional = js::Expr::Cond(js::CondExpr {
+            span: DUMMY_SP,
+            test: Box::new(obj_and_has),
+            cons: Box::new(map_has),
+            alt: Box::new(object_has),
+        });
+        
+        // Create IIFE: (obj, key) => conditional
+        let iife = js::ArrowExpr {
+            span: DUMMY_SP,
+            params: vec![obj_param, key_param],
+            body: Box::new(js::BlockStmtOrExpr::Expr(Box::new(conditional))),
+            is_async: false,
+            is_generator: false,
+            type_params: None,
+            return_type: None,
+            ctxt: SyntaxContext::empty(),
+        };
+        
+        // Call the IIFE with receiver and key: ((obj, key) => ...)(receiver, key)
+        Ok(js::Expr::Call(js::CallExpr {
+            span: DUMMY_SP,
+            callee: js::Callee::Expr(Box::new(js::Expr::Paren(js::ParenExpr {
+                span: DUMMY_SP,
+                expr: Box::new(js::Expr::Arrow(iife)),
+            }))),
+            args: vec![
+                js::ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(receiver),
+                },
+                js::ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(js_args[0].clone()),
+                },
+            ],
+            type_args: None,
+            ctxt: SyntaxContext::empty(),
+        }))
+    } else {
+        Err(format!("contains_key expects exactly 1 argument, got {}", js_args.len()))
+    }
+}
+
+fn map_unwrap(
+    state: &mut TranspilerState,
+    _method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    _js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    // Result-aware: {ok: v} unwraps to v, everything else passes
+    // through unchanged (Options are plain values / null in JS):
+    //   ((v) => v && v.ok !== undefined ? v.ok : v)(receiver)
+    let v = || js::Expr::Ident(state.mk_ident("v"));
+    let body = js::Expr::Cond(js::CondExpr {
+        span: DUMMY_SP,
+        test: Box::new(state.mk_binary_expr(
+            v(),
+            js::BinaryOp::LogicalAnd,
+            state.mk_result_field_check("v", "ok"),
+        )),
+        cons: Box::new(state.mk_member_expr(v(), "ok")),
+        alt: Box::new(v()),
+    });
+    Ok(state.mk_arrow_iife(&["v"], body, vec![receiver]))
+}
+
+fn map_unwrap_or(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    let method_name = method_call.method.to_string();
+    let receiver_ty = known_receiver_type(state, &method_call.receiver).cloned();
+    if method_name == "unwrap_or_default"
+        && let Some(inner) = receiver_ty.as_ref().and_then(|ty| wrapped_type_arg(ty, "Option"))
+        && let Some(default) = default_value_for_type(state, inner)
+    {
+        // An Option is a plain value or null: (opt ?? <default>)
+        return Ok(js::Expr::Paren(js::ParenExpr {
+            span: DUMMY_SP,
+            expr: Box::new(state.mk_binary_expr(
+                receiver,
+                js::BinaryOp::NullishCoalescing,
+                default,
+            )),
+        }));
+    }
+    // ((v, d) => v === null || v === undefined || (v && v.error !== undefined)
+    //     ? <fallback> : v.ok !== undefined ? v.ok : v)(receiver, default?)
+    // None (null/undefined) and Err both take the fallback;
+    // {ok: v} unwraps; any plain value passes through.
+    let (params, args, fallback): (&[&str], Vec<js::Expr>, js::Expr) =
+        match method_name.as_str() {
+            "unwrap_or" => {
+                if js_args.len() != 1 {
+                    return Err("unwrap_or expects exactly one argument".to_string());
+                }
+                let d = js_args.into_iter().next().unwrap();
+                (
+                    &["v", "d"],
+                    vec![receiver, d],
+                    js::Expr::Ident(state.mk_ident("d")),
+                )
+            }
+            "unwrap_or_else" => {
+                if js_args.len() != 1 {
+                    return Err(
+                        "unwrap_or_else expects exactly one argument".to_string()
+                    );
+                }
+                let f = js_args.into_iter().next().unwrap();
+                (
+                    &["v", "d"],
+                    vec![receiver, f],
+                    state.mk_call_expr(js::Expr::Ident(state.mk_ident("d")), vec![]),
+                )
+            }
+            _ => {
+                // Result<T, _> still needs the shape-aware unwrap; only the
+                // fallback comes from T
+                let default = receiver_ty
+                    .as_ref()
+                    .and_then(|ty| wrapped_type_arg(ty, "Result"))
+                    .and_then(|inner| default_value_for_type(state, inner));
+                let d = default.unwrap_or_else(|| {
+                    state.add_warning(format!(
+                        "`{}.unwrap_or_default()`: the receiver's type is unknown, \
+                         defaulting to null",
+                        source_snippet(&method_call.receiver)
+                    ));
+                    state.mk_null_lit()
+                });
+                (
+                    &["v", "d"],
+                    vec![receiver, d],
+                    js::Expr::Ident(state.mk_ident("d")),
+                )
+            }
+        };
+    let v = || js::Expr::Ident(state.mk_ident("v"));
+    let needs_fallback = state.mk_binary_expr(
+        state.mk_nullish_check("v"),
+        js::BinaryOp::LogicalOr,
+        state.mk_binary_expr(
+            v(),
+            js::BinaryOp::LogicalAnd,
+            state.mk_result_field_check("v", "error"),
+        ),
+    );
+    let body = js::Expr::Cond(js::CondExpr {
+        span: DUMMY_SP,
+        test: Box::new(needs_fallback),
+        cons: Box::new(fallback),
+        alt: Box::new(js::Expr::Cond(js::CondExpr {
+            span: DUMMY_SP,
+            test: Box::new(state.mk_result_field_check("v", "ok")),
+            cons: Box::new(state.mk_member_expr(v(), "ok")),
+            alt: Box::new(v()),
+        })),
+    });
+    Ok(state.mk_arrow_iife(params, body, args))
+}
+
+/// The declared type of a method receiver that names a variable
+/// (`x`, `&x`, `(x)`), when it was annotated or is a typed parameter
+fn known_receiver_type<'a>(state: &'a TranspilerState, receiver: &Expr) -> Option<&'a Type> {
+    match receiver {
+        Expr::Path(path) => state.variable_type(&path.path.get_ident()?.to_string()),
+        Expr::Reference(reference) => known_receiver_type(state, &reference.expr),
+        Expr::Paren(paren) => known_receiver_type(state, &paren.expr),
+        _ => None,
+    }
+}
+
+/// `T` when `ty` is `wrapper<T, ..>` (or a reference to one)
+fn wrapped_type_arg<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    match ty {
+        Type::Reference(type_ref) => wrapped_type_arg(&type_ref.elem, wrapper),
+        Type::Path(type_path) => {
+            let segment = type_path.path.segments.last()?;
+            if segment.ident != wrapper {
+                return None;
+            }
+            match &segment.arguments {
+                syn::PathArguments::AngleBracketed(args) => {
+                    args.args.iter().find_map(|arg| match arg {
+                        syn::GenericArgument::Type(inner) => Some(inner),
+                        _ => None,
+                    })
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// A Rust integer type name, of any width or signedness
+fn is_integer_type_name(name: &str) -> bool {
+    matches!(
+        name,
+        "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64" | "u128"
+            | "usize"
+    )
+}
+
+/// `i64`, `u64`, `i128` or `u128`: the integers a JS number can't hold exactly
+fn is_wide_int_name(name: &str) -> bool {
+    matches!(name, "i64" | "u64" | "i128" | "u128")
+}
+
+fn is_wide_int_type(ty: &Type) -> bool {
+    match ty {
+        Type::Path(type_path) => type_path
+            .path
+            .get_ident()
+            .is_some_and(|ident| is_wide_int_name(&ident.to_string())),
+        Type::Paren(paren) => is_wide_int_type(&paren.elem),
+        Type::Group(group) => is_wide_int_type(&group.elem),
+        _ => false,
+    }
+}
+
+/// Whether `expr` is known to be a 64/128-bit integer: a suffixed literal,
+/// a binding declared with such a type, a cast to one, or arithmetic on any
+/// of those
+fn is_wide_int_expr(expr: &Expr, state: &TranspilerState) -> bool {
+    match expr {
+        Expr::Lit(lit) => matches!(&lit.lit, syn::Lit::Int(i) if is_wide_int_name(i.suffix())),
+        Expr::Path(path) => path
+            .path
+            .get_ident()
+            .and_then(|ident| state.variable_type(&ident.to_string()))
+            .is_some_and(is_wide_int_type),
+        Expr::Cast(cast) => is_wide_int_type(&cast.ty),
+        Expr::Paren(paren) => is_wide_int_expr(&paren.expr, state),
+        Expr::Group(group) => is_wide_int_expr(&group.expr, state),
+        Expr::Unary(unary) => {
+            matches!(unary.op, syn::UnOp::Neg(_)) && is_wide_int_expr(&unary.expr, state)
+        }
+        Expr::Binary(bin) => {
+            matches!(
+                bin.op,
+                syn::BinOp::Add(_)
+                    | syn::BinOp::Sub(_)
+                    | syn::BinOp::Mul(_)
+                    | syn::BinOp::Div(_)
+                    | syn::BinOp::Rem(_)
+                    | syn::BinOp::BitXor(_)
+                    | syn::BinOp::BitAnd(_)
+                    | syn::BinOp::BitOr(_)
+                    | syn::BinOp::Shl(_)
+                    | syn::BinOp::Shr(_)
+            ) && (is_wide_int_expr(&bin.left, state) || is_wide_int_expr(&bin.right, state))
+        }
+        _ => false,
+    }
+}
+
+/// The type of an unannotated `let` from its initializer, for the cases
+/// that name it outright: `HashSet::new()`, `Vec::with_capacity(n)`,
+/// `String::from(..)`, `vec![..]`
+fn infer_init_type(init: &Expr) -> Option<Type> {
+    match init {
+        Expr::Call(call) => {
+            let Expr::Path(func) = &*call.func else {
+                return None;
+            };
+            let segments = &func.path.segments;
+            if segments.len() < 2 {
+                return None;
+            }
+            let type_segment = &segments[segments.len() - 2];
+            let ctor = segments.last().unwrap().ident.to_string();
+            let is_ctor = matches!(ctor.as_str(), "new" | "with_capacity" | "from" | "default");
+            let is_known = matches!(
+                type_segment.ident.to_string().as_str(),
+                "HashSet" | "BTreeSet" | "HashMap" | "BTreeMap" | "Vec" | "String"
+            );
+            (is_ctor && is_known).then(|| {
+                Type::Path(syn::TypePath {
+                    qself: None,
+                    path: type_segment.ident.clone().into(),
+                })
+            })
+        }
+        Expr::Macro(mac) if mac.mac.path.is_ident("vec") => Some(Type::Path(syn::TypePath {
+            qself: None,
+            path: syn::Ident::new("Vec", proc_macro2::Span::call_site()).into(),
+        })),
+        _ => None,
+    }
+}
+
+/// Whether the receiver is a Vec or array literal, or a binding known to
+/// hold a Vec, array or slice
+fn receiver_is_array(state: &TranspilerState, method_call: &syn::ExprMethodCall) -> bool {
+    let is_literal = match &*method_call.receiver {
+        Expr::Array(_) => true,
+        Expr::Macro(mac) => mac.mac.path.is_ident("vec"),
+        _ => false,
+    };
+    is_literal || known_receiver_type(state, &method_call.receiver).is_some_and(|ty| {
+        let ty = match ty {
+            Type::Reference(type_ref) => &*type_ref.elem,
+            ty => ty,
+        };
+        matches!(ty, Type::Slice(_)) || format_rust_type(ty) == "Array"
+    })
+}
+
+/// Whether a method receiver is a variable declared (or initialized) as a
+/// HashSet/BTreeSet, which is a JS `Set`
+fn receiver_is_set(state: &TranspilerState, method_call: &syn::ExprMethodCall) -> bool {
+    known_receiver_type(state, &method_call.receiver)
+        .is_some_and(|ty| format_rust_type(ty) == "Set")
+}
+
+/// Set operations on a JS `Set` receiver: insert -> add, contains -> has,
+/// remove -> delete, len -> size, is_empty -> size === 0
+fn set_method_call(
+    state: &TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> js::Expr {
+    match method_call.method.to_string().as_str() {
+        "insert" => state.mk_call_expr(state.mk_member_expr(receiver, "add"), js_args),
+        "contains" => state.mk_call_expr(state.mk_member_expr(receiver, "has"), js_args),
+        "remove" => state.mk_call_expr(state.mk_member_expr(receiver, "delete"), js_args),
+        "len" => state.mk_member_expr(receiver, "size"),
+        "is_empty" => state.mk_binary_expr(
+            state.mk_member_expr(receiver, "size"),
+            js::BinaryOp::EqEqEq,
+            state.mk_num_lit(0.0),
+        ),
+        _ => plain_method_call(state, method_call, receiver, js_args),
+    }
+}
+
+/// JavaScript for `T::default()` when `T` has an obvious JS counterpart
+fn default_value_for_type(state: &TranspilerState, ty: &Type) -> Option<js::Expr> {
+    match format_rust_type(ty).as_str() {
+        "number" => Some(state.mk_num_lit(0.0)),
+        "boolean" => Some(state.mk_bool_lit(false)),
+        "string" => Some(state.mk_str_lit("")),
+        "Array" => Some(js::Expr::Array(js::ArrayLit {
+            span: DUMMY_SP,
+            elems: vec![],
+        })),
+        "Map" => Some(js::Expr::Object(js::ObjectLit {
+            span: DUMMY_SP,
+            props: vec![],
+        })),
+        _ => None,
+    }
+}
+
+// Result predicates/adapters, matching the {ok}/{error} shape.
+fn map_is_ok(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    let method_name = method_call.method.to_string();
+    if !js_args.is_empty() {
+        return Ok(plain_method_call(state, method_call, receiver, js_args));
+    }
+    // is_ok:  ((v) => v !== null && v !== undefined && v.error === undefined)(r)
+    // is_err: ((v) => v !== null && v !== undefined && v.error !== undefined)(r)
+    let v = || js::Expr::Ident(state.mk_ident("v"));
+    let not_nullish = state.mk_binary_expr(
+        state.mk_binary_expr(v(), js::BinaryOp::NotEqEq, state.mk_null_lit()),
+        js::BinaryOp::LogicalAnd,
+        state.mk_binary_expr(v(), js::BinaryOp::NotEqEq, state.mk_undefined()),
+    );
+    let err_op = if method_name == "is_ok" {
+        js::BinaryOp::EqEqEq
+    } else {
+        js::BinaryOp::NotEqEq
+    };
+    let err_check = state.mk_binary_expr(
+        state.mk_member_expr(v(), "error"),
+        err_op,
+        state.mk_undefined(),
+    );
+    let body = state.mk_binary_expr(not_nullish, js::BinaryOp::LogicalAnd, err_check);
+    Ok(state.mk_arrow_iife(&["v"], body, vec![receiver]))
+}
+
+/// Whether the receiver is an Option: written as `Some(..)`, or declared
+/// with an `Option<T>` type
+fn receiver_is_option(state: &TranspilerState, receiver: &Expr) -> bool {
+    if let Expr::Call(call) = receiver
+        && let Expr::Path(path) = &*call.func
+        && path.path.is_ident("Some")
+    {
+        return true;
+    }
+    known_receiver_type(state, receiver)
+        .and_then(|ty| wrapped_type_arg(ty, "Option"))
+        .is_some()
+}
+
+/// Whether the receiver is a Result: written as `Ok(..)`/`Err(..)`, or
+/// declared with a `Result<T, E>` type
+fn receiver_is_result(state: &TranspilerState, receiver: &Expr) -> bool {
+    if let Expr::Call(call) = receiver
+        && let Expr::Path(path) = &*call.func
+        && (path.path.is_ident("Ok") || path.path.is_ident("Err"))
+    {
+        return true;
+    }
+    known_receiver_type(state, receiver)
+        .and_then(|ty| wrapped_type_arg(ty, "Result"))
+        .is_some()
+}
+
+/// Option::filter keeps the value only when the predicate holds:
+/// ((v, p) => v != null && p(v) ? v : null)(receiver, pred)
+/// Any other receiver is an iterator and keeps Array.prototype.filter.
+fn map_filter(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    if js_args.len() != 1 || !receiver_is_option(state, &method_call.receiver) {
+        return Ok(plain_method_call(state, method_call, receiver, js_args));
+    }
+    let v = || js::Expr::Ident(state.mk_ident("v"));
+    let body = js::Expr::Cond(js::CondExpr {
+        span: DUMMY_SP,
+        test: Box::new(state.mk_binary_expr(
+            state.mk_binary_expr(v(), js::BinaryOp::NotEq, state.mk_null_lit()),
+            js::BinaryOp::LogicalAnd,
+            state.mk_call_expr(js::Expr::Ident(state.mk_ident("p")), vec![v()]),
+        )),
+        cons: Box::new(v()),
+        alt: Box::new(state.mk_null_lit()),
+    });
+    let mut args = vec![receiver];
+    args.extend(js_args);
+    Ok(state.mk_arrow_iife(&["v", "p"], body, args))
+}
+
+/// Option combinators on null-or-value options:
+/// - `a.or(b)` -> `(a ?? b)`
+/// - `a.and(b)` -> `((a, b) => a != null ? b : null)(a, b)`
+/// - `a.xor(b)` -> `((a, b) => a != null ? (b != null ? null : a) : b)(a, b)`
+///
+/// On a Result, `or`/`and` pick by the `{error}` field instead:
+/// `((a, b) => a.error !== undefined ? b : a)(a, b)` for `or`. Any other
+/// receiver or arity is a plain method call.
+fn map_option_combinator(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    let method = method_call.method.to_string();
+    let is_option = receiver_is_option(state, &method_call.receiver);
+    let is_result = !is_option && method != "xor" && receiver_is_result(state, &method_call.receiver);
+    if js_args.len() != 1 || !(is_option || is_result) {
+        return Ok(plain_method_call(state, method_call, receiver, js_args));
+    }
+    let other = js_args.into_iter().next().unwrap();
+    let var = |name: &str| js::Expr::Ident(state.mk_ident(name));
+    let is_some = |name: &str| state.mk_binary_expr(var(name), js::BinaryOp::NotEq, state.mk_null_lit());
+    let cond = |test, cons, alt| {
+        js::Expr::Cond(js::CondExpr {
+            span: DUMMY_SP,
+            test: Box::new(test),
+            cons: Box::new(cons),
+            alt: Box::new(alt),
+        })
+    };
+    let body = match (is_result, method.as_str()) {
+        (true, "or") => cond(state.mk_result_field_check("a", "error"), var("b"), var("a")),
+        (true, _) => cond(state.mk_result_field_check("a", "error"), var("a"), var("b")),
+        (false, "or") => {
+            return Ok(js::Expr::Paren(js::ParenExpr {
+                span: DUMMY_SP,
+                expr: Box::new(state.mk_binary_expr(receiver, js::BinaryOp::NullishCoalescing, other)),
+            }));
         }
-        "is_none" => {
-            // Check if receiver is a function call to avoid duplication
-            if matches!(method_call.receiver.as_ref(), syn::Expr::Call(_)) {
-                // Create IIFE to cache function call result: (val => val === null || val === undefined)(function_call())
-                let param = js::Pat::Ident(js::BindingIdent {
-                    id: state.mk_ident("val"),
-                    type_ann: None,
-                });
-                
-                let null_check = state.mk_binary_expr(
-                    js::Expr::Ident(state.mk_ident("val")), 
-                    js::BinaryOp::EqEqEq, 
-                    state.mk_null_lit()
-                );
-                let undefined_check = state.mk_binary_expr(
-                    js::Expr::Ident(state.mk_ident("val")), 
-                    js::BinaryOp::EqEqEq, 
-                    state.mk_undefined()
-                );
-                let condition = state.mk_binary_expr(null_check, js::BinaryOp::LogicalOr, undefined_check);
-                
-                let iife = js::ArrowExpr {
-                    span: DUMMY_SP,
-                    params: vec![param],
-                    body: Box::new(js::BlockStmtOrExpr::Expr(Box::new(condition))),
-                    is_async: false,
-                    is_generator: false,
-                    type_params: None,
-                    return_type: None,
-                    ctxt: SyntaxContext::empty(),
-                };
-                
-                Ok(js::Expr::Call(js::CallExpr {
-                    span: DUMMY_SP,
-                    callee: js::Callee::Expr(Box::new(js::Expr::Paren(js::ParenExpr {
-                        span: DUMMY_SP,
-                        expr: Box::new(js::Expr::Arrow(iife)),
-                    }))),
-                    args: vec![js::ExprOrSpread {
+        (false, "and") => cond(is_some("a"), var("b"), state.mk_null_lit()),
+        _ => cond(
+            is_some("a"),
+            cond(is_some("b"), state.mk_null_lit(), var("a")),
+            var("b"),
+        ),
+    };
+    Ok(state.mk_arrow_iife(&["a", "b"], body, vec![receiver, other]))
+}
+
+/// `s.as_bytes()` -> `Array.from(new TextEncoder().encode(s))`, the UTF-8
+/// bytes as a plain array: a Uint8Array would wrap what its `map` returns
+/// to a byte and has no `push`
+fn map_as_bytes(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    if !js_args.is_empty() {
+        return Ok(plain_method_call(state, method_call, receiver, js_args));
+    }
+    let array_from = state.mk_member_expr(js::Expr::Ident(state.mk_ident("Array")), "from");
+    Ok(state.mk_call_expr(array_from, vec![utf8_encode(state, receiver)]))
+}
+
+/// `new TextEncoder().encode(text)`
+fn utf8_encode(state: &TranspilerState, text: js::Expr) -> js::Expr {
+    let encoder = js::Expr::New(js::NewExpr {
+        span: DUMMY_SP,
+        callee: Box::new(js::Expr::Ident(state.mk_ident("TextEncoder"))),
+        args: Some(vec![]),
+        type_args: None,
+        ctxt: SyntaxContext::empty(),
+    });
+    state.mk_call_expr(state.mk_member_expr(encoder, "encode"), vec![text])
+}
+
+/// `new TextDecoder("utf-8", { fatal: true }).decode(new Uint8Array(bytes))`.
+/// A fatal decoder throws on invalid UTF-8; the other one substitutes
+/// U+FFFD, like `String::from_utf8_lossy`.
+fn utf8_decode(state: &TranspilerState, bytes: js::Expr, fatal: bool) -> js::Expr {
+    let mk_new = |class: &str, args: Vec<js::Expr>| {
+        js::Expr::New(js::NewExpr {
+            span: DUMMY_SP,
+            callee: Box::new(js::Expr::Ident(state.mk_ident(class))),
+            args: Some(
+                args.into_iter()
+                    .map(|expr| js::ExprOrSpread {
                         spread: None,
-                        expr: Box::new(receiver),
-                    }],
-                    type_args: None,
-                    ctxt: SyntaxContext::empty(),
-                }))
-            } else {
-                // Option::is_none() -> value === null || value === undefined
-                let null_check =
-                    state.mk_binary_expr(receiver.clone(), js::BinaryOp::EqEqEq, state.mk_null_lit());
-                let undefined_check =
-                    state.mk_binary_expr(receiver, js::BinaryOp::EqEqEq, state.mk_undefined());
-                Ok(state.mk_binary_expr(null_check, js::BinaryOp::LogicalOr, undefined_check))
-            }
-        }
-        "contains_key" => {
-            // Universal contains_key: use has() for Maps, hasOwnProperty() for Objects
-            // ((obj, key) => obj && typeof obj.has === 'function' ? obj.has(key) : obj.hasOwnProperty(key))(receiver, key)
-            if js_args.len() == 1 {
-                // Create parameters for the IIFE
-                let obj_param = js::Pat::Ident(js::BindingIdent {
-                    id: state.mk_ident("obj"),
-                    type_ann: None,
-                });
-                let key_param = js::Pat::Ident(js::BindingIdent {
-                    id: state.mk_ident("key"),
-                    type_ann: None,
-                });
-                
-                // Create obj && typeof obj.has === 'function' check
-                let obj_exists = js::Expr::Ident(state.mk_ident("obj"));
-                let has_member = state.mk_member_expr(js::Expr::Ident(state.mk_ident("obj")), "has");
-                let typeof_has = js::Expr::Unary(js::UnaryExpr {
-                    span: DUMMY_SP,
-                    op: js::UnaryOp::TypeOf,
-                    arg: Box::new(has_member),
-                });
-                let is_function = state.mk_binary_expr(
-                    typeof_has,
-                    js::BinaryOp::EqEqEq,
-                    state.mk_str_lit("function")
-                );
-                let obj_and_has = state.mk_binary_expr(obj_exists, js::BinaryOp::LogicalAnd, is_function);
-                
-                // Create obj.has(key) call for Maps
-                let map_has = state.mk_call_expr(
-                    state.mk_member_expr(js::Expr::Ident(state.mk_ident("obj")), "has"),
-                    vec![js::Expr::Ident(state.mk_ident("key"))]
-                );
-                
-                // Create obj.hasOwnProperty(key) call for Objects  
-                let object_has = state.mk_call_expr(
-                    state.mk_member_expr(js::Expr::Ident(state.mk_ident("obj")), "hasOwnProperty"),
-                    vec![js::Expr::Ident(state.mk_ident("key"))]
-                );
-                
-                // Create conditional expression
-                let conditional = js::Expr::Cond(js::CondExpr {
-                    span: DUMMY_SP,
-                    test: Box::new(obj_and_has),
-                    cons: Box::new(map_has),
-                    alt: Box::new(object_has),
-                });
-                
-                // Create IIFE: (obj, key) => conditional
-                let iife = js::ArrowExpr {
-                    span: DUMMY_SP,
-                    params: vec![obj_param, key_param],
-                    body: Box::new(js::BlockStmtOrExpr::Expr(Box::new(conditional))),
-                    is_async: false,
-                    is_generator: false,
-                    type_params: None,
-                    return_type: None,
-                    ctxt: SyntaxContext::empty(),
-                };
-                
-                // Call the IIFE with receiver and key: ((obj, key) => ...)(receiver, key)
-                Ok(js::Expr::Call(js::CallExpr {
-                    span: DUMMY_SP,
-                    callee: js::Callee::Expr(Box::new(js::Expr::Paren(js::ParenExpr {
+                        expr: Box::new(expr),
+                    })
+                    .collect(),
+            ),
+            type_args: None,
+            ctxt: SyntaxContext::empty(),
+        })
+    };
+    let mut decoder_args = vec![];
+    if fatal {
+        decoder_args.push(state.mk_str_lit("utf-8"));
+        decoder_args.push(js::Expr::Object(js::ObjectLit {
+            span: DUMMY_SP,
+            props: vec![js::PropOrSpread::Prop(Box::new(js::Prop::KeyValue(
+                js::KeyValueProp {
+                    key: js::PropName::Ident(state.mk_ident_name("fatal")),
+                    value: Box::new(js::Expr::Lit(js::Lit::Bool(js::Bool {
                         span: DUMMY_SP,
-                        expr: Box::new(js::Expr::Arrow(iife)),
+                        value: true,
                     }))),
-                    args: vec![
-                        js::ExprOrSpread {
-                            spread: None,
-                            expr: Box::new(receiver),
-                        },
-                        js::ExprOrSpread {
-                            spread: None,
-                            expr: Box::new(js_args[0].clone()),
-                        },
-                    ],
-                    type_args: None,
-                    ctxt: SyntaxContext::empty(),
-                }))
-            } else {
-                Err(format!("contains_key expects exactly 1 argument, got {}", js_args.len()))
-            }
+                },
+            )))],
+        }));
+    }
+    let decoder = mk_new("TextDecoder", decoder_args);
+    let bytes = mk_new("Uint8Array", vec![bytes]);
+    state.mk_call_expr(state.mk_member_expr(decoder, "decode"), vec![bytes])
+}
+
+/// `String::from_utf8(bytes)` as a Result:
+///   (function() { try { return {ok: <fatal decode>}; }
+///                 catch (e) { return {error: e.message}; } }).call(this)
+fn utf8_decode_result(state: &TranspilerState, bytes: js::Expr) -> js::Expr {
+    let ok = result_object(state, "ok", utf8_decode(state, bytes, true));
+    let message = state.mk_member_expr(js::Expr::Ident(state.mk_ident("e")), "message");
+    let try_stmt = js::Stmt::Try(Box::new(js::TryStmt {
+        span: DUMMY_SP,
+        block: js::BlockStmt {
+            span: DUMMY_SP,
+            stmts: vec![state.mk_return_stmt(Some(ok))],
+            ctxt: SyntaxContext::empty(),
+        },
+        handler: Some(js::CatchClause {
+            span: DUMMY_SP,
+            param: Some(js::Pat::Ident(js::BindingIdent {
+                id: state.mk_ident("e"),
+                type_ann: None,
+            })),
+            body: js::BlockStmt {
+                span: DUMMY_SP,
+                stmts: vec![state.mk_return_stmt(Some(result_object(state, "error", message)))],
+                ctxt: SyntaxContext::empty(),
+            },
+        }),
+        finalizer: None,
+    }));
+    state.mk_iife(vec![try_stmt])
+}
+
+/// `{ok: value}` / `{error: value}`, the shape Ok()/Err() construct
+fn result_object(state: &TranspilerState, field: &str, value: js::Expr) -> js::Expr {
+    js::Expr::Object(js::ObjectLit {
+        span: DUMMY_SP,
+        props: vec![js::PropOrSpread::Prop(Box::new(js::Prop::KeyValue(
+            js::KeyValueProp {
+                key: js::PropName::Ident(state.mk_ident_name(field)),
+                value: Box::new(value),
+            },
+        )))],
+    })
+}
+
+/// Option -> Result: ((v, e) => v != null ? {ok: v} : {error: e})(receiver, e)
+/// `ok_or_else(f)` builds the error with `e()`.
+fn map_ok_or(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    if js_args.len() != 1 {
+        return Ok(plain_method_call(state, method_call, receiver, js_args));
+    }
+    let v = || js::Expr::Ident(state.mk_ident("v"));
+    let e = js::Expr::Ident(state.mk_ident("e"));
+    let error = if method_call.method == "ok_or_else" {
+        state.mk_call_expr(e, vec![])
+    } else {
+        e
+    };
+    let body = js::Expr::Cond(js::CondExpr {
+        span: DUMMY_SP,
+        test: Box::new(state.mk_binary_expr(v(), js::BinaryOp::NotEq, state.mk_null_lit())),
+        cons: Box::new(result_object(state, "ok", v())),
+        alt: Box::new(result_object(state, "error", error)),
+    });
+    let mut args = vec![receiver];
+    args.extend(js_args);
+    Ok(state.mk_arrow_iife(&["v", "e"], body, args))
+}
+
+/// `s.split_whitespace()` -> `s.trim().split(/\s+/).filter((w)=>w !== "")`;
+/// the filter makes an empty or all-blank string give `[]` like Rust
+fn map_split_whitespace(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    if !js_args.is_empty() {
+        return Ok(plain_method_call(state, method_call, receiver, js_args));
+    }
+    let trimmed = state.mk_call_expr(state.mk_member_expr(receiver, "trim"), vec![]);
+    let whitespace = js::Expr::Lit(js::Lit::Regex(js::Regex {
+        span: DUMMY_SP,
+        exp: "\\s+".into(),
+        flags: "".into(),
+    }));
+    let words = state.mk_call_expr(state.mk_member_expr(trimmed, "split"), vec![whitespace]);
+    let non_empty = js::Expr::Arrow(js::ArrowExpr {
+        span: DUMMY_SP,
+        params: vec![js::Pat::Ident(js::BindingIdent {
+            id: state.mk_ident("w"),
+            type_ann: None,
+        })],
+        body: Box::new(js::BlockStmtOrExpr::Expr(Box::new(state.mk_binary_expr(
+            js::Expr::Ident(state.mk_ident("w")),
+            js::BinaryOp::NotEqEq,
+            state.mk_str_lit(""),
+        )))),
+        is_async: false,
+        is_generator: false,
+        type_params: None,
+        return_type: None,
+        ctxt: SyntaxContext::empty(),
+    });
+    Ok(state.mk_call_expr(state.mk_member_expr(words, "filter"), vec![non_empty]))
+}
+
+/// `s.splitn(n, sep)`: at most n pieces, the last one keeping the rest of
+/// the string (JS `split(sep, n)` would drop it):
+/// ((s, n, sep) => ((p) => p.length <= n ? p
+///     : p.slice(0, n - 1).concat([p.slice(n - 1).join(sep)]))(s.split(sep)))(receiver, n, sep)
+fn map_splitn(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    if js_args.len() != 2 {
+        return Ok(plain_method_call(state, method_call, receiver, js_args));
+    }
+    let id = |name: &str| js::Expr::Ident(state.mk_ident(name));
+    let n_minus_one = || state.mk_binary_expr(id("n"), js::BinaryOp::Sub, state.mk_num_lit(1.0));
+    let head = state.mk_call_expr(
+        state.mk_member_expr(id("p"), "slice"),
+        vec![state.mk_num_lit(0.0), n_minus_one()],
+    );
+    let rest = state.mk_call_expr(
+        state.mk_member_expr(
+            state.mk_call_expr(state.mk_member_expr(id("p"), "slice"), vec![n_minus_one()]),
+            "join",
+        ),
+        vec![id("sep")],
+    );
+    let limited = state.mk_call_expr(
+        state.mk_member_expr(head, "concat"),
+        vec![js::Expr::Array(js::ArrayLit {
+            span: DUMMY_SP,
+            elems: vec![Some(js::ExprOrSpread {
+                spread: None,
+                expr: Box::new(rest),
+            })],
+        })],
+    );
+    let pieces = js::Expr::Cond(js::CondExpr {
+        span: DUMMY_SP,
+        test: Box::new(state.mk_binary_expr(
+            state.mk_member_expr(id("p"), "length"),
+            js::BinaryOp::LtEq,
+            id("n"),
+        )),
+        cons: Box::new(id("p")),
+        alt: Box::new(limited),
+    });
+    let split = state.mk_call_expr(state.mk_member_expr(id("s"), "split"), vec![id("sep")]);
+    let body = state.mk_arrow_iife(&["p"], pieces, vec![split]);
+    let mut args = vec![receiver];
+    args.extend(js_args);
+    Ok(state.mk_arrow_iife(&["s", "n", "sep"], body, args))
+}
+
+/// `s.rsplit(sep)` -> `s.split(sep).reverse()`
+fn map_rsplit(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    if js_args.len() != 1 {
+        return Ok(plain_method_call(state, method_call, receiver, js_args));
+    }
+    let split = state.mk_call_expr(state.mk_member_expr(receiver, "split"), js_args);
+    Ok(state.mk_call_expr(state.mk_member_expr(split, "reverse"), vec![]))
+}
+
+/// `map_or(d, f)` / `map_or_else(d, f)`, with the receiver bound once:
+/// ((v, d, f) => v != null ? f(v) : d)(receiver, d, f)
+/// `map_or_else` calls `d()` instead. A receiver known to be a Result tests
+/// and unwraps `v.ok`, and its `map_or_else` fallback gets `v.error`.
+fn map_map_or(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    if js_args.len() != 2 {
+        return Ok(plain_method_call(state, method_call, receiver, js_args));
+    }
+    let lazy_default = method_call.method == "map_or_else";
+    let is_result = known_receiver_type(state, &method_call.receiver)
+        .and_then(|ty| wrapped_type_arg(ty, "Result"))
+        .is_some();
+    let v = || js::Expr::Ident(state.mk_ident("v"));
+    let (test, value) = if is_result {
+        (state.mk_result_field_check("v", "ok"), state.mk_member_expr(v(), "ok"))
+    } else {
+        (
+            state.mk_binary_expr(v(), js::BinaryOp::NotEq, state.mk_null_lit()),
+            v(),
+        )
+    };
+    let d = js::Expr::Ident(state.mk_ident("d"));
+    let fallback = match (lazy_default, is_result) {
+        (false, _) => d,
+        (true, false) => state.mk_call_expr(d, vec![]),
+        (true, true) => state.mk_call_expr(d, vec![state.mk_member_expr(v(), "error")]),
+    };
+    let body = js::Expr::Cond(js::CondExpr {
+        span: DUMMY_SP,
+        test: Box::new(test),
+        cons: Box::new(state.mk_call_expr(js::Expr::Ident(state.mk_ident("f")), vec![value])),
+        alt: Box::new(fallback),
+    });
+    let mut args = vec![receiver];
+    args.extend(js_args);
+    Ok(state.mk_arrow_iife(&["v", "d", "f"], body, args))
+}
+
+fn map_ok(
+    state: &mut TranspilerState,
+    method_call: &syn::ExprMethodCall,
+    receiver: js::Expr,
+    js_args: Vec<js::Expr>,
+) -> Result<js::Expr, String> {
+    if !js_args.is_empty() {
+        return Ok(plain_method_call(state, method_call, receiver, js_args));
+    }
+    // Result -> Option: Err becomes null, Ok unwraps, rest passes:
+    // ((v) => v && v.error !== undefined ? null
+    //       : v && v.ok !== undefined ? v.ok : v)(receiver)
+    let v = || js::Expr::Ident(state.mk_ident("v"));
+    let body = js::Expr::Cond(js::CondExpr {
+        span: DUMMY_SP,
+        test: Box::new(state.mk_binary_expr(
+            v(),
+            js::BinaryOp::LogicalAnd,
+            state.mk_result_field_check("v", "error"),
+        )),
+        cons: Box::new(state.mk_null_lit()),
+        alt: Box::new(js::Expr::Cond(js::CondExpr {
+            span: DUMMY_SP,
+            test: Box::new(state.mk_binary_expr(
+                v(),
+                js::BinaryOp::LogicalAnd,
+                state.mk_result_field_check("v", "ok"),
+            )),
+            cons: Box::new(state.mk_member_expr(v(), "ok")),
+            alt: Box::new(v()),
+        })),
+    });
+    Ok(state.mk_arrow_iife(&["v"], body, vec![receiver]))
+}
+
+/// `successors(..)`, `iter::successors(..)` or `std::iter::successors(..)`
+/// with its two arguments.
+fn is_successors_call(call: &syn::ExprCall) -> bool {
+    match &*call.func {
+        Expr::Path(path) => {
+            call.args.len() == 2
+                && path
+                    .path
+                    .segments
+                    .last()
+                    .is_some_and(|seg| seg.ident == "successors")
         }
-        "unwrap" => {
-            // Result-aware: {ok: v} unwraps to v, everything else passes
-            // through unchanged (Options are plain values / null in JS):
-            //   ((v) => v && v.ok !== undefined ? v.ok : v)(receiver)
-            let v = || js::Expr::Ident(state.mk_ident("v"));
-            let body = js::Expr::Cond(js::CondExpr {
+        _ => false,
+    }
+}
+
+/// Handle `successors(init, f).take(n)` by building the array eagerly:
+/// keep applying `f` to the last element until it yields None (null) or
+/// `n` elements have been collected.
+fn handle_successors_take(
+    call: &syn::ExprCall,
+    count: &Expr,
+    state: &mut TranspilerState,
+) -> Result<js::Expr, String> {
+    let init_js = rust_expr_to_js_with_state(&call.args[0], state)?;
+    let next_fn_js = rust_expr_to_js_with_state(&call.args[1], state)?;
+    let count_js = rust_expr_to_js_with_state(count, state)?;
+
+    let result_var = state.generate_temp_var();
+    let current_var = state.generate_temp_var();
+    let next_fn_var = state.generate_temp_var();
+
+    let current = || js::Expr::Ident(state.mk_ident(&current_var));
+
+    // _current !== null && _current !== undefined && _result.length < n
+    let not_null = state.mk_binary_expr(current(), js::BinaryOp::NotEqEq, state.mk_null_lit());
+    let not_undefined =
+        state.mk_binary_expr(current(), js::BinaryOp::NotEqEq, state.mk_undefined());
+    let below_count = state.mk_binary_expr(
+        state.mk_member_expr(js::Expr::Ident(state.mk_ident(&result_var)), "length"),
+        js::BinaryOp::Lt,
+        count_js,
+    );
+    let test = state.mk_binary_expr(
+        state.mk_binary_expr(not_null, js::BinaryOp::LogicalAnd, not_undefined),
+        js::BinaryOp::LogicalAnd,
+        below_count,
+    );
+
+    // _result.push(_current); _current = f(_current);
+    let push = state.mk_expr_stmt(state.mk_call_expr(
+        state.mk_member_expr(js::Expr::Ident(state.mk_ident(&result_var)), "push"),
+        vec![current()],
+    ));
+    let advance = state.mk_expr_stmt(js::Expr::Assign(js::AssignExpr {
+        span: DUMMY_SP,
+        op: js::AssignOp::Assign,
+        left: state.expr_to_assign_target(current())?,
+        right: Box::new(state.mk_call_expr(
+            js::Expr::Ident(state.mk_ident(&next_fn_var)),
+            vec![current()],
+        )),
+    }));
+
+    let while_stmt = js::Stmt::While(js::WhileStmt {
+        span: DUMMY_SP,
+        test: Box::new(test),
+        body: Box::new(js::Stmt::Block(js::BlockStmt {
+            span: DUMMY_SP,
+            stmts: vec![push, advance],
+            ctxt: SyntaxContext::empty(),
+        })),
+    });
+
+    let stmts = vec![
+        state.mk_var_decl(
+            &result_var,
+            Some(js::Expr::Array(js::ArrayLit {
                 span: DUMMY_SP,
-                test: Box::new(state.mk_binary_expr(
-                    v(),
-                    js::BinaryOp::LogicalAnd,
-                    state.mk_result_field_check("v", "ok"),
-                )),
-                cons: Box::new(state.mk_member_expr(v(), "ok")),
-                alt: Box::new(v()),
+                elems: vec![],
+            })),
+            true,
+        ),
+        state.mk_var_decl(&current_var, Some(init_js), false),
+        state.mk_var_decl(&next_fn_var, Some(next_fn_js), true),
+        while_stmt,
+        state.mk_return_stmt(Some(js::Expr::Ident(state.mk_ident(&result_var)))),
+    ];
+
+    Ok(state.mk_iife(stmts))
+}
+
+/// `mem::swap(&mut a, &mut b)` -> `[a, b] = [b, a]`;
+/// `mem::replace(&mut a, v)` and `mem::take(&mut a)` -> an IIFE that saves
+/// the old value, assigns `v` (or the type's default) and returns the old one.
+fn handle_mem_call(
+    func_name: &str,
+    call: &syn::ExprCall,
+    js_args: Vec<js::Expr>,
+    state: &mut TranspilerState,
+) -> Result<js::Expr, String> {
+    let expected_args = if func_name == "take" { 1 } else { 2 };
+    if call.args.len() != expected_args {
+        return Err(format!(
+            "mem::{} expects {} argument(s), got {}",
+            func_name,
+            expected_args,
+            call.args.len()
+        ));
+    }
+    // The target is the place behind `&mut`, not the reference itself
+    let place = |arg: &Expr, state: &mut TranspilerState| match arg {
+        Expr::Reference(reference) => rust_expr_to_js_with_state(&reference.expr, state),
+        other => rust_expr_to_js_with_state(other, state),
+    };
+
+    match func_name {
+        "swap" => {
+            let a = place(&call.args[0], state)?;
+            let b = place(&call.args[1], state)?;
+            let pattern = js::ArrayPat {
+                span: DUMMY_SP,
+                elems: vec![
+                    Some(js::Pat::Expr(Box::new(a.clone()))),
+                    Some(js::Pat::Expr(Box::new(b.clone()))),
+                ],
+                optional: false,
+                type_ann: None,
+            };
+            let values = js::Expr::Array(js::ArrayLit {
+                span: DUMMY_SP,
+                elems: vec![
+                    Some(js::ExprOrSpread {
+                        spread: None,
+                        expr: Box::new(b),
+                    }),
+                    Some(js::ExprOrSpread {
+                        spread: None,
+                        expr: Box::new(a),
+                    }),
+                ],
             });
-            Ok(state.mk_arrow_iife(&["v"], body, vec![receiver]))
-        }
-        "unwrap_or" | "unwrap_or_else" | "unwrap_or_default" => {
-            // ((v, d) => v === null || v === undefined || (v && v.error !== undefined)
-            //     ? <fallback> : v.ok !== undefined ? v.ok : v)(receiver, default?)
-            // None (null/undefined) and Err both take the fallback;
-            // {ok: v} unwraps; any plain value passes through.
-            let v = || js::Expr::Ident(state.mk_ident("v"));
-            let (params, args, fallback): (&[&str], Vec<js::Expr>, js::Expr) =
-                match method_name.as_str() {
-                    "unwrap_or" => {
-                        if js_args.len() != 1 {
-                            return Err("unwrap_or expects exactly one argument".to_string());
-                        }
-                        let d = js_args.into_iter().next().unwrap();
-                        (
-                            &["v", "d"],
-                            vec![receiver, d],
-                            js::Expr::Ident(state.mk_ident("d")),
-                        )
+            Ok(js::Expr::Assign(js::AssignExpr {
+                span: DUMMY_SP,
+                op: js::AssignOp::Assign,
+                left: js::AssignTarget::Pat(js::AssignTargetPat::Array(pattern)),
+                right: Box::new(values),
+            }))
+        }
+        _ => {
+            // The place is read and then assigned, so what picks it out
+            // (`v[next()]`) is evaluated once, into temps
+            let mut stmts = Vec::new();
+            let target = match place(&call.args[0], state)? {
+                js::Expr::Member(mut member) => {
+                    if !matches!(&*member.obj, js::Expr::Ident(_) | js::Expr::This(_)) {
+                        let obj_var = state.generate_temp_var();
+                        stmts.push(state.mk_var_decl(&obj_var, Some(*member.obj), true));
+                        member.obj = Box::new(js::Expr::Ident(state.mk_ident(&obj_var)));
                     }
-                    "unwrap_or_else" => {
-                        if js_args.len() != 1 {
-                            return Err(
-                                "unwrap_or_else expects exactly one argument".to_string()
-                            );
-                        }
-                        let f = js_args.into_iter().next().unwrap();
-                        (
-                            &["v", "d"],
-                            vec![receiver, f],
-                            state.mk_call_expr(js::Expr::Ident(state.mk_ident("d")), vec![]),
-                        )
+                    if let js::MemberProp::Computed(computed) = &mut member.prop
+                        && !matches!(&*computed.expr, js::Expr::Ident(_) | js::Expr::Lit(_))
+                    {
+                        let key_var = state.generate_temp_var();
+                        let key = std::mem::replace(
+                            &mut computed.expr,
+                            Box::new(js::Expr::Ident(state.mk_ident(&key_var))),
+                        );
+                        stmts.push(state.mk_var_decl(&key_var, Some(*key), true));
                     }
-                    _ => (&["v"], vec![receiver], state.mk_null_lit()),
-                };
-            let needs_fallback = state.mk_binary_expr(
-                state.mk_nullish_check("v"),
-                js::BinaryOp::LogicalOr,
-                state.mk_binary_expr(
-                    v(),
-                    js::BinaryOp::LogicalAnd,
-                    state.mk_result_field_check("v", "error"),
-                ),
-            );
-            let body = js::Expr::Cond(js::CondExpr {
-                span: DUMMY_SP,
-                test: Box::new(needs_fallback),
-                cons: Box::new(fallback),
-                alt: Box::new(js::Expr::Cond(js::CondExpr {
-                    span: DUMMY_SP,
-                    test: Box::new(state.mk_result_field_check("v", "ok")),
-                    cons: Box::new(state.mk_member_expr(v(), "ok")),
-                    alt: Box::new(v()),
-                })),
-            });
-            Ok(state.mk_arrow_iife(params, body, args))
-        }
-        // Result predicates/adapters, matching the {ok}/{error} shape.
-        "is_ok" | "is_err" if js_args.is_empty() => {
-            // is_ok:  ((v) => v !== null && v !== undefined && v.error === undefined)(r)
-            // is_err: ((v) => v !== null && v !== undefined && v.error !== undefined)(r)
-            let v = || js::Expr::Ident(state.mk_ident("v"));
-            let not_nullish = state.mk_binary_expr(
-                state.mk_binary_expr(v(), js::BinaryOp::NotEqEq, state.mk_null_lit()),
-                js::BinaryOp::LogicalAnd,
-                state.mk_binary_expr(v(), js::BinaryOp::NotEqEq, state.mk_undefined()),
-            );
-            let err_op = if method_name == "is_ok" {
-                js::BinaryOp::EqEqEq
+                    js::Expr::Member(member)
+                }
+                target => target,
+            };
+            let old_var = state.generate_temp_var();
+            let old = || js::Expr::Ident(state.mk_ident(&old_var));
+            let new_value = if func_name == "replace" {
+                js_args.into_iter().nth(1).unwrap()
             } else {
-                js::BinaryOp::NotEqEq
+                // An Option is left as `None`, not its inner type's default
+                let declared = known_receiver_type(state, &call.args[0]).and_then(|ty| {
+                    if wrapped_type_arg(ty, "Option").is_some() {
+                        Some(state.mk_null_lit())
+                    } else {
+                        default_value_for_type(state, ty)
+                    }
+                });
+                match declared {
+                    Some(default) => default,
+                    None => runtime_default_for(state, old()),
+                }
             };
-            let err_check = state.mk_binary_expr(
-                state.mk_member_expr(v(), "error"),
-                err_op,
-                state.mk_undefined(),
-            );
-            let body = state.mk_binary_expr(not_nullish, js::BinaryOp::LogicalAnd, err_check);
-            Ok(state.mk_arrow_iife(&["v"], body, vec![receiver]))
-        }
-        "ok" if js_args.is_empty() => {
-            // Result -> Option: Err becomes null, Ok unwraps, rest passes:
-            // ((v) => v && v.error !== undefined ? null
-            //       : v && v.ok !== undefined ? v.ok : v)(receiver)
-            let v = || js::Expr::Ident(state.mk_ident("v"));
-            let body = js::Expr::Cond(js::CondExpr {
+            let assign = js::Expr::Assign(js::AssignExpr {
                 span: DUMMY_SP,
-                test: Box::new(state.mk_binary_expr(
-                    v(),
-                    js::BinaryOp::LogicalAnd,
-                    state.mk_result_field_check("v", "error"),
-                )),
-                cons: Box::new(state.mk_null_lit()),
-                alt: Box::new(js::Expr::Cond(js::CondExpr {
-                    span: DUMMY_SP,
-                    test: Box::new(state.mk_binary_expr(
-                        v(),
-                        js::BinaryOp::LogicalAnd,
-                        state.mk_result_field_check("v", "ok"),
-                    )),
-                    cons: Box::new(state.mk_member_expr(v(), "ok")),
-                    alt: Box::new(v()),
-                })),
+                op: js::AssignOp::Assign,
+                left: state.expr_to_assign_target(target.clone())?,
+                right: Box::new(new_value),
             });
-            Ok(state.mk_arrow_iife(&["v"], body, vec![receiver]))
-        }
-        _ => {
-            // Default method call
-            Ok(state.mk_call_expr(state.mk_member_expr(receiver, &method_name), js_args))
+            stmts.extend([
+                state.mk_var_decl(&old_var, Some(target), true),
+                state.mk_expr_stmt(assign),
+                state.mk_return_stmt(Some(old())),
+            ]);
+            Ok(state.mk_iife(stmts))
         }
     }
 }
 
+/// The default for a value whose Rust type isn't known, picked from the
+/// value's JS shape at runtime (arrays, strings, numbers, booleans; null
+/// stays null and any other object becomes `{}`).
+fn runtime_default_for(state: &TranspilerState, value: js::Expr) -> js::Expr {
+    let typeof_is = |kind: &str| {
+        state.mk_binary_expr(
+            js::Expr::Unary(js::UnaryExpr {
+                span: DUMMY_SP,
+                op: js::UnaryOp::TypeOf,
+                arg: Box::new(value.clone()),
+            }),
+            js::BinaryOp::EqEqEq,
+            state.mk_str_lit(kind),
+        )
+    };
+    let cond = |test, cons, alt| {
+        js::Expr::Cond(js::CondExpr {
+            span: DUMMY_SP,
+            test: Box::new(test),
+            cons: Box::new(cons),
+            alt: Box::new(alt),
+        })
+    };
+    let is_array = state.mk_call_expr(
+        state.mk_member_expr(js::Expr::Ident(state.mk_ident("Array")), "isArray"),
+        vec![value.clone()],
+    );
+    let is_nullish = state.mk_binary_expr(value.clone(), js::BinaryOp::EqEq, state.mk_null_lit());
+    cond(
+        is_array,
+        js::Expr::Array(js::ArrayLit {
+            span: DUMMY_SP,
+            elems: vec![],
+        }),
+        cond(
+            typeof_is("string"),
+            state.mk_str_lit(""),
+            cond(
+                typeof_is("number"),
+                state.mk_num_lit(0.0),
+                cond(
+                    typeof_is("boolean"),
+                    state.mk_bool_lit(false),
+                    cond(
+                        is_nullish,
+                        state.mk_null_lit(),
+                        js::Expr::Object(js::ObjectLit {
+                            span: DUMMY_SP,
+                            props: vec![],
+                        }),
+                    ),
+                ),
+            ),
+        ),
+    )
+}
+
 /// Handle function calls
 fn handle_function_call(
     call: &syn::ExprCall,
     state: &mut TranspilerState,
+) -> Result<js::Expr, String> {
+    let call_expr = handle_function_call_inner(call, state)?;
+    Ok(mark_spread_args(call_expr, &call.args))
+}
+
+fn handle_function_call_inner(
+    call: &syn::ExprCall,
+    state: &mut TranspilerState,
 ) -> Result<js::Expr, String> {
     // Convert arguments first
     let args: Result<Vec<_>, _> = call
@@ -3327,6 +6767,78 @@ fn handle_function_call(
                     .to_string();
                 let method_name = path.path.segments.last().unwrap().ident.to_string();
 
+                // JS collections grow on their own, so a capacity is simply
+                // dropped: Vec::with_capacity(n) -> [], String -> "",
+                // HashMap -> {}, HashSet -> new Set(). `Type::default()` is
+                // the same empty collection.
+                if method_name == "with_capacity" || (method_name == "default" && js_args.is_empty()) {
+                    match type_name.as_str() {
+                        "Vec" | "VecDeque" => {
+                            return Ok(js::Expr::Array(js::ArrayLit {
+                                span: DUMMY_SP,
+                                elems: vec![],
+                            }));
+                        }
+                        "String" => return Ok(state.mk_str_lit("")),
+                        "HashMap" | "BTreeMap" => {
+                            return Ok(js::Expr::Object(js::ObjectLit {
+                                span: DUMMY_SP,
+                                props: vec![],
+                            }));
+                        }
+                        "HashSet" | "BTreeSet" => {
+                            return Ok(js::Expr::New(js::NewExpr {
+                                span: DUMMY_SP,
+                                callee: Box::new(js::Expr::Ident(state.mk_ident("Set"))),
+                                args: Some(vec![]),
+                                type_args: None,
+                                ctxt: SyntaxContext::empty(),
+                            }));
+                        }
+                        _ => {}
+                    }
+                }
+
+                // Collections built from a value: Vec::from(x) ->
+                // Array.from(x), String::from(x) -> String(x), HashSet::from(x)
+                // -> new Set(x), HashMap::from(pairs) -> Object.fromEntries(pairs)
+                if method_name == "from" && js_args.len() == 1 {
+                    let global = |name: &str| js::Expr::Ident(state.mk_ident(name));
+                    match type_name.as_str() {
+                        "Vec" | "VecDeque" => {
+                            return Ok(state.mk_call_expr(state.mk_member_expr(global("Array"), "from"), js_args));
+                        }
+                        "String" => return Ok(state.mk_call_expr(global("String"), js_args)),
+                        "HashMap" | "BTreeMap" => {
+                            return Ok(state.mk_call_expr(
+                                state.mk_member_expr(global("Object"), "fromEntries"),
+                                js_args,
+                            ));
+                        }
+                        "HashSet" | "BTreeSet" => {
+                            return Ok(js::Expr::New(js::NewExpr {
+                                span: DUMMY_SP,
+                                callee: Box::new(global("Set")),
+                                args: Some(
+                                    js_args
+                                        .into_iter()
+                                        .map(|expr| js::ExprOrSpread { spread: None, expr: Box::new(expr) })
+                                        .collect(),
+                                ),
+                                type_args: None,
+                                ctxt: SyntaxContext::empty(),
+                            }));
+                        }
+                        _ => {}
+                    }
+                }
+
+                // cmp::min(a, b) / cmp::max(a, b) -> Math.min(a, b) / Math.max(a, b)
+                if type_name == "cmp" && matches!(method_name.as_str(), "min" | "max") {
+                    let math = state.mk_member_expr(js::Expr::Ident(state.mk_ident("Math")), &method_name);
+                    return Ok(state.mk_call_expr(math, js_args));
+                }
+
                 // Handle constructor calls (Type::new)
                 if method_name == "new" {
                     // Handle special Rust types that should become JS equivalents
@@ -3345,6 +6857,38 @@ fn handle_function_call(
                                 elems: vec![],
                             }));
                         }
+                        "HashSet" | "BTreeSet" => {
+                            // HashSet::new() or BTreeSet::new() becomes new Set()
+                            return Ok(js::Expr::New(js::NewExpr {
+                                span: DUMMY_SP,
+                                callee: Box::new(js::Expr::Ident(state.mk_ident("Set"))),
+                                args: Some(vec![]),
+                                type_args: None,
+                                ctxt: SyntaxContext::empty(),
+                            }));
+                        }
+                        "Date" if js_args.is_empty() => {
+                            // Date::new() is the browser built-in, there is
+                            // no static `Date.new` to dispatch to. A `new`
+                            // taking arguments is a user type's, so it gets
+                            // the runtime dispatch below.
+                            return Ok(js::Expr::New(js::NewExpr {
+                                span: DUMMY_SP,
+                                callee: Box::new(js::Expr::Ident(state.mk_ident("Date"))),
+                                args: Some(vec![]),
+                                type_args: None,
+                                ctxt: SyntaxContext::empty(),
+                            }));
+                        }
+                        "Box" | "Rc" | "Arc" | "RefCell" | "Cell" | "Mutex"
+                            if js_args.len() == 1 =>
+                        {
+                            // Smart pointers and interior-mutability cells have
+                            // no JS counterpart - every JS object is already
+                            // shared and mutable - so the wrapper vanishes:
+                            // Box::new(x) -> x
+                            return Ok(js_args.into_iter().next().unwrap());
+                        }
                         "String" => {
                             // String::new() becomes "" - NOT `new String()`,
                             // which is a boxed object that fails === checks.
@@ -3366,8 +6910,8 @@ fn handle_function_call(
                             // attached as a static `Type.new`, and that is
                             // what must run (it initializes every field).
                             // Types without one (browser built-ins like
-                            // WebSocket, the Arc/Mutex shims, plain structs)
-                            // fall back to the positional `new Type(...)`.
+                            // WebSocket, plain structs) fall back to the
+                            // positional `new Type(...)`.
                             let ctor_args: Vec<js::ExprOrSpread> = js_args
                                 .iter()
                                 .cloned()
@@ -3397,12 +6941,35 @@ fn handle_function_call(
                     }
                 }
 
+                // String::from_utf8(bytes) / str::from_utf8(bytes) decode to
+                // a Result, String::from_utf8_lossy(bytes) to a string
+                if matches!(type_name.as_str(), "String" | "str")
+                    && matches!(method_name.as_str(), "from_utf8" | "from_utf8_lossy")
+                    && let Ok([bytes]) = <[js::Expr; 1]>::try_from(js_args.clone())
+                {
+                    return Ok(if method_name == "from_utf8" {
+                        utf8_decode_result(state, bytes)
+                    } else {
+                        utf8_decode(state, bytes, false)
+                    });
+                }
+
+                // std::mem helpers rewrite their `&mut` targets in place
+                if type_name == "mem" && matches!(method_name.as_str(), "swap" | "replace" | "take") {
+                    return handle_mem_call(&method_name, call, js_args, state);
+                }
+
                 // Handle other static methods (Type::method)
                 // Convert snake_case method names to camelCase for JavaScript
                 let js_method_name = match method_name.as_str() {
                     "all_settled" => "allSettled".to_string(),
                     other => other.to_string(),
                 };
+                // Self::helper() inside an impl calls the struct's static
+                let type_name = match state.get_current_struct_name() {
+                    Some(struct_name) if type_name == "Self" => struct_name.clone(),
+                    _ => type_name,
+                };
                 let static_method =
                     state.mk_member_expr(js::Expr::Ident(state.mk_ident(&type_name)), &js_method_name);
                 return Ok(state.mk_call_expr(static_method, js_args));
@@ -3467,6 +7034,32 @@ fn handle_function_call(
                             ))
                         }
                     }
+                    name if name == "Self"
+                        || state.tuple_structs.contains(name)
+                        || state.get_current_struct_name().is_some_and(|current| current == name) =>
+                    {
+                        // Tuple struct construction: Wrapper(a, b) -> new Wrapper(a, b),
+                        // with Self(a) naming the struct of the impl
+                        let class_name = match name {
+                            "Self" => state.get_current_struct_name().cloned().unwrap_or_else(|| name.to_string()),
+                            _ => name.to_string(),
+                        };
+                        Ok(js::Expr::New(js::NewExpr {
+                            span: DUMMY_SP,
+                            callee: Box::new(js::Expr::Ident(state.mk_ident(&class_name))),
+                            args: Some(
+                                js_args
+                                    .into_iter()
+                                    .map(|expr| js::ExprOrSpread {
+                                        spread: None,
+                                        expr: Box::new(expr),
+                                    })
+                                    .collect(),
+                            ),
+                            type_args: None,
+                            ctxt: SyntaxContext::empty(),
+                        }))
+                    }
                     _ => {
                         // Regular function call
                         let callee = js::Expr::Ident(state.mk_ident(&func_name));
@@ -3485,8 +7078,60 @@ fn handle_function_call(
     }
 }
 
+/// `spread!(v)` in argument position: the argument it wraps.
+fn spread_macro_arg(arg: &Expr) -> Option<&syn::Macro> {
+    match arg {
+        Expr::Macro(m) if m.mac.path.is_ident("spread") => Some(&m.mac),
+        _ => None,
+    }
+}
+
+/// Turn the arguments written as `spread!(v)` into `...v` on the generated
+/// call. Calls that were mapped to something with a different argument list
+/// are left as they are.
+fn mark_spread_args(
+    expr: js::Expr,
+    rust_args: &syn::punctuated::Punctuated<Expr, syn::token::Comma>,
+) -> js::Expr {
+    if !rust_args.iter().any(|arg| spread_macro_arg(arg).is_some()) {
+        return expr;
+    }
+    let mark = |js_args: &mut Vec<js::ExprOrSpread>| {
+        if js_args.len() != rust_args.len() {
+            return;
+        }
+        for (js_arg, rust_arg) in js_args.iter_mut().zip(rust_args.iter()) {
+            if spread_macro_arg(rust_arg).is_some() {
+                js_arg.spread = Some(DUMMY_SP);
+            }
+        }
+    };
+    match expr {
+        js::Expr::Call(mut call) => {
+            mark(&mut call.args);
+            js::Expr::Call(call)
+        }
+        js::Expr::New(mut new_expr) => {
+            if let Some(args) = new_expr.args.as_mut() {
+                mark(args);
+            }
+            js::Expr::New(new_expr)
+        }
+        other => other,
+    }
+}
+
+/// Whether macro arguments start with a format string that is more than
+/// plain text: one with placeholders (`{}`, `{name}`...) or escaped braces
 fn contains_format_arguments(s: &str) -> bool {
-    s.contains("{}") || s.contains("{:?}")
+    let parts = smart_comma_split(s);
+    let Some(format_str) = parts.first().map(|part| part.trim()) else {
+        return false;
+    };
+    format_str.len() >= 2
+        && format_str.starts_with('"')
+        && format_str.ends_with('"')
+        && format_str.contains(['{', '}'])
 }
 
 /// Handle macro expressions
@@ -3497,262 +7142,381 @@ fn handle_macro_expr(mac: &syn::Macro, state: &mut TranspilerState) -> Result<js
         return Err("Invalid macro".to_string());
     };
 
-    let tokens = mac.tokens.to_string();
     debug_print!("MACRO-DEBUG: {}", macro_name.as_str());
 
-    match macro_name.as_str() {
-        "println" | "print" => {
-            let console_method = if macro_name == "println" {
-                "log"
-            } else {
-                "log"
-            };
-            let console_expr =
-                state.mk_member_expr(js::Expr::Ident(state.mk_ident("console")), console_method);
-
-            if tokens.trim().is_empty() {
-                Ok(state.mk_call_expr(console_expr, vec![]))
-            } else if contains_format_arguments(&tokens) {
-                // Format-style macro
-                let format_result = handle_format_like_macro(&tokens, state)?;
-                Ok(state.mk_call_expr(console_expr, vec![format_result]))
-            } else {
-                // Simple string or expression
-                let arg = parse_macro_tokens(&tokens, state)?;
-                Ok(state.mk_call_expr(console_expr, vec![arg]))
-            }
+    match state.macro_handler(&macro_name) {
+        Some(handler) => handler(state, mac),
+        None => {
+            let mut supported = state.registered_macro_names();
+            supported.sort();
+            Err(format!(
+                "Unsupported macro `{}!` — no JavaScript mapping exists for it. \
+                 Supported macros: {}.",
+                macro_name,
+                supported
+                    .iter()
+                    .map(|name| format!("{name}!"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
         }
-        "eprintln" | "eprint" => {
-            let console_expr =
-                state.mk_member_expr(js::Expr::Ident(state.mk_ident("console")), "error");
+    }
+}
 
-            if tokens.trim().is_empty() {
-                Ok(state.mk_call_expr(console_expr, vec![]))
-            } else if tokens.contains("{}") {
-                let format_result = handle_format_like_macro(&tokens, state)?;
-                Ok(state.mk_call_expr(console_expr, vec![format_result]))
-            } else {
-                let arg = parse_macro_tokens(&tokens, state)?;
-                Ok(state.mk_call_expr(console_expr, vec![arg]))
-            }
+/// The built-in macro handlers every `TranspilerState` starts with.
+fn default_macro_handlers() -> HashMap<String, MacroHandler> {
+    let mut handlers: HashMap<String, MacroHandler> = HashMap::new();
+    handlers.insert("println".to_string(), Rc::new(macro_println));
+    handlers.insert("print".to_string(), Rc::new(macro_print));
+    handlers.insert("eprintln".to_string(), Rc::new(macro_eprintln));
+    handlers.insert("eprint".to_string(), Rc::new(macro_print));
+    handlers.insert("format".to_string(), Rc::new(macro_format));
+    handlers.insert("vec".to_string(), Rc::new(macro_vec));
+    handlers.insert("panic".to_string(), Rc::new(macro_panic));
+    handlers.insert("todo".to_string(), Rc::new(macro_todo));
+    handlers.insert("assert".to_string(), Rc::new(macro_assert));
+    handlers.insert("dbg".to_string(), Rc::new(macro_dbg));
+    handlers.insert("write".to_string(), Rc::new(macro_write));
+    handlers.insert("writeln".to_string(), Rc::new(macro_write));
+    handlers.insert("spread".to_string(), Rc::new(macro_spread));
+    // log-crate levels
+    for level in ["error", "warn", "info", "debug", "trace"] {
+        handlers.insert(level.to_string(), Rc::new(macro_log_level));
+    }
+    handlers
+}
+
+fn macro_println(state: &mut TranspilerState, mac: &syn::Macro) -> Result<js::Expr, String> {
+    console_call(state, "log", &mac.tokens.to_string())
+}
+
+/// `console.<method>(..)` for a printing macro's arguments: a format string
+/// and its arguments become one template literal, while arguments that
+/// don't start with a string literal (`println!(value)`) are passed to the
+/// console as they are.
+fn console_call(state: &mut TranspilerState, method: &str, tokens: &str) -> Result<js::Expr, String> {
+    let console_expr = state.mk_member_expr(js::Expr::Ident(state.mk_ident("console")), method);
+    let args = if tokens.trim().is_empty() {
+        vec![]
+    } else if contains_format_arguments(tokens) {
+        vec![handle_format_like_macro(tokens, state)?]
+    } else {
+        parse_comma_separated_exprs(tokens, state)?
+    };
+    Ok(state.mk_call_expr(console_expr, args))
+}
+
+/// log-crate macros go to the console method of the same level:
+/// `warn!("x {}", y)` -> `console.warn(`x ${y}`)`. `trace!` uses
+/// console.debug, since console.trace also prints a stack trace. A leading
+/// `target: ..,` has no console equivalent and is dropped.
+fn macro_log_level(state: &mut TranspilerState, mac: &syn::Macro) -> Result<js::Expr, String> {
+    let level = mac.path.segments.last().unwrap().ident.to_string();
+    let console_method = if level == "trace" { "debug" } else { level.as_str() };
+    let mut parts = smart_comma_split(&mac.tokens.to_string());
+    if parts
+        .first()
+        .is_some_and(|first| first.trim_start().starts_with("target") && first.contains(':'))
+    {
+        parts.remove(0);
+    }
+    console_call(state, console_method, &parts.join(","))
+}
+
+fn macro_eprintln(state: &mut TranspilerState, mac: &syn::Macro) -> Result<js::Expr, String> {
+    console_call(state, "error", &mac.tokens.to_string())
+}
+
+/// print!/eprint! are println!/eprintln! unless `print_to_stdout` is set:
+/// then `process.stdout.write(`...`)` (or stderr), without the newline.
+fn macro_print(state: &mut TranspilerState, mac: &syn::Macro) -> Result<js::Expr, String> {
+    let to_stderr = mac.path.is_ident("eprint");
+    if !state.print_to_stdout {
+        return if to_stderr {
+            macro_eprintln(state, mac)
+        } else {
+            macro_println(state, mac)
+        };
+    }
+    let tokens = mac.tokens.to_string();
+    let text = if tokens.trim().is_empty() {
+        state.mk_str_lit("")
+    } else if contains_format_arguments(&tokens) {
+        handle_format_like_macro(&tokens, state)?
+    } else {
+        parse_macro_tokens(&tokens, state)?
+    };
+    let stream = state.mk_member_expr(
+        js::Expr::Ident(state.mk_ident("process")),
+        if to_stderr { "stderr" } else { "stdout" },
+    );
+    Ok(state.mk_call_expr(state.mk_member_expr(stream, "write"), vec![text]))
+}
+
+fn macro_format(state: &mut TranspilerState, mac: &syn::Macro) -> Result<js::Expr, String> {
+    let tokens = mac.tokens.to_string();
+    handle_format_like_macro(&tokens, state)
+}
+
+/// write!(f, "...", args) -> f.write_str(`...`), with a trailing "\n" for
+/// writeln!. The formatter `toString()` hands to `fmt` collects the strings.
+/// A destination known to be a Vec (a byte buffer) gets the text's UTF-8
+/// bytes pushed instead: `buf.push(...new TextEncoder().encode(`...`))`.
+fn macro_write(state: &mut TranspilerState, mac: &syn::Macro) -> Result<js::Expr, String> {
+    let macro_name = mac.path.segments.last().unwrap().ident.to_string();
+    let tokens = mac.tokens.to_string();
+    let parts = smart_comma_split(&tokens);
+    let Some(target) = parts.first() else {
+        return Err(format!("{}! expects a destination argument", macro_name));
+    };
+    let target_is_vec = syn::parse_str::<Expr>(target).is_ok_and(|target| {
+        known_receiver_type(state, &target).is_some_and(|ty| wrapped_type_arg(ty, "Vec").is_some()
+            || matches!(ty, Type::Path(p) if p.path.is_ident("Vec")))
+    });
+    let target_js = parse_macro_tokens(target, state)?;
+    let rest = parts[1..].join(", ");
+    let mut text = if rest.is_empty() {
+        state.mk_str_lit("")
+    } else {
+        handle_format_like_macro(&rest, state)?
+    };
+    if macro_name == "writeln" {
+        text = state.mk_binary_expr(text, js::BinaryOp::Add, state.mk_str_lit("\n"));
+    }
+    if target_is_vec {
+        return Ok(push_spread(state, target_js, utf8_encode(state, text)));
+    }
+    Ok(state.mk_call_expr(state.mk_member_expr(target_js, "write_str"), vec![text]))
+}
+
+fn macro_vec(state: &mut TranspilerState, mac: &syn::Macro) -> Result<js::Expr, String> {
+    let tokens = mac.tokens.to_string();
+    if tokens.trim().is_empty() {
+        // vec!() -> []
+        Ok(js::Expr::Array(js::ArrayLit {
+            span: DUMMY_SP,
+            elems: vec![],
+        }))
+    } else if tokens.contains(';') {
+        // vec![value; count] -> Array.from({length: count}, () => value)
+        let parts: Vec<&str> = tokens.split(';').collect();
+        if parts.len() == 2 {
+            let value_expr = parse_macro_tokens(parts[0].trim(), state)?;
+            let count_expr = parse_macro_tokens(parts[1].trim(), state)?;
+
+            // Create Array.from call
+            let array_from =
+                state.mk_member_expr(js::Expr::Ident(state.mk_ident("Array")), "from");
+
+            // Create {length: count} object
+            let length_obj = js::Expr::Object(js::ObjectLit {
+                span: DUMMY_SP,
+                props: vec![js::PropOrSpread::Prop(Box::new(js::Prop::KeyValue(
+                    js::KeyValueProp {
+                        key: js::PropName::Ident(state.mk_ident_name("length")),
+                        value: Box::new(count_expr),
+                    },
+                )))],
+            });
+
+            // Create () => value arrow function
+            let arrow_fn = js::ArrowExpr {
+                span: DUMMY_SP,
+                params: vec![],
+                body: Box::new(js::BlockStmtOrExpr::Expr(Box::new(value_expr))),
+                is_async: false,
+                is_generator: false,
+                type_params: None,
+                return_type: None,
+                ctxt: SyntaxContext::empty(),
+            };
+
+            Ok(state.mk_call_expr(array_from, vec![length_obj, js::Expr::Arrow(arrow_fn)]))
+        } else {
+            Err("Invalid vec! syntax with semicolon".to_string())
         }
-        "format" => handle_format_like_macro(&tokens, state),
-        "vec" => {
-            if tokens.trim().is_empty() {
-                // vec!() -> []
-                Ok(js::Expr::Array(js::ArrayLit {
-                    span: DUMMY_SP,
-                    elems: vec![],
-                }))
-            } else if tokens.contains(';') {
-                // vec![value; count] -> Array.from({length: count}, () => value)
-                let parts: Vec<&str> = tokens.split(';').collect();
-                if parts.len() == 2 {
-                    let value_expr = parse_macro_tokens(parts[0].trim(), state)?;
-                    let count_expr = parse_macro_tokens(parts[1].trim(), state)?;
-
-                    // Create Array.from call
-                    let array_from =
-                        state.mk_member_expr(js::Expr::Ident(state.mk_ident("Array")), "from");
-
-                    // Create {length: count} object
-                    let length_obj = js::Expr::Object(js::ObjectLit {
-                        span: DUMMY_SP,
-                        props: vec![js::PropOrSpread::Prop(Box::new(js::Prop::KeyValue(
-                            js::KeyValueProp {
-                                key: js::PropName::Ident(state.mk_ident_name("length")),
-                                value: Box::new(count_expr),
-                            },
-                        )))],
-                    });
+    } else {
+        // vec![a, b, c] -> [a, b, c]
+        let elements = parse_comma_separated_exprs(&tokens, state)?;
+        let js_elements: Vec<Option<js::ExprOrSpread>> = elements
+            .into_iter()
+            .map(|expr| {
+                Some(js::ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(expr),
+                })
+            })
+            .collect();
+
+        Ok(js::Expr::Array(js::ArrayLit {
+            span: DUMMY_SP,
+            elems: js_elements,
+        }))
+    }
+}
+
+fn macro_panic(state: &mut TranspilerState, mac: &syn::Macro) -> Result<js::Expr, String> {
+    let tokens = mac.tokens.to_string();
+    // panic!("msg") → (function() { throw new Error("msg"); }).call(this)
+    // panic!("msg {}", arg) → (function() { throw new Error(`msg ${arg}`); }).call(this)
+    let error_msg = if tokens.trim().is_empty() {
+        state.mk_str_lit("explicit panic")
+    } else if contains_format_arguments(&tokens) {
+        handle_format_like_macro(&tokens, state)?
+    } else {
+        parse_macro_tokens(&tokens, state)?
+    };
+    let new_error = js::Expr::New(js::NewExpr {
+        span: DUMMY_SP,
+        callee: Box::new(js::Expr::Ident(state.mk_ident("Error"))),
+        args: Some(vec![js::ExprOrSpread {
+            spread: None,
+            expr: Box::new(error_msg),
+        }]),
+        type_args: None,
+        ctxt: SyntaxContext::empty(),
+    });
+    let throw_stmt = js::Stmt::Throw(js::ThrowStmt {
+        span: DUMMY_SP,
+        arg: Box::new(new_error),
+    });
+    Ok(state.mk_iife(vec![throw_stmt]))
+}
+
+fn macro_todo(state: &mut TranspilerState, mac: &syn::Macro) -> Result<js::Expr, String> {
+    let tokens = mac.tokens.to_string();
+    // todo!() → (function() { throw new Error("not yet implemented"); }).call(this)
+    // todo!("msg") → (function() { throw new Error("msg"); }).call(this)
+    let error_msg = if tokens.trim().is_empty() {
+        state.mk_str_lit("not yet implemented")
+    } else if contains_format_arguments(&tokens) {
+        handle_format_like_macro(&tokens, state)?
+    } else {
+        parse_macro_tokens(&tokens, state)?
+    };
+    let new_error = js::Expr::New(js::NewExpr {
+        span: DUMMY_SP,
+        callee: Box::new(js::Expr::Ident(state.mk_ident("Error"))),
+        args: Some(vec![js::ExprOrSpread {
+            spread: None,
+            expr: Box::new(error_msg),
+        }]),
+        type_args: None,
+        ctxt: SyntaxContext::empty(),
+    });
+    let throw_stmt = js::Stmt::Throw(js::ThrowStmt {
+        span: DUMMY_SP,
+        arg: Box::new(new_error),
+    });
+    Ok(state.mk_iife(vec![throw_stmt]))
+}
 
-                    // Create () => value arrow function
-                    let arrow_fn = js::ArrowExpr {
-                        span: DUMMY_SP,
-                        params: vec![],
-                        body: Box::new(js::BlockStmtOrExpr::Expr(Box::new(value_expr))),
-                        is_async: false,
-                        is_generator: false,
-                        type_params: None,
-                        return_type: None,
-                        ctxt: SyntaxContext::empty(),
-                    };
+fn macro_assert(state: &mut TranspilerState, mac: &syn::Macro) -> Result<js::Expr, String> {
+    let tokens = mac.tokens.to_string();
+    // assert!(condition) → IIFE: if (!condition) { throw new Error("assertion failed: condition") }
+    // assert!(condition, "msg") → IIFE: if (!condition) { throw new Error("msg") }
+    let parts = smart_comma_split(&tokens);
+    if parts.is_empty() {
+        return Err("assert! requires at least one argument".to_string());
+    }
 
-                    Ok(state.mk_call_expr(array_from, vec![length_obj, js::Expr::Arrow(arrow_fn)]))
-                } else {
-                    Err("Invalid vec! syntax with semicolon".to_string())
-                }
-            } else {
-                // vec![a, b, c] -> [a, b, c]
-                let elements = parse_comma_separated_exprs(&tokens, state)?;
-                let js_elements: Vec<Option<js::ExprOrSpread>> = elements
-                    .into_iter()
-                    .map(|expr| {
-                        Some(js::ExprOrSpread {
-                            spread: None,
-                            expr: Box::new(expr),
-                        })
-                    })
-                    .collect();
+    let condition_str = parts[0].trim();
+    let condition_expr = parse_macro_tokens(condition_str, state)?;
 
-                Ok(js::Expr::Array(js::ArrayLit {
-                    span: DUMMY_SP,
-                    elems: js_elements,
-                }))
-            }
+    let error_msg = if parts.len() > 1 {
+        // Custom message provided
+        let msg_tokens = parts[1..].join(", ");
+        let msg_trimmed = msg_tokens.trim();
+        if contains_format_arguments(msg_trimmed) {
+            handle_format_like_macro(msg_trimmed, state)?
+        } else {
+            parse_macro_tokens(msg_trimmed, state)?
         }
-        "panic" => {
-            // panic!("msg") → (function() { throw new Error("msg"); }).call(this)
-            // panic!("msg {}", arg) → (function() { throw new Error(`msg ${arg}`); }).call(this)
-            let error_msg = if tokens.trim().is_empty() {
-                state.mk_str_lit("explicit panic")
-            } else if contains_format_arguments(&tokens) {
-                handle_format_like_macro(&tokens, state)?
-            } else {
-                parse_macro_tokens(&tokens, state)?
-            };
-            let new_error = js::Expr::New(js::NewExpr {
-                span: DUMMY_SP,
-                callee: Box::new(js::Expr::Ident(state.mk_ident("Error"))),
-                args: Some(vec![js::ExprOrSpread {
-                    spread: None,
-                    expr: Box::new(error_msg),
-                }]),
-                type_args: None,
-                ctxt: SyntaxContext::empty(),
-            });
-            let throw_stmt = js::Stmt::Throw(js::ThrowStmt {
-                span: DUMMY_SP,
-                arg: Box::new(new_error),
-            });
-            Ok(state.mk_iife(vec![throw_stmt]))
-        }
-        "todo" => {
-            // todo!() → (function() { throw new Error("not yet implemented"); }).call(this)
-            // todo!("msg") → (function() { throw new Error("msg"); }).call(this)
-            let error_msg = if tokens.trim().is_empty() {
-                state.mk_str_lit("not yet implemented")
-            } else if contains_format_arguments(&tokens) {
-                handle_format_like_macro(&tokens, state)?
-            } else {
-                parse_macro_tokens(&tokens, state)?
-            };
-            let new_error = js::Expr::New(js::NewExpr {
-                span: DUMMY_SP,
-                callee: Box::new(js::Expr::Ident(state.mk_ident("Error"))),
-                args: Some(vec![js::ExprOrSpread {
-                    spread: None,
-                    expr: Box::new(error_msg),
-                }]),
-                type_args: None,
-                ctxt: SyntaxContext::empty(),
-            });
-            let throw_stmt = js::Stmt::Throw(js::ThrowStmt {
-                span: DUMMY_SP,
-                arg: Box::new(new_error),
-            });
-            Ok(state.mk_iife(vec![throw_stmt]))
-        }
-        "assert" => {
-            // assert!(condition) → IIFE: if (!condition) { throw new Error("assertion failed: condition") }
-            // assert!(condition, "msg") → IIFE: if (!condition) { throw new Error("msg") }
-            let parts = smart_comma_split(&tokens);
-            if parts.is_empty() {
-                return Err("assert! requires at least one argument".to_string());
-            }
+    } else {
+        state.mk_str_lit(&format!("assertion failed: {}", condition_str))
+    };
 
-            let condition_str = parts[0].trim();
-            let condition_expr = parse_macro_tokens(condition_str, state)?;
+    let new_error = js::Expr::New(js::NewExpr {
+        span: DUMMY_SP,
+        callee: Box::new(js::Expr::Ident(state.mk_ident("Error"))),
+        args: Some(vec![js::ExprOrSpread {
+            spread: None,
+            expr: Box::new(error_msg),
+        }]),
+        type_args: None,
+        ctxt: SyntaxContext::empty(),
+    });
+    let throw_stmt = js::Stmt::Throw(js::ThrowStmt {
+        span: DUMMY_SP,
+        arg: Box::new(new_error),
+    });
+    let throw_block = js::Stmt::Block(js::BlockStmt {
+        span: DUMMY_SP,
+        stmts: vec![throw_stmt],
+        ctxt: SyntaxContext::empty(),
+    });
 
-            let error_msg = if parts.len() > 1 {
-                // Custom message provided
-                let msg_tokens = parts[1..].join(", ");
-                let msg_trimmed = msg_tokens.trim();
-                if contains_format_arguments(msg_trimmed) {
-                    handle_format_like_macro(msg_trimmed, state)?
-                } else {
-                    parse_macro_tokens(msg_trimmed, state)?
-                }
-            } else {
-                state.mk_str_lit(&format!("assertion failed: {}", condition_str))
-            };
+    // if (!condition) { throw new Error(...) }
+    let negated_condition = js::Expr::Unary(js::UnaryExpr {
+        span: DUMMY_SP,
+        op: js::UnaryOp::Bang,
+        arg: Box::new(js::Expr::Paren(js::ParenExpr {
+            span: DUMMY_SP,
+            expr: Box::new(condition_expr),
+        })),
+    });
 
-            let new_error = js::Expr::New(js::NewExpr {
-                span: DUMMY_SP,
-                callee: Box::new(js::Expr::Ident(state.mk_ident("Error"))),
-                args: Some(vec![js::ExprOrSpread {
-                    spread: None,
-                    expr: Box::new(error_msg),
-                }]),
-                type_args: None,
-                ctxt: SyntaxContext::empty(),
-            });
-            let throw_stmt = js::Stmt::Throw(js::ThrowStmt {
-                span: DUMMY_SP,
-                arg: Box::new(new_error),
-            });
-            let throw_block = js::Stmt::Block(js::BlockStmt {
-                span: DUMMY_SP,
-                stmts: vec![throw_stmt],
-                ctxt: SyntaxContext::empty(),
-            });
+    let if_stmt = js::Stmt::If(js::IfStmt {
+        span: DUMMY_SP,
+        test: Box::new(negated_condition),
+        cons: Box::new(throw_block),
+        alt: None,
+    });
 
-            // if (!condition) { throw new Error(...) }
-            let negated_condition = js::Expr::Unary(js::UnaryExpr {
-                span: DUMMY_SP,
-                op: js::UnaryOp::Bang,
-                arg: Box::new(js::Expr::Paren(js::ParenExpr {
-                    span: DUMMY_SP,
-                    expr: Box::new(condition_expr),
-                })),
-            });
+    Ok(state.mk_iife(vec![if_stmt]))
+}
 
-            let if_stmt = js::Stmt::If(js::IfStmt {
-                span: DUMMY_SP,
-                test: Box::new(negated_condition),
-                cons: Box::new(throw_block),
-                alt: None,
-            });
+/// `spread!(args)` / `spread!(&args)` -> `args`. As a call argument the
+/// call site turns it into `...args`, which is how a `&[T]` parameter gets
+/// forwarded to a variadic JS function such as `console.log`.
+fn macro_spread(state: &mut TranspilerState, mac: &syn::Macro) -> Result<js::Expr, String> {
+    let mut inner: Expr = mac.parse_body().map_err(|e| format!("spread!: {e}"))?;
+    while let Expr::Reference(reference) = inner {
+        inner = *reference.expr;
+    }
+    rust_expr_to_js_with_state(&inner, state)
+}
 
-            Ok(state.mk_iife(vec![if_stmt]))
-        }
-        "dbg" => {
-            // dbg!(expr) → (console.log("expr =", expr), expr)
-            let trimmed = tokens.trim();
-            if trimmed.is_empty() {
-                // dbg!() with no args - just log empty
-                let console_log = state.mk_member_expr(
-                    js::Expr::Ident(state.mk_ident("console")),
-                    "log",
-                );
-                return Ok(state.mk_call_expr(console_log, vec![]));
-            }
+fn macro_dbg(state: &mut TranspilerState, mac: &syn::Macro) -> Result<js::Expr, String> {
+    let tokens = mac.tokens.to_string();
+    // dbg!(expr) → (console.log("expr =", expr), expr)
+    let trimmed = tokens.trim();
+    if trimmed.is_empty() {
+        // dbg!() with no args - just log empty
+        let console_log = state.mk_member_expr(
+            js::Expr::Ident(state.mk_ident("console")),
+            "log",
+        );
+        return Ok(state.mk_call_expr(console_log, vec![]));
+    }
 
-            let expr = parse_macro_tokens(trimmed, state)?;
-            let label = format!("{} =", trimmed);
+    let expr = parse_macro_tokens(trimmed, state)?;
+    let label = format!("{} =", trimmed);
 
-            let console_log = state.mk_member_expr(
-                js::Expr::Ident(state.mk_ident("console")),
-                "log",
-            );
-            let log_call = state.mk_call_expr(
-                console_log,
-                vec![state.mk_str_lit(&label), expr.clone()],
-            );
+    let console_log = state.mk_member_expr(
+        js::Expr::Ident(state.mk_ident("console")),
+        "log",
+    );
+    let log_call = state.mk_call_expr(
+        console_log,
+        vec![state.mk_str_lit(&label), expr.clone()],
+    );
 
-            // Use comma operator: (console.log("expr =", expr), expr)
-            Ok(js::Expr::Seq(js::SeqExpr {
-                span: DUMMY_SP,
-                exprs: vec![Box::new(log_call), Box::new(expr)],
-            }))
-        }
-        _ => Err(format!(
-            "Unsupported macro `{}!` — no JavaScript mapping exists for it. \
-             Supported macros include format!, println!/eprint!/print!, vec!, \
-             panic!, assert!/assert_eq!, todo!/unimplemented!, dbg!.",
-            macro_name
-        )),
-    }
+    // Use comma operator: (console.log("expr =", expr), expr)
+    Ok(js::Expr::Seq(js::SeqExpr {
+        span: DUMMY_SP,
+        exprs: vec![Box::new(log_call), Box::new(expr)],
+    }))
 }
 
 /// Handle format-like macros (format!, println! with {}, etc.)
@@ -3773,32 +7537,41 @@ fn handle_format_like_macro(
         format_str = &format_str[1..format_str.len() - 1];
     }
 
-    // Get format arguments
-    let format_args: Result<Vec<_>, _> = parts
-        .iter()
-        .skip(1)
-        .map(|arg| parse_macro_tokens(arg.trim(), state))
-        .collect();
-    let js_args = format_args?;
-
-    // Check if there are placeholders
-    if !format_str.contains("{}") && !format_str.contains("{:?}") {
-        return Ok(state.mk_template_literal(vec![format_str.into()], vec![]));
-        // this will return just the quoted format string.
-        // return Ok(state.mk_str_lit(format_str));
+    // Get format arguments, `name = value` ones apart
+    let mut js_args = Vec::new();
+    let mut named_args = Vec::new();
+    for arg in parts.iter().skip(1) {
+        let parsed = syn::parse_str::<syn::Expr>(arg.trim()).ok();
+        match parsed.as_ref().and_then(named_format_arg) {
+            Some((name, value)) => named_args.push((name, rust_expr_to_js_with_state(value, state)?)),
+            None => js_args.push(parse_macro_tokens(arg.trim(), state)?),
+        }
     }
 
-    // Enhanced parsing to handle both {} and {:?} placeholders
+    // Placeholders, and `{{`/`}}` even in a string without any
     let (template_parts, template_exprs) =
-        parse_format_string_with_debug(format_str, js_args, state)?;
+        parse_format_string_with_debug(format_str, js_args, &named_args, state)?;
 
     Ok(state.mk_template_literal(template_parts, template_exprs))
 }
 
-/// Parse format string handling both {} and {:?} placeholders
+/// `name = value` in the arguments of a format macro
+fn named_format_arg(arg: &Expr) -> Option<(String, &Expr)> {
+    match arg {
+        Expr::Assign(assign) => match &*assign.left {
+            Expr::Path(path) => Some((path.path.get_ident()?.to_string(), &assign.right)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Parse format string handling both {} and {:?} placeholders. `{name}`
+/// is the `name = value` argument of that name, else the variable in scope.
 fn parse_format_string_with_debug(
     format_str: &str,
     js_args: Vec<js::Expr>,
+    named_args: &[(String, js::Expr)],
     state: &mut TranspilerState,
 ) -> Result<(Vec<String>, Vec<js::Expr>), String> {
     let mut template_parts = Vec::new();
@@ -3840,29 +7613,38 @@ fn parse_format_string_with_debug(
                 return Err("Unclosed placeholder in format string".to_string());
             }
 
-            // Process the placeholder
-            if arg_index < js_args.len() {
-                let expr = if placeholder_content == ":?" {
-                    // Debug format - wrap with debug_repr
-                    state.mk_call_expr(
-                        js::Expr::Ident(state.mk_ident("debug_repr")),
-                        vec![js_args[arg_index].clone()],
-                    )
-                } else if placeholder_content.is_empty() {
-                    // Regular format
-                    js_args[arg_index].clone()
-                } else {
-                    // Other format specifiers - for now treat as regular
-                    // Could be extended to handle other format types like {:x}, {:02}, etc.
-                    js_args[arg_index].clone()
-                };
-
-                template_exprs.push(expr);
+            // Process the placeholder: the value is the next argument, one
+            // picked by position (`{0}`), or a variable captured by name
+            // (`{name}`); what follows a `:` is the format spec
+            let (argument, spec) = placeholder_content
+                .split_once(':')
+                .unwrap_or((placeholder_content.as_str(), ""));
+            let value = if argument.is_empty() {
                 arg_index += 1;
+                js_args.get(arg_index - 1).cloned()
+            } else if let Ok(position) = argument.parse::<usize>() {
+                js_args.get(position).cloned()
+            } else if let Some((_, value)) = named_args.iter().find(|(name, _)| name == argument) {
+                Some(value.clone())
             } else {
+                let js_name = state
+                    .resolve_variable(argument)
+                    .unwrap_or_else(|| escape_js_identifier(argument));
+                Some(js::Expr::Ident(state.mk_ident(&js_name)))
+            };
+            let expr = match value {
+                // Debug format - wrap with debug_repr
+                Some(value) if spec == "?" => state.mk_call_expr(
+                    js::Expr::Ident(state.mk_ident("debug_repr")),
+                    vec![value],
+                ),
+                // Other format specifiers - for now treat as regular
+                // Could be extended to handle other format types like {:x}, {:02}, etc.
+                Some(value) => value,
                 // Not enough arguments - add empty string
-                template_exprs.push(state.mk_str_lit(""));
-            }
+                None => state.mk_str_lit(""),
+            };
+            template_exprs.push(expr);
         } else if ch == '}' {
             if let Some(&next_ch) = chars.peek() {
                 if next_ch == '}' {
@@ -3918,39 +7700,21 @@ fn handle_format_macro_with_state(
             if let syn::Lit::Str(str_lit) = &lit.lit {
                 let format_str = str_lit.value();
 
-                /* This will return just the quotes:
-
-                                if !format_str.contains("{}") {
-                                    return Ok(state.mk_str_lit(&format_str));
-                                }
-                */
-
-                /* this will return the empty template (backticks): */
-
-                if !format_str.contains("{}") {
-                    return Ok(js::Expr::Tpl(js::Tpl {
-                        span: DUMMY_SP,
-                        exprs: vec![],
-                        quasis: vec![js::TplElement {
-                            span: DUMMY_SP,
-                            tail: true,
-                            cooked: Some(format_str.clone().into()),
-                            raw: swc_atoms::Atom::new(format_str.to_string()),
-                        }],
-                    }));
+                // Get format arguments, `name = value` ones apart
+                let mut js_args = Vec::new();
+                let mut named_args = Vec::new();
+                for arg in args.iter().skip(1) {
+                    match named_format_arg(arg) {
+                        Some((name, value)) => {
+                            named_args.push((name, rust_expr_to_js_with_state(value, state)?))
+                        }
+                        None => js_args.push(rust_expr_to_js_with_state(arg, state)?),
+                    }
                 }
 
-                // Get format arguments
-                let format_args: Result<Vec<_>, _> = args
-                    .iter()
-                    .skip(1)
-                    .map(|arg| rust_expr_to_js_with_state(arg, state))
-                    .collect();
-                let js_args = format_args?;
-
                 // Parse with debug support
                 let (template_parts, template_exprs) =
-                    parse_format_string_with_debug(&format_str, js_args, state)?;
+                    parse_format_string_with_debug(&format_str, js_args, &named_args, state)?;
 
                 return Ok(state.mk_template_literal(template_parts, template_exprs));
             }
@@ -3980,6 +7744,275 @@ fn handle_format_macro_with_state(
     }
 }
 
+/// Whether `expr?` applies to an Option: the operand is one, or - when its
+/// type isn't known - the enclosing function returns one (then `?` could
+/// not be on a Result)
+fn is_option_try(state: &TranspilerState, try_expr: &syn::ExprTry) -> bool {
+    if receiver_is_option(state, &try_expr.expr) {
+        return true;
+    }
+    if receiver_is_result(state, &try_expr.expr) || known_receiver_type(state, &try_expr.expr).is_some() {
+        return false;
+    }
+    state
+        .return_type()
+        .and_then(|ty| wrapped_type_arg(ty, "Option"))
+        .is_some()
+}
+
+/// `let x = opt?;` on an Option -> `const x = opt; if (x == null) return null;`
+/// binding first so `opt` is evaluated once, and `let x = fut.await?;` via
+/// `handle_local_with_await_try`. Any other `let` is a single declaration
+/// from `handle_local_statement`.
+fn handle_local_with_try(
+    block_action: BlockAction,
+    local: &syn::Local,
+    state: &mut TranspilerState,
+) -> Result<Vec<js::Stmt>, String> {
+    let binding = match &local.pat {
+        Pat::Ident(pat_ident) => Some(&pat_ident.ident),
+        Pat::Type(type_pat) => match &*type_pat.pat {
+            Pat::Ident(pat_ident) => Some(&pat_ident.ident),
+            _ => None,
+        },
+        _ => None,
+    };
+    if let (Some(ident), Some(init)) = (binding, &local.init)
+        && let Expr::Try(try_expr) = &*init.expr
+        && init.diverge.is_none()
+        && is_option_try(state, try_expr)
+    {
+        if state.value_iife_depth > 0 {
+            return Err(format!(
+                "`{}`: `?` on an Option can't return from the function inside an `if`, `match` \
+                 or block used as a value; move the `let` out of it",
+                source_snippet(try_expr)
+            ));
+        }
+        let mut unwrapped = local.clone();
+        if let Some(init) = unwrapped.init.as_mut() {
+            init.expr = try_expr.expr.clone();
+        }
+        let decl = handle_local_statement(block_action, &unwrapped, state)?;
+        let js_name = state
+            .resolve_variable(&ident.to_string())
+            .unwrap_or_else(|| escape_js_identifier(&ident.to_string()));
+        let early_return = js::Stmt::If(js::IfStmt {
+            span: DUMMY_SP,
+            test: Box::new(state.mk_binary_expr(
+                js::Expr::Ident(state.mk_ident(&js_name)),
+                js::BinaryOp::EqEq,
+                state.mk_null_lit(),
+            )),
+            cons: Box::new(state.mk_return_stmt(Some(state.mk_null_lit()))),
+            alt: None,
+        });
+        return Ok(vec![decl, early_return]);
+    }
+    if let (Some(ident), Some(init)) = (binding, &local.init)
+        && let Expr::Try(try_expr) = &*init.expr
+        && let Expr::Await(await_expr) = &*try_expr.expr
+        && init.diverge.is_none()
+    {
+        return handle_local_with_await_try(local, ident, await_expr, state);
+    }
+    if let Some(init) = &local.init
+        && init.diverge.is_none()
+        && let Some((decls, tail)) = inlinable_block_init(&init.expr)
+        && let Some(pat_ident) = binding_pat_ident(&local.pat)
+    {
+        return handle_local_with_inlined_block(block_action, local, pat_ident, decls, tail, state);
+    }
+    Ok(vec![handle_local_statement(block_action, local, state)?])
+}
+
+/// The `let`s and tail of a block initializer that is nothing but
+/// declarations followed by a value, e.g. `{ let a = 1; a + 1 }`
+fn inlinable_block_init(init: &Expr) -> Option<(Vec<&syn::Local>, &Expr)> {
+    let block = match init {
+        Expr::Block(block_expr) if block_expr.label.is_none() && block_expr.attrs.is_empty() => {
+            &block_expr.block
+        }
+        Expr::Const(const_expr) if const_expr.attrs.is_empty() => &const_expr.block,
+        _ => return None,
+    };
+    let (Stmt::Expr(tail, None), decls) = block.stmts.split_last()? else {
+        return None;
+    };
+    if decls.is_empty() {
+        return None;
+    }
+    let decls = decls
+        .iter()
+        .map(|stmt| match stmt {
+            Stmt::Local(local) if included_in_js(&local.attrs) => Some(local),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some((decls, tail))
+}
+
+/// The variable a `let x` / `let x: T` pattern binds
+fn binding_pat_ident(pat: &Pat) -> Option<&syn::PatIdent> {
+    match pat {
+        Pat::Ident(pat_ident) if pat_ident.subpat.is_none() => Some(pat_ident),
+        Pat::Type(type_pat) => binding_pat_ident(&type_pat.pat),
+        _ => None,
+    }
+}
+
+/// `let x = { let a = 1; a + 1 };` without an IIFE: the block's declarations
+/// go into the surrounding JS scope (under names that stay reserved there,
+/// so they remain invisible to the Rust code after the block), then
+/// `const x = a + 1;`
+fn handle_local_with_inlined_block(
+    block_action: BlockAction,
+    local: &syn::Local,
+    pat_ident: &syn::PatIdent,
+    decls: Vec<&syn::Local>,
+    tail: &Expr,
+    state: &mut TranspilerState,
+) -> Result<Vec<js::Stmt>, String> {
+    let declared_type = match &local.pat {
+        Pat::Type(type_pat) => Some((*type_pat.ty).clone()),
+        _ => None,
+    };
+    let wide = declared_type.as_ref().is_some_and(is_wide_int_type);
+
+    state.enter_scope();
+    let mut stmts = Vec::new();
+    for decl in decls {
+        stmts.extend(handle_local_with_try(block_action, decl, state)?);
+    }
+    let init_expr = state.with_wide_int(wide, |state| local_init_to_js(block_action, tail, state));
+    state.exit_scope_keeping_js_names();
+    let init_expr = init_expr?;
+
+    let var_name = pat_ident.ident.to_string();
+    let js_var_name = escape_js_identifier(&var_name);
+    let is_mutable = pat_ident.mutability.is_some();
+    state.declare_variable_type(&var_name, declared_type.or_else(|| infer_init_type(tail)));
+    let unique_js_var_name = state.declare_variable(var_name, js_var_name, is_mutable);
+    stmts.push(state.mk_var_decl(&unique_js_var_name, Some(init_expr), !is_mutable));
+    Ok(stmts)
+}
+
+/// `let x = fut.await?;` awaits in place and returns from the enclosing
+/// async function on failure - a rejected promise becomes `{error: e}`, a
+/// resolved `{error: ..}` Result is passed on as is - then binds the value,
+/// unwrapping `{ok: ..}` from transpiled functions that return a Result:
+///
+/// ```js
+/// let _temp1;
+/// try { _temp1 = await fut; } catch (e) { return { error: e }; }
+/// if (_temp1 && _temp1.error !== undefined) return _temp1;
+/// const x = _temp1 && _temp1.ok !== undefined ? _temp1.ok : _temp1;
+/// ```
+fn handle_local_with_await_try(
+    local: &syn::Local,
+    ident: &syn::Ident,
+    await_expr: &syn::ExprAwait,
+    state: &mut TranspilerState,
+) -> Result<Vec<js::Stmt>, String> {
+    let awaited = js::Expr::Await(js::AwaitExpr {
+        span: DUMMY_SP,
+        arg: Box::new(rust_expr_to_js_with_state(&await_expr.base, state)?),
+    });
+    let temp = state.generate_temp_var();
+    let temp_ident = || js::Expr::Ident(state.mk_ident(&temp));
+
+    let assign = js::Expr::Assign(js::AssignExpr {
+        span: DUMMY_SP,
+        op: js::AssignOp::Assign,
+        left: state.expr_to_assign_target(temp_ident())?,
+        right: Box::new(awaited),
+    });
+    let error_obj = result_object(state, "error", js::Expr::Ident(state.mk_ident("e")));
+    let try_stmt = js::Stmt::Try(Box::new(js::TryStmt {
+        span: DUMMY_SP,
+        block: js::BlockStmt {
+            span: DUMMY_SP,
+            stmts: vec![state.mk_expr_stmt(assign)],
+            ctxt: SyntaxContext::empty(),
+        },
+        handler: Some(js::CatchClause {
+            span: DUMMY_SP,
+            param: Some(js::Pat::Ident(js::BindingIdent {
+                id: state.mk_ident("e"),
+                type_ann: None,
+            })),
+            body: js::BlockStmt {
+                span: DUMMY_SP,
+                stmts: vec![state.mk_return_stmt(Some(error_obj))],
+                ctxt: SyntaxContext::empty(),
+            },
+        }),
+        finalizer: None,
+    }));
+
+    let is_result = |field: &str| {
+        state.mk_binary_expr(
+            temp_ident(),
+            js::BinaryOp::LogicalAnd,
+            state.mk_result_field_check(&temp, field),
+        )
+    };
+    let early_return = js::Stmt::If(js::IfStmt {
+        span: DUMMY_SP,
+        test: Box::new(is_result("error")),
+        cons: Box::new(state.mk_return_stmt(Some(temp_ident()))),
+        alt: None,
+    });
+    let value = js::Expr::Cond(js::CondExpr {
+        span: DUMMY_SP,
+        test: Box::new(is_result("ok")),
+        cons: Box::new(state.mk_member_expr(temp_ident(), "ok")),
+        alt: Box::new(temp_ident()),
+    });
+
+    let (annotation, is_mutable) = match &local.pat {
+        Pat::Type(type_pat) => (
+            Some((*type_pat.ty).clone()),
+            matches!(&*type_pat.pat, Pat::Ident(p) if p.mutability.is_some()),
+        ),
+        Pat::Ident(pat_ident) => (None, pat_ident.mutability.is_some()),
+        _ => (None, false),
+    };
+    let var_name = ident.to_string();
+    state.declare_variable_type(&var_name, annotation);
+    let js_var_name = state.declare_variable(var_name.clone(), escape_js_identifier(&var_name), is_mutable);
+
+    Ok(vec![
+        state.mk_var_decl(&temp, None, false),
+        try_stmt,
+        early_return,
+        state.mk_var_decl(&js_var_name, Some(value), !is_mutable),
+    ])
+}
+
+/// For `let m: HashMap<_, _> = iter.collect();` the collect target comes
+/// from the annotation rather than a turbofish; hand back the initializer
+/// with the annotated type moved into the turbofish so `map_collect` sees it.
+fn annotated_collect_init(local: &syn::Local) -> Option<syn::Expr> {
+    let (Pat::Type(type_pat), Some(init)) = (&local.pat, &local.init) else {
+        return None;
+    };
+    let Expr::MethodCall(call) = &*init.expr else {
+        return None;
+    };
+    if call.method != "collect" || call.turbofish.is_some() {
+        return None;
+    }
+    let mut call = call.clone();
+    call.turbofish = Some(syn::AngleBracketedGenericArguments {
+        colon2_token: Some(Default::default()),
+        lt_token: Default::default(),
+        args: std::iter::once(syn::GenericArgument::Type((*type_pat.ty).clone())).collect(),
+        gt_token: Default::default(),
+    });
+    Some(Expr::MethodCall(call))
+}
+
 /// Handle local variable declarations
 fn handle_local_statement(
     block_action: BlockAction,
@@ -3987,7 +8020,11 @@ fn handle_local_statement(
     state: &mut TranspilerState,
 ) -> Result<js::Stmt, String> {
     if let Some(init) = &local.init {
-        let init_expr = rust_expr_to_js_with_action_and_state(block_action, &init.expr, state)?;
+        let annotated_collect = annotated_collect_init(local);
+        let init_rust = annotated_collect.as_ref().unwrap_or(&*init.expr);
+        let wide = matches!(&local.pat, Pat::Type(type_pat) if is_wide_int_type(&type_pat.ty));
+        let init_expr =
+            state.with_wide_int(wide, |state| local_init_to_js(block_action, init_rust, state))?;
 
         match &local.pat {
             Pat::Ident(pat_ident) => {
@@ -3995,19 +8032,21 @@ fn handle_local_statement(
                 let js_var_name = escape_js_identifier(&var_name);
                 let is_mutable = pat_ident.mutability.is_some();
 
+                state.declare_variable_type(&var_name, infer_init_type(&init.expr));
                 let unique_js_var_name = state.declare_variable(var_name, js_var_name, is_mutable);
 
                 Ok(state.mk_var_decl(&unique_js_var_name, Some(init_expr), !is_mutable))
             }
             Pat::Type(type_pat) => {
                 // Handle typed patterns like `let x: i32 = 23;`
-                // We ignore the type annotation and just handle the inner pattern
+                // The type annotation is only recorded for later lookups
                 match &*type_pat.pat {
                     Pat::Ident(pat_ident) => {
                         let var_name = pat_ident.ident.to_string();
                         let js_var_name = escape_js_identifier(&var_name);
                         let is_mutable = pat_ident.mutability.is_some();
 
+                        state.declare_variable_type(&var_name, Some((*type_pat.ty).clone()));
                         let unique_js_var_name = state.declare_variable(var_name, js_var_name, is_mutable);
 
                         Ok(state.mk_var_decl(&unique_js_var_name, Some(init_expr), !is_mutable))
@@ -4128,18 +8167,24 @@ fn handle_local_statement(
             _ => panic!("Unsupported destructuring pattern {:?}", &local.pat),
         }
     } else {
-        // Variable declaration without initialization
-        if let Pat::Ident(pat_ident) = &local.pat {
-            let var_name = pat_ident.ident.to_string();
-            let js_var_name = escape_js_identifier(&var_name);
-            let is_mutable = pat_ident.mutability.is_some();
+        // Variable declaration without initialization (`let x;`, `let x: T;`).
+        // Later `x = ..` assignments resolve to the name declared here.
+        let (pat_ident, ty) = match &local.pat {
+            Pat::Ident(pat_ident) => (pat_ident, None),
+            Pat::Type(type_pat) => match &*type_pat.pat {
+                Pat::Ident(pat_ident) => (pat_ident, Some((*type_pat.ty).clone())),
+                _ => return Err("a deferred-init `let` must bind a single variable".to_string()),
+            },
+            _ => return Err("a deferred-init `let` must bind a single variable".to_string()),
+        };
+        let var_name = pat_ident.ident.to_string();
+        let js_var_name = escape_js_identifier(&var_name);
+        let is_mutable = pat_ident.mutability.is_some();
 
-            let unique_js_var_name = state.declare_variable(var_name, js_var_name, is_mutable);
+        state.declare_variable_type(&var_name, ty);
+        let unique_js_var_name = state.declare_variable(var_name, js_var_name, is_mutable);
 
-            Ok(state.mk_var_decl(&unique_js_var_name, None, false)) // Always use let for uninitialized
-        } else {
-            panic!("Unsupported variable pattern {:?}", &local.pat)
-        }
+        Ok(state.mk_var_decl(&unique_js_var_name, None, false)) // Always use let for uninitialized
     }
 }
 
@@ -4182,11 +8227,11 @@ fn smart_comma_split(input: &str) -> Vec<String> {
                 in_quotes = !in_quotes;
                 current_part.push(ch);
             }
-            '(' => {
+            '(' if !in_quotes => {
                 paren_depth += 1;
                 current_part.push(ch);
             }
-            ')' => {
+            ')' if !in_quotes => {
                 paren_depth -= 1;
                 current_part.push(ch);
             }
@@ -4307,13 +8352,23 @@ pub fn escape_js_identifier(rust_ident: &str) -> String {
 pub fn generate_js_class_for_struct_with_state(
     input_struct: &ItemStruct,
 ) -> Result<js::ModuleItem, String> {
-    let mut state = TranspilerState::new();
+    struct_to_class(input_struct, &mut TranspilerState::new())
+}
+
+fn struct_to_class(
+    input_struct: &ItemStruct,
+    state: &mut TranspilerState,
+) -> Result<js::ModuleItem, String> {
     let struct_name = input_struct.ident.to_string();
+    if matches!(input_struct.fields, Fields::Unnamed(_)) {
+        state.register_tuple_struct(&struct_name);
+    }
 
     let fields: Vec<(String, String)> = match &input_struct.fields {
         Fields::Named(fields_named) => fields_named
             .named
             .iter()
+            .filter(|field| included_in_js(&field.attrs))
             .filter_map(|field| {
                 if let Some(ident) = &field.ident {
                     let field_name = ident.to_string();
@@ -4330,11 +8385,49 @@ pub fn generate_js_class_for_struct_with_state(
                 }
             })
             .collect(),
-        Fields::Unnamed(_) => {
-            vec![("data".to_string(), "Array".to_string())]
-        }
+        Fields::Unnamed(fields_unnamed) => fields_unnamed
+            .unnamed
+            .iter()
+            .enumerate()
+            .map(|(i, field)| (format!("_{}", i), format_rust_type(&field.ty)))
+            .collect(),
         Fields::Unit => vec![],
     };
+    let js_fields: Vec<&syn::Field> = input_struct
+        .fields
+        .iter()
+        .filter(|field| included_in_js(&field.attrs) || field.ident.is_none())
+        .collect();
+    // The Rust type of each entry of `fields`
+    let field_types: Vec<&Type> = js_fields.iter().map(|field| &field.ty).collect();
+    // The key each named field has in the JSON form. `rename_all` only
+    // changes the keys: the class keeps the Rust field names, which is what
+    // the transpiled `self.field` accesses in methods refer to.
+    let rename_all = json_rename_all(&input_struct.attrs)?;
+    let json_keys: Vec<String> = js_fields
+        .iter()
+        .zip(&fields)
+        .map(|(field, (name, _))| {
+            json_rename(&field.attrs).unwrap_or_else(|| match rename_all {
+                Some(case) => case.apply(name),
+                None => name.clone(),
+            })
+        })
+        .collect();
+    // The class each field's JSON is reconstructed into by fromJSON, if any
+    let field_classes: Vec<Option<NestedJson>> = field_types
+        .iter()
+        .map(|ty| NestedJson::of(ty, &input_struct.generics))
+        .collect();
+    // `Option` fields, which are serialized as null when absent
+    let nullable: Vec<bool> = field_types
+        .iter()
+        .map(|ty| wrapped_type_arg(ty, "Option").is_some())
+        .collect();
+    // Tuple structs are stored positionally (`this[0]`, `this[1]`, ...) so
+    // `.0`/`.1` access works the same as on plain tuples; the `_0`, `_1`
+    // names are only used for the constructor parameters.
+    let is_tuple = matches!(input_struct.fields, Fields::Unnamed(_));
 
     // Create constructor parameters
     let constructor_params: Vec<js::ParamOrTsParamProp> = fields
@@ -4353,11 +8446,16 @@ pub fn generate_js_class_for_struct_with_state(
 
     // Create constructor body
     let mut constructor_body = Vec::new();
-    for (name, _) in &fields {
+    for (i, (name, _)) in fields.iter().enumerate() {
+        let target = if is_tuple {
+            tuple_field_expr(state, state.mk_this_expr(), i)
+        } else {
+            state.mk_member_expr(state.mk_this_expr(), name)
+        };
         let assignment = js::Expr::Assign(js::AssignExpr {
             span: DUMMY_SP,
             op: js::AssignOp::Assign,
-            left: state.expr_to_assign_target(state.mk_member_expr(state.mk_this_expr(), name))?,
+            left: state.expr_to_assign_target(target)?,
             right: Box::new(js::Expr::Ident(state.mk_ident(name))),
         });
         constructor_body.push(state.mk_expr_stmt(assignment));
@@ -4378,14 +8476,24 @@ pub fn generate_js_class_for_struct_with_state(
     };
 
     // Create toJSON method
-    let to_json_method = create_to_json_method(&fields, &mut state)?;
+    let to_json_method =
+        create_to_json_method(&fields, &json_keys, &nullable, is_tuple, state)?;
 
     // Create fromJSON static method
-    let from_json_method = create_from_json_static_method(&struct_name, &fields, &mut state)?;
+    let from_json_method =
+        create_from_json_static_method(
+            &struct_name,
+            &fields,
+            &json_keys,
+            &field_classes,
+            &nullable,
+            is_tuple,
+            state,
+        )?;
 
     // Create class with all methods
     let class = js::Class {
-        span: DUMMY_SP,
+        span: state.doc_comment_span(&input_struct.attrs),
         decorators: vec![],
         body: vec![
             js::ClassMember::Constructor(constructor),
@@ -4409,27 +8517,65 @@ pub fn generate_js_class_for_struct_with_state(
     ))))
 }
 
+/// `obj[index]`, the positional field of a tuple or tuple struct
+fn tuple_field_expr(state: &TranspilerState, obj: js::Expr, index: usize) -> js::Expr {
+    js::Expr::Member(js::MemberExpr {
+        span: DUMMY_SP,
+        obj: Box::new(obj),
+        prop: js::MemberProp::Computed(js::ComputedPropName {
+            span: DUMMY_SP,
+            expr: Box::new(state.mk_num_lit(index as f64)),
+        }),
+    })
+}
+
 // Helper function to create toJSON method
 fn create_to_json_method(
     fields: &[(String, String)],
+    json_keys: &[String],
+    nullable: &[bool],
+    is_tuple: bool,
     state: &mut TranspilerState,
 ) -> Result<js::ClassMethod, String> {
-    // Create object properties for each field
-    let mut props = Vec::new();
+    let value = |state: &TranspilerState, i: usize, field: js::Expr| {
+        nullable_json_value(state, field, nullable.get(i).copied().unwrap_or(false))
+    };
+    let return_obj = if is_tuple && fields.len() == 1 {
+        // Newtype structs serialize as their inner value, like serde does
+        value(state, 0, tuple_field_expr(state, state.mk_this_expr(), 0))
+    } else if is_tuple {
+        // Tuple structs serialize as an array
+        js::Expr::Array(js::ArrayLit {
+            span: DUMMY_SP,
+            elems: (0..fields.len())
+                .map(|i| {
+                    let field = tuple_field_expr(state, state.mk_this_expr(), i);
+                    Some(js::ExprOrSpread {
+                        spread: None,
+                        expr: Box::new(value(state, i, field)),
+                    })
+                })
+                .collect(),
+        })
+    } else {
+        // Create object properties for each field
+        let mut props = Vec::new();
 
-    for (name, _) in fields {
-        props.push(js::PropOrSpread::Prop(Box::new(js::Prop::KeyValue(
-            js::KeyValueProp {
-                key: js::PropName::Ident(state.mk_ident_name(name)),
-                value: Box::new(state.mk_member_expr(state.mk_this_expr(), name)),
-            },
-        ))));
-    }
+        for (i, ((name, _), key)) in fields.iter().zip(json_keys).enumerate() {
+            let field = state.mk_member_expr(state.mk_this_expr(), name);
+            props.push(js::PropOrSpread::Prop(Box::new(js::Prop::KeyValue(
+                js::KeyValueProp {
+                    key: json_prop_name(state, key),
+                    value: Box::new(value(state, i, field)),
+                },
+            ))));
+        }
 
-    let return_obj = js::Expr::Object(js::ObjectLit {
-        span: DUMMY_SP,
-        props,
-    });
+        js::Expr::Object(js::ObjectLit {
+            span: DUMMY_SP,
+            props,
+        })
+    };
 
     let method_body = js::BlockStmt {
         span: DUMMY_SP,
@@ -4460,17 +8606,286 @@ fn create_to_json_method(
     })
 }
 
+/// The JSON key given by a field's `#[js_rename = "..."]`, if any
+fn json_rename(attrs: &[syn::Attribute]) -> Option<String> {
+    attrs.iter().find_map(|attr| match &attr.meta {
+        syn::Meta::NameValue(syn::MetaNameValue {
+            path,
+            value: Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(key), .. }),
+            ..
+        }) if path.is_ident("js_rename") => Some(key.value()),
+        _ => None,
+    })
+}
+
+/// A naming convention for `#[js(rename_all = "...")]`, spelled as in serde
+#[derive(Clone, Copy)]
+enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "lowercase" => RenameRule::Lower,
+            "UPPERCASE" => RenameRule::Upper,
+            "PascalCase" => RenameRule::Pascal,
+            "camelCase" => RenameRule::Camel,
+            "snake_case" => RenameRule::Snake,
+            "SCREAMING_SNAKE_CASE" => RenameRule::ScreamingSnake,
+            "kebab-case" => RenameRule::Kebab,
+            "SCREAMING-KEBAB-CASE" => RenameRule::ScreamingKebab,
+            _ => return None,
+        })
+    }
+
+    /// Renames a snake_case field name
+    fn apply(self, field: &str) -> String {
+        let capitalize = |word: &str| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        };
+        let words = field.split('_').filter(|word| !word.is_empty());
+        match self {
+            RenameRule::Lower | RenameRule::Snake => field.to_string(),
+            RenameRule::Upper | RenameRule::ScreamingSnake => field.to_uppercase(),
+            RenameRule::Pascal => words.map(capitalize).collect(),
+            RenameRule::Camel => words
+                .enumerate()
+                .map(|(i, word)| if i == 0 { word.to_string() } else { capitalize(word) })
+                .collect(),
+            RenameRule::Kebab => field.replace('_', "-"),
+            RenameRule::ScreamingKebab => field.replace('_', "-").to_uppercase(),
+        }
+    }
+}
+
+/// The struct-level `#[js(rename_all = "...")]` convention, if any
+fn json_rename_all(attrs: &[syn::Attribute]) -> Result<Option<RenameRule>, String> {
+    let mut rule = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("js")) {
+        attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident("rename_all") {
+                return Err(meta.error("unsupported #[js] option, expected `rename_all`"));
+            }
+            let name: syn::LitStr = meta.value()?.parse()?;
+            rule = Some(RenameRule::from_name(&name.value()).ok_or_else(|| {
+                meta.error(format!("unknown rename_all convention \"{}\"", name.value()))
+            })?);
+            Ok(())
+        })
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(rule)
+}
+
+/// Whether a JSON key can be written as a bare identifier in JS
+fn is_plain_json_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// The property name for a JSON key, quoted when it isn't an identifier
+fn json_prop_name(state: &TranspilerState, key: &str) -> js::PropName {
+    if is_plain_json_key(key) {
+        js::PropName::Ident(state.mk_ident_name(key))
+    } else {
+        js::PropName::Str(js::Str { span: DUMMY_SP, value: key.into(), raw: None })
+    }
+}
+
+/// `json.key`, or `json["key"]` when the key isn't an identifier
+fn json_member(state: &TranspilerState, json: js::Expr, key: &str) -> js::Expr {
+    if is_plain_json_key(key) {
+        return state.mk_member_expr(json, key);
+    }
+    js::Expr::Member(js::MemberExpr {
+        span: DUMMY_SP,
+        obj: Box::new(json),
+        prop: js::MemberProp::Computed(js::ComputedPropName {
+            span: DUMMY_SP,
+            expr: Box::new(state.mk_str_lit(key)),
+        }),
+    })
+}
+
+/// `value ?? null` for an `Option` field, so `None` is always `null` in
+/// the JSON and in the rebuilt instance, never `undefined` or a missing key
+fn nullable_json_value(state: &TranspilerState, value: js::Expr, nullable: bool) -> js::Expr {
+    if !nullable {
+        return value;
+    }
+    state.mk_binary_expr(value, js::BinaryOp::NullishCoalescing, state.mk_null_lit())
+}
+
+/// A struct field whose JSON fromJSON turns back into instances
+enum NestedJson {
+    /// A single value of the named class (or enum)
+    Instance(String),
+    /// A `Vec` of them, reconstructed element by element
+    Array(String),
+}
+
+impl NestedJson {
+    fn of(ty: &Type, generics: &syn::Generics) -> Option<Self> {
+        match wrapped_type_arg(ty, "Vec") {
+            Some(elem) => field_class_name(elem, generics).map(NestedJson::Array),
+            None => field_class_name(ty, generics).map(NestedJson::Instance),
+        }
+    }
+}
+
+/// The type a struct field holds when that looks like another transpiled
+/// class or enum: a plain capitalized type name that isn't a built-in or one
+/// of the struct's own type parameters, seen through `Option`, `Box`, `Rc`
+/// and `Arc`
+fn field_class_name(ty: &Type, generics: &syn::Generics) -> Option<String> {
+    if let Type::Reference(type_ref) = ty {
+        return field_class_name(&type_ref.elem, generics);
+    }
+    if let Some(inner) = ["Option", "Box", "Rc", "Arc"]
+        .iter()
+        .find_map(|wrapper| wrapped_type_arg(ty, wrapper))
+    {
+        return field_class_name(inner, generics);
+    }
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    let name = segment.ident.to_string();
+    let is_type_param = generics.type_params().any(|param| param.ident == segment.ident);
+    let is_plain = type_path.qself.is_none() && segment.arguments.is_none();
+    (is_plain
+        && !is_type_param
+        && name != "Self"
+        && name.starts_with(|c: char| c.is_ascii_uppercase())
+        && format_rust_type(ty) == "object")
+        .then_some(name)
+}
+
+/// `json.f != null && typeof Class !== "undefined" ? Class.fromJSON(json.f)
+/// : json.f`: nested JSON becomes an instance again when the class (or enum)
+/// has been defined, and is passed through as is otherwise. An enum's
+/// `fromJSON` parses a JSON string, so when `Class` turns out not to be a
+/// class (a function) the value is handed over as `JSON.stringify(json.f)`.
+fn reconstruct_from_json(state: &TranspilerState, value: js::Expr, class: &str) -> js::Expr {
+    let present = state.mk_binary_expr(value.clone(), js::BinaryOp::NotEq, state.mk_null_lit());
+    let defined = state.mk_binary_expr(
+        js::Expr::Unary(js::UnaryExpr {
+            span: DUMMY_SP,
+            op: js::UnaryOp::TypeOf,
+            arg: Box::new(js::Expr::Ident(state.mk_ident(class))),
+        }),
+        js::BinaryOp::NotEqEq,
+        state.mk_str_lit("undefined"),
+    );
+    let is_class = state.mk_binary_expr(
+        js::Expr::Unary(js::UnaryExpr {
+            span: DUMMY_SP,
+            op: js::UnaryOp::TypeOf,
+            arg: Box::new(js::Expr::Ident(state.mk_ident(class))),
+        }),
+        js::BinaryOp::EqEqEq,
+        state.mk_str_lit("function"),
+    );
+    let stringified = state.mk_call_expr(
+        state.mk_member_expr(js::Expr::Ident(state.mk_ident("JSON")), "stringify"),
+        vec![value.clone()],
+    );
+    let argument = js::Expr::Cond(js::CondExpr {
+        span: DUMMY_SP,
+        test: Box::new(is_class),
+        cons: Box::new(value.clone()),
+        alt: Box::new(stringified),
+    });
+    let from_json = state.mk_member_expr(js::Expr::Ident(state.mk_ident(class)), "fromJSON");
+    js::Expr::Cond(js::CondExpr {
+        span: DUMMY_SP,
+        test: Box::new(state.mk_binary_expr(present, js::BinaryOp::LogicalAnd, defined)),
+        cons: Box::new(state.mk_call_expr(from_json, vec![argument])),
+        alt: Box::new(value),
+    })
+}
+
+/// A collection field missing from (or null in) the JSON starts out empty:
+/// `json.items ?? []` for an `Array`, `json.index ?? {}` for a `Map`
+fn json_field_with_default(state: &TranspilerState, value: js::Expr, ty: &str) -> js::Expr {
+    let empty = match ty {
+        "Array" => js::Expr::Array(js::ArrayLit {
+            span: DUMMY_SP,
+            elems: vec![],
+        }),
+        "Map" => js::Expr::Object(js::ObjectLit {
+            span: DUMMY_SP,
+            props: vec![],
+        }),
+        _ => return value,
+    };
+    state.mk_binary_expr(value, js::BinaryOp::NullishCoalescing, empty)
+}
+
 // Helper function to create fromJSON static method
 fn create_from_json_static_method(
     struct_name: &str,
     fields: &[(String, String)],
+    json_keys: &[String],
+    field_classes: &[Option<NestedJson>],
+    nullable: &[bool],
+    is_tuple: bool,
     state: &mut TranspilerState,
 ) -> Result<js::ClassMethod, String> {
-    // Create constructor arguments from json properties
-    let constructor_args: Vec<js::Expr> = fields
-        .iter()
-        .map(|(name, _)| state.mk_member_expr(js::Expr::Ident(state.mk_ident("json")), name))
-        .collect();
+    // Create constructor arguments from json properties (or array elements,
+    // or the value itself for a newtype struct - the inverse of toJSON)
+    let json = || js::Expr::Ident(state.mk_ident("json"));
+    let constructor_args: Vec<js::Expr> = if is_tuple && fields.len() == 1 {
+        vec![json()]
+    } else if is_tuple {
+        (0..fields.len())
+            .map(|i| tuple_field_expr(state, json(), i))
+            .collect()
+    } else {
+        json_keys.iter().map(|key| json_member(state, json(), key)).collect()
+    };
+    let constructor_args = constructor_args
+        .into_iter()
+        .zip(fields.iter().zip(field_classes))
+        .zip(nullable)
+        .map(|((arg, ((_, ty), class)), &nullable)| match class {
+            Some(NestedJson::Instance(class)) if nullable => {
+                let value = js::Expr::Paren(js::ParenExpr {
+                    span: DUMMY_SP,
+                    expr: Box::new(reconstruct_from_json(state, arg, class)),
+                });
+                nullable_json_value(state, value, true)
+            }
+            Some(NestedJson::Instance(class)) => reconstruct_from_json(state, arg, class),
+            Some(NestedJson::Array(class)) => {
+                let item = js::Expr::Ident(state.mk_ident("item"));
+                let each = state.mk_arrow(&["item"], reconstruct_from_json(state, item, class));
+                let array = js::Expr::Paren(js::ParenExpr {
+                    span: DUMMY_SP,
+                    expr: Box::new(json_field_with_default(state, arg, ty)),
+                });
+                state.mk_call_expr(state.mk_member_expr(array, "map"), vec![each])
+            }
+            None if nullable => nullable_json_value(state, arg, true),
+            None => json_field_with_default(state, arg, ty),
+        })
+        .collect::<Vec<_>>();
 
     // Create new StructName(json.field1, json.field2, ...)
     let new_instance = js::Expr::New(js::NewExpr {
@@ -4521,24 +8936,68 @@ fn create_from_json_static_method(
     })
 }
 
+/// Numeric values for a C-like enum - all unit variants, at least one with an
+/// explicit `= n` - following Rust's rule that an implicit discriminant is
+/// the previous one plus one (starting at 0). `None` for any other enum,
+/// whose unit variants stay name strings.
+fn enum_discriminants(input_enum: &ItemEnum) -> Result<Option<Vec<f64>>, String> {
+    let all_unit = input_enum.variants.iter().all(|v| matches!(v.fields, Fields::Unit));
+    let any_explicit = input_enum.variants.iter().any(|v| v.discriminant.is_some());
+    if !all_unit || !any_explicit {
+        return Ok(None);
+    }
+    let literal = |expr: &Expr| match expr {
+        Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(i), .. }) => i.base10_parse::<f64>().ok(),
+        Expr::Unary(syn::ExprUnary { op: syn::UnOp::Neg(_), expr, .. }) => match &**expr {
+            Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(i), .. }) => i.base10_parse::<f64>().ok().map(|v| -v),
+            _ => None,
+        },
+        _ => None,
+    };
+    let mut next = 0.0;
+    let mut values = Vec::new();
+    for variant in &input_enum.variants {
+        let value = match &variant.discriminant {
+            Some((_, expr)) => literal(expr).ok_or_else(|| {
+                format!(
+                    "Unsupported discriminant for `{}::{}`: only integer literals are supported, found `{}`",
+                    input_enum.ident,
+                    variant.ident,
+                    source_snippet(expr)
+                )
+            })?,
+            None => next,
+        };
+        values.push(value);
+        next = value + 1.0;
+    }
+    Ok(Some(values))
+}
+
 /// Generate JavaScript enum
 pub fn generate_js_enum_with_state(input_enum: &ItemEnum) -> Result<Vec<js::ModuleItem>, String> {
     let mut state = TranspilerState::new();
     let enum_name = input_enum.ident.to_string();
 
     let mut properties = Vec::new();
+    let discriminants = enum_discriminants(input_enum)?;
 
     // Add enum variants
-    for variant in &input_enum.variants {
+    for (index, variant) in input_enum.variants.iter().enumerate() {
         let variant_name = variant.ident.to_string();
 
         match &variant.fields {
             Fields::Unit => {
-                // Simple enum variants become string values
+                // Simple enum variants become string values, or their
+                // numbers in a C-like enum
+                let value = match &discriminants {
+                    Some(values) => state.mk_num_lit(values[index]),
+                    None => state.mk_str_lit_single_quote(&variant_name),
+                };
                 properties.push(js::PropOrSpread::Prop(Box::new(js::Prop::KeyValue(
                     js::KeyValueProp {
                         key: js::PropName::Ident(state.mk_ident_name(&variant_name)),
-                        value: Box::new(state.mk_str_lit_single_quote(&variant_name)),
+                        value: Box::new(value),
                     },
                 ))));
             }
@@ -4700,7 +9159,7 @@ pub fn generate_js_enum_with_state(input_enum: &ItemEnum) -> Result<Vec<js::Modu
         js::BlockStmt {
             span: DUMMY_SP,
             stmts: vec![
-                // if (typeof value === 'string') {
+                // if (typeof value === 'string') { ('number' in a C-like enum)
                 js::Stmt::If(js::IfStmt {
                     span: DUMMY_SP,
                     test: Box::new(state.mk_binary_expr(
@@ -4710,7 +9169,7 @@ pub fn generate_js_enum_with_state(input_enum: &ItemEnum) -> Result<Vec<js::Modu
                             arg: Box::new(js::Expr::Ident(state.mk_ident("value"))),
                         }),
                         js::BinaryOp::EqEqEq,
-                        state.mk_str_lit("string"),
+                        state.mk_str_lit(if discriminants.is_some() { "number" } else { "string" }),
                     )),
                     cons: Box::new(js::Stmt::Block(js::BlockStmt {
                         span: DUMMY_SP,
@@ -4861,6 +9320,25 @@ pub fn ast_to_code_verbose(module_items: &[js::ModuleItem]) -> Result<String, St
 
 /// Convert JavaScript AST to compact code string (single line arrays)
 pub fn ast_to_code_compact(module_items: &[js::ModuleItem]) -> Result<String, String> {
+    emit_compact(module_items, &[])
+}
+
+/// Convert JavaScript AST built with `state` to code, with the comments the
+/// transpiler attached to its nodes. The comments are dropped from `state`
+/// afterwards, so each batch of items is emitted this way once.
+pub fn ast_to_code_with_state(
+    module_items: &[js::ModuleItem],
+    state: &mut TranspilerState,
+) -> Result<String, String> {
+    let generated = std::mem::take(&mut state.generated_comments);
+    emit_compact(module_items, &generated)
+}
+
+/// `ast_to_code_compact`, leading nodes with the comments in `generated`
+fn emit_compact(
+    module_items: &[js::ModuleItem],
+    generated: &[Vec<GeneratedComment>],
+) -> Result<String, String> {
     use swc_common::SourceMap;
     use swc_common::sync::Lrc;
     use swc_ecma_codegen::{Config, Emitter};
@@ -4882,11 +9360,12 @@ pub fn ast_to_code_compact(module_items: &[js::ModuleItem]) -> Result<String, St
         body: module_items.to_vec(),
         shebang: None,
     };
+    let (comments, own_line) = comments_of(&module, generated);
 
     let mut emitter = Emitter {
         cfg: config,
         cm: cm.clone(),
-        comments: None,
+        comments: Some(&comments),
         wr: swc_ecma_codegen::text_writer::JsWriter::new(cm, "\n", &mut buf, None),
     };
 
@@ -4894,7 +9373,11 @@ pub fn ast_to_code_compact(module_items: &[js::ModuleItem]) -> Result<String, St
         .emit_module(&module)
         .map_err(|e| format!("Codegen error: {}", e))?;
 
-    let code = String::from_utf8(buf).map_err(|e| format!("UTF8 error: {}", e))?;
+    let mut code = String::from_utf8(buf).map_err(|e| format!("UTF8 error: {}", e))?;
+    // The emitter continues the line after a block comment
+    for text in own_line {
+        code = code.replace(&format!("/*{text}*/ "), &format!("/*{text}*/\n"));
+    }
 
     // Post-process to ensure arrays are compact
     let compact_code = code
@@ -4906,22 +9389,402 @@ pub fn ast_to_code_compact(module_items: &[js::ModuleItem]) -> Result<String, St
     Ok(compact_code)
 }
 
+/// A comment in the generated code. swc has no comment nodes: comments
+/// live outside the AST, keyed by the start of the span of the node they
+/// lead, so the node gets a synthetic span to carry them.
+#[derive(Clone)]
+struct GeneratedComment {
+    kind: swc_common::comments::CommentKind,
+    /// What goes between `//` and the line end, or between `/*` and `*/`
+    text: String,
+    /// A block comment on lines of its own, rather than inline
+    own_line: bool,
+}
+
+impl GeneratedComment {
+    /// `// text`
+    fn line(text: &str) -> Self {
+        GeneratedComment {
+            kind: swc_common::comments::CommentKind::Line,
+            text: format!(" {text}"),
+            own_line: false,
+        }
+    }
+
+    /// `/* text */` inline, before the node
+    fn block(text: &str) -> Self {
+        GeneratedComment {
+            kind: swc_common::comments::CommentKind::Block,
+            text: format!(" {} ", text.replace("*/", "*\\/")),
+            own_line: false,
+        }
+    }
+
+    /// `/*text*/` on lines of its own, `text` being ready to emit
+    fn own_line_block(text: &str) -> Self {
+        GeneratedComment {
+            kind: swc_common::comments::CommentKind::Block,
+            text: text.to_string(),
+            own_line: true,
+        }
+    }
+}
+
+/// Make `comment` lead the node with `span`, after (or, with `prepend`,
+/// before) the comments already there. A node without comments so far has
+/// no span of its own and gets a new synthetic one.
+fn attach_comment(
+    state: &mut TranspilerState,
+    span: &mut Span,
+    comment: GeneratedComment,
+    prepend: bool,
+) {
+    let registry = &mut state.generated_comments;
+    let index = span.lo.0 as usize;
+    if span.lo.is_dummy() || index > registry.len() {
+        registry.push(vec![comment]);
+        // Position 0 is the dummy one, which the emitter skips
+        let pos = BytePos(registry.len() as u32);
+        *span = Span::new(pos, pos);
+    } else if prepend {
+        registry[index - 1].insert(0, comment);
+    } else {
+        registry[index - 1].push(comment);
+    }
+}
+
+/// The span of a statement, where a comment leading it is attached; `None`
+/// for the kinds generated code never comments
+fn stmt_span_mut(stmt: &mut js::Stmt) -> Option<&mut Span> {
+    match stmt {
+        js::Stmt::Expr(expr_stmt) => Some(&mut expr_stmt.span),
+        js::Stmt::Return(return_stmt) => Some(&mut return_stmt.span),
+        js::Stmt::If(if_stmt) => Some(&mut if_stmt.span),
+        js::Stmt::Block(block) => Some(&mut block.span),
+        js::Stmt::Throw(throw_stmt) => Some(&mut throw_stmt.span),
+        js::Stmt::Empty(empty) => Some(&mut empty.span),
+        js::Stmt::Decl(js::Decl::Var(var_decl)) => Some(&mut var_decl.span),
+        js::Stmt::Decl(js::Decl::Fn(fn_decl)) => Some(&mut fn_decl.function.span),
+        js::Stmt::Decl(js::Decl::Class(class_decl)) => Some(&mut class_decl.class.span),
+        _ => None,
+    }
+}
+
+/// The generated comments of every node in `module`, ready for the emitter
+fn comments_of(
+    module: &js::Module,
+    registry: &[Vec<GeneratedComment>],
+) -> (swc_common::comments::SingleThreadedComments, Vec<String>) {
+    use swc_common::comments::{Comment, Comments, SingleThreadedComments};
+    use swc_ecma_visit::{Visit, VisitWith};
+
+    struct SpanStarts(Vec<BytePos>);
+    impl Visit for SpanStarts {
+        fn visit_span(&mut self, span: &Span) {
+            if !span.lo.is_dummy() {
+                self.0.push(span.lo);
+            }
+        }
+    }
+    let mut starts = SpanStarts(Vec::new());
+    module.visit_with(&mut starts);
+
+    let comments = SingleThreadedComments::default();
+    let mut own_line = Vec::new();
+    for pos in starts.0 {
+        let Some(generated) = registry.get(pos.0 as usize - 1) else {
+            continue;
+        };
+        if comments.has_leading(pos) {
+            continue;
+        }
+        for comment in generated {
+            if comment.own_line {
+                own_line.push(comment.text.clone());
+            }
+            comments.add_leading(
+                pos,
+                Comment { kind: comment.kind, span: DUMMY_SP, text: comment.text.as_str().into() },
+            );
+        }
+    }
+    (comments, own_line)
+}
+
 /// Convert JavaScript AST to code string, trimmed of trailing semicolons and whitespace
 pub fn ast_to_code_trimmed(module_items: &[js::ModuleItem]) -> Result<String, String> {
-    let code = ast_to_code(module_items)?;
+    Ok(trim_code(&ast_to_code(module_items)?))
+}
+
+/// `ast_to_code_trimmed` with the comments attached while building with `state`
+fn ast_to_code_trimmed_with_state(
+    module_items: &[js::ModuleItem],
+    state: &mut TranspilerState,
+) -> Result<String, String> {
+    Ok(trim_code(&ast_to_code_with_state(module_items, state)?))
+}
 
+fn trim_code(code: &str) -> String {
     // Properly trim trailing semicolons and whitespace
-    Ok(code
-        .trim_end_matches('\n')
+    code.trim_end_matches('\n')
         .trim_end_matches(';') // Remove trailing semicolon
         .trim_end() // Remove any remaining whitespace
-        .to_string())
+        .to_string()
+}
+
+/// How transpiled items are turned into JavaScript source.
+#[derive(Clone, Debug, Default)]
+pub struct CodegenOptions {
+    /// Emit for an ES module: declarations generated for `pub` items carry
+    /// `export`. Off by default, which keeps every declaration global.
+    pub module: bool,
+    /// Maps the Rust module path of a `use` (e.g. `["crate", "foo"]`) to the
+    /// specifier of the ES import, or `None` to drop the import. Defaults to
+    /// `default_import_path`.
+    pub resolve_import: Option<ImportResolver>,
+    /// Wrap each enum object in `Object.freeze({...})` so its variants can't
+    /// be reassigned. Members an impl block adds to the enum afterwards
+    /// (associated consts, static methods) then can't be attached, so this is
+    /// off by default.
+    pub freeze_enums: bool,
+    /// Emit `i64`/`u64`/`i128`/`u128` values as BigInt: suffixed literals
+    /// get an `n`, as do untyped literals in arithmetic with (or a `let`
+    /// annotated as) such a type, and `as i64` becomes `BigInt(..)`. BigInt
+    /// doesn't mix with plain numbers at runtime, so this is off by default.
+    pub bigint_integers: bool,
+    /// Keep the `///` doc comments of structs, methods and functions as
+    /// leading `/** ... */` comments on the class, method or function.
+    pub doc_comments: bool,
+    /// Emit `print!`/`eprint!` as `process.stdout.write(..)` /
+    /// `process.stderr.write(..)`, which add no newline. That needs Node, so
+    /// by default they go to console.log/console.error like `println!`, each
+    /// call printing a line of its own.
+    pub print_to_stdout: bool,
+}
+
+/// Rust module path of a `use` -> ES module specifier, `None` to skip it
+pub type ImportResolver = fn(&[String]) -> Option<String>;
+
+impl CodegenOptions {
+    /// Options for ES module output
+    pub fn module() -> Self {
+        CodegenOptions {
+            module: true,
+            ..CodegenOptions::default()
+        }
+    }
+
+    fn exports(&self, vis: &syn::Visibility) -> bool {
+        self.module && matches!(vis, syn::Visibility::Public(_))
+    }
+}
+
+/// `const Enum = {...}` -> `const Enum = Object.freeze({...})`
+fn freeze_enum_object(module_items: &mut [js::ModuleItem], enum_name: &str) {
+    let state = TranspilerState::new();
+    for item in module_items {
+        let js::ModuleItem::Stmt(js::Stmt::Decl(js::Decl::Var(var_decl))) = item else {
+            continue;
+        };
+        for decl in &mut var_decl.decls {
+            let is_enum = matches!(&decl.name, js::Pat::Ident(b) if b.id.sym == enum_name);
+            if let (true, Some(init)) = (is_enum, decl.init.take()) {
+                let freeze = state.mk_member_expr(js::Expr::Ident(state.mk_ident("Object")), "freeze");
+                decl.init = Some(Box::new(state.mk_call_expr(freeze, vec![*init])));
+            }
+        }
+    }
+}
+
+/// `class X` / `const X` / `function x` -> `export class X` etc. Statements
+/// that are not declarations (e.g. prototype assignments) stay as they are.
+fn export_declarations(module_items: Vec<js::ModuleItem>) -> Vec<js::ModuleItem> {
+    module_items
+        .into_iter()
+        .map(|item| match item {
+            js::ModuleItem::Stmt(js::Stmt::Decl(decl)) => {
+                js::ModuleItem::ModuleDecl(js::ModuleDecl::ExportDecl(js::ExportDecl {
+                    span: DUMMY_SP,
+                    decl,
+                }))
+            }
+            other => other,
+        })
+        .collect()
+}
+
+/// The default import resolution: `crate::a::b` -> `./a/b.js`,
+/// `super::a` -> `../a.js`, the crate root -> `./index.js`. Paths into other
+/// crates are dropped.
+pub fn default_import_path(module_path: &[String]) -> Option<String> {
+    let (prefix, rest) = match module_path.first().map(String::as_str) {
+        Some("crate") | Some("self") => ("./".to_string(), &module_path[1..]),
+        Some("super") => {
+            let supers = module_path.iter().take_while(|s| *s == "super").count();
+            ("../".repeat(supers), &module_path[supers..])
+        }
+        _ => return None,
+    };
+    if rest.is_empty() {
+        Some(format!("{prefix}index.js"))
+    } else {
+        Some(format!("{prefix}{}.js", rest.join("/")))
+    }
+}
+
+/// One name brought in by a `use`, before it is grouped per module
+enum UseImport {
+    /// `use m::Name` / `use m::Name as Local`
+    Named { name: String, local: String },
+    /// `use crate::m` / `use m::{self}`: the whole module under one name
+    Namespace { local: String },
+}
+
+fn collect_use_imports(
+    tree: &syn::UseTree,
+    prefix: &mut Vec<String>,
+    out: &mut Vec<(Vec<String>, UseImport)>,
+) -> Result<(), String> {
+    let is_root = |prefix: &[String]| {
+        prefix
+            .iter()
+            .all(|s| s == "crate" || s == "self" || s == "super")
+    };
+    let mut add = |name: String, local: String, prefix: &[String]| {
+        if name == "self" {
+            // use crate::m::{self} imports the module m itself
+            out.push((prefix.to_vec(), UseImport::Namespace { local }));
+        } else if is_root(prefix) && name.starts_with(|c: char| c.is_lowercase()) {
+            // use crate::m names a module at the root
+            let mut module_path = prefix.to_vec();
+            module_path.push(name);
+            out.push((module_path, UseImport::Namespace { local }));
+        } else {
+            out.push((prefix.to_vec(), UseImport::Named { name, local }));
+        }
+    };
+    match tree {
+        syn::UseTree::Path(path) => {
+            prefix.push(path.ident.to_string());
+            let result = collect_use_imports(&path.tree, prefix, out);
+            prefix.pop();
+            result
+        }
+        syn::UseTree::Name(name) => {
+            let local = match (name.ident == "self", prefix.last()) {
+                (true, Some(module)) => module.clone(),
+                _ => name.ident.to_string(),
+            };
+            add(name.ident.to_string(), local, prefix);
+            Ok(())
+        }
+        syn::UseTree::Rename(rename) => {
+            add(rename.ident.to_string(), rename.rename.to_string(), prefix);
+            Ok(())
+        }
+        // A std glob (a prelude, say) is skipped like any other std item
+        syn::UseTree::Glob(_) if is_std_path(prefix) => Ok(()),
+        syn::UseTree::Glob(_) => Err(format!(
+            "glob imports have no ES module equivalent: `use {}::*`; import the names explicitly",
+            prefix.join("::")
+        )),
+        syn::UseTree::Group(group) => {
+            for item in &group.items {
+                collect_use_imports(item, prefix, out)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Whether a `use` path is in `std`, `core` or `alloc`, which JS provides
+/// (or lacks) on its own
+fn is_std_path(path: &[String]) -> bool {
+    matches!(path.first().map(String::as_str), Some("std") | Some("core") | Some("alloc"))
+}
+
+/// Translate a `use` declaration to ES `import` statements in module mode,
+/// one per imported module. `std`/`core`/`alloc` items and paths the
+/// resolver drops produce nothing, as does script mode.
+pub fn transpile_use_to_js_with_options(
+    item_use: &syn::ItemUse,
+    options: &CodegenOptions,
+) -> Result<String, String> {
+    if !options.module {
+        return Ok(String::new());
+    }
+    let mut imports = Vec::new();
+    collect_use_imports(&item_use.tree, &mut Vec::new(), &mut imports)?;
+
+    let resolve = options.resolve_import.unwrap_or(default_import_path);
+    let state = TranspilerState::new();
+    let mut decls: Vec<(String, Vec<js::ImportSpecifier>)> = Vec::new();
+    for (module_path, import) in imports {
+        if is_std_path(&module_path) {
+            continue;
+        }
+        let Some(src) = resolve(&module_path) else {
+            continue;
+        };
+        let specifier = match import {
+            UseImport::Named { name, local } => {
+                js::ImportSpecifier::Named(js::ImportNamedSpecifier {
+                    span: DUMMY_SP,
+                    imported: (name != local)
+                        .then(|| js::ModuleExportName::Ident(state.mk_ident(&name))),
+                    local: state.mk_ident(&local),
+                    is_type_only: false,
+                })
+            }
+            UseImport::Namespace { local } => {
+                js::ImportSpecifier::Namespace(js::ImportStarAsSpecifier {
+                    span: DUMMY_SP,
+                    local: state.mk_ident(&local),
+                })
+            }
+        };
+        match decls.iter_mut().find(|(existing, _)| *existing == src) {
+            Some((_, specifiers)) => specifiers.push(specifier),
+            None => decls.push((src, vec![specifier])),
+        }
+    }
+
+    let module_items: Vec<js::ModuleItem> = decls
+        .into_iter()
+        .map(|(src, specifiers)| {
+            js::ModuleItem::ModuleDecl(js::ModuleDecl::Import(js::ImportDecl {
+                span: DUMMY_SP,
+                specifiers,
+                src: Box::new(js::Str {
+                    span: DUMMY_SP,
+                    value: src.into(),
+                    raw: None,
+                }),
+                type_only: false,
+                with: None,
+                phase: Default::default(),
+            }))
+        })
+        .collect();
+    ast_to_code(&module_items)
 }
 
 /// Convenience function to transpile a complete impl block to JavaScript code
 pub fn transpile_impl_to_js(input_impl: &ItemImpl) -> Result<String, String> {
-    let module_items = generate_js_methods_for_impl_with_state(input_impl)?;
-    ast_to_code(&module_items)
+    let mut state = TranspilerState::new();
+    let module_items = impl_to_module_items(input_impl, &mut state)?;
+    ast_to_code_with_state(&module_items, &mut state)
+}
+
+/// Transpile an impl block with a caller-provided state. Methods of an enum
+/// impl are recorded in it, so code transpiled with the same state later
+/// calls them on the enum object (`Shape.area(s)` for `s: Shape`).
+pub fn transpile_impl_to_js_with_state(
+    input_impl: &ItemImpl,
+    state: &mut TranspilerState,
+) -> Result<String, String> {
+    let module_items = impl_to_module_items(input_impl, state)?;
+    ast_to_code_with_state(&module_items, state)
 }
 
 /// Convenience function to transpile a struct to JavaScript code
@@ -4936,6 +9799,75 @@ pub fn transpile_enum_to_js(input_enum: &ItemEnum) -> Result<String, String> {
     ast_to_code(&module_items)
 }
 
+/// Convenience function to transpile a free function to a top-level
+/// JavaScript `function` declaration
+pub fn transpile_fn_to_js(item_fn: &syn::ItemFn) -> Result<String, String> {
+    transpile_fn_to_js_with_options(item_fn, &CodegenOptions::default())
+}
+
+/// Transpile a struct, exporting the class when `options.module` is set and
+/// the struct is `pub`
+pub fn transpile_struct_to_js_with_options(
+    input_struct: &ItemStruct,
+    options: &CodegenOptions,
+) -> Result<String, String> {
+    let mut state = TranspilerState::new();
+    state.set_doc_comments(options.doc_comments);
+    let mut module_items = vec![struct_to_class(input_struct, &mut state)?];
+    if options.exports(&input_struct.vis) {
+        module_items = export_declarations(module_items);
+    }
+    ast_to_code_with_state(&module_items, &mut state)
+}
+
+/// Transpile an impl block's methods and associated consts
+pub fn transpile_impl_to_js_with_options(
+    input_impl: &ItemImpl,
+    options: &CodegenOptions,
+) -> Result<String, String> {
+    let mut state = TranspilerState::new();
+    state.set_bigint_integers(options.bigint_integers);
+    state.set_doc_comments(options.doc_comments);
+    state.set_print_to_stdout(options.print_to_stdout);
+    let module_items = impl_to_module_items(input_impl, &mut state)?;
+    ast_to_code_with_state(&module_items, &mut state)
+}
+
+/// Transpile an enum, exporting its object and `is*` helper when
+/// `options.module` is set and the enum is `pub`
+pub fn transpile_enum_to_js_with_options(
+    input_enum: &ItemEnum,
+    options: &CodegenOptions,
+) -> Result<String, String> {
+    let mut module_items = generate_js_enum_with_state(input_enum)?;
+    if options.freeze_enums {
+        freeze_enum_object(&mut module_items, &input_enum.ident.to_string());
+    }
+    if options.exports(&input_enum.vis) {
+        module_items = export_declarations(module_items);
+    }
+    ast_to_code(&module_items)
+}
+
+/// Transpile a free function to a JavaScript function declaration,
+/// exported when `options.module` is set and the function is `pub`
+pub fn transpile_fn_to_js_with_options(
+    item_fn: &syn::ItemFn,
+    options: &CodegenOptions,
+) -> Result<String, String> {
+    let mut state = TranspilerState::new();
+    state.set_bigint_integers(options.bigint_integers);
+    state.set_doc_comments(options.doc_comments);
+    state.set_print_to_stdout(options.print_to_stdout);
+    let mut module_items = vec![js::ModuleItem::Stmt(handle_function_definition(
+        item_fn, &mut state,
+    )?)];
+    if options.exports(&item_fn.vis) {
+        module_items = export_declarations(module_items);
+    }
+    ast_to_code_with_state(&module_items, &mut state)
+}
+
 /// Core function that converts Rust while loop to JavaScript while statement
 fn convert_while_to_stmt(
     while_expr: &syn::ExprWhile,
@@ -5513,7 +10445,18 @@ fn detect_enumerate_pattern(expr: &Expr) -> Option<Expr> {
             return Some((*method_call.receiver).clone());
         }
     }
-    None
+    None
+}
+
+/// The value a unit-variant pattern compares against. A qualified
+/// `Enum::Variant` reads the enum object, so C-like enums compare by their
+/// number; a bare variant name compares against its string.
+fn unit_variant_value(path_pat: &syn::ExprPath, state: &mut TranspilerState) -> Result<js::Expr, String> {
+    if path_pat.path.segments.len() >= 2 {
+        return rust_expr_to_js_with_state(&Expr::Path(path_pat.clone()), state);
+    }
+    let variant_name = path_pat.path.segments.last().ok_or("Invalid path pattern")?.ident.to_string();
+    Ok(state.mk_str_lit(&variant_name))
 }
 
 /// Handle pattern matching and variable binding - shared between match and if-let
@@ -5556,16 +10499,19 @@ fn handle_pattern_binding(
             state.mk_bool_lit(true)
         }
         Pat::Ident(pat_ident) => {
-            // Variable binding - always matches, and we need to bind the variable
+            // Variable binding - always matches, and we need to bind the variable.
+            // JS has no references, so `ref x` binds like `x`; `mut x` and
+            // `ref mut x` get a `let` so the arm can assign to it
             let var_name = pat_ident.ident.to_string();
             let js_var_name = escape_js_identifier(&var_name);
-            let js_var_name = state.declare_variable(var_name, js_var_name.clone(), false);
+            let is_mutable = pat_ident.mutability.is_some();
+            let js_var_name = state.declare_variable(var_name, js_var_name.clone(), is_mutable);
 
-            // Add: const x = _match_value;
+            // Add: const x = _match_value; (let x = ... when mutable)
             binding_stmts.push(state.mk_var_decl(
                 &js_var_name,
                 Some(js::Expr::Ident(state.mk_ident(match_var))),
-                true,
+                !is_mutable,
             ));
 
             state.mk_bool_lit(true)
@@ -5590,11 +10536,11 @@ fn handle_pattern_binding(
                         state.mk_binary_expr(null_check, js::BinaryOp::LogicalOr, undefined_check)
                     }
                     _ => {
-                        // For other enum variants, compare against string
+                        let value = unit_variant_value(path_pat, state)?;
                         state.mk_binary_expr(
                             js::Expr::Ident(state.mk_ident(match_var)),
                             js::BinaryOp::EqEqEq,
-                            state.mk_str_lit(&variant_name),
+                            value,
                         )
                     }
                 }
@@ -5623,13 +10569,13 @@ fn handle_pattern_binding(
                         if let Pat::Ident(pat_ident) = inner_pat {
                             let var_name = pat_ident.ident.to_string();
                             let js_var_name = escape_js_identifier(&var_name);
-                            let js_var_name = state.declare_variable(var_name, js_var_name.clone(), false);
+                            let js_var_name = state.declare_variable(var_name, js_var_name.clone(), pat_ident.mutability.is_some());
 
                             // Add: const x = _match_value;
                             binding_stmts.push(state.mk_var_decl(
                                 &js_var_name,
                                 Some(js::Expr::Ident(state.mk_ident(match_var))),
-                                true,
+                                pat_ident.mutability.is_none(),
                             ));
                         }
                     }
@@ -5675,7 +10621,7 @@ fn handle_pattern_binding(
                             Pat::Ident(pat_ident) => {
                                 let var_name = pat_ident.ident.to_string();
                                 let js_var_name = escape_js_identifier(&var_name);
-                                let js_var_name = state.declare_variable(var_name, js_var_name.clone(), false);
+                                let js_var_name = state.declare_variable(var_name, js_var_name.clone(), pat_ident.mutability.is_some());
                                 
                                 // Generate appropriate field access based on pattern type
                                 let field_name = if variant_name == "Ok" || variant_name == "Err" {
@@ -5692,7 +10638,7 @@ fn handle_pattern_binding(
                                         js::Expr::Ident(state.mk_ident(match_var)),
                                         &field_name
                                     )),
-                                    true,
+                                    pat_ident.mutability.is_none(),
                                 ));
                             }
                             Pat::Wild(_) => {
@@ -5737,7 +10683,7 @@ fn handle_pattern_binding(
                             let js_var_name = escape_js_identifier(&var_name);
                             let field_name_str = field_name.to_string();
                             
-                            let unique_js_var_name = state.declare_variable(var_name, js_var_name.clone(), false);
+                            let unique_js_var_name = state.declare_variable(var_name, js_var_name.clone(), pat_ident.mutability.is_some());
                             
                             // Generate: const one = _match_value.one; (using the unique name)
                             binding_stmts.push(state.mk_var_decl(
@@ -5746,7 +10692,7 @@ fn handle_pattern_binding(
                                     js::Expr::Ident(state.mk_ident(match_var)),
                                     &field_name_str
                                 )),
-                                true,
+                                pat_ident.mutability.is_none(),
                             ));
                         } else {
                             panic!("Complex patterns in struct fields not yet supported: {:?}", field_pat.pat);
@@ -5781,12 +10727,12 @@ fn handle_pattern_binding(
                         // Simple variable binding: (a, b) => const a = _match_value[0]; const b = _match_value[1];
                         let var_name = pat_ident.ident.to_string();
                         let js_var_name = escape_js_identifier(&var_name);
-                        let js_var_name = state.declare_variable(var_name, js_var_name.clone(), false);
+                        let js_var_name = state.declare_variable(var_name, js_var_name.clone(), pat_ident.mutability.is_some());
                         
                         binding_stmts.push(state.mk_var_decl(
                             &js_var_name,
                             Some(array_access_expr),
-                            true,
+                            pat_ident.mutability.is_none(),
                         ));
                         
                         // Always matches for variable binding
@@ -5819,12 +10765,12 @@ fn handle_pattern_binding(
                                     if let Pat::Ident(pat_ident) = inner_pat {
                                         let var_name = pat_ident.ident.to_string();
                                         let js_var_name = escape_js_identifier(&var_name);
-                                        let js_var_name = state.declare_variable(var_name, js_var_name.clone(), false);
+                                        let js_var_name = state.declare_variable(var_name, js_var_name.clone(), pat_ident.mutability.is_some());
                                         
                                         binding_stmts.push(state.mk_var_decl(
                                             &js_var_name,
                                             Some(array_access_expr),
-                                            true,
+                                            pat_ident.mutability.is_none(),
                                         ));
                                     }
                                 }
@@ -5859,6 +10805,13 @@ fn handle_pattern_binding(
                                     js::BinaryOp::LogicalOr, 
                                     undefined_check
                                 ));
+                            } else {
+                                let value = unit_variant_value(path_pat, state)?;
+                                conditions.push(state.mk_binary_expr(
+                                    array_access_expr,
+                                    js::BinaryOp::EqEqEq,
+                                    value,
+                                ));
                             }
                         }
                     }
@@ -5915,12 +10868,59 @@ fn handle_pattern_binding(
                 }).unwrap()
             }
         }
+        Pat::Range(range_pat) => range_pattern_condition(range_pat, match_var, state)?,
         x => panic!("Unsupported pattern {:?}", &x),
     };
 
     Ok((condition, binding_stmts))
 }
 
+/// `lo..=hi` -> `v >= lo && v <= hi` (`v < hi` for `lo..hi`); either bound
+/// may be missing. A char range compares code points:
+/// `'a'..='z'` -> `v.codePointAt(0) >= 97 && v.codePointAt(0) <= 122`.
+fn range_pattern_condition(
+    range_pat: &syn::PatRange,
+    match_var: &str,
+    state: &mut TranspilerState,
+) -> Result<js::Expr, String> {
+    let is_char = |bound: &Option<Box<Expr>>| {
+        matches!(bound.as_deref(), Some(Expr::Lit(lit)) if matches!(lit.lit, syn::Lit::Char(_)))
+    };
+    let by_code_point = is_char(&range_pat.start) || is_char(&range_pat.end);
+    let subject = if by_code_point {
+        state.mk_call_expr(
+            state.mk_member_expr(js::Expr::Ident(state.mk_ident(match_var)), "codePointAt"),
+            vec![state.mk_num_lit(0.0)],
+        )
+    } else {
+        js::Expr::Ident(state.mk_ident(match_var))
+    };
+    let bound = |expr: &Expr, state: &mut TranspilerState| match expr {
+        Expr::Lit(syn::ExprLit { lit: syn::Lit::Char(c), .. }) => {
+            Ok(state.mk_num_lit(c.value() as u32 as f64))
+        }
+        _ => rust_expr_to_js_with_state(expr, state),
+    };
+
+    let upper_op = match range_pat.limits {
+        syn::RangeLimits::Closed(_) => js::BinaryOp::LtEq,
+        syn::RangeLimits::HalfOpen(_) => js::BinaryOp::Lt,
+    };
+    let mut checks = Vec::new();
+    if let Some(start) = &range_pat.start {
+        let lo = bound(start, state)?;
+        checks.push(state.mk_binary_expr(subject.clone(), js::BinaryOp::GtEq, lo));
+    }
+    if let Some(end) = &range_pat.end {
+        let hi = bound(end, state)?;
+        checks.push(state.mk_binary_expr(subject.clone(), upper_op, hi));
+    }
+    Ok(checks
+        .into_iter()
+        .reduce(|acc, check| state.mk_binary_expr(acc, js::BinaryOp::LogicalAnd, check))
+        .unwrap_or_else(|| state.mk_bool_lit(true)))
+}
+
 /// Handle match expressions
 fn handle_match_expr(
     match_expr: &syn::ExprMatch,
@@ -5965,7 +10965,7 @@ fn handle_match_expr(
         } else {
             // Chain the if statements
             if let Some(ref mut chain) = if_chain {
-                chain_if_statement(chain, current_if);
+                chain_if_statement(state, chain, current_if);
             }
         }
     }
@@ -5980,8 +10980,57 @@ fn handle_match_expr(
     Ok(state.mk_iife(stmts))
 }
 
+/// Convert a `match` in statement position to an if/else-if chain over the
+/// cached scrutinee. Each arm body is a statement list, so `return`,
+/// `break` and `continue` in an arm act on the enclosing function or loop.
+fn convert_match_to_stmt(
+    match_expr: &syn::ExprMatch,
+    state: &mut TranspilerState,
+) -> Result<js::Stmt, String> {
+    let match_value = rust_expr_to_js_with_state(&match_expr.expr, state)?;
+    let temp_var = "_match_value";
+    let mut stmts = vec![state.mk_var_decl(temp_var, Some(match_value), true)];
+
+    let mut if_chain: Option<js::Stmt> = None;
+    for arm in &match_expr.arms {
+        state.enter_scope();
+        let (condition, mut arm_stmts) = handle_pattern_binding(&arm.pat, temp_var, state)?;
+        let body = match &*arm.body {
+            Expr::Block(block_expr) if block_expr.label.is_none() => block_expr.block.clone(),
+            body => Block {
+                brace_token: Default::default(),
+                stmts: vec![Stmt::Expr(body.clone(), Some(Default::default()))],
+            },
+        };
+        arm_stmts.extend(rust_block_to_js_with_state(BlockAction::NoReturn, &body, state)?);
+        state.exit_scope();
+
+        let current_if = js::Stmt::If(js::IfStmt {
+            span: DUMMY_SP,
+            test: Box::new(condition),
+            cons: Box::new(js::Stmt::Block(js::BlockStmt {
+                span: DUMMY_SP,
+                stmts: arm_stmts,
+                ctxt: SyntaxContext::empty(),
+            })),
+            alt: None,
+        });
+        match &mut if_chain {
+            Some(chain) => chain_if_statement(state, chain, current_if),
+            None => if_chain = Some(current_if),
+        }
+    }
+    stmts.extend(if_chain);
+
+    Ok(js::Stmt::Block(js::BlockStmt {
+        span: DUMMY_SP,
+        stmts,
+        ctxt: SyntaxContext::empty(),
+    }))
+}
+
 /// Helper function to chain if statements for match arms
-fn chain_if_statement(current: &mut js::Stmt, next: js::Stmt) {
+fn chain_if_statement(state: &mut TranspilerState, current: &mut js::Stmt, next: js::Stmt) {
     if let js::Stmt::If(if_stmt) = current {
         if if_stmt.alt.is_none() {
             // Check if the next statement is "if (true)" and convert to simple else
@@ -5990,19 +11039,15 @@ fn chain_if_statement(current: &mut js::Stmt, next: js::Stmt) {
                     // Convert "else if (true)" to just "else { // Default case"
                     // We need to add the comment to the block somehow
                     if let js::Stmt::Block(block_stmt) = &*next_if.cons {
-                        // Create a new block with a comment by modifying the first statement
+                        // The comment leads the block's first statement
                         let mut new_stmts = block_stmt.stmts.clone();
-                        if !new_stmts.is_empty() {
-                            // Add comment as an expression statement at the beginning
-                            let comment_stmt = js::Stmt::Expr(js::ExprStmt {
-                                span: DUMMY_SP,
-                                expr: Box::new(js::Expr::Ident(js::Ident::new(
-                                    "// Default case".into(),
-                                    DUMMY_SP,
-                                    SyntaxContext::empty(),
-                                ))),
-                            });
-                            new_stmts.insert(0, comment_stmt);
+                        if let Some(span) = new_stmts.first_mut().and_then(stmt_span_mut) {
+                            attach_comment(
+                                state,
+                                span,
+                                GeneratedComment::line("Default case"),
+                                true,
+                            );
                         }
 
                         let new_block = js::Stmt::Block(js::BlockStmt {
@@ -6023,7 +11068,7 @@ fn chain_if_statement(current: &mut js::Stmt, next: js::Stmt) {
         } else {
             // Recursively chain
             if let Some(ref mut alt) = if_stmt.alt {
-                chain_if_statement(alt, next);
+                chain_if_statement(state, alt, next);
             }
         }
     }
@@ -6116,21 +11161,31 @@ fn handle_closure_expr(
         })
         .collect();
 
-    // Handle closure body
-    let body = match &*closure.body {
-        Expr::Block(block_expr) => {
-            let stmts = rust_block_to_js_with_state(BlockAction::Return, &block_expr.block, state)?;
-            js::BlockStmtOrExpr::BlockStmt(js::BlockStmt {
-                span: DUMMY_SP,
-                stmts,
-                ctxt: SyntaxContext::empty(),
-            })
-        }
-        _ => {
-            let expr = rust_expr_to_js_with_state(&closure.body, state)?;
-            js::BlockStmtOrExpr::Expr(Box::new(expr))
-        }
-    };
+    // Handle closure body. `?` and `return` in it refer to the closure, so
+    // its own return type (if written) replaces the enclosing function's.
+    let outer_return_type = state.return_type().cloned();
+    state.declare_return_type(match &closure.output {
+        syn::ReturnType::Type(_, ty) => Some((**ty).clone()),
+        syn::ReturnType::Default => None,
+    });
+    let body = state.in_function_body(|state| {
+        Ok::<_, String>(match &*closure.body {
+            Expr::Block(block_expr) => {
+                let stmts = rust_block_to_js_with_state(BlockAction::Return, &block_expr.block, state)?;
+                js::BlockStmtOrExpr::BlockStmt(js::BlockStmt {
+                    span: DUMMY_SP,
+                    stmts,
+                    ctxt: SyntaxContext::empty(),
+                })
+            }
+            _ => {
+                let expr = rust_expr_to_js_with_state(&closure.body, state)?;
+                js::BlockStmtOrExpr::Expr(Box::new(expr))
+            }
+        })
+    });
+    state.declare_return_type(outer_return_type);
+    let body = body?;
 
     let arrow_expr = js::Expr::Arrow(js::ArrowExpr {
         span: DUMMY_SP,
@@ -6192,13 +11247,7 @@ fn handle_struct_expr(
             if let syn::Member::Named(field_name) = &field.member {
                 let field_name_str = field_name.to_string();
                 let field_value = rust_expr_to_js_with_state(&field.expr, state)?;
-                
-                props.push(js::PropOrSpread::Prop(Box::new(js::Prop::KeyValue(
-                    js::KeyValueProp {
-                        key: js::PropName::Ident(state.mk_ident_name(&field_name_str)),
-                        value: Box::new(field_value),
-                    },
-                ))));
+                props.push(object_field_prop(state, &field_name_str, field_value));
             }
         }
         
@@ -6208,197 +11257,105 @@ fn handle_struct_expr(
             props,
         }))
     } else {
-        // Check if this is a Self struct expression
-        if struct_name == "Self" && state.get_current_struct_name().is_some() {
-            // Check if we're in a static method context
-            if state.is_in_static_method() {
-                // In static methods, Self { ... } should use constructor + field assignment
-                // We'll use an IIFE to create the object and assign fields
-                let struct_name = state.get_current_struct_name().unwrap().clone();
-                
-                // Create constructor call: new StructName()
-                let constructor_call = js::Expr::New(js::NewExpr {
+        // Self { .. } builds an instance of the impl's struct, in static and
+        // instance methods alike, so the result keeps the class prototype
+        let struct_name = match state.get_current_struct_name() {
+            Some(current) if struct_name == "Self" => current.clone(),
+            _ => struct_name,
+        };
+        // Regular struct literal. The generated JS class constructor is
+        // positional in DECLARATION order, which the expression site does
+        // not know - so never construct positionally from source order
+        // (Counter { label, count } would silently swap arguments).
+        // Instead assign each field by name:
+        //   (() => { const obj = new Counter(); obj.label = ...; return obj; })()
+        let constructor_call = js::Expr::New(js::NewExpr {
+            span: DUMMY_SP,
+            callee: Box::new(js::Expr::Ident(state.mk_ident(&struct_name))),
+            args: Some(vec![]),
+            type_args: None,
+            ctxt: SyntaxContext::empty(),
+        });
+        let mut stmts = vec![state.mk_var_decl("obj", Some(constructor_call), true)];
+        for field in &struct_expr.fields {
+            if let syn::Member::Named(field_name) = &field.member {
+                let field_name_str = field_name.to_string();
+                let clean_field_name = field_name_str
+                    .strip_prefix("r#")
+                    .unwrap_or(&field_name_str)
+                    .to_string();
+                let field_value = rust_expr_to_js_with_state(&field.expr, state)?;
+                let obj_access = state.mk_member_expr(
+                    js::Expr::Ident(state.mk_ident("obj")),
+                    &clean_field_name,
+                );
+                let assignment = js::Expr::Assign(js::AssignExpr {
                     span: DUMMY_SP,
-                    callee: Box::new(js::Expr::Ident(state.mk_ident(&struct_name))),
-                    args: Some(vec![]),
-                    type_args: None,
-                    ctxt: SyntaxContext::empty(),
+                    op: js::AssignOp::Assign,
+                    left: state.expr_to_assign_target(obj_access)?,
+                    right: Box::new(field_value),
                 });
-                
-                // Create variable declaration: const obj = new StructName();
-                let obj_var = state.mk_var_decl("obj", Some(constructor_call), true);
-                
-                // Create field assignments: obj.field = value;
-                let mut assignment_stmts = vec![obj_var];
-                for field in &struct_expr.fields {
-                    if let syn::Member::Named(field_name) = &field.member {
-                        let field_name_str = field_name.to_string();
-                        // Handle raw identifiers by removing the r# prefix
-                        let clean_field_name = if field_name_str.starts_with("r#") {
-                            &field_name_str[2..] // Remove "r#" prefix
-                        } else {
-                            &field_name_str
-                        };
-                        let field_value = rust_expr_to_js_with_state(&field.expr, state)?;
-                        
-                        // Create obj.field assignment
-                        let obj_access = state.mk_member_expr(
-                            js::Expr::Ident(state.mk_ident("obj")),
-                            clean_field_name
-                        );
-                        let assignment = js::Expr::Assign(js::AssignExpr {
-                            span: DUMMY_SP,
-                            op: js::AssignOp::Assign,
-                            left: state.expr_to_assign_target(obj_access)?,
-                            right: Box::new(field_value),
-                        });
-                        assignment_stmts.push(js::Stmt::Expr(js::ExprStmt {
-                            span: DUMMY_SP,
-                            expr: Box::new(assignment),
-                        }));
-                    }
-                }
-                
-                // Add return statement: return obj;
-                assignment_stmts.push(js::Stmt::Return(js::ReturnStmt {
+                stmts.push(js::Stmt::Expr(js::ExprStmt {
                     span: DUMMY_SP,
-                    arg: Some(Box::new(js::Expr::Ident(state.mk_ident("obj")))),
+                    expr: Box::new(assignment),
                 }));
-                
-                // Create IIFE: (() => { ... })()
-                let iife_func = js::ArrowExpr {
-                    span: DUMMY_SP,
-                    params: vec![],
-                    body: Box::new(js::BlockStmtOrExpr::BlockStmt(js::BlockStmt {
-                        span: DUMMY_SP,
-                        stmts: assignment_stmts,
-                        ctxt: SyntaxContext::empty(),
-                    })),
-                    is_async: false,
-                    is_generator: false,
-                    type_params: None,
-                    return_type: None,
-                    ctxt: SyntaxContext::empty(),
-                };
-                
-                // Call the IIFE
-                Ok(js::Expr::Call(js::CallExpr {
-                    span: DUMMY_SP,
-                    callee: js::Callee::Expr(Box::new(js::Expr::Paren(js::ParenExpr {
-                        span: DUMMY_SP,
-                        expr: Box::new(js::Expr::Arrow(iife_func)),
-                    }))),
-                    args: vec![],
-                    type_args: None,
-                    ctxt: SyntaxContext::empty(),
-                }))
-            } else {
-                // In non-static methods, Self { ... } should create an object literal with named fields
-                let mut props = Vec::new();
-                
-                // Convert named fields to object properties
-                for field in &struct_expr.fields {
-                    if let syn::Member::Named(field_name) = &field.member {
-                        let field_name_str = field_name.to_string();
-                        let field_value = rust_expr_to_js_with_state(&field.expr, state)?;
-                        
-                        props.push(js::PropOrSpread::Prop(Box::new(js::Prop::KeyValue(
-                            js::KeyValueProp {
-                                key: js::PropName::Ident(state.mk_ident_name(&field_name_str)),
-                                value: Box::new(field_value),
-                            },
-                        ))));
-                    }
-                }
-                
-                // Return object literal for Self
-                Ok(js::Expr::Object(js::ObjectLit {
-                    span: DUMMY_SP,
-                    props,
-                }))
             }
-        } else {
-            // Regular struct literal. The generated JS class constructor is
-            // positional in DECLARATION order, which the expression site does
-            // not know - so never construct positionally from source order
-            // (Counter { label, count } would silently swap arguments).
-            // Instead assign each field by name:
-            //   (() => { const obj = new Counter(); obj.label = ...; return obj; })()
-            let constructor_call = js::Expr::New(js::NewExpr {
+        }
+        stmts.push(js::Stmt::Return(js::ReturnStmt {
+            span: DUMMY_SP,
+            arg: Some(Box::new(js::Expr::Ident(state.mk_ident("obj")))),
+        }));
+        // A field value may contain .await (legal in an async fn); the
+        // IIFE must then be async and awaited, like mk_iife does.
+        let is_async = stmts_contain_await(&stmts);
+        let iife_func = js::ArrowExpr {
+            span: DUMMY_SP,
+            params: vec![],
+            body: Box::new(js::BlockStmtOrExpr::BlockStmt(js::BlockStmt {
                 span: DUMMY_SP,
-                callee: Box::new(js::Expr::Ident(state.mk_ident(&struct_name))),
-                args: Some(vec![]),
-                type_args: None,
+                stmts,
                 ctxt: SyntaxContext::empty(),
-            });
-            let mut stmts = vec![state.mk_var_decl("obj", Some(constructor_call), true)];
-            for field in &struct_expr.fields {
-                if let syn::Member::Named(field_name) = &field.member {
-                    let field_name_str = field_name.to_string();
-                    let clean_field_name = field_name_str
-                        .strip_prefix("r#")
-                        .unwrap_or(&field_name_str)
-                        .to_string();
-                    let field_value = rust_expr_to_js_with_state(&field.expr, state)?;
-                    let obj_access = state.mk_member_expr(
-                        js::Expr::Ident(state.mk_ident("obj")),
-                        &clean_field_name,
-                    );
-                    let assignment = js::Expr::Assign(js::AssignExpr {
-                        span: DUMMY_SP,
-                        op: js::AssignOp::Assign,
-                        left: state.expr_to_assign_target(obj_access)?,
-                        right: Box::new(field_value),
-                    });
-                    stmts.push(js::Stmt::Expr(js::ExprStmt {
-                        span: DUMMY_SP,
-                        expr: Box::new(assignment),
-                    }));
-                }
-            }
-            stmts.push(js::Stmt::Return(js::ReturnStmt {
-                span: DUMMY_SP,
-                arg: Some(Box::new(js::Expr::Ident(state.mk_ident("obj")))),
-            }));
-            // A field value may contain .await (legal in an async fn); the
-            // IIFE must then be async and awaited, like mk_iife does.
-            let is_async = stmts_contain_await(&stmts);
-            let iife_func = js::ArrowExpr {
+            })),
+            is_async,
+            is_generator: false,
+            type_params: None,
+            return_type: None,
+            ctxt: SyntaxContext::empty(),
+        };
+        let call = js::Expr::Call(js::CallExpr {
+            span: DUMMY_SP,
+            callee: js::Callee::Expr(Box::new(js::Expr::Paren(js::ParenExpr {
                 span: DUMMY_SP,
-                params: vec![],
-                body: Box::new(js::BlockStmtOrExpr::BlockStmt(js::BlockStmt {
-                    span: DUMMY_SP,
-                    stmts,
-                    ctxt: SyntaxContext::empty(),
-                })),
-                is_async,
-                is_generator: false,
-                type_params: None,
-                return_type: None,
-                ctxt: SyntaxContext::empty(),
-            };
-            let call = js::Expr::Call(js::CallExpr {
+                expr: Box::new(js::Expr::Arrow(iife_func)),
+            }))),
+            args: vec![],
+            type_args: None,
+            ctxt: SyntaxContext::empty(),
+        });
+        Ok(if is_async {
+            js::Expr::Await(js::AwaitExpr {
                 span: DUMMY_SP,
-                callee: js::Callee::Expr(Box::new(js::Expr::Paren(js::ParenExpr {
-                    span: DUMMY_SP,
-                    expr: Box::new(js::Expr::Arrow(iife_func)),
-                }))),
-                args: vec![],
-                type_args: None,
-                ctxt: SyntaxContext::empty(),
-            });
-            Ok(if is_async {
-                js::Expr::Await(js::AwaitExpr {
-                    span: DUMMY_SP,
-                    arg: Box::new(call),
-                })
-            } else {
-                call
+                arg: Box::new(call),
             })
-        }
+        } else {
+            call
+        })
     }
 }
 
+/// `name: value` for an object literal, or the shorthand `name` when the
+/// value is a variable of that same name (`Point { x, y }` -> `{ x, y }`)
+fn object_field_prop(state: &TranspilerState, name: &str, value: js::Expr) -> js::PropOrSpread {
+    let prop = match value {
+        js::Expr::Ident(ident) if ident.sym == name => js::Prop::Shorthand(ident),
+        value => js::Prop::KeyValue(js::KeyValueProp {
+            key: js::PropName::Ident(state.mk_ident_name(name)),
+            value: Box::new(value),
+        }),
+    };
+    js::PropOrSpread::Prop(Box::new(prop))
+}
+
 /// Handle range expressions
 fn handle_range_expr(
     range_expr: &syn::ExprRange,
@@ -6408,12 +11365,21 @@ fn handle_range_expr(
         (Some(start), Some(end)) => {
             let start_js = rust_expr_to_js_with_state(start, state)?;
             let end_js = rust_expr_to_js_with_state(end, state)?;
+            let end_js = exclusive_range_end(range_expr, end_js, state);
 
             // Create Array.from({length: end - start}, (_, i) => i + start)
             let array_from = state.mk_member_expr(js::Expr::Ident(state.mk_ident("Array")), "from");
 
+            // end - (start): the start may itself be a sum
+            let start_operand = match &start_js {
+                js::Expr::Ident(_) | js::Expr::Lit(_) => start_js.clone(),
+                other => js::Expr::Paren(js::ParenExpr {
+                    span: DUMMY_SP,
+                    expr: Box::new(other.clone()),
+                }),
+            };
             let length_expr =
-                state.mk_binary_expr(end_js.clone(), js::BinaryOp::Sub, start_js.clone());
+                state.mk_binary_expr(end_js.clone(), js::BinaryOp::Sub, start_operand);
 
             let length_obj = js::Expr::Object(js::ObjectLit {
                 span: DUMMY_SP,
@@ -6459,6 +11425,7 @@ fn handle_range_expr(
         (None, Some(end)) => {
             // Range to end (..end) -> Array.from({length: end}, (_, i) => i)
             let end_js = rust_expr_to_js_with_state(end, state)?;
+            let end_js = exclusive_range_end(range_expr, end_js, state);
             let array_from = state.mk_member_expr(js::Expr::Ident(state.mk_ident("Array")), "from");
 
             let length_obj = js::Expr::Object(js::ObjectLit {
@@ -6495,18 +11462,57 @@ fn handle_range_expr(
 
             Ok(state.mk_call_expr(array_from, vec![length_obj, js::Expr::Arrow(arrow_fn)]))
         }
-        x => {
-            // Other range types not easily representable
-            state.add_warning("Infinite or complex ranges not fully supported".to_string());
-            panic!("Infinite or complex ranges not fully supported: {:?}", x);
-            Ok(js::Expr::Array(js::ArrayLit {
-                span: DUMMY_SP,
-                elems: vec![],
-            }))
+        // `start..` and `..` never end, so there is no array to build; they
+        // only make sense as a slice index, which Expr::Index handles
+        _ => Err(format!(
+            "open-ended range `{}` is only supported as a slice index (`v[{}]`); \
+             give it an end to iterate over it",
+            source_snippet(range_expr),
+            source_snippet(range_expr)
+        )),
+    }
+}
+
+/// The exclusive end of a range: `end + 1` for `..=end`
+fn exclusive_range_end(
+    range_expr: &syn::ExprRange,
+    end: js::Expr,
+    state: &TranspilerState,
+) -> js::Expr {
+    match range_expr.limits {
+        syn::RangeLimits::Closed(_) => {
+            state.mk_binary_expr(end, js::BinaryOp::Add, state.mk_num_lit(1.0))
         }
+        syn::RangeLimits::HalfOpen(_) => end,
     }
 }
 
+/// `v[a..b]` -> `v.slice(a, b)`, `v[a..]` -> `v.slice(a)`, `v[..b]` ->
+/// `v.slice(0, b)`, `v[..]` -> `v.slice()`; an inclusive end gets `+ 1`.
+fn handle_slice_index(
+    obj: js::Expr,
+    range_expr: &syn::ExprRange,
+    state: &mut TranspilerState,
+) -> Result<js::Expr, String> {
+    let start = range_expr
+        .start
+        .as_ref()
+        .map(|start| rust_expr_to_js_with_state(start, state))
+        .transpose()?;
+    let end = range_expr
+        .end
+        .as_ref()
+        .map(|end| rust_expr_to_js_with_state(end, state))
+        .transpose()?
+        .map(|end| exclusive_range_end(range_expr, end, state));
+    let args = match (start, end) {
+        (start, Some(end)) => vec![start.unwrap_or_else(|| state.mk_num_lit(0.0)), end],
+        (Some(start), None) => vec![start],
+        (None, None) => vec![],
+    };
+    Ok(state.mk_call_expr(state.mk_member_expr(obj, "slice"), args))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -6571,8 +11577,9 @@ mod tests {
 /// Generate JavaScript methods for a Rust impl block, returning a readable
 /// error instead of panicking.
 pub fn try_generate_js_methods_for_impl(input_impl: &ItemImpl) -> Result<String, String> {
-    let module_items = generate_js_methods_for_impl_with_state(input_impl)?;
-    ast_to_code(&module_items)
+    let mut state = TranspilerState::new();
+    let module_items = impl_to_module_items(input_impl, &mut state)?;
+    ast_to_code_with_state(&module_items, &mut state)
         .map_err(|e| format!("internal error emitting JavaScript from the AST: {e}"))
 }
 
@@ -6595,7 +11602,7 @@ pub fn handle_format_macro(args: &Punctuated<Expr, Comma>) -> String {
         expr: Box::new(expr),
     }))];
 
-    let code = ast_to_code_trimmed(&module_items)
+    let code = ast_to_code_trimmed_with_state(&module_items, &mut state)
         .expect("Failed to convert format macro to JavaScript code");
     code
 }
@@ -6606,64 +11613,9 @@ fn handle_reference_expr(
     ref_expr: &syn::ExprReference,
     state: &mut TranspilerState,
 ) -> Result<js::Expr, String> {
-    let inner_expr = rust_expr_to_js_with_state(&ref_expr.expr, state)?;
-
-    match &*ref_expr.expr {
-        // For simple path expressions (variables), handle specially
-        Expr::Path(_) => {
-            if ref_expr.mutability.is_some() {
-                // Mutable reference - return the variable with a comment
-                // We need to create a JavaScript comment, not a string literal
-                Ok(js::Expr::Ident(js::Ident::new(
-                    format!(
-                        "{} /* was &mut in Rust */",
-                        match &inner_expr {
-                            js::Expr::Ident(ident) => ident.sym.to_string(),
-                            _ => "unknown".to_string(),
-                        }
-                    )
-                    .into(),
-                    DUMMY_SP,
-                    SyntaxContext::empty(),
-                )))
-            } else {
-                // Immutable reference to variable - just return the variable
-                Ok(inner_expr)
-            }
-        }
-        // For string literals, just return the literal (no comment needed)
-        Expr::Lit(lit) if matches!(lit.lit, syn::Lit::Str(_)) => Ok(inner_expr),
-        // For other expressions, we need to create a comment expression
-        _ => {
-            let comment = if ref_expr.mutability.is_some() {
-                " /* was &mut in Rust */"
-            } else {
-                " /* was & in Rust */"
-            };
-
-            // Create an identifier with the expression and comment combined
-            // This is a bit of a hack, but it's the cleanest way to add comments
-            Ok(js::Expr::Ident(js::Ident::new(
-                format!(
-                    "{}{}",
-                    // Convert the inner expression back to string
-                    match ast_to_code_trimmed(&[js::ModuleItem::Stmt(js::Stmt::Expr(
-                        js::ExprStmt {
-                            span: DUMMY_SP,
-                            expr: Box::new(inner_expr.clone()),
-                        }
-                    ))]) {
-                        Ok(code) => code.trim_end_matches(';').trim().to_string(),
-                        Err(_) => "expr".to_string(),
-                    },
-                    comment
-                )
-                .into(),
-                DUMMY_SP,
-                SyntaxContext::empty(),
-            )))
-        }
-    }
+    // JS passes every object by reference already, so `&x` and `&mut x` are
+    // just `x`
+    rust_expr_to_js_with_state(&ref_expr.expr, state)
 }
 
 /// Render a syn node back to compact single-line Rust source, for use in
@@ -6683,16 +11635,23 @@ pub fn source_snippet<T: quote::ToTokens>(node: &T) -> String {
 /// panicking. Prefer this in proc-macros so the error can be surfaced as a
 /// `compile_error!` pointing at the user's code.
 pub fn try_rust_block_to_js(block: &Block) -> Result<String, String> {
-    let mut state = TranspilerState::new();
+    try_rust_block_to_js_with_state(block, &mut TranspilerState::new())
+}
 
-    let stmts = rust_block_to_js_with_state(BlockAction::Return, block, &mut state)?;
+/// Convert Rust block to JavaScript using a caller-provided state, e.g. one
+/// with custom method mappings registered.
+pub fn try_rust_block_to_js_with_state(
+    block: &Block,
+    state: &mut TranspilerState,
+) -> Result<String, String> {
+    let stmts = rust_block_to_js_with_state(BlockAction::Return, block, state)?;
 
     let module_items: Vec<js::ModuleItem> = stmts
         .into_iter()
         .map(|stmt| js::ModuleItem::Stmt(stmt))
         .collect();
 
-    ast_to_code(&module_items)
+    ast_to_code_with_state(&module_items, state)
         .map_err(|e| format!("internal error emitting JavaScript from the AST: {e}"))
 }
 
@@ -6704,17 +11663,22 @@ pub fn rust_block_to_js(block: &Block) -> String {
 /// Convert Rust expression to JavaScript, returning a readable error instead
 /// of panicking.
 pub fn try_rust_expr_to_js(expr: &Expr) -> Result<String, String> {
-    let mut state = TranspilerState::new();
+    try_rust_expr_to_js_with_state(expr, &mut TranspilerState::new())
+}
 
-    let js_expr =
-        rust_expr_to_js_with_action_and_state(BlockAction::Return, expr, &mut state)?;
+/// Convert Rust expression to JavaScript using a caller-provided state.
+pub fn try_rust_expr_to_js_with_state(
+    expr: &Expr,
+    state: &mut TranspilerState,
+) -> Result<String, String> {
+    let js_expr = rust_expr_to_js_with_action_and_state(BlockAction::Return, expr, state)?;
 
     let module_items = vec![js::ModuleItem::Stmt(js::Stmt::Expr(js::ExprStmt {
         span: DUMMY_SP,
         expr: Box::new(js_expr),
     }))];
 
-    ast_to_code_trimmed(&module_items)
+    ast_to_code_trimmed_with_state(&module_items, state)
         .map_err(|e| format!("internal error emitting JavaScript from the AST: {e}"))
 }
 
@@ -6765,14 +11729,20 @@ fn create_enum_from_json_function(
     
     // Create switch cases for each variant
     let mut switch_cases = Vec::new();
+    let discriminants = enum_discriminants(input_enum)?;
     
-    for variant in &input_enum.variants {
+    for (index, variant) in input_enum.variants.iter().enumerate() {
         let variant_name = variant.ident.to_string();
         
         match &variant.fields {
             Fields::Unit => {
-                // Unit variants: just return the string
-                let return_stmt = state.mk_return_stmt(Some(state.mk_str_lit(&variant_name)));
+                // Unit variants: just return the string (the number in a
+                // C-like enum; the JSON form is always the name)
+                let value = match &discriminants {
+                    Some(values) => state.mk_num_lit(values[index]),
+                    None => state.mk_str_lit(&variant_name),
+                };
+                let return_stmt = state.mk_return_stmt(Some(value));
                 switch_cases.push(js::SwitchCase {
                     span: DUMMY_SP,
                     test: Some(Box::new(state.mk_str_lit(&variant_name))),
@@ -6899,8 +11869,9 @@ fn create_enum_to_json_switch_body(
     state: &mut TranspilerState,
 ) -> Result<js::BlockStmt, String> {
     let mut switch_cases = Vec::new();
+    let discriminants = enum_discriminants(input_enum)?;
     
-    for variant in &input_enum.variants {
+    for (index, variant) in input_enum.variants.iter().enumerate() {
         let variant_name = variant.ident.to_string();
         
         match &variant.fields {
@@ -6914,9 +11885,13 @@ fn create_enum_to_json_switch_body(
                     vec![state.mk_str_lit(&variant_name)]
                 );
                 let return_stmt = state.mk_return_stmt(Some(json_stringify_call));
+                let case_value = match &discriminants {
+                    Some(values) => state.mk_num_lit(values[index]),
+                    None => state.mk_str_lit(&variant_name),
+                };
                 switch_cases.push(js::SwitchCase {
                     span: DUMMY_SP,
-                    test: Some(Box::new(state.mk_str_lit(&variant_name))),
+                    test: Some(Box::new(case_value)),
                     cons: vec![return_stmt],
                 });
             }
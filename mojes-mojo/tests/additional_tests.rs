@@ -81,19 +81,19 @@ fn test_reference_expressions() {
     // Mutable reference to variable - also returns just the variable
     let expr: Expr = parse_quote!(&mut y);
     let js_code = rust_expr_to_js(&expr);
-    assert_eq!(js_code, "y /* was &mut in Rust */"); // This should have the comment
+    assert_eq!(js_code, "y");
 
     // Reference to string literal - should return just the string
     let expr: Expr = parse_quote!(&"hello");
     let js_code = rust_expr_to_js(&expr);
     assert_eq!(js_code, "\"hello\"");
 
-    // Reference to a more complex expression (not a path) - should have comment
+    // Reference to a more complex expression (not a path) - the expression
     let expr: Expr = parse_quote!(&(x + y));
     let js_code = rust_expr_to_js(&expr);
-    assert_eq!(js_code, "(x + y) /* was & in Rust */");
+    assert_eq!(js_code, "(x + y)");
 }
-// Additional test to verify the path vs non-path reference behavior
+// Additional test: every kind of referenced expression comes out unchanged
 #[test]
 fn test_reference_expression_types() {
     // Path expressions (variables) - no comment
@@ -101,20 +101,20 @@ fn test_reference_expression_types() {
     let js_code = rust_expr_to_js(&expr);
     assert_eq!(js_code, "variable_name");
 
-    // Literal expressions - no comment for string literals
+    // Literal expressions
     let expr: Expr = parse_quote!(&42);
     let js_code = rust_expr_to_js(&expr);
-    assert_eq!(js_code, "42 /* was & in Rust */");
+    assert_eq!(js_code, "42");
 
-    // Complex expressions - should have comment
+    // Calls
     let expr: Expr = parse_quote!(&func_call());
     let js_code = rust_expr_to_js(&expr);
-    assert_eq!(js_code, "func_call() /* was & in Rust */");
+    assert_eq!(js_code, "func_call()");
 
-    // Field access - should have comment
+    // Field access
     let expr: Expr = parse_quote!(&obj.field);
     let js_code = rust_expr_to_js(&expr);
-    assert_eq!(js_code, "obj.field /* was & in Rust */");
+    assert_eq!(js_code, "obj.field");
 }
 
 // Test mutable vs immutable references more thoroughly
@@ -123,12 +123,12 @@ fn test_mutable_reference_behavior() {
     // Mutable reference to variable
     let expr: Expr = parse_quote!(&mut x);
     let js_code = rust_expr_to_js(&expr);
-    assert_eq!(js_code, "x /* was &mut in Rust */");
+    assert_eq!(js_code, "x");
 
     // Mutable reference to complex expression
     let expr: Expr = parse_quote!(&mut (a + b));
     let js_code = rust_expr_to_js(&expr);
-    assert_eq!(js_code, "(a + b) /* was &mut in Rust */");
+    assert_eq!(js_code, "(a + b)");
 }
 
 // ==================== BITWISE OPERATIONS TESTS ====================
@@ -202,7 +202,7 @@ fn test_tuple_struct() {
 
     let js_class = generate_js_class_for_struct(&struct_def);
     assert!(js_class.contains("class Color"));
-    assert!(js_class.contains("constructor(data)"));
+    assert!(js_class.contains("constructor(_0, _1, _2)"));
 }
 
 // ==================== UNIT STRUCT TESTS ====================
@@ -298,8 +298,8 @@ fn test_custom_enum_pattern_matching_multiple_params() {
     // Should handle variants with different numbers of parameters
     assert!(js_code.contains("_match_value.type === \"Move\""));
     assert!(js_code.contains("_match_value.type === \"Resize\""));
-    // Stop is a unit variant, so it's compared directly as a string
-    assert!(js_code.contains("_match_value === \"Stop\""));
+    // Stop is a unit variant, compared against the enum's value
+    assert!(js_code.contains("_match_value === Command.Stop"));
     
     // Should bind parameters correctly for Move variant
     assert!(js_code.contains("const x = _match_value.value0"));
@@ -392,6 +392,7 @@ fn test_custom_enum_mixed_patterns_javascript_evaluation() {
     // Test literal pattern
     let test_literal = format!(
         r#"
+        const Command = {{ Stop: "Stop" }};
         const value = 42;
         {}
         "#,
@@ -400,9 +401,10 @@ fn test_custom_enum_mixed_patterns_javascript_evaluation() {
     let result = eval_js(&test_literal).unwrap();
     assert_eq!(result.as_string().unwrap(), "answer");
     
-    // Test unit variant (Stop is just a string)
+    // Test unit variant (Stop is a string on the enum object)
     let test_unit = format!(
         r#"
+        const Command = {{ Stop: "Stop" }};
         const value = "Stop";
         {}
         "#,
@@ -414,6 +416,7 @@ fn test_custom_enum_mixed_patterns_javascript_evaluation() {
     // Test data variant (Move has parameters)
     let test_data = format!(
         r#"
+        const Command = {{ Stop: "Stop" }};
         const value = {{ type: "Move", value0: 10, value1: 20 }};
         {}
         "#,
@@ -425,6 +428,7 @@ fn test_custom_enum_mixed_patterns_javascript_evaluation() {
     // Test variable binding fallback
     let test_fallback = format!(
         r#"
+        const Command = {{ Stop: "Stop" }};
         const value = 999;
         {}
         "#,
@@ -519,7 +523,7 @@ fn test_mixed_tuple_and_struct_enum_patterns() {
     println!("DEBUG test_mixed_tuple_and_struct_enum_patterns js code: {}", &js_code);
     
     // Should handle unit variant
-    assert!(js_code.contains("_match_value === \"Stop\""));
+    assert!(js_code.contains("_match_value === Command.Stop"));
     
     // Should handle tuple-style variant (using value0, value1)
     assert!(js_code.contains("_match_value.type === \"Move\""));
@@ -1107,7 +1111,7 @@ fn test_mixed_expressions_in_blocks() {
 
     assert!(js_code.contains("data.map"));
     // The .len() now uses IIFE, so check for the universal length function
-    assert!(js_code.contains("obj.length") && js_code.contains("Object.keys")); // Universal len() solution
+    assert!(js_code.contains(".length !== undefined") && js_code.contains("Object.keys")); // Universal len() solution
 
     println!("Mixed expressions JS output:\n{}", js_code);
 
@@ -89,6 +89,19 @@ mod tests_dom {
         assert!(is_valid_js(&test_code));
     }
 
+    #[test]
+    fn test_request_animation_frame_passes_through() {
+        let expr: Expr = parse_quote! {
+            requestAnimationFrame(|t| draw(t))
+        };
+        let js_code = rust_expr_to_js(&expr);
+        println!("DEBUG requestAnimationFrame: {}", &js_code);
+        assert_eq!(js_code, "requestAnimationFrame(((t)=>draw(t)))");
+
+        let expr: Expr = parse_quote! { cancelAnimationFrame(frame_id) };
+        assert_eq!(rust_expr_to_js(&expr), "cancelAnimationFrame(frame_id)");
+    }
+
     #[test]
     fn test_closure_in_interval_no_iife() {
         // Test that closures in setInterval don't get wrapped in IIFE
@@ -289,6 +302,53 @@ mod tests_dom {
         assert!(js_code.contains("event.preventDefault"));
     }
 
+    #[test]
+    fn test_add_event_listener_with_closure_handler() {
+        let block: Block = parse_quote!({
+            let mut clicks = 0;
+            button.addEventListener("click", move |e| {
+                clicks += 1;
+                e.preventDefault();
+            });
+        });
+        let js_code = rust_block_to_js(&block);
+        println!("DEBUG addEventListener: {}", &js_code);
+        assert!(
+            js_code.contains("button.addEventListener(\"click\", ((e)=>{"),
+            "expected an arrow handler:\n{js_code}"
+        );
+        assert!(js_code.contains("clicks += 1;"), "{js_code}");
+
+        // The handler runs with the event when the listener fires
+        let test_code = format!(
+            "let prevented = false; let handler;\n\
+             const button = {{ addEventListener: (type, f) => {{ handler = f; }} }};\n\
+             {js_code}\n\
+             handler({{ preventDefault: () => {{ prevented = true; }} }});\n\
+             prevented && clicks === 1"
+        );
+        assert_eq!(eval_js(&test_code).unwrap().as_boolean(), Some(true));
+    }
+
+    #[test]
+    fn test_event_member_access_in_handler() {
+        let expr: Expr = parse_quote! {
+            input.addEventListener("keydown", |e| {
+                if e.key == "Enter" {
+                    e.preventDefault();
+                    e.stopPropagation();
+                    submit(e.target.value.clone(), e.r#type);
+                }
+            })
+        };
+        let js_code = rust_expr_to_js(&expr);
+        println!("DEBUG event members: {}", &js_code);
+        assert!(js_code.contains("e.key === \"Enter\""), "{js_code}");
+        assert!(js_code.contains("e.preventDefault();"), "{js_code}");
+        assert!(js_code.contains("e.stopPropagation();"), "{js_code}");
+        assert!(js_code.contains("submit(e.target.value, e.type)"), "{js_code}");
+    }
+
     #[test]
     fn test_nested_for_loops() {
         // Test nested for loops don't interfere with each other
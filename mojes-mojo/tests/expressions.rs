@@ -167,8 +167,14 @@ fn test_method_call_expressions_0() {
     let expr: Expr = parse_quote!(vec.push(item));
     assert_eq!(rust_expr_to_js(&expr), "vec.push(item)");
 
+    // contains() on an untyped receiver dispatches at runtime
     let expr: Expr = parse_quote!(text.contains("test"));
-    assert_eq!(rust_expr_to_js(&expr), "text.includes(\"test\")");
+    let js_code = rust_expr_to_js(&expr);
+    let test_code = format!(r#"
+        const text = "a test string";
+        {};
+    "#, js_code);
+    assert!(eval_js(&test_code).unwrap().as_boolean().unwrap());
 
     // Option method calls
     let expr: Expr = parse_quote!(opt.is_some());
@@ -207,8 +213,14 @@ fn test_method_call_expressions() {
     let expr: Expr = parse_quote!(vec.push(item));
     assert_eq!(rust_expr_to_js(&expr), "vec.push(item)");
 
+    // contains() on an untyped receiver dispatches at runtime
     let expr: Expr = parse_quote!(text.contains("test"));
-    assert_eq!(rust_expr_to_js(&expr), "text.includes(\"test\")");
+    let js_code = rust_expr_to_js(&expr);
+    let test_code = format!(r#"
+        const text = "a test string";
+        {};
+    "#, js_code);
+    assert!(eval_js(&test_code).unwrap().as_boolean().unwrap());
 
     // Option method calls
     let expr: Expr = parse_quote!(opt.is_some());
@@ -322,7 +334,6 @@ fn test_all_string_methods() {
         (parse_quote!(s.ends_with("x")), "s.endsWith(\"x\")"),
         (parse_quote!(s.replace("a", "b")), "s.replace(\"a\", \"b\")"),
         (parse_quote!(s.split(",")), "s.split(\",\")"),
-        (parse_quote!(s.contains("test")), "s.includes(\"test\")"),
     ];
 
     // Test methods with string matching (except len())
@@ -331,6 +342,15 @@ fn test_all_string_methods() {
         assert_eq!(js_code, expected, "Failed for expression: {:?}", expr);
     }
 
+    // Test s.contains() with execution since it dispatches at runtime
+    let expr: Expr = parse_quote!(s.contains("world"));
+    let js_code = rust_expr_to_js(&expr);
+    let test_code = format!(r#"
+        const s = "hello world";
+        {};
+    "#, js_code);
+    assert!(eval_js(&test_code).unwrap().as_boolean().unwrap());
+
     // Test s.len() separately with execution since it now uses IIFE
     let expr: Expr = parse_quote!(s.len());
     let js_code = rust_expr_to_js(&expr);
@@ -354,7 +374,7 @@ fn test_all_array_methods() {
         (parse_quote!(arr.map(func)), "arr.map(func)"),
         (parse_quote!(arr.filter(pred)), "arr.filter(pred)"),
         (parse_quote!(arr.find(pred)), "arr.find(pred)"),
-        (parse_quote!(arr.contains(item)), "arr.includes(item)"),
+        // Note: arr.contains() dispatches at runtime, tested separately below
     ];
 
     // Test methods with string matching (except len() and remove())
@@ -374,6 +394,16 @@ fn test_all_array_methods() {
     let result = eval_js(&test_code).unwrap();
     assert_eq!(result.as_number().unwrap(), 6.0);
 
+    // Test arr.contains() with execution since it dispatches at runtime
+    let expr: Expr = parse_quote!(arr.contains(item));
+    let js_code = rust_expr_to_js(&expr);
+    let test_code = format!(r#"
+        const arr = [1, 2, 3];
+        const item = 2;
+        {};
+    "#, js_code);
+    assert!(eval_js(&test_code).unwrap().as_boolean().unwrap());
+
     // Test arr.remove() - now uses universal IIFE instead of direct splice
     let expr: Expr = parse_quote!(arr.remove(index));
     let js_code = rust_expr_to_js(&expr);
@@ -180,6 +180,96 @@ fn test_eprint_macro() {
     assert!(js_code.contains("\"Error: \""));
 }
 
+#[test]
+fn test_println_of_a_variable() {
+    let expr: Expr = parse_quote!(println!(some_var));
+    assert_eq!(rust_expr_to_js(&expr), "console.log(some_var)");
+
+    let expr: Expr = parse_quote!(eprintln!(err.message, code));
+    assert_eq!(rust_expr_to_js(&expr), "console.error(err.message, code)");
+
+    // A string literal, even with commas, is still a single argument
+    let expr: Expr = parse_quote!(info!("a, b"));
+    assert_eq!(rust_expr_to_js(&expr), "console.info(\"a, b\")");
+}
+
+#[test]
+fn test_print_to_stdout_writes_without_newline() {
+    let item_fn: syn::ItemFn = parse_quote! {
+        fn progress(done: u32) {
+            print!("{}/3 ", done);
+            print!("done");
+            eprint!("!");
+            println!();
+        }
+    };
+    let options = CodegenOptions {
+        print_to_stdout: true,
+        ..Default::default()
+    };
+    let js_code = transpile_fn_to_js_with_options(&item_fn, &options).unwrap();
+    println!("JS: {}", &js_code);
+    assert!(js_code.contains("process.stdout.write(`${done}/3 `)"), "{js_code}");
+    assert!(js_code.contains("process.stderr.write(\"!\")"), "{js_code}");
+
+    // With the writes captured, the pieces join up on one line until println!
+    let result = eval_js(&format!(
+        "let out = '';\n\
+         const process = {{ stdout: {{ write: (s) => {{ out += s; }} }}, \
+                           stderr: {{ write: (s) => {{ out += '[' + s + ']'; }} }} }};\n\
+         const console = {{ log: () => {{ out += '\\n'; }} }};\n\
+         {js_code}\nprogress(2);\nout"
+    ))
+    .unwrap();
+    assert_eq!(result.as_string().unwrap().to_std_string().unwrap(), "2/3 done[!]\n");
+
+    // Without the option print! keeps going to the console (see test_print_macro)
+    let js_code = transpile_fn_to_js(&item_fn).unwrap();
+    assert!(!js_code.contains("process."), "{js_code}");
+}
+
+#[test]
+fn test_log_crate_macros_map_to_console_levels() {
+    let expr: Expr = parse_quote!(warn!("x {}", y));
+    let js_code = rust_expr_to_js(&expr);
+    println!("DEBUG test_log_crate_macros js code: {}", &js_code);
+    assert_eq!(js_code, "console.warn(`x ${y}`)");
+
+    let block: Block = parse_quote! {
+        {
+            let y = 3;
+            error!("bad {}", y);
+            warn!("x {}", y);
+            info!(target: "net", "connected");
+            debug!("d");
+            trace!("t {}", y);
+        }
+    };
+    let js_code = rust_block_to_js(&block);
+    println!("DEBUG test_log_crate_macros js code: {}", &js_code);
+    assert!(!js_code.contains("target"), "{js_code}");
+
+    let code = format!(
+        r#"
+        let console_output = [];
+        const console = {{
+            error: (...args) => console_output.push('error:' + args.join(' ')),
+            warn: (...args) => console_output.push('warn:' + args.join(' ')),
+            info: (...args) => console_output.push('info:' + args.join(' ')),
+            debug: (...args) => console_output.push('debug:' + args.join(' ')),
+        }};
+        (function() {{ {} }})();
+        console_output.join('|');
+    "#,
+        js_code
+    );
+    let result = eval_js(&code).unwrap();
+    assert_eq!(
+        result.as_string().unwrap().to_std_string().unwrap(),
+        "error:bad 3|warn:x 3|info:connected|debug:d|debug:t 3"
+    );
+}
+
 #[test]
 fn test_mixed_print_macros() {
     let block: Block = parse_quote! {
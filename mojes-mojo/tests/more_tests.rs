@@ -89,10 +89,11 @@ fn test_nested_block_expressions() {
 
     let js_code = rust_expr_to_js(&expr);
     println!("DEBUG test_nested_block_expressions js code: {}", &js_code);
-    assert_eq!(js_code.matches("call(this)").count(), 2);
+    // The block initializing `foo` is inlined; only the outer one is an IIFE
+    assert_eq!(js_code.matches("call(this)").count(), 1);
     assert!(js_code.contains("const temp = x + 1"));
     assert!(js_code.contains("_rust_retval = temp * 2"));
-    assert!(js_code.contains("_rust_retval = temp2 / 10"));
+    assert!(js_code.contains("const foo = temp2 / 10"));
 }
 
 // ==================== 4. UNARY DEREFERENCE OPERATOR ====================
@@ -169,7 +170,7 @@ fn test_vector_methods_with_args() {
     );
 
     // Test 3: Verify transpiler output format for insert()
-    assert!(js_code.contains("((obj, key, val)=>obj.splice ? obj.splice(key, 0, val) : obj[key] = val)"), 
+    assert!(js_code.contains("((_temp1, _temp2, _temp3)=>_temp1.splice ? _temp1.splice(_temp2, 0, _temp3) : _temp1[_temp2] = _temp3)"), 
             "Should generate universal IIFE for insert(): {}", js_code);
     // Old expectation (partially correct - insert already used IIFE):
     // assert_eq!(js_code, "((obj, key, val)=>obj.splice ? obj.splice(key, 0, val) : obj[key] = val)(vec, 0, item)");
@@ -280,7 +281,7 @@ fn test_match_wildcard_patterns() {
     let js_code = rust_expr_to_js(&expr);
     println!("DEBUG test_match_wildcard_patterns js code: {}", &js_code);
     assert!(js_code.contains("=== 42"));
-    assert!(js_code.contains("} else {\n        // Default case;"));
+    assert!(js_code.contains("} else {\n        // Default case\n        return \"other\";"));
 }
 
 #[test]
@@ -428,7 +429,7 @@ fn test_comprehensive_uncovered_execution() {
     // Should contain all the patterns we expect
     assert!(js_code.contains("+=")); // Compound assignment
     assert!(js_code.contains("[point.x, point.y]")); // Tuple as array
-    assert!(js_code.contains("function()")); // Nested block
+    assert!(js_code.contains("const result = temp + 5")); // Nested block, inlined
     assert!(js_code.contains("let final_value;")); // Uninitialized
     assert!(js_code.contains("final_value = result")); // Later assignment
 
@@ -508,8 +509,7 @@ fn test_cast_expressions() {
     // Cast to custom type (fallback)
     let expr: Expr = parse_quote!(obj as CustomType);
     let js_code = rust_expr_to_js(&expr);
-    assert!(js_code.contains("obj"));
-    assert!(js_code.contains("was") && js_code.contains("as") && js_code.contains("in Rust"));
+    assert_eq!(js_code, "obj");
 }
 
 #[test]
@@ -660,7 +660,7 @@ fn test_comprehensive_uncovered_execution_2() {
     // Should contain all the uncovered patterns
     assert!(js_code.contains("+=")); // Compound assignment
     assert!(js_code.contains("coords[0]")); // Tuple access should translate to array
-    assert!(js_code.contains("function()")); // Nested block
+    assert!(js_code.contains("const result = temp + 5")); // Nested block, inlined
     assert!(js_code.contains("let final_value;")); // Uninitialized
     assert!(js_code.contains("final_value = result")); // Later assignment
 
@@ -265,7 +265,7 @@ fn test_method_calls_in_statements() {
 
     // Should contain the length functionality with IIFE, not method call
     assert!(js_code.contains("return "));
-    assert!(js_code.contains("obj.length") && js_code.contains("Object.keys")); // IIFE length solution
+    assert!(js_code.contains(".length !== undefined") && js_code.contains("Object.keys")); // IIFE length solution
 
     // Should NOT contain invalid JavaScript
     assert!(!js_code.contains("vec.length()")); // This would be invalid JS
@@ -292,7 +292,7 @@ fn test_method_vs_property_in_statements() {
 
     // Length should use IIFE solution  
     assert!(js_code.contains("return "));
-    assert!(js_code.contains("obj.length") && js_code.contains("Object.keys")); // IIFE length solution
+    assert!(js_code.contains(".length !== undefined") && js_code.contains("Object.keys")); // IIFE length solution
     assert!(!js_code.contains("arr.length()")); // Not a method call
 
     println!("Method vs property distinction:\n{}", js_code);
@@ -342,7 +342,7 @@ fn test_vector_operations_in_statements() {
 
     // Should handle length with IIFE solution
     assert!(js_code.contains("return "));
-    assert!(js_code.contains("obj.length") && js_code.contains("Object.keys")); // IIFE length solution
+    assert!(js_code.contains(".length !== undefined") && js_code.contains("Object.keys")); // IIFE length solution
     assert!(!js_code.contains("vec.length()"));
 }
 
@@ -373,7 +373,7 @@ fn test_vector_operations_debug() {
 
     // Test the other parts that should work
     assert!(js_code.contains("vec.push(4)"));
-    assert!(js_code.contains("obj.length") && js_code.contains("Object.keys")); // IIFE length solution
+    assert!(js_code.contains(".length !== undefined") && js_code.contains("Object.keys")); // IIFE length solution
 }
 
 // Test both mutable and immutable to understand the pattern
@@ -478,7 +478,7 @@ fn test_vector_operations_corrected() {
 
     // These should definitely work
     assert!(js_code.contains("data.push(4)"));
-    assert!(js_code.contains("obj.length") && js_code.contains("Object.keys")); // IIFE length solution
+    assert!(js_code.contains(".length !== undefined") && js_code.contains("Object.keys")); // IIFE length solution
     assert!(!js_code.contains("data.length()"));
 
     println!("✓ Vector operations work correctly:\n{}", js_code);
@@ -504,7 +504,7 @@ fn test_all_method_call_statement_types() {
 
     // Second should be a return statement with length functionality
     assert!(js_code.contains("return "));
-    assert!(js_code.contains("obj.length") && js_code.contains("Object.keys")); // IIFE length solution
+    assert!(js_code.contains(".length !== undefined") && js_code.contains("Object.keys")); // IIFE length solution
 
     // Verify correct property vs method distinction
     assert!(js_code.contains("push(item);")); // Method with parentheses
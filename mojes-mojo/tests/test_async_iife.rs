@@ -4,9 +4,11 @@ use syn::{parse_quote, Block};
 #[test]
 fn test_block_with_await_generates_async_iife() {
     // A block expression containing .await should generate an async IIFE
+    // (one with a plain statement, so it cannot be inlined into the `let`)
     let block: Block = parse_quote! {
         {
             let result = {
+                start_fetch();
                 let data = fetch_data().await;
                 data
             };
@@ -32,6 +34,7 @@ fn test_block_without_await_generates_non_async_iife() {
     let block: Block = parse_quote! {
         {
             let result = {
+                start_fetch();
                 let x = 42;
                 x + 1
             };
@@ -50,10 +53,14 @@ fn test_block_without_await_generates_non_async_iife() {
 #[test]
 fn test_match_with_await_generates_async_iife() {
     // A match expression with .await in an arm should generate an async IIFE
+    // (an arm with statements, so it cannot collapse into a ternary)
     let block: Block = parse_quote! {
         {
             let result = match value {
-                1 => fetch_one().await,
+                1 => {
+                    let one = fetch_one().await;
+                    one
+                }
                 _ => fetch_default().await,
             };
         }
@@ -75,10 +82,12 @@ fn test_match_with_await_generates_async_iife() {
 #[test]
 fn test_if_expression_with_await_generates_async_iife() {
     // An if-as-expression with .await in a branch should generate an async IIFE
+    // (a multi-statement branch, so it cannot collapse into a ternary)
     let block: Block = parse_quote! {
         {
             let result = if condition {
-                fetch_a().await
+                let a = fetch_a().await;
+                a
             } else {
                 fetch_b().await
             };
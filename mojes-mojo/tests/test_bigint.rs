@@ -0,0 +1,69 @@
+// Tests for the BigInt option: 64/128-bit integer literals and the
+// arithmetic around them get an `n` only when it is switched on.
+use mojes_mojo::*;
+use syn::{parse_quote, Block, Expr};
+
+fn eval_js(code: &str) -> boa_engine::JsValue {
+    let mut context = boa_engine::Context::default();
+    context
+        .eval(boa_engine::Source::from_bytes(code))
+        .expect("JS execution failed")
+}
+
+fn as_str(v: &boa_engine::JsValue) -> String {
+    let mut ctx = boa_engine::Context::default();
+    v.to_string(&mut ctx).unwrap().to_std_string().unwrap()
+}
+
+fn bigint_state() -> TranspilerState {
+    let mut state = TranspilerState::new();
+    state.set_bigint_integers(true);
+    state
+}
+
+#[test]
+fn test_i64_literal_is_bigint_with_option() {
+    let expr: Expr = parse_quote! { 9007199254740993i64 };
+    let js = try_rust_expr_to_js_with_state(&expr, &mut bigint_state()).unwrap();
+    assert_eq!(js, "9007199254740993n");
+
+    // Off by default
+    let expr: Expr = parse_quote! { 5i64 };
+    assert_eq!(rust_expr_to_js(&expr), "5");
+    let expr: Expr = parse_quote! { 5i32 };
+    assert_eq!(try_rust_expr_to_js_with_state(&expr, &mut bigint_state()).unwrap(), "5");
+}
+
+#[test]
+fn test_untyped_literals_follow_wide_operands() {
+    let block: Block = parse_quote!({
+        let mut total: i64 = 0;
+        total += 2;
+        let idx = 1;
+        total * items[idx] as i64 + 1
+    });
+    let js = try_rust_block_to_js_with_state(&block, &mut bigint_state()).unwrap();
+    println!("JS: {}", &js);
+    assert!(js.contains("let total = 0n;"), "{js}");
+    assert!(js.contains("total += 2n;"), "{js}");
+    assert!(js.contains("const idx = 1;"), "{js}");
+    // The index stays a number, the cast and the literal are BigInt
+    assert!(js.contains("total * BigInt(items[idx]) + 1n"), "{js}");
+}
+
+#[test]
+fn test_bigint_fn_keeps_precision() {
+    let item_fn: syn::ItemFn = parse_quote! {
+        fn wide_sum(a: i64, b: i64) -> i64 {
+            a + b + 1
+        }
+    };
+    let options = CodegenOptions {
+        bigint_integers: true,
+        ..Default::default()
+    };
+    let js = transpile_fn_to_js_with_options(&item_fn, &options).unwrap();
+    println!("JS: {}", &js);
+    let result = eval_js(&format!("{js}\nwide_sum(9007199254740992n, 2n)"));
+    assert_eq!(as_str(&result), "9007199254740995");
+}
@@ -0,0 +1,98 @@
+// Tests for type-aware collect(): the turbofish target decides what the
+// iterator's JS array becomes - a joined string, an object, a Set - and
+// chars()/rev() produce the arrays such chains start from.
+use mojes_mojo::*;
+use syn::{parse_quote, Block, Expr};
+
+fn eval_block(b: &Block) -> String {
+    let js = rust_block_to_js(b);
+    println!("JS: {}", &js);
+    let mut context = boa_engine::Context::default();
+    let value = context
+        .eval(boa_engine::Source::from_bytes(&format!("(function() {{ {js} }})()")))
+        .expect("JS execution failed");
+    value.to_string(&mut context).unwrap().to_std_string().unwrap()
+}
+
+#[test]
+fn test_reverse_string_idiom() {
+    let expr: Expr = parse_quote! { s.chars().rev().collect::<String>() };
+    let js = rust_expr_to_js(&expr);
+    println!("JS: {}", &js);
+    assert!(js.contains(".reverse().join(\"\")"), "expected a joined string:\n{js}");
+
+    let b: Block = parse_quote!({
+        let word = "stressed";
+        word.chars().rev().collect::<String>()
+    });
+    assert_eq!(eval_block(&b), "desserts");
+}
+
+#[test]
+fn test_rev_does_not_mutate_the_source() {
+    let b: Block = parse_quote!({
+        let v = vec![1, 2, 3];
+        let r = v.iter().rev().collect::<Vec<_>>();
+        format!("{}/{}", v.join(","), r.join(","))
+    });
+    assert_eq!(eval_block(&b), "1,2,3/3,2,1");
+}
+
+#[test]
+fn test_collect_into_map_and_set() {
+    let b: Block = parse_quote!({
+        let pairs = vec![("a", 1), ("b", 2)];
+        let m = pairs.iter().collect::<HashMap<_, _>>();
+        let s = vec![1, 1, 2].iter().collect::<HashSet<i32>>();
+        format!("{}{}{}", m["a"], m["b"], s.size)
+    });
+    assert_eq!(eval_block(&b), "122");
+}
+
+#[test]
+fn test_collect_pairs_into_map_via_from_entries() {
+    let expr: Expr = parse_quote! { keys.iter().map(|k| (k, 0)).collect::<BTreeMap<_, _>>() };
+    let js = rust_expr_to_js(&expr);
+    println!("JS: {}", &js);
+    assert!(js.starts_with("Object.fromEntries("), "expected an object build:\n{js}");
+
+    let b: Block = parse_quote!({
+        let names = vec!["x", "y"];
+        let lengths: HashMap<&str, usize> = names.iter().map(|n| (n, n.len() + 1)).collect();
+        lengths["x"] + lengths["y"]
+    });
+    assert_eq!(eval_block(&b), "4");
+}
+
+#[test]
+fn test_collect_into_vec_is_identity() {
+    let expr: Expr = parse_quote! { xs.iter().filter(|x| x > 1).collect::<Vec<_>>() };
+    let vec_js = rust_expr_to_js(&expr);
+    let expr: Expr = parse_quote! { xs.iter().filter(|x| x > 1) };
+    assert_eq!(vec_js, rust_expr_to_js(&expr));
+
+    let b: Block = parse_quote!({
+        let evens: Vec<i32> = vec![1, 2, 3, 4].iter().filter(|x| x % 2 == 0).collect();
+        let joined: String = vec!["a", "b"].iter().map(|s| s.to_uppercase()).collect();
+        format!("{}{}", evens.join(","), joined)
+    });
+    assert_eq!(eval_block(&b), "2,4AB");
+}
+
+#[test]
+fn test_turbofish_tells_string_from_vec() {
+    let string: Expr = parse_quote! { word.chars().collect::<String>() };
+    let vec: Expr = parse_quote! { word.chars().collect::<Vec<_>>() };
+    let (string_js, vec_js) = (rust_expr_to_js(&string), rust_expr_to_js(&vec));
+    println!("JS: {} / {}", &string_js, &vec_js);
+    assert!(string_js.ends_with(".join(\"\")"), "{string_js}");
+    assert!(!vec_js.contains("join"), "{vec_js}");
+
+    let b: Block = parse_quote!({
+        let word = "abc";
+        let letters = word.chars().collect::<Vec<_>>();
+        let same = word.chars().collect::<String>();
+        format!("{} {} {}", letters.len(), letters[1], same)
+    });
+    assert_eq!(eval_block(&b), "3 b abc");
+}
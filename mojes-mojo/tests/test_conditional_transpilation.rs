@@ -0,0 +1,88 @@
+// Tests for JS-target conditional transpilation: `#[mojes_only]` and
+// `#[cfg(feature = "js")]` code is emitted, server-only code
+// (`#[cfg(not(feature = "js"))]`, `#[js_ignore]`) is left out.
+use mojes_mojo::*;
+use syn::{parse_quote, Block, ItemImpl, ItemStruct};
+
+#[test]
+fn test_mojes_only_method_is_the_only_one_emitted() {
+    let input: ItemImpl = parse_quote! {
+        impl Widget {
+            #[mojes_only]
+            fn render(&self) -> String {
+                "<div>".to_string()
+            }
+
+            #[cfg(not(feature = "js"))]
+            fn save_to_disk(&self) {
+                std::fs::write("w.txt", "x").unwrap();
+            }
+
+            #[js_ignore]
+            fn server_stats(&self) -> u64 {
+                0
+            }
+        }
+    };
+    let js = try_generate_js_methods_for_impl(&input).unwrap();
+    println!("JS: {}", &js);
+    assert!(js.contains("Widget.prototype.render"), "{js}");
+    assert!(!js.contains("save_to_disk"), "{js}");
+    assert!(!js.contains("server_stats"), "{js}");
+}
+
+#[test]
+fn test_cfg_predicates_for_the_js_target() {
+    let input: ItemImpl = parse_quote! {
+        impl Widget {
+            #[cfg(feature = "js")]
+            fn a(&self) {}
+            #[cfg(target_arch = "wasm32")]
+            fn b(&self) {}
+            #[cfg(target_arch = "x86_64")]
+            fn c(&self) {}
+            #[cfg(all(feature = "js", not(target_arch = "wasm32")))]
+            fn d(&self) {}
+            // Depends on something unknown: kept
+            #[cfg(debug_assertions)]
+            fn e(&self) {}
+        }
+    };
+    let js = try_generate_js_methods_for_impl(&input).unwrap();
+    println!("JS: {}", &js);
+    for kept in ["prototype.a", "prototype.b", "prototype.e"] {
+        assert!(js.contains(kept), "{kept} missing:\n{js}");
+    }
+    for dropped in ["prototype.c", "prototype.d"] {
+        assert!(!js.contains(dropped), "{dropped} should be dropped:\n{js}");
+    }
+}
+
+#[test]
+fn test_server_only_fields_and_statements_are_dropped() {
+    let input: ItemStruct = parse_quote! {
+        struct Session {
+            user: String,
+            #[cfg(not(feature = "js"))]
+            db_handle: u32,
+        }
+    };
+    let js = generate_js_class_for_struct(&input);
+    println!("JS: {}", &js);
+    assert!(js.contains("constructor(user)"), "{js}");
+
+    let block: Block = parse_quote!({
+        let mut total = 1;
+        #[cfg(not(feature = "js"))]
+        let total = audit(total);
+        #[cfg(feature = "js")]
+        {
+            total += 1;
+        }
+        total
+    });
+    let js = rust_block_to_js(&block);
+    println!("JS: {}", &js);
+    assert!(!js.contains("audit"), "{js}");
+    assert!(js.contains("total += 1"), "{js}");
+}
@@ -0,0 +1,55 @@
+// Tests for `.contains()`: `.includes()` for strings and Vecs, and a runtime
+// dispatch for untyped receivers so set- and map-like values answer too.
+use mojes_mojo::*;
+use syn::{parse_quote, Block, Expr};
+
+fn eval_block(b: &Block, prelude: &str) -> bool {
+    let js = rust_block_to_js(b);
+    println!("JS: {}", &js);
+    let mut context = boa_engine::Context::default();
+    context
+        .eval(boa_engine::Source::from_bytes(&format!(
+            "{prelude}\n(function() {{ {js} }})()"
+        )))
+        .expect("JS execution failed")
+        .as_boolean()
+        .unwrap()
+}
+
+#[test]
+fn test_typed_vec_contains_is_includes() {
+    let b: Block = parse_quote!({
+        let ids: Vec<i32> = vec![1, 2, 3];
+        ids.contains(&2)
+    });
+    let js = rust_block_to_js(&b);
+    assert!(js.contains("return ids.includes(2"), "{js}");
+    assert!(eval_block(&b, ""));
+}
+
+#[test]
+fn test_str_literal_contains_is_includes() {
+    let expr: Expr = parse_quote! { "hello".contains("ell") };
+    assert_eq!(rust_expr_to_js(&expr), "\"hello\".includes(\"ell\")");
+}
+
+#[test]
+fn test_untyped_vec_contains() {
+    let b: Block = parse_quote!({
+        let ids = vec![1, 2, 3];
+        ids.contains(&3) && !ids.contains(&4)
+    });
+    assert!(eval_block(&b, ""));
+}
+
+#[test]
+fn test_set_like_contains() {
+    // A JS Set answers through has(), a plain object through its own keys
+    let b: Block = parse_quote!({
+        seen.contains(&"a") && !seen.contains(&"z") && keys.contains(&"k")
+    });
+    assert!(eval_block(
+        &b,
+        "const seen = new Set(['a', 'b']); const keys = { k: 1 };"
+    ));
+}
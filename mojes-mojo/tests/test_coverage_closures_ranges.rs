@@ -77,6 +77,84 @@ fn test_range_half_open() {
     assert!(js.contains("Array.from") || js.contains("length"));
 }
 
+fn eval_block_str(block: &Block) -> String {
+    let js = rust_block_to_js(block);
+    println!("JS: {}", &js);
+    let value = eval_js(&format!("(function() {{ {js} }})()")).expect("JS execution failed");
+    value.as_string().unwrap().to_std_string().unwrap()
+}
+
+#[test]
+fn test_range_rev_materializes_reversed_copy() {
+    let block: Block = parse_quote! {
+        {
+            let down: Vec<i32> = (0..3).rev().collect();
+            let v = vec![1, 2, 3];
+            let back: Vec<i32> = v.iter().rev().collect();
+            format!("{} {} {}", down.join(","), back.join(","), v.join(","))
+        }
+    };
+    // rev() copies before reversing, so `v` itself keeps its order
+    assert_eq!(eval_block_str(&block), "2,1,0 3,2,1 1,2,3");
+}
+
+#[test]
+fn test_inclusive_range_materializes_its_end() {
+    let block: Block = parse_quote! {
+        {
+            let a = 2;
+            let up: Vec<i32> = (1..=3).collect();
+            let down: Vec<i32> = (a + 1..=a * 3).rev().collect();
+            let head: Vec<i32> = (..=2).collect();
+            format!("{} {} {}", up.join(","), down.join(","), head.join(","))
+        }
+    };
+    assert_eq!(eval_block_str(&block), "1,2,3 6,5,4,3 0,1,2");
+}
+
+#[test]
+fn test_open_ended_range_outside_index_is_an_error() {
+    let expr: Expr = parse_quote! { start.. };
+    let err = try_rust_expr_to_js(&expr).unwrap_err();
+    println!("error: {}", &err);
+    assert!(err.contains("open-ended range"), "{err}");
+
+    let block: Block = parse_quote!({
+        for i in 0.. {
+            log(i);
+        }
+    });
+    let err = try_rust_block_to_js(&block).unwrap_err();
+    assert!(err.contains("open-ended range"), "{err}");
+}
+
+#[test]
+fn test_range_index_slices() {
+    let block: Block = parse_quote! {
+        {
+            let v = vec![10, 20, 30, 40];
+            let start = 1;
+            [
+                v[start..].join(","),
+                v[..2].join(","),
+                v[1..3].join(","),
+                v[..].join(","),
+                v[1..=2].join(","),
+            ]
+            .join("|")
+        }
+    };
+    let js = rust_block_to_js(&block);
+    println!("JS: {}", &js);
+    assert!(js.contains("v.slice(start)"), "{js}");
+    let result = eval_js(&format!("(function() {{ {} }})()", js)).expect("JS execution failed");
+    let mut ctx = boa_engine::Context::default();
+    assert_eq!(
+        result.to_string(&mut ctx).unwrap().to_std_string().unwrap(),
+        "20,30,40|10,20|20,30|10,20,30,40|20,30"
+    );
+}
+
 #[test]
 fn test_struct_expression() {
     // Tests handle_struct_expr (lines 5586-5633)
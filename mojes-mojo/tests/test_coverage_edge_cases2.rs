@@ -71,6 +71,21 @@ fn test_vec_new_call() {
     assert!(js.contains("[]") || js.contains("Array"));
 }
 
+#[test]
+fn test_with_capacity_drops_the_capacity() {
+    for (rust, expected) in [
+        (parse_quote! { Vec::with_capacity(16) }, "[]"),
+        (parse_quote! { String::with_capacity(n * 2) }, "\"\""),
+        (parse_quote! { HashMap::with_capacity(len) }, "{}"),
+        (parse_quote! { std::collections::HashSet::with_capacity(8) }, "new Set()"),
+    ] {
+        let expr: Expr = rust;
+        let js = rust_expr_to_js(&expr);
+        println!("JS with_capacity: {}", &js);
+        assert_eq!(js, expected);
+    }
+}
+
 #[test]
 fn test_self_new_call_in_struct_context() {
     // Tests Self::new() when struct name is set (lines 2798-2805)
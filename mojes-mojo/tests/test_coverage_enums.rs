@@ -1,7 +1,7 @@
 // Tests for enum generation: generate_js_enum with unit, tuple, and struct variants
 // Covers the big uncovered blocks around lines 6081-6216, 6383-6511
 use mojes_mojo::*;
-use syn::{parse_quote, ItemEnum};
+use syn::{parse_quote, Block, Expr, ItemEnum, ItemImpl};
 
 #[test]
 fn test_enum_unit_variants() {
@@ -82,3 +82,279 @@ fn test_enum_with_single_variant() {
     println!("JS: {}", &js);
     assert!(js.contains("Only"));
 }
+
+#[test]
+fn test_enum_freeze_option() {
+    let input: ItemEnum = parse_quote! {
+        enum Color {
+            Red,
+            Green,
+        }
+    };
+    // Unfrozen unless asked for
+    let plain = transpile_enum_to_js_with_options(&input, &CodegenOptions::default()).unwrap();
+    assert!(!plain.contains("Object.freeze"), "{plain}");
+
+    let options = CodegenOptions {
+        freeze_enums: true,
+        ..CodegenOptions::default()
+    };
+    let js = transpile_enum_to_js_with_options(&input, &options).unwrap();
+    println!("JS: {}", &js);
+    assert!(js.contains("const Color = Object.freeze({"), "{js}");
+
+    // Assigning a variant has no effect on the frozen object
+    let mut context = boa_engine::Context::default();
+    let result = context
+        .eval(boa_engine::Source::from_bytes(&format!("{js}\nColor.Red = 'Blue';\nColor.Red")))
+        .expect("JS execution failed");
+    assert_eq!(result.to_string(&mut context).unwrap().to_std_string().unwrap(), "Red");
+}
+
+#[test]
+fn test_enum_with_explicit_discriminants_uses_numbers() {
+    let input: ItemEnum = parse_quote! {
+        enum Priority {
+            Low = 1,
+            High = 4,
+            Urgent,
+        }
+    };
+    let js = generate_js_enum(&input);
+    println!("JS: {}", &js);
+    assert!(js.contains("Low: 1,"), "{js}");
+    assert!(js.contains("High: 4,"), "{js}");
+    // An implicit discriminant follows the previous one
+    assert!(js.contains("Urgent: 5,"), "{js}");
+
+    // The JSON form stays the variant name, on both sides of the round trip
+    let code = format!(
+        "{js}\n[Priority.toJSON(Priority.High), Priority.fromJSON('\"Urgent\"'), isPriority(4), isPriority('High')].join(',')"
+    );
+    let mut context = boa_engine::Context::default();
+    let result = context
+        .eval(boa_engine::Source::from_bytes(&code))
+        .expect("JS execution failed");
+    assert_eq!(
+        result.to_string(&mut context).unwrap().to_std_string().unwrap(),
+        "\"High\",5,true,false"
+    );
+
+    // Without discriminants the variants keep their string values
+    let input: ItemEnum = parse_quote! {
+        enum Plain { A, B }
+    };
+    assert!(generate_js_enum(&input).contains("A: 'A'"));
+}
+
+#[test]
+fn test_match_on_enum_with_discriminants() {
+    let input: ItemEnum = parse_quote! {
+        enum Priority {
+            Low = 1,
+            High = 4,
+        }
+    };
+    let enum_js = generate_js_enum(&input);
+    let block: Block = parse_quote!({
+        let s = match p {
+            Priority::Low => "low",
+            _ => "high",
+        };
+        let t = match p {
+            Priority::High => "high",
+            Priority::Low => "low",
+        };
+        format!("{} {}", s, t)
+    });
+    let js = rust_block_to_js(&block);
+    println!("JS: {}", &js);
+    assert!(js.contains("p === Priority.Low"), "{js}");
+
+    let code = format!("{enum_js}\nconst p = Priority.Low;\n(function() {{ {js} }})()");
+    let mut context = boa_engine::Context::default();
+    let result = context
+        .eval(boa_engine::Source::from_bytes(&code))
+        .expect("JS execution failed");
+    assert_eq!(result.to_string(&mut context).unwrap().to_std_string().unwrap(), "low low");
+}
+
+#[test]
+fn test_tuple_variant_call_uses_generated_factory() {
+    let input: ItemEnum = parse_quote! {
+        enum Color {
+            Red,
+            Rgb(u8, u8, u8),
+        }
+    };
+    let call: Expr = parse_quote! { Color::Rgb(1, 2, 3) };
+    let call_js = rust_expr_to_js(&call);
+    assert_eq!(call_js, "Color.Rgb(1, 2, 3)");
+
+    let js = generate_js_enum(&input);
+    let mut context = boa_engine::Context::default();
+    let result = context
+        .eval(boa_engine::Source::from_bytes(&format!("{js}\nJSON.stringify({call_js})")))
+        .expect("JS execution failed");
+    assert_eq!(
+        result.as_string().unwrap().to_std_string().unwrap(),
+        "{\"type\":\"Rgb\",\"value0\":1,\"value1\":2,\"value2\":3}"
+    );
+}
+
+#[test]
+fn test_enum_impl_methods_take_the_value() {
+    let input: ItemEnum = parse_quote! {
+        enum Shape {
+            Dot,
+            Square(f64),
+        }
+    };
+    let methods: ItemImpl = parse_quote! {
+        impl Shape {
+            fn unit() -> Self {
+                Shape::Square(1.0)
+            }
+            fn area(&self) -> f64 {
+                match self {
+                    Shape::Dot => 0.0,
+                    Shape::Square(side) => side * side,
+                }
+            }
+            fn is_bigger_than(&self, limit: f64) -> bool {
+                self.area() > limit
+            }
+        }
+    };
+    let methods_js = transpile_impl_to_js(&methods).unwrap();
+    println!("JS: {}", &methods_js);
+    assert!(!methods_js.contains("prototype"), "{methods_js}");
+    assert!(methods_js.contains("Shape.area = function(self)"), "{methods_js}");
+    assert!(methods_js.contains("Shape.area(self) > limit"), "{methods_js}");
+
+    let js = generate_js_enum(&input);
+    let mut context = boa_engine::Context::default();
+    let result = context
+        .eval(boa_engine::Source::from_bytes(&format!(
+            "{js}\n{methods_js}\n[Shape.area(Shape.Square(3)), Shape.area(Shape.Dot), \
+             Shape.is_bigger_than(Shape.unit(), 0.5)].join()"
+        )))
+        .expect("JS execution failed");
+    assert_eq!(result.as_string().unwrap().to_std_string().unwrap(), "9,0,true");
+}
+
+#[test]
+fn test_enum_detected_through_if_let() {
+    let methods: ItemImpl = parse_quote! {
+        impl Light {
+            fn is_on(&self) -> bool {
+                if let Self::On = self { true } else { false }
+            }
+        }
+    };
+    let js = transpile_impl_to_js(&methods).unwrap();
+    println!("JS: {}", &js);
+    assert!(js.contains("Light.is_on = function(self)"), "{js}");
+
+    // A struct impl keeps its prototype methods
+    let methods: ItemImpl = parse_quote! {
+        impl Lamp {
+            fn is_on(&self) -> bool {
+                self.on
+            }
+        }
+    };
+    assert!(transpile_impl_to_js(&methods).unwrap().contains("Lamp.prototype.is_on"));
+}
+
+#[test]
+fn test_enum_detected_through_variant_value() {
+    let methods: ItemImpl = parse_quote! {
+        impl Color {
+            fn is_red(&self) -> bool {
+                *self == Color::Red
+            }
+        }
+    };
+    let js = transpile_impl_to_js(&methods).unwrap();
+    println!("JS: {}", &js);
+    assert!(js.contains("Color.is_red = function(self)"), "{js}");
+
+    // Associated consts and functions don't make a struct an enum
+    let methods: ItemImpl = parse_quote! {
+        impl Level {
+            const MAX: i32 = 10;
+            fn clamped(&self) -> i32 {
+                match self.value {
+                    Self::MAX => Self::MAX,
+                    v => Self::limit(v),
+                }
+            }
+        }
+    };
+    let js = transpile_impl_to_js(&methods).unwrap();
+    println!("JS: {}", &js);
+    assert!(js.contains("Level.prototype.clamped"), "{js}");
+}
+
+#[test]
+fn test_enum_methods_called_from_outside_the_impl() {
+    let input: ItemEnum = parse_quote! {
+        enum Shape {
+            Dot,
+            Square(f64),
+        }
+    };
+    let methods: ItemImpl = parse_quote! {
+        impl Shape {
+            fn area(&self) -> f64 {
+                match self {
+                    Shape::Dot => 0.0,
+                    Shape::Square(side) => side * side,
+                }
+            }
+            fn bigger(&self, other: &Self) -> bool {
+                self.area() > other.area()
+            }
+        }
+    };
+    let mut state = TranspilerState::new();
+    let methods_js = transpile_impl_to_js_with_state(&methods, &mut state).unwrap();
+    println!("JS: {}", &methods_js);
+    assert!(methods_js.contains("Shape.area(other)"), "{methods_js}");
+
+    let caller: Block = parse_quote!({
+        let s: Shape = Shape::Square(3.0);
+        let d: Shape = Shape::Dot;
+        format!("{} {} {}", s.area(), d.area(), s.bigger(&d))
+    });
+    let caller_js = try_rust_block_to_js_with_state(&caller, &mut state).unwrap();
+    println!("JS: {}", &caller_js);
+    assert!(caller_js.contains("Shape.area(s)"), "{caller_js}");
+
+    let js = generate_js_enum(&input);
+    let mut context = boa_engine::Context::default();
+    let result = context
+        .eval(boa_engine::Source::from_bytes(&format!(
+            "{js}\n{methods_js}\n(function() {{ {caller_js} }})()"
+        )))
+        .expect("JS execution failed");
+    assert_eq!(result.as_string().unwrap().to_std_string().unwrap(), "9 0 true");
+}
+
+#[test]
+fn test_registered_enum_impl_without_variant_paths() {
+    let methods: ItemImpl = parse_quote! {
+        impl Color {
+            fn describe(&self) -> String {
+                format!("{:?}", self)
+            }
+        }
+    };
+    let mut state = TranspilerState::new();
+    state.register_enum("Color");
+    let js = transpile_impl_to_js_with_state(&methods, &mut state).unwrap();
+    println!("JS: {}", &js);
+    assert!(js.contains("Color.describe = function(self)"), "{js}");
+    assert!(!js.contains("prototype"), "{js}");
+}
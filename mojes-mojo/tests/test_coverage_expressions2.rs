@@ -186,6 +186,37 @@ fn test_function_definition_in_block() {
     assert!(js.contains("function") && js.contains("helper"));
 }
 
+#[test]
+fn test_generic_function_definitions_in_block() {
+    // Type parameters, bounds and where-clauses only matter to rustc
+    let block: Block = parse_quote! {
+        {
+            fn id<T>(x: T) -> T { x }
+            fn largest<T: PartialOrd + Copy>(items: &[T]) -> T
+            where
+                T: std::fmt::Debug,
+            {
+                let mut best = items[0];
+                for item in items.iter() {
+                    if item > best {
+                        best = item;
+                    }
+                }
+                best
+            }
+            fn apply<F>(f: F, v: i32) -> i32 where F: Fn(i32) -> i32 { f(v) }
+            id(largest(&[1, 5, 3])) + apply(|x| x * 2, 1)
+        }
+    };
+    let js = rust_block_to_js(&block);
+    println!("JS generic fns: {}", &js);
+    assert!(js.contains("function id(x) {\n    return x;\n}"), "{js}");
+    assert!(js.contains("function largest(items) {"), "{js}");
+    assert!(js.contains("function apply(f, v) {"), "{js}");
+    let result = eval_js(&format!("(function() {{ {js} }})()")).expect("JS execution failed");
+    assert_eq!(result.as_number().unwrap(), 7.0);
+}
+
 #[test]
 fn test_compound_assignment_ops() {
     // Tests various compound assignment operators
@@ -205,6 +236,30 @@ fn test_compound_assignment_ops() {
     assert!(js.contains("+=") && js.contains("-="));
 }
 
+#[test]
+fn test_compound_assignment_to_indices_and_fields() {
+    let block: Block = parse_quote! {
+        {
+            let mut v = vec![1, 2, 3];
+            let i = 1;
+            v[i] += 1;
+            v[0] *= 10;
+            let mut grid = [[0, 0], [0, 0]];
+            grid[1][0] -= 4;
+            let mut self_like = Counter { count: 0 };
+            self_like.count += 2;
+            v[i] + v[0] + grid[1][0] + self_like.count
+        }
+    };
+    let js = rust_block_to_js(&block);
+    println!("JS: {}", &js);
+    assert!(js.contains("v[i] += 1;"), "{js}");
+    assert!(js.contains("grid[1][0] -= 4;"), "{js}");
+    let code = format!("class Counter {{}}\n(function() {{ {js} }})()");
+    let result = eval_js(&code).expect("JS execution failed");
+    assert_eq!(result.as_number().unwrap(), 3.0 + 10.0 - 4.0 + 2.0);
+}
+
 #[test]
 fn test_bitwise_assignment_ops() {
     // Tests bitwise compound assignment operators
@@ -357,3 +412,21 @@ fn test_map_and_filter_methods() {
     println!("JS find: {}", &js);
     assert!(js.contains("find"));
 }
+
+#[test]
+fn test_repeat_with_const_count() {
+    // A const used as the repeat count is declared so the reference resolves
+    let block: Block = parse_quote! {
+        {
+            const N: usize = 4;
+            let slots = [0; N];
+            slots.len()
+        }
+    };
+    let js = rust_block_to_js(&block);
+    println!("JS repeat const: {}", &js);
+    assert!(js.contains("const N = 4;"), "Expected N to be declared, got: {}", &js);
+    assert!(js.contains("length: N"), "Expected N as the length, got: {}", &js);
+    let result = eval_js(&format!("(function() {{ {} }})()", &js)).unwrap();
+    assert_eq!(result.as_number().unwrap(), 4.0);
+}
@@ -105,3 +105,131 @@ fn test_format_debug_specifier() {
     println!("JS: {}", &js);
     assert!(js.contains("debug") || js.contains("`"));
 }
+
+#[test]
+fn test_format_dollar_before_placeholder_is_literal() {
+    let block: Block = parse_quote! {
+        {
+            let n = 5;
+            format!("price: ${}", n)
+        }
+    };
+    let js = rust_block_to_js(&block);
+    println!("JS: {}", &js);
+    let result = eval_js(&format!("(function() {{ {} }})()", &js)).unwrap();
+    assert_eq!(result.as_string().unwrap().to_std_string().unwrap(), "price: $5");
+}
+
+#[test]
+fn test_format_literal_dollar_brace_is_escaped() {
+    // `${{x}}` is the literal text `${x}` in Rust and must stay literal in JS
+    let block: Block = parse_quote! {
+        {
+            let x = 1;
+            format!("${{x}} = {}", x)
+        }
+    };
+    let js = rust_block_to_js(&block);
+    println!("JS: {}", &js);
+    assert!(js.contains("\\${"), "Expected an escaped `${{`, got: {}", &js);
+    let result = eval_js(&format!("(function() {{ {} }})()", &js)).unwrap();
+    assert_eq!(result.as_string().unwrap().to_std_string().unwrap(), "${x} = 1");
+}
+
+#[test]
+fn test_format_inline_captured_identifiers() {
+    let block: Block = parse_quote! {
+        {
+            let name = "Ann";
+            let n = 3;
+            format!("hi {name}, {n} new, {n:?} again, {}", n + 1)
+        }
+    };
+    let js = rust_block_to_js(&block);
+    println!("JS: {}", &js);
+    assert!(js.contains("`hi ${name}, ${n} new"), "{js}");
+    let result = eval_js(&format!(
+        "function debug_repr(v) {{ return '<' + v + '>'; }}\n(function() {{ {} }})()",
+        &js
+    ))
+    .unwrap();
+    assert_eq!(result.as_string().unwrap().to_std_string().unwrap(), "hi Ann, 3 new, <3> again, 4");
+}
+
+#[test]
+fn test_inline_capture_uses_the_js_name_and_positions() {
+    // `class` is a JS reserved word, so the binding is renamed; the capture
+    // must follow it. `{1}`/`{0}` pick arguments by position.
+    let block: Block = parse_quote! {
+        {
+            let class = "warm";
+            println!("{class}");
+            format!("{1}-{0}-{class}", "a", "b")
+        }
+    };
+    let js = rust_block_to_js(&block);
+    println!("JS: {}", &js);
+    assert!(!js.contains("{class}"), "{js}");
+    let result = eval_js(&format!(
+        "const console = {{ log: () => {{}} }};\n(function() {{ {} }})()",
+        &js
+    ))
+    .unwrap();
+    assert_eq!(result.as_string().unwrap().to_std_string().unwrap(), "b-a-warm");
+}
+
+#[test]
+fn test_named_format_arguments() {
+    // `name = value` arguments win over variables of the same name, in
+    // format! and in the token-parsed macros alike
+    let block: Block = parse_quote! {
+        {
+            let x = 4;
+            let val = 100;
+            println!("{val}", val = x * 2);
+            format!("v={val} {x} {}", x, val = x + 1)
+        }
+    };
+    let js = rust_block_to_js(&block);
+    println!("JS: {}", &js);
+    assert!(js.contains("console.log(`${x * 2}`)"), "{js}");
+    let result = eval_js(&format!(
+        "const console = {{ log: () => {{}} }};\n(function() {{ {} }})()",
+        &js
+    ))
+    .unwrap();
+    assert_eq!(result.as_string().unwrap().to_std_string().unwrap(), "v=5 4 4");
+}
+
+#[test]
+fn test_escaped_braces_without_placeholders() {
+    let expr: Expr = parse_quote! { format!("{{}}") };
+    let js = rust_expr_to_js(&expr);
+    println!("JS: {}", &js);
+    let result = eval_js(&js).unwrap();
+    assert_eq!(result.as_string().unwrap().to_std_string().unwrap(), "{}");
+
+    let expr: Expr = parse_quote! { format!("{{a}} {{{}}}", 1) };
+    let result = eval_js(&rust_expr_to_js(&expr)).unwrap();
+    assert_eq!(result.as_string().unwrap().to_std_string().unwrap(), "{a} {1}");
+}
+
+#[test]
+fn test_escaped_braces_in_other_format_macros() {
+    let block: Block = parse_quote! {
+        {
+            println!("{{ok}}");
+            eprintln!("}}");
+            assert!(true, "{{never}}");
+        }
+    };
+    let js = rust_block_to_js(&block);
+    println!("JS: {}", &js);
+    let result = eval_js(&format!(
+        "const out = [];\n\
+         const console = {{ log: (s) => out.push(s), error: (s) => out.push(s) }};\n\
+         {js}\nout.join(' ')"
+    ))
+    .unwrap();
+    assert_eq!(result.as_string().unwrap().to_std_string().unwrap(), "{ok} }");
+}
@@ -137,3 +137,50 @@ fn test_if_single_expression_else() {
     println!("JS: {}", &js);
     assert!(js.contains("x"));
 }
+
+#[test]
+fn test_if_let_chain_binds_both() {
+    let block: Block = parse_quote! {
+        {
+            let x: Option<i32> = Some(2);
+            let y: Option<i32> = Some(5);
+            if let Some(a) = x && let Some(b) = y && a < b {
+                a * 10 + b
+            } else {
+                -1
+            }
+        }
+    };
+    let js = rust_block_to_js(&block);
+    println!("JS: {}", &js);
+    assert!(!js.contains("let Some"), "let-chain mis-transpiled:\n{js}");
+    let code = format!("(function() {{ {} }})()", &js);
+    assert_eq!(eval_js(&code).unwrap().as_number().unwrap(), 25.0);
+}
+
+#[test]
+fn test_if_let_chain_falls_to_else() {
+    // The else runs once whichever link of the chain fails
+    let block: Block = parse_quote! {
+        {
+            let x: Option<i32> = Some(2);
+            let y: Option<i32> = None;
+            let mut misses = 0;
+            if let Some(a) = x && let Some(b) = y {
+                misses = a + b;
+            } else {
+                misses += 1;
+            }
+            if let Some(a) = y && let Some(b) = x {
+                misses = a + b;
+            } else {
+                misses += 1;
+            }
+            misses
+        }
+    };
+    let js = rust_block_to_js(&block);
+    println!("JS: {}", &js);
+    let code = format!("(function() {{ {} }})()", &js);
+    assert_eq!(eval_js(&code).unwrap().as_number().unwrap(), 2.0);
+}
@@ -116,3 +116,506 @@ fn test_transpile_enum_to_js() {
     println!("JS transpile_enum: {}", &js);
     assert!(js.contains("North") && js.contains("South"));
 }
+
+#[test]
+fn test_return_inside_bare_block_leaves_method() {
+    // A guard written inside braces must return from the method itself
+    let class: ItemStruct = parse_quote! {
+        struct Gate {
+            open: bool,
+        }
+    };
+    let methods: ItemImpl = parse_quote! {
+        impl Gate {
+            fn check(&self) -> i32 {
+                {
+                    if self.open {
+                        return 1;
+                    }
+                }
+                2
+            }
+        }
+    };
+    let methods_js = generate_js_methods_for_impl(&methods);
+    println!("JS: {}", &methods_js);
+    assert!(!methods_js.contains("call(this)"), "bare block became an IIFE:\n{methods_js}");
+
+    let code = format!(
+        "{}\n{}\n`${{new Gate(true).check()}} ${{new Gate(false).check()}}`",
+        generate_js_class_for_struct(&class),
+        methods_js
+    );
+    let mut context = boa_engine::Context::default();
+    let result = context
+        .eval(boa_engine::Source::from_bytes(&code))
+        .expect("JS execution failed");
+    assert_eq!(result.as_string().unwrap().to_std_string().unwrap(), "1 2");
+}
+
+fn eval_str(code: &str) -> String {
+    let mut context = boa_engine::Context::default();
+    let value = context
+        .eval(boa_engine::Source::from_bytes(code))
+        .expect("JS execution failed");
+    value.to_string(&mut context).unwrap().to_std_string().unwrap()
+}
+
+#[test]
+fn test_match_statement_in_method_is_plain_if_else() {
+    let class: ItemStruct = parse_quote! {
+        struct Dial {
+            level: i32,
+        }
+    };
+    let methods: ItemImpl = parse_quote! {
+        impl Dial {
+            fn turn(&mut self, dir: i32) -> i32 {
+                match dir {
+                    1 => self.level += 1,
+                    -1 => self.level -= 1,
+                    _ => {}
+                }
+                self.level
+            }
+        }
+    };
+    let methods_js = generate_js_methods_for_impl(&methods);
+    println!("JS: {}", &methods_js);
+    assert!(!methods_js.contains("call(this)"), "match statement became an IIFE:\n{methods_js}");
+    assert!(methods_js.contains("} else if (_match_value === -1) {"), "{methods_js}");
+
+    let code = format!(
+        "{}\n{}\nconst d = new Dial(5);\nd.turn(1);\nd.turn(1);\n`${{d.turn(-1)}} ${{d.turn(0)}}`",
+        generate_js_class_for_struct(&class),
+        methods_js
+    );
+    assert_eq!(eval_str(&code), "6 6");
+}
+
+#[test]
+fn test_assign_through_index_and_nested_members() {
+    let class: ItemStruct = parse_quote! {
+        struct Buffer {
+            data: Vec<i32>,
+            rows: Vec<Vec<i32>>,
+        }
+    };
+    let methods: ItemImpl = parse_quote! {
+        impl Buffer {
+            fn set(&mut self, i: usize, v: i32) {
+                self.data[i] = v;
+                self.rows[i][0] = v * 10;
+                (*self).data[0] += 1;
+            }
+
+            fn reset(&mut self) -> i32 {
+                let mut n = 1;
+                (n) = 0;
+                n
+            }
+        }
+    };
+    let methods_js = generate_js_methods_for_impl(&methods);
+    println!("JS: {}", &methods_js);
+    assert!(methods_js.contains("this.data[i] = v;"), "{methods_js}");
+    assert!(methods_js.contains("this.rows[i][0] = v * 10;"), "{methods_js}");
+    assert!(methods_js.contains("n = 0;"), "{methods_js}");
+
+    let code = format!(
+        "{}\n{}\nconst b = new Buffer([0, 0], [[1], [2]]);\nb.set(1, 7);\n`${{b.data}} ${{b.rows[1]}} ${{b.reset()}}`",
+        generate_js_class_for_struct(&class),
+        methods_js
+    );
+    assert_eq!(eval_str(&code), "1,7 70 0");
+}
+
+#[test]
+fn test_trait_impl_iterator_next() {
+    let class: ItemStruct = parse_quote! {
+        struct MyIter {
+            n: u32,
+        }
+    };
+    let methods: ItemImpl = parse_quote! {
+        impl Iterator for MyIter {
+            type Item = u32;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                if self.n < 2 {
+                    self.n += 1;
+                    Some(self.n)
+                } else {
+                    None
+                }
+            }
+        }
+    };
+    let methods_js = generate_js_methods_for_impl(&methods);
+    println!("JS: {}", &methods_js);
+    assert!(methods_js.contains("impl Iterator"), "{methods_js}");
+    assert!(methods_js.contains("MyIter.prototype.next = function()"), "{methods_js}");
+
+    let code = format!(
+        "{}\n{}\nconst it = new MyIter(0);\n`${{it.next()}} ${{it.next()}} ${{it.next()}}`",
+        generate_js_class_for_struct(&class),
+        methods_js
+    );
+    assert_eq!(eval_str(&code), "1 2 null");
+}
+
+#[test]
+fn test_debug_and_display_fmt_do_not_collide() {
+    let class: ItemStruct = parse_quote! {
+        struct Celsius {
+            degrees: i32,
+        }
+    };
+    let display: ItemImpl = parse_quote! {
+        impl fmt::Display for Celsius {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}°C", self.degrees)
+            }
+        }
+    };
+    let debug: ItemImpl = parse_quote! {
+        impl fmt::Debug for Celsius {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "Celsius({})", self.degrees)
+            }
+        }
+    };
+    let debug_js = generate_js_methods_for_impl(&debug);
+    println!("JS: {}", &debug_js);
+    assert!(debug_js.contains("Celsius.prototype.fmt_debug = function(f)"), "{debug_js}");
+
+    // Debug coming after Display must not replace what toString() uses
+    let code = format!(
+        "{}\n{}\n{}\n`${{new Celsius(21)}}`",
+        generate_js_class_for_struct(&class),
+        generate_js_methods_for_impl(&display),
+        debug_js
+    );
+    assert_eq!(eval_str(&code), "21°C");
+}
+
+#[test]
+fn test_trailing_option_params_default_to_null() {
+    let methods: ItemImpl = parse_quote! {
+        impl Plot {
+            fn f(&self, x: i32, y: Option<i32>) -> i32 {
+                x + y.unwrap_or(10)
+            }
+            fn g(&self, a: Option<i32>, b: i32) -> i32 {
+                b
+            }
+        }
+    };
+    let methods_js = generate_js_methods_for_impl(&methods);
+    println!("JS: {}", &methods_js);
+    assert!(methods_js.contains("function(x, y = null)"), "{methods_js}");
+    // Only trailing Options can be left out
+    assert!(methods_js.contains("function(a, b)"), "{methods_js}");
+
+    let code = format!(
+        "class Plot {{}}\n{}\nconst p = new Plot();\n`${{p.f(1)}} ${{p.f(1, 2)}}`",
+        methods_js
+    );
+    assert_eq!(eval_str(&code), "11 3");
+}
+
+#[test]
+fn test_iterator_impl_is_iterable_with_for_of() {
+    let class: ItemStruct = parse_quote! {
+        struct Countdown {
+            n: u32,
+        }
+    };
+    let methods: ItemImpl = parse_quote! {
+        impl Iterator for Countdown {
+            type Item = u32;
+
+            fn next(&mut self) -> Option<u32> {
+                if self.n == 0 {
+                    None
+                } else {
+                    self.n -= 1;
+                    Some(self.n + 1)
+                }
+            }
+        }
+    };
+    let methods_js = generate_js_methods_for_impl(&methods);
+    println!("JS: {}", &methods_js);
+    assert!(methods_js.contains("Countdown.prototype[Symbol.iterator]"), "{methods_js}");
+
+    let body: syn::Block = parse_quote!({
+        let mut seen = vec![];
+        for x in Countdown::new(3) {
+            seen.push(x);
+        }
+        seen.join(",")
+    });
+    let code = format!(
+        "{}\n{}\n(function() {{ {} }})()",
+        generate_js_class_for_struct(&class),
+        methods_js,
+        rust_block_to_js(&body)
+    );
+    println!("JS: {}", &code);
+    assert_eq!(eval_str(&code), "3,2,1");
+}
+
+#[test]
+fn test_builder_methods_return_this_for_chaining() {
+    let class: ItemStruct = parse_quote! {
+        struct Request {
+            url: String,
+            retry_count: u32,
+        }
+    };
+    let methods: ItemImpl = parse_quote! {
+        impl Request {
+            fn new() -> Self {
+                Request { url: String::new(), retry_count: 0 }
+            }
+            fn with_url(mut self, url: &str) -> Self {
+                self.url = url.to_string();
+                self
+            }
+            fn with_retries(mut self, n: u32) -> Self {
+                self.retry_count = n;
+                self
+            }
+            fn describe(&self) -> String {
+                format!("{} x{}", self.url, self.retry_count)
+            }
+        }
+    };
+    let methods_js = generate_js_methods_for_impl(&methods);
+    println!("JS: {}", &methods_js);
+    assert!(
+        methods_js.contains("Request.prototype.with_url = function(url) {\n    this.url = url.toString();\n    return this;\n}"),
+        "{methods_js}"
+    );
+
+    let code = format!(
+        "{}\n{}\nRequest.new().with_url(\"/api\").with_retries(3).describe()",
+        generate_js_class_for_struct(&class),
+        methods_js
+    );
+    assert_eq!(eval_str(&code), "/api x3");
+}
+
+#[test]
+fn test_associated_consts_become_static_fields() {
+    let class: ItemStruct = parse_quote! {
+        struct Gauge {
+            level: i32,
+        }
+    };
+    let methods: ItemImpl = parse_quote! {
+        impl Gauge {
+            const MAX: i32 = 100;
+            const LABEL: &'static str = "gauge";
+            fn clamped(&self) -> i32 {
+                if self.level > Self::MAX { Self::MAX } else { self.level }
+            }
+        }
+    };
+    let methods_js = generate_js_methods_for_impl(&methods);
+    println!("JS: {}", &methods_js);
+    assert!(methods_js.contains("Gauge.MAX = 100;"), "{methods_js}");
+    assert!(methods_js.contains("Gauge.LABEL = \"gauge\";"), "{methods_js}");
+
+    let code = format!(
+        "{}\n{}\nnew Gauge(250).clamped() + Gauge.LABEL",
+        generate_js_class_for_struct(&class),
+        methods_js
+    );
+    assert_eq!(eval_str(&code), "100gauge");
+}
+
+#[test]
+fn test_consuming_self_method_uses_this() {
+    let methods: ItemImpl = parse_quote! {
+        impl Ticket {
+            fn consume(self) -> String {
+                format!("used {}", self.code)
+            }
+        }
+    };
+    let js = generate_js_methods_for_impl(&methods);
+    println!("JS: {}", &js);
+    assert!(js.contains("Ticket.prototype.consume = function() {"), "{js}");
+    assert!(js.contains("this.code"), "{js}");
+}
+
+#[test]
+fn test_receiver_kind_of_each_form() {
+    let methods: ItemImpl = parse_quote! {
+        impl Ticket {
+            fn a(self) {}
+            fn b(mut self) {}
+            fn c(&self) {}
+            fn d(&mut self) {}
+            fn e(self: &Self) {}
+            fn f(self: &mut Self) {}
+            fn g(self: Box<Self>) {}
+        }
+    };
+    let kinds: Vec<ReceiverKind> = methods
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            syn::ImplItem::Fn(method) => method.sig.receiver().map(ReceiverKind::of),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            ReceiverKind::Value,
+            ReceiverKind::Value,
+            ReceiverKind::Ref,
+            ReceiverKind::RefMut,
+            ReceiverKind::Ref,
+            ReceiverKind::RefMut,
+            ReceiverKind::Value,
+        ]
+    );
+    assert_eq!(TranspilerState::new().current_receiver(), None);
+}
+
+#[test]
+fn test_self_associated_call_uses_struct_name() {
+    let class: ItemStruct = parse_quote! {
+        struct Temp {
+            celsius: f64,
+        }
+    };
+    let methods: ItemImpl = parse_quote! {
+        impl Temp {
+            fn to_fahrenheit(c: f64) -> f64 {
+                c * 9.0 / 5.0 + 32.0
+            }
+            fn fahrenheit(&self) -> f64 {
+                Self::to_fahrenheit(self.celsius)
+            }
+        }
+    };
+    let methods_js = generate_js_methods_for_impl(&methods);
+    println!("JS: {}", &methods_js);
+    assert!(methods_js.contains("Temp.to_fahrenheit(this.celsius)"), "{methods_js}");
+    assert!(!methods_js.contains("Self."), "{methods_js}");
+
+    let code = format!(
+        "{}\n{}\nnew Temp(100).fahrenheit()",
+        generate_js_class_for_struct(&class),
+        methods_js
+    );
+    assert_eq!(eval_str(&code), "212");
+}
+
+#[test]
+fn test_instance_method_self_literal_keeps_prototype() {
+    let class: ItemStruct = parse_quote! {
+        struct Vec2 {
+            x: f64,
+            y: f64,
+        }
+    };
+    let methods: ItemImpl = parse_quote! {
+        impl Vec2 {
+            fn scaled(&self, k: f64) -> Self {
+                Self { x: self.x * k, y: self.y * k }
+            }
+            fn sum(&self) -> f64 {
+                self.x + self.y
+            }
+        }
+    };
+    let methods_js = generate_js_methods_for_impl(&methods);
+    println!("JS: {}", &methods_js);
+    assert!(methods_js.contains("new Vec2()"), "{methods_js}");
+
+    let code = format!(
+        "{}\n{}\nconst v = new Vec2(1, 2).scaled(3);\n`${{v instanceof Vec2}} ${{v.scaled(2).sum()}}`",
+        generate_js_class_for_struct(&class),
+        methods_js
+    );
+    assert_eq!(eval_str(&code), "true 18");
+}
+
+#[test]
+fn test_inert_attributes_do_not_affect_transpiling() {
+    let class: ItemStruct = parse_quote! {
+        #[derive(Debug, Clone)]
+        #[allow(dead_code)]
+        struct Meter {
+            #[allow(unused)]
+            #[js_rename = "v"]
+            value: u32,
+            #[js_ignore]
+            #[allow(unused)]
+            cache: u32,
+        }
+    };
+    let methods: ItemImpl = parse_quote! {
+        #[allow(clippy::all)]
+        impl Meter {
+            #[inline]
+            #[must_use]
+            fn doubled(&self) -> u32 {
+                #[allow(unused_mut)]
+                let mut d = self.value * 2;
+                d
+            }
+            #[inline(always)]
+            #[deprecated(note = "use doubled")]
+            #[js_ignore]
+            fn old_doubled(&self) -> u32 {
+                self.value + self.value
+            }
+        }
+    };
+    let class_js = generate_js_class_for_struct(&class);
+    let methods_js = generate_js_methods_for_impl(&methods);
+    println!("JS: {}\n{}", &class_js, &methods_js);
+    assert!(methods_js.contains("Meter.prototype.doubled = function()"), "{methods_js}");
+    assert!(!methods_js.contains("old_doubled"), "{methods_js}");
+    assert!(!class_js.contains("cache"), "{class_js}");
+
+    let code = format!(
+        "{class_js}\n{methods_js}\n`${{new Meter(21).doubled()}} ${{JSON.stringify(new Meter(1))}}`"
+    );
+    assert_eq!(eval_str(&code), "42 {\"v\":1}");
+}
+
+#[test]
+fn test_closures_capture_method_parameters() {
+    // `offset` and the reserved-word parameter `class` come from the method;
+    // the inner closure also sees the outer closure's `x`
+    let input: ItemImpl = parse_quote! {
+        impl Bag {
+            fn shifted(&self, offset: i32, class: i32) -> Vec<i32> {
+                self.items
+                    .iter()
+                    .map(|x| x + offset)
+                    .map(|x| {
+                        let scale = |y: i32| y * class + x;
+                        scale(10)
+                    })
+                    .collect()
+            }
+        }
+    };
+    let js = transpile_impl_to_js(&input).unwrap();
+    println!("JS: {}", &js);
+    assert_eq!(
+        eval_str(&format!(
+            "function Bag() {{ this.items = [1, 2]; }}\n{js}\nnew Bag().shifted(100, 2).join()"
+        )),
+        "121,122"
+    );
+}
@@ -156,3 +156,32 @@ fn test_match_char_pattern() {
     println!("JS char match: {}", &js);
     assert!(js.contains("a") && js.contains("b"));
 }
+
+#[test]
+fn test_statement_match_has_no_iife() {
+    let block: Block = parse_quote! {
+        {
+            let mut log = vec![];
+            for code in vec![1, 2, 7] {
+                match code {
+                    1 => log.push("one"),
+                    2 => {
+                        log.push("two");
+                        continue;
+                    }
+                    _ => break,
+                }
+                log.push("after");
+            }
+            log.join(",")
+        }
+    };
+    let js = rust_block_to_js(&block);
+    println!("JS: {}", &js);
+    assert!(!js.contains("call(this)"), "statement match should not use an IIFE:\n{js}");
+    assert!(!js.contains("return undefined"), "no dead returns expected:\n{js}");
+    let code = format!("(function() {{ {} }})()", &js);
+    let result = eval_js(&code).unwrap();
+    let mut ctx = boa_engine::Context::default();
+    assert_eq!(result.to_string(&mut ctx).unwrap().to_std_string().unwrap(), "one,after,two");
+}
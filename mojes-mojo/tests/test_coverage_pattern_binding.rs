@@ -185,3 +185,43 @@ fn test_match_enum_unit_variant_via_path() {
     let result = eval_js(&code).unwrap();
     assert_eq!(result.as_number().unwrap(), 0.0);
 }
+
+#[test]
+fn test_match_ref_binding_binds_like_plain_name() {
+    // `ref v` has no JS counterpart and binds the matched value directly
+    let block: Block = parse_quote! {
+        {
+            let o = Some(4);
+            match o {
+                Some(ref v) => v + 1,
+                None => 0,
+            }
+        }
+    };
+    let js = rust_block_to_js(&block);
+    println!("JS ref binding: {}", &js);
+    assert!(js.contains("const v = _match_value;"), "{js}");
+    let result = eval_js(&format!("(function() {{ {js} }})()")).unwrap();
+    assert_eq!(result.as_number().unwrap(), 5.0);
+}
+
+#[test]
+fn test_match_ref_mut_binding_is_assignable() {
+    let block: Block = parse_quote! {
+        {
+            let o = Some(4);
+            match o {
+                Some(ref mut v) => {
+                    v += 10;
+                    v
+                }
+                None => 0,
+            }
+        }
+    };
+    let js = rust_block_to_js(&block);
+    println!("JS ref mut binding: {}", &js);
+    assert!(js.contains("let v = _match_value;"), "{js}");
+    let result = eval_js(&format!("(function() {{ {js} }})()")).unwrap();
+    assert_eq!(result.as_number().unwrap(), 14.0);
+}
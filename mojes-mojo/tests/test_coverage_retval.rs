@@ -299,3 +299,84 @@ fn test_generic_if_let_single_expr_else() {
     println!("JS generic if-let else: {}", &js);
     assert!(js.contains("99"));
 }
+
+// An if used as a value must hand its branch value to the binding even when
+// the enclosing block does not return (a loop body here), for plain if,
+// if-let Some and the generic if-let paths alike.
+const HELPERS: &str = "function compute(x) { return x * 10; } function other() { return -1; }";
+
+fn run_with_helpers(block: &Block) -> f64 {
+    let js = rust_block_to_js(block);
+    println!("JS: {}", &js);
+    let code = format!("{} (function() {{ {} }})()", HELPERS, &js);
+    eval_js(&code).unwrap().as_number().unwrap()
+}
+
+#[test]
+fn test_let_if_value_from_branch_tail() {
+    let block: Block = parse_quote! {
+        {
+            let c = true;
+            let mut got = 0;
+            for _i in 0..1 {
+                let y = if c {
+                    let base = 4;
+                    compute(base)
+                } else {
+                    other()
+                };
+                got = y;
+            }
+            got
+        }
+    };
+    assert_eq!(run_with_helpers(&block), 40.0);
+}
+
+#[test]
+fn test_let_if_let_some_value_in_loop_body() {
+    let block: Block = parse_quote! {
+        {
+            let opt = Some(3);
+            let mut got = 0;
+            for _i in 0..1 {
+                let y = if let Some(v) = opt { compute(v) } else { other() };
+                got = y;
+            }
+            got
+        }
+    };
+    assert_eq!(run_with_helpers(&block), 30.0);
+}
+
+#[test]
+fn test_let_if_let_none_takes_else_value_in_loop_body() {
+    let block: Block = parse_quote! {
+        {
+            let opt: Option<i32> = None;
+            let mut got = 0;
+            for _i in 0..1 {
+                let y = if let Some(v) = opt { compute(v) } else { other() };
+                got = y;
+            }
+            got
+        }
+    };
+    assert_eq!(run_with_helpers(&block), -1.0);
+}
+
+#[test]
+fn test_let_if_let_ok_value_in_loop_body() {
+    let block: Block = parse_quote! {
+        {
+            let r: Result<i32, i32> = Ok(2);
+            let mut got = 0;
+            for _i in 0..1 {
+                let y = if let Ok(v) = r { compute(v) } else { other() };
+                got = y;
+            }
+            got
+        }
+    };
+    assert_eq!(run_with_helpers(&block), 20.0);
+}
@@ -1,7 +1,7 @@
 // Tests for struct-to-class generation and impl block transpilation
 // Covers generate_js_class_for_struct, generate_js_methods_for_impl
 use mojes_mojo::*;
-use syn::{parse_quote, ItemImpl, ItemStruct};
+use syn::{parse_quote, Expr, ItemEnum, ItemImpl, ItemStruct};
 
 #[test]
 fn test_basic_struct_to_class() {
@@ -81,3 +81,365 @@ fn test_struct_single_field() {
     assert!(js.contains("class Wrapper"));
     assert!(js.contains("value"));
 }
+
+fn eval_js(code: &str) -> boa_engine::JsValue {
+    let mut context = boa_engine::Context::default();
+    context
+        .eval(boa_engine::Source::from_bytes(code))
+        .expect("JS execution failed")
+}
+
+#[test]
+fn test_tuple_struct_positional_fields() {
+    let input: ItemStruct = parse_quote! {
+        struct Pair(i32, String);
+    };
+    let class_js = generate_js_class_for_struct(&input);
+    println!("JS: {}", &class_js);
+    assert!(class_js.contains("constructor(_0, _1)"), "{class_js}");
+    assert!(class_js.contains("this[0] = _0"), "{class_js}");
+
+    let block: syn::Block = parse_quote!({
+        let p = Pair(41, "x".to_string());
+        p.0 + 1
+    });
+    let mut state = TranspilerState::new();
+    state.register_tuple_struct("Pair");
+    let stmts = rust_block_to_js_with_state(BlockAction::Return, &block, &mut state).unwrap();
+    let module_items: Vec<swc_ecma_ast::ModuleItem> =
+        stmts.into_iter().map(swc_ecma_ast::ModuleItem::Stmt).collect();
+    let body_js = ast_to_code_with_state(&module_items, &mut state).unwrap();
+    println!("JS: {}", &body_js);
+    let result = eval_js(&format!("{class_js}\n(function() {{ {body_js} }})()"));
+    assert_eq!(result.as_number().unwrap(), 42.0);
+
+    // JSON round-trips through an array, like serde's tuple struct encoding
+    let result = eval_js(&format!(
+        "{class_js}\nconst p = Pair.fromJSON(JSON.parse(JSON.stringify(new Pair(1, 'a'))));\n\
+         JSON.stringify(p) + p[1]"
+    ));
+    assert_eq!(
+        result.as_string().unwrap().to_std_string().unwrap(),
+        "[1,\"a\"]a"
+    );
+}
+
+#[test]
+fn test_self_constructor_in_tuple_struct_impl() {
+    let input: ItemStruct = parse_quote! {
+        struct Meters(f64);
+    };
+    let methods: ItemImpl = parse_quote! {
+        impl Meters {
+            fn new(x: f64) -> Self {
+                Self(x)
+            }
+            fn doubled(&self) -> Meters {
+                Self(self.0 * 2.0)
+            }
+        }
+    };
+    let methods_js = generate_js_methods_for_impl(&methods);
+    println!("JS: {}", &methods_js);
+    assert!(methods_js.contains("new Meters(x)"), "{methods_js}");
+    assert!(!methods_js.contains("new Self"), "{methods_js}");
+    let result = eval_js(&format!(
+        "{}\n{methods_js}\nMeters.new(1.5).doubled()[0]",
+        generate_js_class_for_struct(&input)
+    ));
+    assert_eq!(result.as_number().unwrap(), 3.0);
+}
+
+#[test]
+fn test_unknown_capitalized_callee_stays_a_call() {
+    // A glob-imported enum tuple variant (`use Shape::*;`) is not a class
+    let expr: Expr = parse_quote! { Square(2.0) };
+    let js = rust_expr_to_js(&expr);
+    println!("JS: {}", &js);
+    assert!(js.contains("Square(2)"), "{js}");
+    assert!(!js.contains("new Square"), "{js}");
+}
+
+#[test]
+fn test_newtype_struct_serializes_as_inner_value() {
+    let input: ItemStruct = parse_quote! {
+        struct UserId(u64);
+    };
+    let class_js = generate_js_class_for_struct(&input);
+    println!("JS: {}", &class_js);
+    let result = eval_js(&format!(
+        "{class_js}\nJSON.stringify(new UserId(7)) + UserId.fromJSON(7)[0]"
+    ));
+    assert_eq!(result.as_string().unwrap().to_std_string().unwrap(), "77");
+}
+
+#[test]
+fn test_field_init_shorthand_in_object_output() {
+    let expr: Expr = parse_quote! { Shape::Rect { w, h: height } };
+    let js = rust_expr_to_js(&expr);
+    println!("JS: {}", &js);
+    assert!(js.contains("type: \"Rect\""), "{js}");
+    assert!(js.contains("w, h: height"), "expected shorthand `w`:\n{js}");
+
+    let methods: ItemImpl = parse_quote! {
+        impl Point {
+            fn moved(&self, x: f64) -> Self {
+                let y = self.y;
+                Self { x, y }
+            }
+        }
+    };
+    let js = generate_js_methods_for_impl(&methods);
+    println!("JS: {}", &js);
+    assert!(!js.contains("x: x") && !js.contains("y: y"), "{js}");
+}
+
+#[test]
+fn test_from_json_defaults_missing_collections() {
+    let input: ItemStruct = parse_quote! {
+        struct Inventory {
+            owner: String,
+            items: Vec<String>,
+            counts: HashMap<String, u32>,
+        }
+    };
+    let class_js = generate_js_class_for_struct(&input);
+    println!("JS: {}", &class_js);
+    assert!(class_js.contains("json.items ?? []"), "{class_js}");
+    assert!(class_js.contains("json.counts ?? {}"), "{class_js}");
+    // Scalars are passed through untouched
+    assert!(class_js.contains("json.owner,"), "{class_js}");
+
+    let result = eval_js(&format!(
+        "{class_js}\nconst inv = Inventory.fromJSON({{ owner: 'ann', items: null }});\n\
+         `${{inv.items.length}} ${{Object.keys(inv.counts).length}} ${{inv.owner}}`"
+    ));
+    assert_eq!(result.as_string().unwrap().to_std_string().unwrap(), "0 0 ann");
+}
+
+#[test]
+fn test_from_json_reconstructs_nested_structs() {
+    let address: ItemStruct = parse_quote! {
+        struct Address {
+            city: String,
+        }
+    };
+    let person: ItemStruct = parse_quote! {
+        struct Person {
+            name: String,
+            home: Address,
+            work: Option<Address>,
+        }
+    };
+    let methods: ItemImpl = parse_quote! {
+        impl Address {
+            fn label(&self) -> String {
+                format!("in {}", self.city)
+            }
+        }
+    };
+    let person_js = generate_js_class_for_struct(&person);
+    println!("JS: {}", &person_js);
+    assert!(person_js.contains("Address.fromJSON(typeof Address === \"function\" ? json.home"), "{person_js}");
+
+    let code = format!(
+        "{}\n{}\n{}\n\
+         const p = new Person('bo', new Address('Oslo'), null);\n\
+         const back = Person.fromJSON(JSON.parse(JSON.stringify(p)));\n\
+         `${{back.home.label()}} ${{back.work}}`",
+        generate_js_class_for_struct(&address),
+        generate_js_methods_for_impl(&methods),
+        person_js
+    );
+    let result = eval_js(&code);
+    assert_eq!(result.as_string().unwrap().to_std_string().unwrap(), "in Oslo null");
+}
+
+#[test]
+fn test_from_json_round_trips_enum_fields() {
+    // An enum's fromJSON takes a JSON string, not the parsed value
+    let color: ItemEnum = parse_quote! {
+        enum Color {
+            Red,
+            Rgb(u8, u8, u8),
+        }
+    };
+    let pixel: ItemStruct = parse_quote! {
+        struct Pixel {
+            x: i32,
+            color: Color,
+        }
+    };
+    let code = format!(
+        "{}\n{}\n\
+         const a = Pixel.fromJSON(JSON.parse(JSON.stringify(new Pixel(1, Color.Red))));\n\
+         const b = Pixel.fromJSON(JSON.parse(JSON.stringify(new Pixel(2, Color.Rgb(1, 2, 3)))));\n\
+         `${{a.x}} ${{a.color}} ${{b.x}} ${{b.color.type}} ${{b.color.value2}}`",
+        generate_js_enum(&color),
+        generate_js_class_for_struct(&pixel)
+    );
+    let result = eval_js(&code);
+    assert_eq!(result.as_string().unwrap().to_std_string().unwrap(), "1 Red 2 Rgb 3");
+}
+
+#[test]
+fn test_from_json_leaves_type_params_and_builtins_alone() {
+    let input: ItemStruct = parse_quote! {
+        struct Tagged<T> {
+            value: T,
+            tag: String,
+            at: u64,
+        }
+    };
+    let class_js = generate_js_class_for_struct(&input);
+    println!("JS: {}", &class_js);
+    assert!(!class_js.contains(".fromJSON(json."), "{class_js}");
+}
+
+#[test]
+fn test_from_json_reconstructs_vec_of_structs() {
+    let line: ItemStruct = parse_quote! {
+        struct Line {
+            qty: u32,
+            price: f64,
+        }
+    };
+    let order: ItemStruct = parse_quote! {
+        struct Order {
+            lines: Vec<Line>,
+        }
+    };
+    let methods: ItemImpl = parse_quote! {
+        impl Line {
+            fn total(&self) -> f64 {
+                self.qty as f64 * self.price
+            }
+        }
+    };
+    let order_js = generate_js_class_for_struct(&order);
+    println!("JS: {}", &order_js);
+    assert!(order_js.contains("(json.lines ?? []).map((item)=>"), "{order_js}");
+
+    let code = format!(
+        "{}\n{}\n{}\n\
+         const o = new Order([new Line(2, 1.5), new Line(1, 4)]);\n\
+         const back = Order.fromJSON(JSON.parse(JSON.stringify(o)));\n\
+         `${{back.lines.map((l) => l.total()).join(',')}} ${{Order.fromJSON({{}}).lines.length}}`",
+        generate_js_class_for_struct(&line),
+        generate_js_methods_for_impl(&methods),
+        order_js
+    );
+    let result = eval_js(&code);
+    assert_eq!(result.as_string().unwrap().to_std_string().unwrap(), "3,4 0");
+}
+
+#[test]
+fn test_option_struct_field_round_trips_null_and_present() {
+    let address: ItemStruct = parse_quote! {
+        struct Address {
+            city: String,
+        }
+    };
+    let person: ItemStruct = parse_quote! {
+        struct Person {
+            name: String,
+            home: Option<Address>,
+        }
+    };
+    let methods: ItemImpl = parse_quote! {
+        impl Address {
+            fn label(&self) -> String {
+                format!("in {}", self.city)
+            }
+        }
+    };
+    let person_js = generate_js_class_for_struct(&person);
+    println!("JS: {}", &person_js);
+    assert!(person_js.contains("home: this.home ?? null"), "{person_js}");
+
+    let code = format!(
+        "{}\n{}\n{}\n\
+         const absent = JSON.stringify(new Person(\"Ann\", undefined));\n\
+         const none = Person.fromJSON(JSON.parse(absent));\n\
+         const missing = Person.fromJSON({{ name: \"Bo\" }});\n\
+         const some = Person.fromJSON(JSON.parse(JSON.stringify(new Person(\"Cy\", new Address(\"Oslo\")))));\n\
+         `${{absent}} ${{none.home === null}} ${{missing.home === null}} ${{some.home.label()}}`",
+        generate_js_class_for_struct(&address),
+        generate_js_methods_for_impl(&methods),
+        person_js
+    );
+    let result = eval_js(&code);
+    assert_eq!(
+        result.as_string().unwrap().to_std_string().unwrap(),
+        "{\"name\":\"Ann\",\"home\":null} true true in Oslo"
+    );
+}
+
+#[test]
+fn test_js_rename_changes_json_key() {
+    let input: ItemStruct = parse_quote! {
+        struct Account {
+            #[js_rename = "userId"]
+            user_id: u32,
+            #[js_rename = "display-name"]
+            name: String,
+        }
+    };
+    let class_js = generate_js_class_for_struct(&input);
+    println!("JS: {}", &class_js);
+    assert!(class_js.contains("userId: this.user_id"), "{class_js}");
+    assert!(class_js.contains("json.userId"), "{class_js}");
+
+    let code = format!(
+        "{class_js}\n\
+         const json = JSON.stringify(new Account(7, \"Ann\"));\n\
+         const back = Account.fromJSON(JSON.parse(json));\n\
+         `${{json}} ${{back.user_id}} ${{back.name}}`"
+    );
+    let result = eval_js(&code);
+    assert_eq!(
+        result.as_string().unwrap().to_std_string().unwrap(),
+        "{\"userId\":7,\"display-name\":\"Ann\"} 7 Ann"
+    );
+}
+
+#[test]
+fn test_rename_all_camel_case_json_keys() {
+    let input: ItemStruct = parse_quote! {
+        #[js(rename_all = "camelCase")]
+        struct Event {
+            created_at: u64,
+            #[js_rename = "kind"]
+            event_type: String,
+            owner_user_id: u32,
+        }
+    };
+    let class_js = generate_js_class_for_struct(&input);
+    println!("JS: {}", &class_js);
+    // Class fields keep their Rust names for the methods
+    assert!(class_js.contains("this.created_at = created_at"), "{class_js}");
+
+    let code = format!(
+        "{class_js}\n\
+         const json = JSON.stringify(new Event(5, \"click\", 9));\n\
+         const back = Event.fromJSON(JSON.parse(json));\n\
+         `${{json}} ${{back.created_at}} ${{back.owner_user_id}}`"
+    );
+    let result = eval_js(&code);
+    assert_eq!(
+        result.as_string().unwrap().to_std_string().unwrap(),
+        "{\"createdAt\":5,\"kind\":\"click\",\"ownerUserId\":9} 5 9"
+    );
+}
+
+#[test]
+fn test_rename_all_rejects_unknown_convention() {
+    let input: ItemStruct = parse_quote! {
+        #[js(rename_all = "sPoNgEcAsE")]
+        struct Event {
+            created_at: u64,
+        }
+    };
+    let err = generate_js_class_for_struct_with_state(&input).unwrap_err();
+    assert!(err.contains("sPoNgEcAsE"), "{err}");
+}
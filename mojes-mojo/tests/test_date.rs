@@ -0,0 +1,59 @@
+// Tests for the mojes_dom_api::Date binding: Date::now() stays a static call,
+// Date::new() is the built-in constructor rather than the `Type.new` dispatch.
+use mojes_mojo::*;
+use syn::{parse_quote, Block, Expr};
+
+fn eval_block(b: &Block) -> boa_engine::JsValue {
+    let js = rust_block_to_js(b);
+    println!("JS: {}", &js);
+    let mut context = boa_engine::Context::default();
+    context
+        .eval(boa_engine::Source::from_bytes(&format!("(function() {{ {js} }})()")))
+        .expect("JS execution failed")
+}
+
+#[test]
+fn test_date_now_and_new() {
+    let expr: Expr = parse_quote! { Date::now() };
+    assert_eq!(rust_expr_to_js(&expr), "Date.now()");
+
+    let expr: Expr = parse_quote! { Date::new() };
+    assert_eq!(rust_expr_to_js(&expr), "new Date()");
+
+    let expr: Expr = parse_quote! { Date::new().getTime() };
+    assert_eq!(rust_expr_to_js(&expr), "new Date().getTime()");
+}
+
+#[test]
+fn test_date_getters_run() {
+    let b: Block = parse_quote!({
+        let start = Date::now();
+        let d = Date::new();
+        d.getTime() >= start && d.getMonth() < 12 && d.getFullYear() >= 2024
+    });
+    assert_eq!(eval_block(&b).as_boolean(), Some(true));
+}
+
+#[test]
+fn test_date_new_with_arguments_dispatches_to_user_type() {
+    // A user's `Date::new(y, m, d)` runs its static `new`; the built-in
+    // constructor is only the fallback
+    let expr: Expr = parse_quote! { Date::new(2024, 1, 15) };
+    let js = rust_expr_to_js(&expr);
+    println!("JS: {}", &js);
+    assert!(js.contains("Date.new"), "{js}");
+
+    let b: Block = parse_quote!({
+        let d = Date::new(2024, 1, 15);
+        d.day
+    });
+    let js = rust_block_to_js(&b);
+    let mut context = boa_engine::Context::default();
+    let result = context
+        .eval(boa_engine::Source::from_bytes(&format!(
+            "class Date {{ static new(y, m, d) {{ return {{ day: d }}; }} }}\n\
+             (function() {{ {js} }})()"
+        )))
+        .expect("JS execution failed");
+    assert_eq!(result.as_number(), Some(15.0));
+}
@@ -0,0 +1,93 @@
+// Tests for custom string conversion: an inherent `to_string(&self)` or an
+// `impl Display` becomes the class's toString(), so `.to_string()` and
+// `format!("{}", value)` produce the custom text.
+use mojes_mojo::*;
+use syn::{parse_quote, Block, ItemImpl, ItemStruct};
+
+fn eval_str(code: &str) -> String {
+    let mut context = boa_engine::Context::default();
+    let value = context
+        .eval(boa_engine::Source::from_bytes(code))
+        .expect("JS execution failed");
+    value.to_string(&mut context).unwrap().to_std_string().unwrap()
+}
+
+fn point_class() -> String {
+    let point: ItemStruct = parse_quote! {
+        struct Point {
+            x: i32,
+            y: i32,
+        }
+    };
+    generate_js_class_for_struct(&point)
+}
+
+#[test]
+fn test_custom_to_string_becomes_class_to_string() {
+    let methods: ItemImpl = parse_quote! {
+        impl Point {
+            fn to_string(&self) -> String {
+                format!("P{}/{}", self.x, self.y)
+            }
+        }
+    };
+    let methods_js = generate_js_methods_for_impl(&methods);
+    println!("JS: {}", &methods_js);
+    assert!(methods_js.contains("Point.prototype.toString = function()"), "{methods_js}");
+
+    let block: Block = parse_quote!({
+        let p = Point::new(1, 2);
+        format!("{} and {}", p.to_string(), p)
+    });
+    let body_js = rust_block_to_js(&block);
+    println!("JS: {}", &body_js);
+    let result = eval_str(&format!(
+        "{}\n{}\n(function() {{ {} }})()",
+        point_class(),
+        methods_js,
+        body_js
+    ));
+    assert_eq!(result, "P1/2 and P1/2");
+}
+
+#[test]
+fn test_display_impl_fmt_becomes_class_to_string() {
+    let display: ItemImpl = parse_quote! {
+        impl fmt::Display for Point {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "({}, ", self.x)?;
+                write!(f, "{})", self.y)
+            }
+        }
+    };
+    let display_js = generate_js_methods_for_impl(&display);
+    println!("JS: {}", &display_js);
+    assert!(display_js.contains("f.write_str("), "{display_js}");
+
+    let block: Block = parse_quote!({
+        let p = Point::new(3, 4);
+        format!("at {}", p)
+    });
+    let body_js = rust_block_to_js(&block);
+    let result = eval_str(&format!(
+        "{}\n{}\n(function() {{ {} }})()",
+        point_class(),
+        display_js,
+        body_js
+    ));
+    assert_eq!(result, "at (3, 4)");
+}
+
+#[test]
+fn test_debug_fmt_does_not_replace_to_string() {
+    let debug: ItemImpl = parse_quote! {
+        impl fmt::Debug for Point {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "Point")
+            }
+        }
+    };
+    let debug_js = generate_js_methods_for_impl(&debug);
+    println!("JS: {}", &debug_js);
+    assert!(!debug_js.contains("toString"), "{debug_js}");
+}
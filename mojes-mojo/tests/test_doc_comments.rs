@@ -0,0 +1,68 @@
+// Tests for `CodegenOptions::doc_comments`: `///` docs on structs, methods
+// and functions become leading `/** */` comments, and are dropped by default.
+use mojes_mojo::*;
+use syn::{parse_quote, ItemFn, ItemImpl, ItemStruct};
+
+fn with_docs() -> CodegenOptions {
+    CodegenOptions {
+        doc_comments: true,
+        ..CodegenOptions::default()
+    }
+}
+
+#[test]
+fn test_documented_method_gets_jsdoc_comment() {
+    let input: ItemImpl = parse_quote! {
+        impl Counter {
+            /// Adds one to the count.
+            fn bump(&mut self) {
+                self.count += 1;
+            }
+            fn reset(&mut self) {
+                self.count = 0;
+            }
+        }
+    };
+    let js = transpile_impl_to_js_with_options(&input, &with_docs()).unwrap();
+    println!("JS: {}", &js);
+    assert!(
+        js.contains("/** Adds one to the count. */\nCounter.prototype.bump = function()"),
+        "{js}"
+    );
+    assert_eq!(js.matches("/**").count(), 1, "{js}");
+}
+
+#[test]
+fn test_multi_line_docs_on_struct_and_fn() {
+    let input: ItemStruct = parse_quote! {
+        /// A 2D point.
+        ///
+        /// Coordinates are in pixels.
+        struct Point { x: i32, y: i32 }
+    };
+    let js = transpile_struct_to_js_with_options(&input, &with_docs()).unwrap();
+    println!("JS: {}", &js);
+    assert!(
+        js.starts_with("/**\n * A 2D point.\n *\n * Coordinates are in pixels.\n */\nclass Point {"),
+        "{js}"
+    );
+
+    let input: ItemFn = parse_quote! {
+        /// Squares `n`; never writes */ into the output verbatim
+        fn square(n: i32) -> i32 { n * n }
+    };
+    let js = transpile_fn_to_js_with_options(&input, &with_docs()).unwrap();
+    println!("JS: {}", &js);
+    assert!(js.starts_with("/** Squares `n`; never writes *\\/ into"), "{js}");
+    assert!(js.contains("*/\nfunction square(n)"), "{js}");
+}
+
+#[test]
+fn test_doc_comments_are_dropped_by_default() {
+    let input: ItemFn = parse_quote! {
+        /// Squares `n`
+        fn square(n: i32) -> i32 { n * n }
+    };
+    let js = transpile_fn_to_js_with_options(&input, &CodegenOptions::default()).unwrap();
+    assert!(!js.contains("Squares"), "{js}");
+}
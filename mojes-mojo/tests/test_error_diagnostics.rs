@@ -41,6 +41,21 @@ fn nested_struct_gives_friendly_guidance() {
     assert!(err.contains("#[js_type]"), "suggests the fix: {err}");
 }
 
+#[test]
+fn invalid_assignment_target_is_an_error() {
+    let e: syn::Expr = parse_quote!(make_slot() = 5);
+    let err = try_rust_expr_to_js(&e).unwrap_err();
+    assert!(err.contains("invalid assignment target"), "explains the problem: {err}");
+
+    // Compound assignment goes through the same check
+    let b: syn::Block = parse_quote!({
+        next_id() += 1;
+    });
+    let err = try_rust_block_to_js(&b).unwrap_err();
+    assert!(err.contains("invalid assignment target"), "{err}");
+    assert!(err.contains("next_id ()"), "carries source context: {err}");
+}
+
 #[test]
 fn valid_code_still_transpiles_without_error() {
     let b: syn::Block = parse_quote!({
@@ -0,0 +1,153 @@
+// Tests for comments the transpiler writes into its output: they are real
+// JS comments attached to the nodes they describe, never identifiers that
+// only look like comments.
+use mojes_mojo::*;
+use syn::{parse_quote, Expr, ItemImpl};
+
+fn eval_js(code: &str) -> boa_engine::JsValue {
+    let mut context = boa_engine::Context::default();
+    context
+        .eval(boa_engine::Source::from_bytes(code))
+        .expect("JS execution failed")
+}
+
+#[test]
+fn test_methods_header_is_a_line_comment() {
+    let input: ItemImpl = parse_quote! {
+        impl Counter {
+            fn get(&self) -> i32 {
+                self.count
+            }
+        }
+    };
+    let js = generate_js_methods_for_impl(&input);
+    println!("JS: {}", &js);
+    assert!(js.starts_with("// Methods for Counter\nCounter.prototype.get ="), "{js}");
+    // No dangling `;` from an identifier statement
+    assert!(!js.contains("Methods for Counter;"), "{js}");
+}
+
+#[test]
+fn test_header_of_impl_without_methods_and_of_trait_impl() {
+    let input: ItemImpl = parse_quote! {
+        impl Marker {}
+    };
+    let js = generate_js_methods_for_impl(&input);
+    println!("JS: {}", &js);
+    assert!(js.contains("// Methods for Marker\n"), "{js}");
+    eval_js(&js);
+
+    let input: ItemImpl = parse_quote! {
+        impl Display for Point {
+            fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+                write!(f, "pt")
+            }
+        }
+    };
+    let js = generate_js_methods_for_impl(&input);
+    assert!(js.starts_with("// Methods for Point (impl Display)\n"), "{js}");
+}
+
+#[test]
+fn test_default_case_comment_leads_else_branch() {
+    let expr: Expr = parse_quote! {
+        match value {
+            1 => "one",
+            _ => "other",
+        }
+    };
+    let js = rust_expr_to_js(&expr);
+    println!("JS: {}", &js);
+    assert!(js.contains("// Default case\n"), "{js}");
+    assert!(!js.contains("Default case;"), "{js}");
+    let result = eval_js(&format!("const value = 5;\n{js}"));
+    assert_eq!(result.as_string().unwrap().to_std_string().unwrap(), "other");
+}
+
+#[test]
+fn test_verbatim_expression_is_commented_undefined() {
+    let expr = Expr::Verbatim(quote::quote! { some tokens });
+    let js = rust_expr_to_js(&expr);
+    println!("JS: {}", &js);
+    assert_eq!(js, "/* verbatim: some tokens */ undefined");
+    assert!(eval_js(&js).is_undefined());
+}
+
+#[test]
+fn test_mut_reference_is_the_plain_variable() {
+    let expr: Expr = parse_quote! { &mut x };
+    assert_eq!(rust_expr_to_js(&expr), "x");
+
+    let expr: Expr = parse_quote! { bump(&mut counter, &totals[0]) };
+    let js = rust_expr_to_js(&expr);
+    assert_eq!(js, "bump(counter, totals[0])");
+    let result = eval_js(&format!(
+        "function bump(c, t) {{ c.n += t; return c.n; }}\n\
+         const counter = {{ n: 1 }};\nconst totals = [41];\n{js}"
+    ));
+    assert_eq!(result.as_number().unwrap(), 42.0);
+}
+
+#[test]
+fn test_cast_to_user_type_passes_value_through() {
+    let mut state = TranspilerState::new();
+    let expr: Expr = parse_quote! { x as MyType };
+    let js = try_rust_expr_to_js_with_state(&expr, &mut state).unwrap();
+    assert_eq!(js, "x");
+    assert!(
+        state.get_warnings().iter().any(|w| w.contains("x as MyType")),
+        "{:?}",
+        state.get_warnings()
+    );
+
+    let expr: Expr = parse_quote! { (a + b) as Celsius };
+    let js = rust_expr_to_js(&expr);
+    let result = eval_js(&format!("const a = 40;\nconst b = 2;\n{js}"));
+    assert_eq!(result.as_number().unwrap(), 42.0);
+}
+
+#[test]
+fn test_comments_belong_to_the_state_they_were_built_with() {
+    let expr: Expr = parse_quote! {
+        match value {
+            1 => "one",
+            _ => "other",
+        }
+    };
+    // Emitting drops the comments, so a reused state starts afresh
+    let mut state = TranspilerState::new();
+    let first = try_rust_expr_to_js_with_state(&expr, &mut state).unwrap();
+    let second = try_rust_expr_to_js_with_state(&expr, &mut state).unwrap();
+    assert_eq!(first, second);
+    assert_eq!(second.matches("// Default case").count(), 1, "{second}");
+
+    // Items emitted without their state carry no stray comments
+    let input: ItemImpl = parse_quote! {
+        impl Counter {
+            fn get(&self) -> i32 {
+                self.count
+            }
+        }
+    };
+    let items = generate_js_methods_for_impl_with_state(&input).unwrap();
+    let js = ast_to_code(&items).unwrap();
+    assert!(!js.contains("//"), "{js}");
+
+    // Each thread's states number their comments independently
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            std::thread::spawn(|| {
+                let expr: Expr = parse_quote! {
+                    match value {
+                        1 => "one",
+                        _ => "other",
+                    }
+                };
+                rust_expr_to_js(&expr)
+            })
+        })
+        .collect();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), first);
+    }
+}
@@ -0,0 +1,81 @@
+// Tests for HashSet/BTreeSet as a JS Set: the constructor, and insert /
+// contains / remove / len / is_empty on receivers known to be sets.
+use mojes_mojo::*;
+use syn::{parse_quote, Block, Expr};
+
+fn eval_block(b: &Block) -> String {
+    let js = rust_block_to_js(b);
+    println!("JS: {}", &js);
+    let mut context = boa_engine::Context::default();
+    let value = context
+        .eval(boa_engine::Source::from_bytes(&format!("(function() {{ {js} }})()")))
+        .expect("JS execution failed");
+    value.to_string(&mut context).unwrap().to_std_string().unwrap()
+}
+
+#[test]
+fn test_hashset_new_is_js_set() {
+    let expr: Expr = parse_quote! { HashSet::new() };
+    assert_eq!(rust_expr_to_js(&expr), "new Set()");
+    let expr: Expr = parse_quote! { std::collections::BTreeSet::new() };
+    assert_eq!(rust_expr_to_js(&expr), "new Set()");
+}
+
+#[test]
+fn test_hashset_build_and_query() {
+    let b: Block = parse_quote!({
+        let mut seen = HashSet::new();
+        seen.insert("a");
+        seen.insert("b");
+        seen.insert("a");
+        let had_b = seen.remove(&"b");
+        format!("{} {} {} {} {}", seen.len(), seen.contains(&"a"), seen.contains(&"b"), had_b, seen.is_empty())
+    });
+    let js = rust_block_to_js(&b);
+    assert!(js.contains("seen.add(\"a\")"), "{js}");
+    assert!(js.contains("seen.size"), "{js}");
+    assert_eq!(eval_block(&b), "1 true false true false");
+}
+
+#[test]
+fn test_annotated_set_parameter() {
+    let b: Block = parse_quote!({
+        fn count_new(known: &HashSet<u32>, items: Vec<u32>) -> usize {
+            let mut n = 0;
+            for item in items {
+                if !known.contains(&item) {
+                    n += 1;
+                }
+            }
+            n
+        }
+        let mut known: BTreeSet<u32> = BTreeSet::new();
+        known.insert(1);
+        count_new(&known, vec![1, 2, 3])
+    });
+    assert_eq!(eval_block(&b), "2");
+}
+
+#[test]
+fn test_collections_from_and_default_run() {
+    let b: Block = parse_quote!({
+        let s = HashSet::from([1, 2, 2]);
+        let mut empty: HashSet<i32> = HashSet::default();
+        empty.insert(7);
+        let m = HashMap::from([("a", 1), ("b", 2)]);
+        let mut v = Vec::from([3, 4]);
+        v.push(5);
+        let name = String::from("ab");
+        format!(
+            "{} {} {} {} {} {} {}",
+            s.contains(&1),
+            s.len(),
+            empty.contains(&7),
+            m.len(),
+            m.contains_key("b"),
+            v.len(),
+            name.len()
+        )
+    });
+    assert_eq!(eval_block(&b), "true 2 true 2 true 3 2");
+}
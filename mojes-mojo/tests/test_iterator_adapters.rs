@@ -0,0 +1,189 @@
+// Tests for iterator adapters that have no JS counterpart: into_iter(),
+// copied() and cloned() hand the array through unchanged, sum()/product()
+// fold it, drain() splices the range out, retain()/dedup() filter the
+// array in place, flat_map() is Array.flatMap, and take_while()/
+// skip_while() slice around the leading run that matches, and step_by()
+// keeps every n-th element.
+use mojes_mojo::*;
+use syn::{parse_quote, Block, Expr};
+
+fn eval_block(b: &Block) -> String {
+    let js = rust_block_to_js(b);
+    println!("JS: {}", &js);
+    let mut context = boa_engine::Context::default();
+    let value = context
+        .eval(boa_engine::Source::from_bytes(&format!("(function() {{ {js} }})()")))
+        .expect("JS execution failed");
+    value.to_string(&mut context).unwrap().to_std_string().unwrap()
+}
+
+#[test]
+fn test_copied_and_cloned_are_identity() {
+    let expr: Expr = parse_quote! { v.iter().copied() };
+    assert_eq!(rust_expr_to_js(&expr), "v");
+
+    let expr: Expr = parse_quote! { names.iter().cloned().collect::<Vec<String>>() };
+    assert_eq!(rust_expr_to_js(&expr), "names");
+}
+
+#[test]
+fn test_copied_sum() {
+    let expr: Expr = parse_quote! { v.iter().copied().sum() };
+    let js = rust_expr_to_js(&expr);
+    println!("JS: {}", &js);
+    assert!(js.starts_with("v.reduce("), "{js}");
+
+    let b: Block = parse_quote!({
+        let v = vec![1, 2, 3, 4];
+        let total: i32 = v.iter().copied().sum();
+        let product: i32 = v.iter().cloned().product();
+        format!("{} {}", total, product)
+    });
+    assert_eq!(eval_block(&b), "10 24");
+}
+
+#[test]
+fn test_sum_of_empty_is_zero() {
+    let b: Block = parse_quote!({
+        let v: Vec<i32> = vec![];
+        v.iter().copied().sum::<i32>()
+    });
+    assert_eq!(eval_block(&b), "0");
+}
+
+#[test]
+fn test_into_iter_map() {
+    let expr: Expr = parse_quote! { v.into_iter().map(|x| x * 2) };
+    assert_eq!(rust_expr_to_js(&expr), "v.map(((x)=>x * 2))");
+}
+
+#[test]
+fn test_drain_ranges() {
+    let expr: Expr = parse_quote! { v.drain(..) };
+    assert_eq!(rust_expr_to_js(&expr), "v.splice(0)");
+    let expr: Expr = parse_quote! { v.drain(1..3) };
+    assert_eq!(rust_expr_to_js(&expr), "v.splice(1, 3 - 1)");
+    let expr: Expr = parse_quote! { v.drain(..=n) };
+    assert_eq!(rust_expr_to_js(&expr), "v.splice(0, n + 1)");
+
+    let b: Block = parse_quote!({
+        let mut v = vec![1, 2, 3, 4, 5];
+        let middle: Vec<i32> = v.drain(1..3).collect();
+        let rest: Vec<i32> = v.drain(..).collect();
+        format!("{} {} {}", middle.join(","), rest.join(","), v.len())
+    });
+    assert_eq!(eval_block(&b), "2,3 1,4,5 0");
+}
+
+#[test]
+fn test_drain_evaluates_start_once() {
+    let expr: Expr = parse_quote! { v.drain(next()..4) };
+    let js = rust_expr_to_js(&expr);
+    assert_eq!(js, "((_temp1, _temp2)=>_temp1.splice(_temp2, 4 - _temp2))(v, next())");
+
+    let b: Block = parse_quote!({
+        let mut v = vec![1, 2, 3, 4, 5];
+        let mut calls = 0;
+        let mut next = || {
+            calls += 1;
+            calls
+        };
+        let taken: Vec<i32> = v.drain(next()..=2).collect();
+        format!("{} {} {}", taken.join(","), v.join(","), calls)
+    });
+    assert_eq!(eval_block(&b), "2,3 1,4,5 1");
+}
+
+#[test]
+fn test_retain_filters_in_place() {
+    let b: Block = parse_quote!({
+        let mut v = vec![1, 2, 3, 4, 5, 6];
+        v.retain(|x| x % 2 == 0);
+        format!("{} {}", v.join(","), v.len())
+    });
+    assert_eq!(eval_block(&b), "2,4,6 3");
+
+    // Other collections' retain is their own
+    let b: Block = parse_quote!({
+        let mut seen: HashSet<i32> = HashSet::new();
+        seen.retain(|x| *x > 1);
+        let mut name: String = String::new();
+        name.retain(|c| c != ' ');
+    });
+    let js = rust_block_to_js(&b);
+    assert!(js.contains("seen.retain("), "{js}");
+    assert!(js.contains("name.retain("), "{js}");
+    assert!(!js.contains("splice"), "{js}");
+}
+
+#[test]
+fn test_dedup_removes_consecutive_duplicates() {
+    let expr: Expr = parse_quote! { self.items.dedup() };
+    let js = rust_expr_to_js(&expr);
+    println!("JS: {}", &js);
+    assert!(js.ends_with("(this.items)"), "receiver is evaluated once:\n{js}");
+
+    let b: Block = parse_quote!({
+        let mut v = vec![1, 1, 2, 3, 3, 3, 1];
+        v.dedup();
+        v.join(",")
+    });
+    assert_eq!(eval_block(&b), "1,2,3,1");
+}
+
+#[test]
+fn test_flat_map_becomes_flat_map() {
+    let expr: Expr = parse_quote! { v.iter().flat_map(|x| expand(x)) };
+    assert_eq!(rust_expr_to_js(&expr), "v.flatMap(((x)=>expand(x)))");
+}
+
+#[test]
+fn test_flat_map_composes_with_other_adapters() {
+    let b: Block = parse_quote!({
+        let v = vec![1, 2, 3];
+        let out: Vec<i32> = v
+            .iter()
+            .filter(|x| **x != 2)
+            .flat_map(|x| vec![*x, *x * 10])
+            .map(|x| x + 1)
+            .collect();
+        out.join(",")
+    });
+    assert_eq!(eval_block(&b), "2,11,4,31");
+}
+
+#[test]
+fn test_take_while_and_skip_while() {
+    let b: Block = parse_quote!({
+        let v = vec![1, 3, 5, 2, 8];
+        let head: Vec<i32> = v.iter().take_while(|x| *x < 5).collect();
+        let tail: Vec<i32> = v.iter().skip_while(|x| **x < 5).collect();
+        let all: Vec<i32> = v.iter().take_while(|x| **x < 100).collect();
+        format!("{} | {} | {}", head.join(","), tail.join(","), all.len())
+    });
+    assert_eq!(eval_block(&b), "1,3 | 5,2,8 | 5");
+}
+
+#[test]
+fn test_take_while_chains_into_map() {
+    let b: Block = parse_quote!({
+        let v = vec![2, 4, 7, 8];
+        let halves: Vec<i32> = v.iter().take_while(|x| *x % 2 == 0).map(|x| x / 2).collect();
+        halves.join(",")
+    });
+    assert_eq!(eval_block(&b), "1,2");
+}
+
+#[test]
+fn test_step_by_on_range() {
+    let expr: Expr = parse_quote! { (0..10).step_by(2) };
+    let js = rust_expr_to_js(&expr);
+    assert!(js.ends_with(".filter((_, i)=>i % 2 === 0)"), "{js}");
+
+    let b: Block = parse_quote!({
+        let evens: Vec<i32> = (0..10).step_by(2).collect();
+        let odds: Vec<i32> = (1..10).step_by(4).map(|x| x * 10).collect();
+        format!("{} | {}", evens.join(","), odds.join(","))
+    });
+    assert_eq!(eval_block(&b), "0,2,4,6,8 | 10,50,90");
+}
@@ -0,0 +1,146 @@
+// Tests for if/match/blocks used as the right-hand side of a `let`:
+// single-expression branches become a ternary, a block of declarations plus
+// a value is inlined, anything else keeps the value-returning IIFE.
+use mojes_mojo::*;
+use syn::{parse_quote, Block};
+
+fn eval_js(code: &str) -> boa_engine::JsValue {
+    let mut context = boa_engine::Context::default();
+    context
+        .eval(boa_engine::Source::from_bytes(code))
+        .expect("JS execution failed")
+}
+
+fn as_str(v: &boa_engine::JsValue) -> String {
+    let mut ctx = boa_engine::Context::default();
+    v.to_string(&mut ctx).unwrap().to_std_string().unwrap()
+}
+
+#[test]
+fn test_let_if_becomes_ternary() {
+    let block: Block = parse_quote!({
+        let x = if c { 1 } else { 2 };
+    });
+    let js = rust_block_to_js(&block);
+    println!("JS: {}", &js);
+    assert!(js.contains("const x = c ? 1 : 2;"), "expected a ternary:\n{js}");
+    assert!(!js.contains("call(this)"), "no IIFE expected:\n{js}");
+}
+
+#[test]
+fn test_let_else_if_chain_becomes_nested_ternary() {
+    let block: Block = parse_quote!({
+        let n = 7;
+        let size = if n < 5 { "small" } else if n < 10 { "medium" } else { "large" };
+        size
+    });
+    let js = rust_block_to_js(&block);
+    println!("JS: {}", &js);
+    assert!(!js.contains("call(this)"), "no IIFE expected:\n{js}");
+    let result = eval_js(&format!("(function() {{ {js} }})()"));
+    assert_eq!(as_str(&result), "medium");
+}
+
+#[test]
+fn test_let_match_becomes_ternary() {
+    let block: Block = parse_quote!({
+        let code = 2;
+        let name = match code {
+            1 => "one",
+            2 | 3 => "two or three",
+            _ => "many",
+        };
+        name
+    });
+    let js = rust_block_to_js(&block);
+    println!("JS: {}", &js);
+    assert!(js.contains("code === 1 ?"), "expected a ternary on the variable:\n{js}");
+    assert!(!js.contains("_match_value"), "no match IIFE expected:\n{js}");
+    let result = eval_js(&format!("(function() {{ {js} }})()"));
+    assert_eq!(as_str(&result), "two or three");
+}
+
+#[test]
+fn test_let_if_with_statements_keeps_returning_iife() {
+    let block: Block = parse_quote!({
+        let c = false;
+        let x = if c {
+            let t = 10;
+            t + 1
+        } else {
+            let t = 20;
+            t + 2
+        };
+        x
+    });
+    let js = rust_block_to_js(&block);
+    println!("JS: {}", &js);
+    assert!(js.contains("call(this)"), "multi-statement branches need an IIFE:\n{js}");
+    let result = eval_js(&format!("(function() {{ {js} }})()"));
+    assert_eq!(as_str(&result), "22");
+}
+
+#[test]
+fn test_let_match_with_bindings_keeps_iife() {
+    let block: Block = parse_quote!({
+        let opt = Some(4);
+        let doubled = match opt {
+            Some(v) => v * 2,
+            None => 0,
+        };
+        doubled
+    });
+    let js = rust_block_to_js(&block);
+    println!("JS: {}", &js);
+    let result = eval_js(&format!("(function() {{ {js} }})()"));
+    assert_eq!(as_str(&result), "8");
+}
+
+#[test]
+fn test_let_if_ternary_with_await() {
+    let block: Block = parse_quote!({
+        let result = if condition { fetch_a().await } else { fetch_b().await };
+    });
+    let js = rust_block_to_js(&block);
+    println!("JS: {}", &js);
+    assert!(
+        js.contains("const result = condition ? await fetch_a() : await fetch_b();"),
+        "each branch should be awaited in place:\n{js}"
+    );
+}
+
+#[test]
+fn test_let_block_is_inlined_without_iife() {
+    let block: Block = parse_quote!({
+        let x = {
+            let a = 1;
+            a + 1
+        };
+        x
+    });
+    let js = rust_block_to_js(&block);
+    println!("JS: {}", &js);
+    assert!(!js.contains("call(this)"), "no IIFE expected:\n{js}");
+    assert!(js.contains("const a = 1;\nconst x = a + 1;"), "{js}");
+}
+
+#[test]
+fn test_inlined_block_bindings_stay_private() {
+    // The block's `a` shadows the outer one only inside the block, and a
+    // later `let b` doesn't collide with the block's `b` in JS
+    let block: Block = parse_quote!({
+        let a = 1;
+        let x = {
+            let a = 20;
+            let b = 300;
+            a + b
+        };
+        let b = 4000;
+        format!("{} {} {}", a, x, b)
+    });
+    let js = rust_block_to_js(&block);
+    println!("JS: {}", &js);
+    assert!(!js.contains("call(this)"), "no IIFE expected:\n{js}");
+    let result = eval_js(&format!("(function() {{ {js} }})()"));
+    assert_eq!(as_str(&result), "1 320 4000");
+}
@@ -0,0 +1,58 @@
+// Tests for the pluggable macro table on TranspilerState: user handlers for
+// their own macros, overriding a built-in, and the recoverable error for
+// macros nobody handles.
+use mojes_mojo::*;
+use swc_ecma_ast as js;
+use syn::{parse_quote, Block, Expr};
+
+#[test]
+fn test_register_custom_macro_handler() {
+    // A tiny templating macro: tpl!("name") -> render("name")
+    let mut state = TranspilerState::new();
+    state.register_macro_handler("tpl", |state, mac| {
+        let name: syn::LitStr = mac.parse_body().map_err(|e| e.to_string())?;
+        Ok(state.mk_call_expr(
+            js::Expr::Ident(state.mk_ident("render")),
+            vec![state.mk_str_lit(&name.value())],
+        ))
+    });
+    let expr: Expr = parse_quote! { tpl!("header") };
+    let js = try_rust_expr_to_js_with_state(&expr, &mut state).unwrap();
+    println!("JS: {}", &js);
+    assert_eq!(js, "render(\"header\")");
+}
+
+#[test]
+fn test_custom_macro_in_statement_position() {
+    let mut state = TranspilerState::new();
+    state.register_macro_handler("trace_here", |state, _mac| {
+        Ok(state.mk_call_expr(js::Expr::Ident(state.mk_ident("traceHere")), vec![]))
+    });
+    let block: Block = parse_quote!({
+        trace_here!();
+        let x = 1;
+    });
+    let js = try_rust_block_to_js_with_state(&block, &mut state).unwrap();
+    println!("JS: {}", &js);
+    assert!(js.contains("traceHere();"), "custom statement macro missing:\n{js}");
+}
+
+#[test]
+fn test_override_builtin_macro() {
+    let mut state = TranspilerState::new();
+    assert!(state.macro_handler("dbg").is_some());
+    state.register_macro_handler("dbg", |state, _mac| Ok(state.mk_undefined()));
+    let expr: Expr = parse_quote! { dbg!(x) };
+    let js = try_rust_expr_to_js_with_state(&expr, &mut state).unwrap();
+    assert_eq!(js, "undefined");
+}
+
+#[test]
+fn test_unknown_macro_is_a_recoverable_error() {
+    let expr: Expr = parse_quote! { no_such_macro!(1, 2) };
+    let err = try_rust_expr_to_js(&expr).unwrap_err();
+    println!("error: {}", &err);
+    assert!(err.contains("Unsupported macro `no_such_macro!`"), "{err}");
+    // The message lists what is available
+    assert!(err.contains("format!") && err.contains("vec!"), "{err}");
+}
@@ -0,0 +1,53 @@
+// Tests for map_or/map_or_else: a ternary on the receiver, bound once through
+// an arrow IIFE, with the Result shape used when the receiver is known to be
+// a Result.
+use mojes_mojo::*;
+use syn::{parse_quote, Block, Expr};
+
+fn eval_block(b: &Block) -> boa_engine::JsValue {
+    let js = rust_block_to_js(b);
+    println!("JS: {}", &js);
+    let mut context = boa_engine::Context::default();
+    context
+        .eval(boa_engine::Source::from_bytes(&format!("(function() {{ {js} }})()")))
+        .expect("JS execution failed")
+}
+
+#[test]
+fn test_map_or_ternary_shape() {
+    let expr: Expr = parse_quote! { opt.map_or(0, |x| x * 2) };
+    let js = rust_expr_to_js(&expr);
+    println!("JS: {}", &js);
+    assert!(js.starts_with("((v, d, f)=>v != null ? f(v) : d)(opt, 0,"), "{js}");
+}
+
+#[test]
+fn test_map_or_else_calls_default() {
+    let expr: Expr = parse_quote! { next().map_or_else(|| -1, |x| x + 1) };
+    let js = rust_expr_to_js(&expr);
+    println!("JS: {}", &js);
+    assert!(js.contains("v != null ? f(v) : d()"), "{js}");
+    // The receiver is evaluated once, as an argument
+    assert_eq!(js.matches("next()").count(), 1, "{js}");
+}
+
+#[test]
+fn test_map_or_on_option_values() {
+    let b: Block = parse_quote!({
+        let some: Option<i32> = Some(4);
+        let none: Option<i32> = None;
+        some.map_or(0, |x| x * 10) + none.map_or(1, |x| x * 10)
+            + none.map_or_else(|| 100, |x| x)
+    });
+    assert_eq!(eval_block(&b).as_number().unwrap(), 141.0);
+}
+
+#[test]
+fn test_map_or_on_typed_result() {
+    let b: Block = parse_quote!({
+        let good: Result<i32, String> = Ok(3);
+        let bad: Result<i32, String> = Err("boom".to_string());
+        good.map_or(0, |x| x + 1) + bad.map_or_else(|e| e.len(), |x| x)
+    });
+    assert_eq!(eval_block(&b).as_number().unwrap(), 8.0);
+}
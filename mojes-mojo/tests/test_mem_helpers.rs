@@ -0,0 +1,93 @@
+// Tests for std::mem::swap / replace / take, which rewrite the place behind
+// the `&mut` argument instead of calling a nonexistent `mem` object.
+use mojes_mojo::*;
+use syn::{parse_quote, Block};
+
+fn eval_block(b: &Block) -> String {
+    let js = rust_block_to_js(b);
+    println!("JS: {}", &js);
+    assert!(!js.contains("mem."), "mem:: call leaked into JS:\n{js}");
+    let mut context = boa_engine::Context::default();
+    let value = context
+        .eval(boa_engine::Source::from_bytes(&format!("(function() {{ {js} }})()")))
+        .expect("JS execution failed");
+    value.to_string(&mut context).unwrap().to_std_string().unwrap()
+}
+
+#[test]
+fn test_mem_swap() {
+    let b: Block = parse_quote!({
+        let mut a = 1;
+        let mut b = 2;
+        std::mem::swap(&mut a, &mut b);
+        format!("{} {}", a, b)
+    });
+    assert_eq!(eval_block(&b), "2 1");
+}
+
+#[test]
+fn test_mem_swap_fields() {
+    let b: Block = parse_quote!({
+        let mut pair = ("l", "r");
+        mem::swap(&mut pair.0, &mut pair.1);
+        format!("{}{}", pair.0, pair.1)
+    });
+    assert_eq!(eval_block(&b), "rl");
+}
+
+#[test]
+fn test_mem_replace_returns_old_value() {
+    let b: Block = parse_quote!({
+        let mut state = 3;
+        let old = mem::replace(&mut state, 7);
+        format!("{} {}", old, state)
+    });
+    assert_eq!(eval_block(&b), "3 7");
+}
+
+#[test]
+fn test_mem_take_uses_declared_default() {
+    let b: Block = parse_quote!({
+        let mut name: String = "abc".to_string();
+        let taken = std::mem::take(&mut name);
+        format!("[{}] [{}]", taken, name)
+    });
+    assert_eq!(eval_block(&b), "[abc] []");
+}
+
+#[test]
+fn test_mem_take_unknown_type_defaults_at_runtime() {
+    let b: Block = parse_quote!({
+        let mut items = vec![1, 2, 3];
+        let taken = mem::take(&mut items);
+        items.push(9);
+        format!("{} {}", taken.len(), items.len())
+    });
+    assert_eq!(eval_block(&b), "3 1");
+}
+
+#[test]
+fn test_mem_take_leaves_none_in_option() {
+    let b: Block = parse_quote!({
+        let mut slot: Option<i32> = Some(5);
+        let taken = mem::take(&mut slot);
+        format!("{} {}", taken.unwrap(), slot.is_none())
+    });
+    assert_eq!(eval_block(&b), "5 true");
+}
+
+#[test]
+fn test_mem_take_evaluates_the_place_once() {
+    let b: Block = parse_quote!({
+        let mut v = vec![10, 20, 30];
+        let mut calls = 0;
+        let mut next = || {
+            calls += 1;
+            calls
+        };
+        let taken = mem::take(&mut v[next()]);
+        let old = mem::replace(&mut v[next()], 7);
+        format!("{} {} {} {}", taken, old, v.join(","), calls)
+    });
+    assert_eq!(eval_block(&b), "20 30 10,0,7 2");
+}
@@ -0,0 +1,65 @@
+// Tests for the pluggable method-mapping table on TranspilerState: custom
+// mappings registered from outside the crate, overriding built-ins, and the
+// built-in table still handling the well-known methods.
+use mojes_mojo::*;
+use syn::{parse_quote, Expr};
+
+#[test]
+fn test_register_custom_method_mapping() {
+    let mut state = TranspilerState::new();
+    state.register_method_mapping("set_text", |state, _call, receiver, args| {
+        Ok(state.mk_call_expr(state.mk_member_expr(receiver, "shimSetText"), args))
+    });
+
+    let expr: Expr = parse_quote! {
+        label.set_text("hello")
+    };
+    let js = try_rust_expr_to_js_with_state(&expr, &mut state).unwrap();
+    println!("JS: {}", &js);
+    assert_eq!(js, "label.shimSetText(\"hello\")");
+}
+
+#[test]
+fn test_custom_mapping_sees_rust_call() {
+    // The original call is available, e.g. to branch on the argument count
+    let mut state = TranspilerState::new();
+    state.register_method_mapping("emit", |state, call, receiver, mut args| {
+        if call.args.is_empty() {
+            args.push(state.mk_str_lit("default"));
+        }
+        Ok(state.mk_call_expr(state.mk_member_expr(receiver, "dispatch"), args))
+    });
+
+    let expr: Expr = parse_quote! { bus.emit() };
+    let js = try_rust_expr_to_js_with_state(&expr, &mut state).unwrap();
+    println!("JS: {}", &js);
+    assert_eq!(js, "bus.dispatch(\"default\")");
+}
+
+#[test]
+fn test_override_builtin_mapping() {
+    let mut state = TranspilerState::new();
+    assert!(state.method_mapping("len").is_some());
+    state.register_method_mapping("len", |state, _call, receiver, _args| {
+        Ok(state.mk_member_expr(receiver, "size"))
+    });
+
+    let expr: Expr = parse_quote! { items.len() };
+    let js = try_rust_expr_to_js_with_state(&expr, &mut state).unwrap();
+    println!("JS: {}", &js);
+    assert_eq!(js, "items.size");
+}
+
+#[test]
+fn test_builtin_mappings_still_apply() {
+    let expr: Expr = parse_quote! { name.to_uppercase() };
+    assert_eq!(rust_expr_to_js(&expr), "name.toUpperCase()");
+
+    let expr: Expr = parse_quote! { text.starts_with("x") };
+    assert_eq!(rust_expr_to_js(&expr), "text.startsWith(\"x\")");
+
+    // Unmapped methods stay plain method calls
+    let expr: Expr = parse_quote! { widget.refresh(1) };
+    assert_eq!(rust_expr_to_js(&expr), "widget.refresh(1)");
+    assert!(TranspilerState::new().method_mapping("refresh").is_none());
+}
@@ -0,0 +1,66 @@
+// Tests for ES module output: with `CodegenOptions::module()` the
+// declarations generated for `pub` items are exported, private items and the
+// default (script) output stay global.
+use mojes_mojo::*;
+use syn::{parse_quote, ItemEnum, ItemFn, ItemStruct};
+
+#[test]
+fn test_pub_struct_is_exported_in_module_mode() {
+    let input: ItemStruct = parse_quote! {
+        pub struct Point { x: i32, y: i32 }
+    };
+    let js = transpile_struct_to_js_with_options(&input, &CodegenOptions::module()).unwrap();
+    println!("JS: {}", &js);
+    assert!(js.contains("export class Point"), "{js}");
+}
+
+#[test]
+fn test_script_mode_and_private_items_have_no_export() {
+    let public: ItemStruct = parse_quote! {
+        pub struct Point { x: i32 }
+    };
+    let js = transpile_struct_to_js_with_options(&public, &CodegenOptions::default()).unwrap();
+    assert!(!js.contains("export"), "{js}");
+
+    let private: ItemStruct = parse_quote! {
+        struct Hidden { x: i32 }
+    };
+    let js = transpile_struct_to_js_with_options(&private, &CodegenOptions::module()).unwrap();
+    assert!(js.contains("class Hidden") && !js.contains("export"), "{js}");
+}
+
+#[test]
+fn test_pub_enum_and_fn_are_exported() {
+    let input: ItemEnum = parse_quote! {
+        pub enum Color { Red, Green }
+    };
+    let js = transpile_enum_to_js_with_options(&input, &CodegenOptions::module()).unwrap();
+    println!("JS: {}", &js);
+    assert!(js.contains("export const Color"), "{js}");
+
+    let input: ItemFn = parse_quote! {
+        pub fn add(a: i32, b: i32) -> i32 { a + b }
+    };
+    let js = transpile_fn_to_js_with_options(&input, &CodegenOptions::module()).unwrap();
+    println!("JS: {}", &js);
+    assert!(js.contains("export function add(a, b)"), "{js}");
+}
+
+#[test]
+fn test_free_function_transpiles_to_top_level_function() {
+    let input: ItemFn = parse_quote! {
+        fn clamp_percent(value: i32) -> i32 {
+            if value > 100 { 100 } else if value < 0 { 0 } else { value }
+        }
+    };
+    let js = transpile_fn_to_js(&input).unwrap();
+    println!("JS: {}", &js);
+    assert!(js.starts_with("function clamp_percent(value) {"), "{js}");
+    assert!(!js.contains("export"), "{js}");
+
+    let mut context = boa_engine::Context::default();
+    let result = context
+        .eval(boa_engine::Source::from_bytes(&format!("{js}\nclamp_percent(140) + clamp_percent(-3) + clamp_percent(7)")))
+        .expect("JS execution failed");
+    assert_eq!(result.as_number().unwrap(), 107.0);
+}
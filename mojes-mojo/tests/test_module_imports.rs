@@ -0,0 +1,74 @@
+// Tests for translating `use` declarations into ES imports in module mode:
+// crate-relative paths become relative module specifiers, std items are
+// dropped, and the path resolution can be replaced.
+use mojes_mojo::*;
+use syn::{parse_quote, ItemUse};
+
+fn imports(item: ItemUse, options: &CodegenOptions) -> String {
+    let js = transpile_use_to_js_with_options(&item, options).unwrap();
+    println!("JS: {}", &js);
+    js
+}
+
+#[test]
+fn test_use_crate_item_becomes_import() {
+    let js = imports(parse_quote! { use crate::foo::Bar; }, &CodegenOptions::module());
+    assert_eq!(js.trim(), "import { Bar } from \"./foo.js\";");
+}
+
+#[test]
+fn test_use_group_and_rename() {
+    let js = imports(
+        parse_quote! { use crate::shapes::{Circle, Square as Box, util::area}; },
+        &CodegenOptions::module(),
+    );
+    assert!(js.contains("import { Circle, Square as Box } from \"./shapes.js\";"), "{js}");
+    assert!(js.contains("import { area } from \"./shapes/util.js\";"), "{js}");
+}
+
+#[test]
+fn test_use_module_and_super() {
+    let js = imports(parse_quote! { use crate::helpers; }, &CodegenOptions::module());
+    assert_eq!(js.trim(), "import * as helpers from \"./helpers.js\";");
+
+    let js = imports(parse_quote! { use super::model::User; }, &CodegenOptions::module());
+    assert_eq!(js.trim(), "import { User } from \"../model.js\";");
+}
+
+#[test]
+fn test_std_imports_and_script_mode_produce_nothing() {
+    let js = imports(
+        parse_quote! { use std::collections::{HashMap, HashSet}; },
+        &CodegenOptions::module(),
+    );
+    assert!(js.trim().is_empty(), "{js}");
+
+    let js = imports(parse_quote! { use crate::foo::Bar; }, &CodegenOptions::default());
+    assert!(js.is_empty(), "{js}");
+}
+
+#[test]
+fn test_custom_import_resolver() {
+    let options = CodegenOptions {
+        resolve_import: Some(|path| Some(format!("/static/{}.mjs", path[1..].join("-")))),
+        ..CodegenOptions::module()
+    };
+    let js = imports(parse_quote! { use crate::ui::widgets::Button; }, &options);
+    assert_eq!(js.trim(), "import { Button } from \"/static/ui-widgets.mjs\";");
+}
+
+#[test]
+fn test_std_globs_are_skipped_and_user_globs_rejected() {
+    for item in [
+        parse_quote! { use std::collections::*; },
+        parse_quote! { use std::io::prelude::*; },
+        parse_quote! { use core::{fmt::*, cmp::Ordering}; },
+    ] {
+        let js = imports(item, &CodegenOptions::module());
+        assert!(js.trim().is_empty(), "{js}");
+    }
+
+    let item: ItemUse = parse_quote! { use crate::shapes::*; };
+    let err = transpile_use_to_js_with_options(&item, &CodegenOptions::module()).unwrap_err();
+    assert!(err.contains("use crate::shapes::*"), "{err}");
+}
@@ -1,7 +1,9 @@
 // Tests for unwrap_or, unwrap_or_else, unwrap_or_default transpilation.
-// These now emit a Result-aware dispatcher instead of a bare `??`:
+// These emit a Result-aware dispatcher instead of a bare `??`:
 // null/undefined (None) and {error: ..} (Err) take the default,
-// {ok: v} unwraps to v, and any plain value passes through.
+// {ok: v} unwraps to v, and any plain value passes through. The exception is
+// unwrap_or_default on a variable declared as Option<T>, which becomes
+// `(x ?? <T default>)`.
 use mojes_mojo::*;
 use syn::{parse_quote, Expr, Block};
 
@@ -107,3 +109,54 @@ fn test_unwrap_or_default_execution_with_null() {
     // x is None (null), ?? null should return null
     assert!(result.is_null(), "Expected null result");
 }
+
+#[test]
+fn test_unwrap_or_default_string_option() {
+    // A declared Option<String> gets the String default instead of null
+    let block: Block = parse_quote! {
+        {
+            let name: Option<String> = None;
+            name.unwrap_or_default()
+        }
+    };
+    let js = rust_block_to_js(&block);
+    println!("JS unwrap_or_default String option: {}", &js);
+    assert!(js.contains("name ?? \"\""), "Expected `name ?? \"\"`, got: {}", &js);
+    let code = format!("(function() {{ {} }})()", &js);
+    let result = eval_js(&code).unwrap();
+    assert_eq!(result.as_string().unwrap().to_std_string().unwrap(), "");
+}
+
+#[test]
+fn test_unwrap_or_default_typed_parameters() {
+    // Parameter types are known too; Results keep the shape-aware unwrap
+    let block: Block = parse_quote! {
+        {
+            fn total(items: Option<Vec<i32>>, bonus: Result<i32, String>) -> usize {
+                items.unwrap_or_default().len() + bonus.unwrap_or_default()
+            }
+            total(None, Err("no bonus".to_string()))
+        }
+    };
+    let js = rust_block_to_js(&block);
+    println!("JS unwrap_or_default typed params: {}", &js);
+    assert!(js.contains("(items ?? [])"), "Expected `(items ?? [])`, got: {}", &js);
+    let code = format!("(function() {{ {} }})()", &js);
+    let result = eval_js(&code).unwrap();
+    assert_eq!(result.as_number().unwrap(), 0.0);
+}
+
+#[test]
+fn test_unwrap_or_default_unknown_type_warns() {
+    let mut state = TranspilerState::new();
+    let expr: Expr = parse_quote! {
+        x.unwrap_or_default()
+    };
+    let js = try_rust_expr_to_js_with_state(&expr, &mut state).unwrap();
+    println!("JS unwrap_or_default unknown: {}", &js);
+    assert!(
+        state.get_warnings().iter().any(|w| w.contains("x.unwrap_or_default()")),
+        "Expected a warning about the unknown type, got: {:?}",
+        state.get_warnings()
+    );
+}
@@ -0,0 +1,258 @@
+// Tests for numeric methods mapped onto Math: the receiver becomes the first
+// argument, literal receivers (with or without a type suffix) included;
+// cmp::min/max, clamp and cmp (as a -1/0/1 comparator) likewise.
+use mojes_mojo::*;
+use syn::{parse_quote, Block, Expr};
+
+fn eval_block(b: &Block) -> boa_engine::JsValue {
+    let js = rust_block_to_js(b);
+    println!("JS: {}", &js);
+    let mut context = boa_engine::Context::default();
+    context
+        .eval(boa_engine::Source::from_bytes(&format!("(function() {{ {js} }})()")))
+        .expect("JS execution failed")
+}
+
+#[test]
+fn test_methods_on_suffixed_integer_literals() {
+    let expr: Expr = parse_quote! { 10u32.max(20) };
+    assert_eq!(rust_expr_to_js(&expr), "Math.max(10, 20)");
+
+    let expr: Expr = parse_quote! { 5i32.pow(2) };
+    assert_eq!(rust_expr_to_js(&expr), "Math.pow(5, 2)");
+
+    let expr: Expr = parse_quote! { 5.min(3) };
+    assert_eq!(rust_expr_to_js(&expr), "Math.min(5, 3)");
+}
+
+#[test]
+fn test_methods_on_float_literals() {
+    let expr: Expr = parse_quote! { 2.0.sqrt() };
+    assert_eq!(rust_expr_to_js(&expr), "Math.sqrt(2)");
+
+    let expr: Expr = parse_quote! { 2.5f64.floor() };
+    assert_eq!(rust_expr_to_js(&expr), "Math.floor(2.5)");
+}
+
+#[test]
+fn test_numeric_methods_on_expressions() {
+    let expr: Expr = parse_quote! { (a - b).abs() };
+    assert_eq!(rust_expr_to_js(&expr), "Math.abs((a - b))");
+
+    let b: Block = parse_quote!({
+        let x = -3.7_f64;
+        x.abs().round() + 2f64.powi(3) + x.signum() + 9.0.ln().min(0.0)
+    });
+    assert_eq!(eval_block(&b).as_number().unwrap(), 4.0 + 8.0 - 1.0);
+}
+
+#[test]
+fn test_iterator_min_max_keep_method_call() {
+    let expr: Expr = parse_quote! { scores.iter().max() };
+    let js = rust_expr_to_js(&expr);
+    println!("JS: {}", &js);
+    assert!(js.ends_with(".max()"), "zero-argument max is Iterator::max:\n{js}");
+    assert!(!js.contains("Math"), "{js}");
+}
+
+#[test]
+fn test_cmp_min_max_become_math() {
+    let expr: Expr = parse_quote! { cmp::min(3, 7) };
+    assert_eq!(rust_expr_to_js(&expr), "Math.min(3, 7)");
+
+    let expr: Expr = parse_quote! { std::cmp::max(a, b) };
+    assert_eq!(rust_expr_to_js(&expr), "Math.max(a, b)");
+}
+
+#[test]
+fn test_clamp_bounds_both_ends() {
+    let expr: Expr = parse_quote! { x.clamp(0, 10) };
+    assert_eq!(rust_expr_to_js(&expr), "Math.min(Math.max(x, 0), 10)");
+
+    let b: Block = parse_quote!({
+        let values = vec![-4, 5, 42];
+        let clamped: Vec<i32> = values.iter().map(|v| v.clamp(0, 10)).collect();
+        clamped.join(",")
+    });
+    let result = eval_block(&b);
+    let mut context = boa_engine::Context::default();
+    assert_eq!(result.to_string(&mut context).unwrap().to_std_string().unwrap(), "0,5,10");
+}
+
+#[test]
+fn test_cmp_is_a_sort_comparator() {
+    let b: Block = parse_quote!({
+        let mut words = vec!["pear", "apple", "fig"];
+        words.sort(|a, b| a.cmp(b));
+        format!("{}|{}|{}", words.join(","), 2.cmp(&2), 3.cmp(&1))
+    });
+    let result = eval_block(&b);
+    let mut context = boa_engine::Context::default();
+    assert_eq!(
+        result.to_string(&mut context).unwrap().to_std_string().unwrap(),
+        "apple,fig,pear|0|1"
+    );
+}
+
+#[test]
+fn test_to_string_on_number_literals_is_parenthesized() {
+    let expr: Expr = parse_quote! { 5.to_string() };
+    assert_eq!(rust_expr_to_js(&expr), "(5).toString()");
+
+    let expr: Expr = parse_quote! { 2.5f32.to_string() };
+    assert_eq!(rust_expr_to_js(&expr), "(2.5).toString()");
+
+    let b: Block = parse_quote!({
+        format!("{}|{}", 42u32.to_string() + "!", 7.to_string().len())
+    });
+    let result = eval_block(&b);
+    let mut context = boa_engine::Context::default();
+    assert_eq!(result.to_string(&mut context).unwrap().to_std_string().unwrap(), "42!|1");
+}
+
+#[test]
+fn test_math_namespace_calls_and_constants() {
+    // The mojes_dom_api::Math binding: associated items become Math members
+    let expr: Expr = parse_quote! { Math::random() };
+    assert_eq!(rust_expr_to_js(&expr), "Math.random()");
+
+    let expr: Expr = parse_quote! { Math::floor(x * 10.0) };
+    assert_eq!(rust_expr_to_js(&expr), "Math.floor(x * 10)");
+
+    let expr: Expr = parse_quote! { Math::PI * r * r };
+    assert_eq!(rust_expr_to_js(&expr), "Math.PI * r * r");
+
+    let b: Block = parse_quote!({
+        let r = Math::random();
+        Math::floor(r * 6.0) + Math::max(Math::E, 0.0).round()
+    });
+    let n = eval_block(&b).as_number().unwrap();
+    assert!((3.0..9.0).contains(&n), "{n}");
+}
+
+#[test]
+fn test_cmp_and_clamp_on_user_types() {
+    // Another arity is a user method
+    let expr: Expr = parse_quote! { range.clamp(x) };
+    assert_eq!(rust_expr_to_js(&expr), "range.clamp(x)");
+    let expr: Expr = parse_quote! { a.cmp(&b, strict) };
+    assert_eq!(rust_expr_to_js(&expr), "a.cmp(b, strict)");
+
+    // A receiver of a declared user type keeps its own Ord
+    let b: Block = parse_quote!({
+        fn order(a: Version, b: Version) -> i32 {
+            a.cmp(&b)
+        }
+    });
+    let js = rust_block_to_js(&b);
+    assert!(js.contains("return a.cmp(b);"), "{js}");
+
+    // An untyped receiver uses its cmp method when it has one
+    let b: Block = parse_quote!({
+        let mut versions = vec![Version::new(2), Version::new(1)];
+        versions.sort_by(|a, b| a.cmp(b));
+        let mut n = vec![3, 1, 2];
+        n.sort_by(|a, b| a.cmp(b));
+        format!("{} {}", versions[0].major, n.join(","))
+    });
+    let js = rust_block_to_js(&b);
+    println!("JS: {}", &js);
+    let code = format!(
+        "class Version {{ constructor(m) {{ this.major = m; }} cmp(o) {{ return this.major - o.major; }} }}\n\
+         (function() {{ {js} }})()"
+    );
+    let mut context = boa_engine::Context::default();
+    let result = context.eval(boa_engine::Source::from_bytes(&code)).expect("JS execution failed");
+    assert_eq!(result.to_string(&mut context).unwrap().to_std_string().unwrap(), "1 1,2,3");
+}
+
+#[test]
+fn test_math_methods_leave_non_number_receivers_alone() {
+    // A struct's own max/round run as its methods
+    let b: Block = parse_quote!({
+        fn newest(a: Version, b: Version) -> Version {
+            a.max(b)
+        }
+        let v: Version = newest(Version::new(1), Version::new(3));
+        v.round()
+    });
+    let js = rust_block_to_js(&b);
+    println!("JS: {}", &js);
+    assert!(js.contains("return a.max(b);"), "{js}");
+    assert!(js.contains("v.round()"), "{js}");
+    let code = format!(
+        "class Version {{ constructor(m) {{ this.major = m; }} \
+         static new(m) {{ return new Version(m); }} \
+         max(o) {{ return this.major >= o.major ? this : o; }} round() {{ return this.major * 10; }} }}\n\
+         (function() {{ {js} }})()"
+    );
+    let mut context = boa_engine::Context::default();
+    let result = context.eval(boa_engine::Source::from_bytes(&code)).expect("JS execution failed");
+    assert_eq!(result.as_number(), Some(30.0));
+
+    // A String is not a number either
+    let b: Block = parse_quote!({
+        fn later(s: String, t: String) -> String {
+            s.max(t)
+        }
+    });
+    let js = rust_block_to_js(&b);
+    assert!(js.contains("return s.max(t);"), "{js}");
+    assert!(!js.contains("Math."), "{js}");
+
+    // Numbers still go to Math
+    let b: Block = parse_quote!({
+        let x: f64 = 2.5;
+        x.max(4.0)
+    });
+    assert_eq!(eval_block(&b).as_number(), Some(4.0));
+}
+
+#[test]
+fn test_ordering_values_match_cmp() {
+    let expr: Expr = parse_quote! { Ordering::Less };
+    assert_eq!(rust_expr_to_js(&expr), "-1");
+
+    let b: Block = parse_quote!({
+        fn describe(a: i32, b: i32) -> String {
+            match a.cmp(&b) {
+                Ordering::Less => "less".to_string(),
+                Ordering::Equal => "equal".to_string(),
+                Ordering::Greater => "greater".to_string(),
+            }
+        }
+        let mut words = vec!["bb", "a", "ccc", "dd"];
+        words.sort_by(|x, y| {
+            if x.len() == y.len() {
+                std::cmp::Ordering::Equal
+            } else if x.len() > y.len() {
+                Ordering::Greater
+            } else {
+                Ordering::Less
+            }
+        });
+        let mut n = vec![1, 3, 2];
+        n.sort_by(|a, b| b.cmp(a).reverse());
+        let mut pairs = vec![(2, 1), (1, 2), (1, 1)];
+        pairs.sort_by(|p, q| p.0.cmp(&q.0).then_with(|| p.1.cmp(&q.1)));
+        let flipped = 1.cmp(&1).then(Ordering::Greater);
+        format!(
+            "{} {} {} {} {} {} {}",
+            describe(1, 2),
+            describe(2, 2),
+            describe(3, 2),
+            words.join(","),
+            n.join(","),
+            pairs.iter().map(|p| format!("{}{}", p.0, p.1)).collect::<Vec<_>>().join(","),
+            flipped
+        )
+    });
+    assert_eq!(
+        eval_block(&b).to_string(&mut boa_engine::Context::default()).unwrap().to_std_string().unwrap(),
+        "less equal greater a,bb,dd,ccc 1,2,3 11,12,21 1"
+    );
+
+    // Vec::reverse is still the array method
+    let expr: Expr = parse_quote! { v.reverse() };
+    assert_eq!(rust_expr_to_js(&expr), "v.reverse()");
+}
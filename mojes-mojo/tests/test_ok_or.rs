@@ -0,0 +1,57 @@
+// Tests for ok_or/ok_or_else: an Option becomes the crate's Result shape,
+// {ok: v} for a value and {error: e} for None, so the usual Result consumers
+// (match, ?, unwrap, is_err) work on the converted value.
+use mojes_mojo::*;
+use syn::{parse_quote, Block, Expr};
+
+fn eval_block(b: &Block) -> boa_engine::JsValue {
+    let js = rust_block_to_js(b);
+    println!("JS: {}", &js);
+    let mut context = boa_engine::Context::default();
+    context
+        .eval(boa_engine::Source::from_bytes(&format!("(function() {{ {js} }})()")))
+        .expect("JS execution failed")
+}
+
+fn as_str(v: &boa_engine::JsValue) -> String {
+    let mut ctx = boa_engine::Context::default();
+    v.to_string(&mut ctx).unwrap().to_std_string().unwrap()
+}
+
+#[test]
+fn test_ok_or_shape() {
+    let expr: Expr = parse_quote! { opt.ok_or("missing") };
+    let js = rust_expr_to_js(&expr);
+    println!("JS: {}", &js);
+    assert!(js.contains("v != null ? {"), "{js}");
+    assert!(js.contains("ok: v"), "{js}");
+    assert!(js.contains("error: e"), "{js}");
+}
+
+#[test]
+fn test_ok_or_matches_as_result() {
+    let b: Block = parse_quote!({
+        let present: Option<i32> = Some(7);
+        let absent: Option<i32> = None;
+        let a = match present.ok_or("none") {
+            Ok(v) => v,
+            Err(_) => 0,
+        };
+        let b = match absent.ok_or("none") {
+            Ok(_) => "unexpected".to_string(),
+            Err(e) => e.to_string(),
+        };
+        format!("{}-{}", a, b)
+    });
+    assert_eq!(as_str(&eval_block(&b)), "7-none");
+}
+
+#[test]
+fn test_ok_or_else_calls_closure_for_none() {
+    let b: Block = parse_quote!({
+        let absent: Option<i32> = None;
+        let r = absent.ok_or_else(|| "computed".to_string());
+        r.is_err() && Some(1).ok_or_else(|| "x".to_string()).unwrap() == 1
+    });
+    assert!(eval_block(&b).as_boolean().unwrap());
+}
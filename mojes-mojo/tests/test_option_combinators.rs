@@ -0,0 +1,77 @@
+// Tests for the Option combinators or/and/xor over null-or-value options,
+// or/and on `{ok}`/`{error}` Results, and other receivers left alone.
+use mojes_mojo::*;
+use syn::{parse_quote, Block, Expr};
+
+fn eval_block(b: &Block) -> String {
+    let js = rust_block_to_js(b);
+    println!("JS: {}", &js);
+    let mut context = boa_engine::Context::default();
+    let value = context
+        .eval(boa_engine::Source::from_bytes(&format!("(function() {{ {js} }})()")))
+        .expect("JS execution failed");
+    value.to_string(&mut context).unwrap().to_std_string().unwrap()
+}
+
+#[test]
+fn test_or_is_nullish_coalescing() {
+    let expr: Expr = parse_quote! { Some(1).or(fallback) };
+    assert_eq!(rust_expr_to_js(&expr), "(1 ?? fallback)");
+
+    let b: Block = parse_quote!({
+        let missing: Option<i32> = None;
+        let present: Option<i32> = Some(0);
+        format!("{}/{}", missing.or(Some(7)).unwrap(), present.or(Some(7)).unwrap())
+    });
+    // A present 0 is kept: only null/undefined fall through
+    assert_eq!(eval_block(&b), "7/0");
+}
+
+#[test]
+fn test_and_keeps_other_only_when_some() {
+    let b: Block = parse_quote!({
+        let missing: Option<i32> = None;
+        format!("{}/{}", Some(1).and(Some("x")).unwrap(), missing.and(Some("x")).is_none())
+    });
+    assert_eq!(eval_block(&b), "x/true");
+}
+
+#[test]
+fn test_xor_needs_exactly_one_some() {
+    let b: Block = parse_quote!({
+        let none: Option<i32> = None;
+        format!(
+            "{}{}{}{}",
+            Some(1).xor(none).unwrap(),
+            none.xor(Some(2)).unwrap(),
+            Some(1).xor(Some(2)).is_none(),
+            none.xor(none).is_none()
+        )
+    });
+    assert_eq!(eval_block(&b), "12truetrue");
+}
+
+#[test]
+fn test_result_or_and_pick_by_error() {
+    let b: Block = parse_quote!({
+        let r: Result<i32, String> = Err("bad".to_string());
+        let good: Result<i32, String> = Ok(2);
+        format!(
+            "{} {} {}",
+            r.or(good).unwrap(),
+            good.and(Ok(5)).unwrap(),
+            r.and(good).is_err()
+        )
+    });
+    assert_eq!(eval_block(&b), "2 5 true");
+}
+
+#[test]
+fn test_other_receivers_keep_their_method() {
+    // An untyped receiver, or another arity, is a user method
+    let expr: Expr = parse_quote! { q.and(a, b) };
+    assert_eq!(rust_expr_to_js(&expr), "q.and(a, b)");
+
+    let expr: Expr = parse_quote! { filter.or(other) };
+    assert_eq!(rust_expr_to_js(&expr), "filter.or(other)");
+}
@@ -0,0 +1,46 @@
+// Tests for Option::filter, told apart from iterator filter by the receiver:
+// `Some(..)` or an Option-typed binding keeps or drops the single value.
+use mojes_mojo::*;
+use syn::{parse_quote, Block, Expr};
+
+fn eval_block(b: &Block) -> boa_engine::JsValue {
+    let js = rust_block_to_js(b);
+    println!("JS: {}", &js);
+    let mut context = boa_engine::Context::default();
+    context
+        .eval(boa_engine::Source::from_bytes(&format!("(function() {{ {js} }})()")))
+        .expect("JS execution failed")
+}
+
+#[test]
+fn test_some_filter_keeps_matching_value() {
+    let expr: Expr = parse_quote! { Some(5).filter(|x| *x > 3) };
+    let js = rust_expr_to_js(&expr);
+    println!("JS: {}", &js);
+    assert!(js.starts_with("((v, p)=>v != null && p(v) ? v : null)(5,"), "{js}");
+
+    let b: Block = parse_quote!({
+        let kept = Some(5).filter(|x| *x > 3);
+        let dropped = Some(2).filter(|x| *x > 3);
+        kept.unwrap() + if dropped.is_none() { 100 } else { 0 }
+    });
+    assert_eq!(eval_block(&b).as_number().unwrap(), 105.0);
+}
+
+#[test]
+fn test_typed_option_filter() {
+    let b: Block = parse_quote!({
+        let name: Option<String> = None;
+        name.filter(|n| n.len() > 0).is_none()
+    });
+    assert!(eval_block(&b).as_boolean().unwrap());
+}
+
+#[test]
+fn test_iterator_filter_unchanged() {
+    let expr: Expr = parse_quote! { items.iter().filter(|x| **x > 3) };
+    let js = rust_expr_to_js(&expr);
+    println!("JS: {}", &js);
+    assert!(js.contains(".filter("), "{js}");
+    assert!(!js.contains("v != null"), "{js}");
+}
@@ -103,3 +103,16 @@ fn unwrap_still_passes_plain_values_through() {
     });
     assert_eq!(as_num(&eval_block_returning(&b)), 11.0);
 }
+
+#[test]
+fn parse_turbofish_picks_the_target_type() {
+    let b: Block = parse_quote!({
+        let int = "1.5".parse::<i32>().is_err();
+        let float = "1.5".parse::<f64>().unwrap();
+        let flag = "true".parse::<bool>().unwrap();
+        let bad_flag = "yes".parse::<bool>().is_err();
+        let text = "42".parse::<String>().unwrap();
+        format!("{} {} {} {} {}", int, float, flag, bad_flag, text.len())
+    });
+    assert_eq!(as_str(&eval_block_returning(&b)), "true 1.5 true true 2");
+}
@@ -160,3 +160,39 @@ fn test_promise_all_settled_execution() {
     println!("Promise.allSettled execution result: {}", output_str);
     assert!(output_str.contains("\"method\":\"allSettled\""), "Expected method:'allSettled' in result, got: {}", output_str);
 }
+
+#[test]
+fn test_promise_then_callback_is_an_arrow() {
+    let expr: Expr = parse_quote! {
+        p.then(|v| console.log(v))
+    };
+    let js_code = rust_expr_to_js(&expr);
+    println!("then => {}", js_code);
+    assert_eq!(js_code, "p.then(((v)=>console.log(v)))");
+
+    let result = eval_js(&format!("const p = Promise.resolve(20);\n{js_code}.then((x) => x ?? 22).value"))
+        .unwrap();
+    assert_eq!(result.as_number().unwrap(), 22.0);
+}
+
+#[test]
+fn test_promise_chain_stays_plain_method_calls() {
+    // then/catch/finally have no mapping, and callbacks that call map() or
+    // filter() on the resolved value leave the promise methods alone
+    let state = TranspilerState::new();
+    for name in ["then", "catch", "finally"] {
+        assert!(state.method_mapping(name).is_none(), "{name}");
+    }
+    let expr: Expr = parse_quote! {
+        fetch_items()
+            .then(|items| items.iter().filter(|x| x.ok).map(|x| x.id).collect::<Vec<_>>())
+            .catch(|e| console.log(e))
+            .finally(|| done())
+    };
+    let js_code = rust_expr_to_js(&expr);
+    println!("chain => {}", js_code);
+    assert_eq!(
+        js_code,
+        "fetch_items().then(((items)=>items.filter(((x)=>x.ok)).map(((x)=>x.id)))).catch(((e)=>console.log(e))).finally((()=>done()))"
+    );
+}
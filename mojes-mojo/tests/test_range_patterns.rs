@@ -0,0 +1,59 @@
+// Tests for range patterns in match arms: inclusive and exclusive numeric
+// ranges, open-ended ranges, and char ranges compared by code point.
+use mojes_mojo::*;
+use syn::{parse_quote, Block};
+
+fn eval_block(b: &Block) -> String {
+    let js = rust_block_to_js(b);
+    println!("JS: {}", &js);
+    let mut context = boa_engine::Context::default();
+    let value = context
+        .eval(boa_engine::Source::from_bytes(&format!("(function() {{ {js} }})()")))
+        .expect("JS execution failed");
+    value.to_string(&mut context).unwrap().to_std_string().unwrap()
+}
+
+#[test]
+fn test_numeric_range_pattern() {
+    let block: Block = parse_quote!({
+        let n = 7;
+        let kind = match n {
+            0..=9 => "digit",
+            _ => "other",
+        };
+        kind
+    });
+    let js = rust_block_to_js(&block);
+    println!("JS: {}", &js);
+    assert!(js.contains("n >= 0 && n <= 9 ? \"digit\" : \"other\""), "{js}");
+    assert_eq!(eval_block(&block), "digit");
+}
+
+#[test]
+fn test_exclusive_and_open_ended_ranges() {
+    let b: Block = parse_quote!({
+        let grade = |score: i32| match score {
+            90.. => "A",
+            75..90 => "B",
+            ..75 => "C",
+        };
+        format!("{}{}{}{}", grade(95), grade(90), grade(89), grade(10))
+    });
+    assert_eq!(eval_block(&b), "AABC");
+}
+
+#[test]
+fn test_char_range_compares_code_points() {
+    let block: Block = parse_quote!({
+        let classify = |c: char| match c {
+            'a'..='z' => "lower",
+            'A'..='Z' => "upper",
+            '0'..='9' => "digit",
+            _ => "other",
+        };
+        format!("{} {} {} {}", classify('q'), classify('Q'), classify('5'), classify('-'))
+    });
+    let js = rust_block_to_js(&block);
+    assert!(js.contains(".codePointAt(0) >= 97 && _match_value.codePointAt(0) <= 122"), "{js}");
+    assert_eq!(eval_block(&block), "lower upper digit other");
+}
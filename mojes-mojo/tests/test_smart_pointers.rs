@@ -0,0 +1,60 @@
+// Tests for smart pointers and cells as transparent wrappers: Box/Rc/Arc/
+// RefCell/Cell/Mutex::new(x) become x, and borrow()/borrow_mut()/lock()
+// hand the value back unchanged.
+use mojes_mojo::*;
+use syn::{parse_quote, Block, Expr};
+
+fn eval_block(b: &Block) -> boa_engine::JsValue {
+    let js = rust_block_to_js(b);
+    println!("JS: {}", &js);
+    let mut context = boa_engine::Context::default();
+    context
+        .eval(boa_engine::Source::from_bytes(&format!("(function() {{ {js} }})()")))
+        .expect("JS execution failed")
+}
+
+#[test]
+fn test_box_new_is_transparent() {
+    let expr: Expr = parse_quote! { Box::new(5) };
+    assert_eq!(rust_expr_to_js(&expr), "5");
+}
+
+#[test]
+fn test_rc_and_arc_new_are_transparent() {
+    let expr: Expr = parse_quote! { Rc::new(vec![1, 2]) };
+    assert_eq!(rust_expr_to_js(&expr), "[1, 2]");
+
+    let expr: Expr = parse_quote! { std::sync::Arc::new(config) };
+    assert_eq!(rust_expr_to_js(&expr), "config");
+}
+
+#[test]
+fn test_refcell_borrow_mut_mutates_shared_value() {
+    let b: Block = parse_quote!({
+        let shared = Rc::new(RefCell::new(vec![1]));
+        let other = shared.clone();
+        other.borrow_mut().push(2);
+        shared.borrow().len()
+    });
+    assert_eq!(eval_block(&b).as_number().unwrap(), 2.0);
+}
+
+#[test]
+fn test_mutex_lock_unwrap() {
+    let b: Block = parse_quote!({
+        let counter = Arc::new(Mutex::new(vec![10]));
+        counter.lock().unwrap().push(20);
+        counter.lock().unwrap().len()
+    });
+    assert_eq!(eval_block(&b).as_number().unwrap(), 2.0);
+}
+
+#[test]
+fn test_cell_new_and_other_constructors_unchanged() {
+    let expr: Expr = parse_quote! { Cell::new(0) };
+    assert_eq!(rust_expr_to_js(&expr), "0");
+
+    // Regular types keep the runtime-dispatched constructor
+    let expr: Expr = parse_quote! { Counter::new(0) };
+    assert!(rust_expr_to_js(&expr).contains("new Counter(0)"));
+}
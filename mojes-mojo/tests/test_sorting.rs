@@ -0,0 +1,91 @@
+// Tests for comparator-based sorting and selection: sort_by() hands its
+// closure to Array.sort, with cmp() producing the -1/0/1 comparator result,
+// sort_by_key() compares the extracted keys the same way, and
+// min_by/max_by(_key) reduce to the chosen element.
+use mojes_mojo::*;
+use syn::{parse_quote, Block, Expr, ItemStruct};
+
+fn eval_block(b: &Block) -> String {
+    let js = rust_block_to_js(b);
+    println!("JS: {}", &js);
+    let mut context = boa_engine::Context::default();
+    let value = context
+        .eval(boa_engine::Source::from_bytes(&format!("(function() {{ {js} }})()")))
+        .expect("JS execution failed");
+    value.to_string(&mut context).unwrap().to_std_string().unwrap()
+}
+
+#[test]
+fn test_sort_by_passes_closure_to_sort() {
+    let expr: Expr = parse_quote! { v.sort_by(f) };
+    assert_eq!(rust_expr_to_js(&expr), "v.sort(f)");
+}
+
+#[test]
+fn test_sort_by_cmp_ascending_and_descending() {
+    let b: Block = parse_quote!({
+        let mut v = vec![10, 2, 33, 4];
+        v.sort_by(|a, b| a.cmp(b));
+        let mut w = vec![10, 2, 33, 4];
+        w.sort_by(|a, b| b.cmp(a));
+        format!("{} {}", v.join(","), w.join(","))
+    });
+    // Numbers compare numerically, not as strings like a bare JS sort()
+    assert_eq!(eval_block(&b), "2,4,10,33 33,10,4,2");
+}
+
+#[test]
+fn test_sort_by_key() {
+    let b: Block = parse_quote!({
+        let mut words = vec!["pear", "fig", "banana"];
+        words.sort_by_key(|w| w.len());
+        let mut names = vec!["cy", "Ann", "bo"];
+        names.sort_by_key(|n| n.to_lowercase());
+        format!("{} {}", words.join(","), names.join(","))
+    });
+    assert_eq!(eval_block(&b), "fig,pear,banana Ann,bo,cy");
+}
+
+#[test]
+fn test_max_by_key_and_min_by_key_on_struct_field() {
+    let class: ItemStruct = parse_quote! {
+        struct Player {
+            name: String,
+            score: u32,
+        }
+    };
+    let b: Block = parse_quote!({
+        let mut players = Vec::new();
+        players.push(Player { name: "ann".to_string(), score: 7 });
+        players.push(Player { name: "bo".to_string(), score: 9 });
+        players.push(Player { name: "cy".to_string(), score: 9 });
+        players.push(Player { name: "di".to_string(), score: 3 });
+        let best = players.iter().max_by_key(|p| p.score).unwrap();
+        let worst = players.iter().min_by_key(|p| p.score).unwrap();
+        let none: Vec<Player> = vec![];
+        format!("{} {} {}", best.name, worst.name, none.iter().max_by_key(|p| p.score).is_none())
+    });
+    let js = rust_block_to_js(&b);
+    println!("JS: {}", &js);
+    let code = format!(
+        "{}\n(function() {{ {js} }})()",
+        generate_js_class_for_struct(&class)
+    );
+    let mut context = boa_engine::Context::default();
+    let value = context
+        .eval(boa_engine::Source::from_bytes(&code))
+        .expect("JS execution failed");
+    // Ties: the last maximum wins, like Rust's max_by_key
+    assert_eq!(value.as_string().unwrap().to_std_string().unwrap(), "cy di true");
+}
+
+#[test]
+fn test_min_by_and_max_by_with_comparator() {
+    let b: Block = parse_quote!({
+        let v = vec![4, 11, 2, 8];
+        let lo = v.iter().min_by(|a, b| a.cmp(b)).unwrap();
+        let hi = v.iter().max_by(|a, b| a.cmp(b)).unwrap();
+        format!("{} {}", lo, hi)
+    });
+    assert_eq!(eval_block(&b), "2 11");
+}
@@ -0,0 +1,47 @@
+// Tests for the `spread!` helper: a slice argument wrapped in it is emitted
+// as `...args`, so logging wrappers can forward to variadic JS functions.
+use mojes_mojo::*;
+use syn::{parse_quote, Block, Expr};
+
+fn eval_js(code: &str) -> boa_engine::JsValue {
+    let mut context = boa_engine::Context::default();
+    context
+        .eval(boa_engine::Source::from_bytes(code))
+        .expect("JS execution failed")
+}
+
+#[test]
+fn test_spread_function_argument() {
+    let expr: Expr = parse_quote! { log_all("prefix", spread!(&args)) };
+    assert_eq!(rust_expr_to_js(&expr), "log_all(\"prefix\", ...args)");
+}
+
+#[test]
+fn test_spread_method_argument_to_variadic_call() {
+    let block: Block = parse_quote!({
+        fn log_with_tag(tag: &str, args: &[i32]) {
+            console.log(tag, spread!(args));
+        }
+    });
+    let js = rust_block_to_js(&block);
+    println!("JS: {}", &js);
+    assert!(js.contains("console.log(tag, ...args)"), "{js}");
+}
+
+#[test]
+fn test_spread_evaluates_as_separate_arguments() {
+    let block: Block = parse_quote!({
+        let values = vec![3, 9, 4];
+        Math.max(spread!(&values))
+    });
+    let js = rust_block_to_js(&block);
+    println!("JS: {}", &js);
+    let result = eval_js(&format!("(function() {{ {js} }})()"));
+    assert_eq!(result.as_number().unwrap(), 9.0);
+}
+
+#[test]
+fn test_spread_outside_call_is_the_value() {
+    let expr: Expr = parse_quote! { spread!(&items) };
+    assert_eq!(rust_expr_to_js(&expr), "items");
+}
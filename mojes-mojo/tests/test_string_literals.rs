@@ -0,0 +1,48 @@
+// Tests for string literal emission: the JS string must evaluate to exactly
+// the Rust value, for raw strings, embedded newlines and non-ASCII text.
+use mojes_mojo::*;
+use syn::{parse_quote, Expr};
+
+fn eval_str(js: &str) -> String {
+    let mut context = boa_engine::Context::default();
+    let value = context
+        .eval(boa_engine::Source::from_bytes(js))
+        .expect("JS execution failed");
+    value.as_string().unwrap().to_std_string().unwrap()
+}
+
+#[test]
+fn test_raw_string_keeps_backslash() {
+    let expr: Expr = parse_quote! { r"a\b" };
+    let js = rust_expr_to_js(&expr);
+    println!("JS: {}", &js);
+    assert_eq!(js, r#""a\\b""#);
+    assert_eq!(eval_str(&js), r"a\b");
+}
+
+#[test]
+fn test_raw_string_with_quotes() {
+    let expr: Expr = parse_quote! { r#"say "hi" \d+"# };
+    let js = rust_expr_to_js(&expr);
+    println!("JS: {}", &js);
+    assert_eq!(eval_str(&js), r#"say "hi" \d+"#);
+}
+
+#[test]
+fn test_string_with_literal_newline() {
+    // A newline typed directly inside the literal, not the `\n` escape
+    let expr: Expr = syn::parse_str("\"first\nsecond\"").unwrap();
+    let js = rust_expr_to_js(&expr);
+    println!("JS: {}", &js);
+    assert_eq!(js, r#""first\nsecond""#);
+    assert_eq!(eval_str(&js), "first\nsecond");
+}
+
+#[test]
+fn test_unicode_passes_through() {
+    let expr: Expr = parse_quote! { "héllo ✓ \u{1F600}" };
+    let js = rust_expr_to_js(&expr);
+    println!("JS: {}", &js);
+    assert!(js.contains("héllo ✓ 😀"), "Expected unescaped unicode, got: {}", &js);
+    assert_eq!(eval_str(&js), "héllo ✓ 😀");
+}
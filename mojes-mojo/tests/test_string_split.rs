@@ -0,0 +1,48 @@
+// Tests for the split variants beyond split(): split_whitespace, splitn
+// (which keeps the remainder in its last piece, unlike JS split with a
+// limit) and rsplit. Results are joined with "|" and compared under Boa.
+use mojes_mojo::*;
+use syn::{parse_quote, Expr};
+
+fn eval_joined(expr: &Expr) -> String {
+    let js = rust_expr_to_js(expr);
+    println!("JS: {}", &js);
+    let mut context = boa_engine::Context::default();
+    let value = context
+        .eval(boa_engine::Source::from_bytes(&format!("({js}).join(\"|\")")))
+        .expect("JS execution failed");
+    value.to_string(&mut context).unwrap().to_std_string().unwrap()
+}
+
+#[test]
+fn test_split_whitespace() {
+    let expr: Expr = parse_quote! { "a b c".split_whitespace() };
+    assert!(rust_expr_to_js(&expr).contains(".trim().split(/\\s+/)"));
+    assert_eq!(eval_joined(&expr), "a|b|c");
+
+    let expr: Expr = parse_quote! { "  spaced \t out\n".split_whitespace() };
+    assert_eq!(eval_joined(&expr), "spaced|out");
+
+    // No words at all is an empty sequence, not [""]
+    let expr: Expr = parse_quote! { "   ".split_whitespace().collect::<Vec<_>>().len() };
+    let js = rust_expr_to_js(&expr);
+    let mut context = boa_engine::Context::default();
+    let value = context.eval(boa_engine::Source::from_bytes(&js)).unwrap();
+    assert_eq!(value.as_number().unwrap(), 0.0);
+}
+
+#[test]
+fn test_splitn_keeps_remainder() {
+    let expr: Expr = parse_quote! { "k=v=w".splitn(2, "=") };
+    assert_eq!(eval_joined(&expr), "k|v=w");
+
+    let expr: Expr = parse_quote! { "a,b".splitn(5, ",") };
+    assert_eq!(eval_joined(&expr), "a|b");
+}
+
+#[test]
+fn test_rsplit() {
+    let expr: Expr = parse_quote! { "a/b/c".rsplit("/") };
+    assert_eq!(rust_expr_to_js(&expr), "\"a/b/c\".split(\"/\").reverse()");
+    assert_eq!(eval_joined(&expr), "c|b|a");
+}
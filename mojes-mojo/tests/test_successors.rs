@@ -0,0 +1,48 @@
+// Tests for std::iter::successors(init, f).take(n): the sequence is built
+// eagerly into an array, stopping at None or after n elements.
+use mojes_mojo::*;
+use syn::{parse_quote, Expr};
+
+fn eval_js(code: &str) -> boa_engine::JsValue {
+    let mut context = boa_engine::Context::default();
+    context
+        .eval(boa_engine::Source::from_bytes(code))
+        .expect("JS execution failed")
+}
+
+fn as_str(v: &boa_engine::JsValue) -> String {
+    let mut ctx = boa_engine::Context::default();
+    v.to_string(&mut ctx).unwrap().to_std_string().unwrap()
+}
+
+#[test]
+fn test_successors_take_doubling() {
+    let expr: Expr = parse_quote! {
+        std::iter::successors(Some(1), |&x| Some(x * 2)).take(5)
+    };
+    let js = rust_expr_to_js(&expr);
+    println!("JS: {}", &js);
+    assert!(js.contains("while"), "expected an array-building loop:\n{js}");
+    assert_eq!(as_str(&eval_js(&format!("({js}).join(',')"))), "1,2,4,8,16");
+}
+
+#[test]
+fn test_successors_stops_at_none() {
+    let expr: Expr = parse_quote! {
+        successors(Some(1000), |&x| if x >= 10 { Some(x / 10) } else { None }).take(10)
+    };
+    let js = rust_expr_to_js(&expr);
+    println!("JS: {}", &js);
+    assert_eq!(as_str(&eval_js(&format!("({js}).join(',')"))), "1000,100,10,1");
+}
+
+#[test]
+fn test_successors_collect_chain() {
+    // .collect() after take is a no-op, the array is already materialized
+    let expr: Expr = parse_quote! {
+        iter::successors(Some(3), |&x| Some(x + 3)).take(4).collect::<Vec<_>>()
+    };
+    let js = rust_expr_to_js(&expr);
+    println!("JS: {}", &js);
+    assert_eq!(as_str(&eval_js(&format!("({js}).join(',')"))), "3,6,9,12");
+}
@@ -1,5 +1,5 @@
 use mojes_mojo::*;
-use syn::{parse_quote, Block};
+use syn::{parse_quote, Block, ItemFn};
 
 #[test]
 fn test_try_without_await_generates_iife_error_check() {
@@ -34,10 +34,11 @@ fn test_try_without_await_generates_iife_error_check() {
 
 #[test]
 fn test_try_with_await_generates_try_catch() {
-    // expr.await? should generate an async IIFE with try/catch
+    // expr.await? inside an expression should generate an async IIFE with
+    // try/catch (a `let` binds it with statements instead, see below)
     let block: Block = parse_quote! {
         {
-            let result = fetch(url).await?;
+            let result = process(fetch(url).await?);
             result
         }
     };
@@ -108,3 +109,158 @@ fn test_try_with_await_in_async_fn() {
         js
     );
 }
+
+#[test]
+fn test_try_on_option_returns_null_early() {
+    // In a function returning Option, `let x = opt?;` binds the value and
+    // returns null (None) from the function when it is missing
+    let block: Block = parse_quote! {
+        {
+            fn first_doubled(values: Vec<i32>) -> Option<i32> {
+                let first = values.first()?;
+                Some(first * 2)
+            }
+            fn describe(name: Option<String>) -> Option<String> {
+                let n: String = name?;
+                Some(format!("<{}>", n))
+            }
+        }
+    };
+    let js = rust_block_to_js(&block);
+    println!("Option try JS: {}", &js);
+    assert!(js.contains("if (first == null) return null;"), "{js}");
+    assert!(js.contains("if (n == null) return null;"), "{js}");
+    assert!(!js.contains("n.ok"), "an Option is not unwrapped via .ok: {js}");
+
+    let block: Block = parse_quote! {
+        {
+            fn describe(name: Option<String>) -> Option<String> {
+                let n = name?;
+                Some(format!("<{}>", n))
+            }
+            let missing = describe(None);
+            let present = describe(Some("a".to_string()));
+            missing.is_none() && present.unwrap() == "<a>"
+        }
+    };
+    let js = rust_block_to_js(&block);
+    let mut context = boa_engine::Context::default();
+    let result = context
+        .eval(boa_engine::Source::from_bytes(&format!("(function() {{ {js} }})()")))
+        .expect("JS execution failed");
+    assert!(result.as_boolean().unwrap(), "{js}");
+}
+
+#[test]
+fn test_let_await_try_awaits_then_propagates_error() {
+    let item: ItemFn = parse_quote! {
+        async fn load(url: &str) -> Result<String, String> {
+            let r = fetch(url).await?;
+            let checked = validate(r.body).await?;
+            Ok(checked)
+        }
+    };
+    let js = transpile_fn_to_js_with_options(&item, &CodegenOptions::default()).unwrap();
+    println!("let await? JS: {}", &js);
+    assert!(js.contains("_temp1 = await fetch(url);"), "{js}");
+    assert!(js.contains("if (_temp1 && _temp1.error !== undefined) return _temp1;"), "{js}");
+    assert!(!js.contains("async function()"), "no async IIFE in let position: {js}");
+
+    // A rejected promise and an {error} Result both end load() early; an
+    // {ok} Result from a transpiled async fn is unwrapped
+    let code = format!(
+        "async function fetch(url) {{ if (url === \"down\") throw \"offline\"; return {{ body: url }}; }}\n\
+         async function validate(body) {{ return body === \"bad\" ? {{ error: \"invalid\" }} : {{ ok: body + \"!\" }}; }}\n\
+         {js}\n\
+         var out = [];\n\
+         [\"fine\", \"down\", \"bad\"].forEach((url, i) => {{\n\
+             load(url).then((r) => out[i] = r.ok !== undefined ? \"ok:\" + r.ok : \"error:\" + r.error);\n\
+         }});"
+    );
+    let mut context = boa_engine::Context::default();
+    context
+        .eval(boa_engine::Source::from_bytes(&code))
+        .expect("JS execution failed");
+    context.run_jobs();
+    let out = context
+        .eval(boa_engine::Source::from_bytes("out.join(\",\")"))
+        .unwrap();
+    assert_eq!(
+        out.to_string(&mut context).unwrap().to_std_string().unwrap(),
+        "ok:fine!,error:offline,error:invalid"
+    );
+}
+
+#[test]
+fn test_try_on_option_inside_value_if_is_refused() {
+    // The early return would only leave the IIFE the `if` becomes, so the
+    // function would go on with the IIFE's null as the value
+    let block: Block = parse_quote! {
+        {
+            fn f(v: Option<i32>) -> Option<i32> {
+                let y = if true {
+                    let x = v?;
+                    x + 1
+                } else {
+                    0
+                };
+                Some(y + 10)
+            }
+        }
+    };
+    let err = try_rust_block_to_js(&block).unwrap_err();
+    println!("error: {}", &err);
+    assert!(err.contains("can't return from the function"), "{err}");
+}
+
+#[test]
+fn test_try_on_option_inside_an_expression_is_refused() {
+    // `null * 2` would go on instead of returning None
+    let block: Block = parse_quote! {
+        {
+            fn doubled(v: Vec<i32>) -> Option<i32> {
+                Some(v.first()? * 2)
+            }
+        }
+    };
+    let err = try_rust_block_to_js(&block).unwrap_err();
+    println!("error: {}", &err);
+    assert!(err.contains("only return early as `let x = opt?;`"), "{err}");
+}
+
+#[test]
+fn test_try_in_result_closure_inside_option_fn() {
+    // The closure's own return type and the operand's declared type decide,
+    // not the enclosing function returning an Option
+    let block: Block = parse_quote! {
+        {
+            fn f(n: Option<i32>) -> Option<i32> {
+                let check = |s: i32| -> Result<i32, String> {
+                    let r: Result<i32, String> = if s > 0 { Ok(s) } else { Err("negative".to_string()) };
+                    let k = r?;
+                    Ok(k * 2)
+                };
+                let v = n?;
+                match check(v) {
+                    Ok(x) => Some(x),
+                    Err(_) => None,
+                }
+            }
+            let a = f(Some(4)).unwrap_or(0);
+            let c = f(None).unwrap_or(0);
+            format!("{} {}", a, c)
+        }
+    };
+    let js = rust_block_to_js(&block);
+    println!("JS: {}", &js);
+    assert!(!js.contains("if (k == null)"), "a Result is not null-checked:\n{js}");
+    assert!(js.contains(".error) return"), "{js}");
+    let mut context = boa_engine::Context::default();
+    let result = context
+        .eval(boa_engine::Source::from_bytes(&format!("(function() {{ {js} }})()")))
+        .expect("JS execution failed");
+    assert_eq!(
+        result.to_string(&mut context).unwrap().to_std_string().unwrap(),
+        "8 0"
+    );
+}
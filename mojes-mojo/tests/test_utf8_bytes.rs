@@ -0,0 +1,101 @@
+// Tests for byte strings: as_bytes() encodes to an array of UTF-8 bytes,
+// String::from_utf8 decodes back into a Result, and write! to a Vec<u8>
+// appends the formatted text's bytes.
+use mojes_mojo::*;
+use syn::{parse_quote, Block, Expr};
+
+// Boa has no TextEncoder/TextDecoder; these go through the URI functions,
+// which speak UTF-8 too
+const TEXT_CODEC: &str = r#"
+class TextEncoder {
+    encode(s) {
+        const e = encodeURIComponent(s), out = [];
+        for (let i = 0; i < e.length; i++) {
+            if (e[i] === "%") { out.push(parseInt(e.slice(i + 1, i + 3), 16)); i += 2; }
+            else out.push(e.charCodeAt(i));
+        }
+        return new Uint8Array(out);
+    }
+}
+class TextDecoder {
+    constructor(label, options) { this.fatal = !!(options && options.fatal); }
+    decode(bytes) {
+        const escaped = Array.from(bytes, b => "%" + b.toString(16).padStart(2, "0")).join("");
+        try { return decodeURIComponent(escaped); }
+        catch (e) { if (this.fatal) throw new TypeError("invalid utf-8"); return "�"; }
+    }
+}
+"#;
+
+fn eval_block(b: &Block) -> String {
+    let js = rust_block_to_js(b);
+    println!("JS: {}", &js);
+    let mut context = boa_engine::Context::default();
+    let result = context
+        .eval(boa_engine::Source::from_bytes(&format!("{TEXT_CODEC}\n(function() {{ {js} }})()")))
+        .expect("JS execution failed");
+    result.to_string(&mut context).unwrap().to_std_string().unwrap()
+}
+
+#[test]
+fn test_as_bytes_encodes_utf8() {
+    let expr: Expr = parse_quote! { name.as_bytes() };
+    assert_eq!(rust_expr_to_js(&expr), "Array.from(new TextEncoder().encode(name))");
+
+    let b: Block = parse_quote!({
+        let bytes = "hé".as_bytes();
+        format!("{} {} {}", bytes.len(), bytes[0], bytes[2])
+    });
+    assert_eq!(eval_block(&b), "3 104 169");
+}
+
+#[test]
+fn test_as_bytes_map_to_wider_type_and_grow() {
+    let b: Block = parse_quote!({
+        let scaled: Vec<u32> = "ab".as_bytes().iter().map(|b| *b as u32 * 10).collect();
+        let mut copy = "a".as_bytes().to_vec();
+        copy.push(33);
+        format!("{} {} {}", scaled.join(","), copy.len(), copy[1])
+    });
+    assert_eq!(eval_block(&b), "970,980 2 33");
+}
+
+#[test]
+fn test_from_utf8_round_trip() {
+    let b: Block = parse_quote!({
+        let bytes = "grüße".as_bytes();
+        std::str::from_utf8(bytes).unwrap()
+    });
+    assert_eq!(eval_block(&b), "grüße");
+
+    let b: Block = parse_quote!({
+        let text = String::from_utf8(vec![104, 105]);
+        text.is_ok()
+    });
+    assert_eq!(eval_block(&b), "true");
+}
+
+#[test]
+fn test_from_utf8_invalid_bytes_is_err() {
+    let b: Block = parse_quote!({
+        let text = String::from_utf8(vec![0xff, 0xfe]);
+        text.is_err()
+    });
+    assert_eq!(eval_block(&b), "true");
+
+    let b: Block = parse_quote!({
+        String::from_utf8_lossy(&[0x6f, 0x6b])
+    });
+    assert_eq!(eval_block(&b), "ok");
+}
+
+#[test]
+fn test_write_to_byte_buffer() {
+    let b: Block = parse_quote!({
+        let mut buf = Vec::new();
+        write!(buf, "{}-{}", 1, "é").unwrap();
+        writeln!(&mut buf, "!").unwrap();
+        format!("{} {}", buf.len(), String::from_utf8(buf).unwrap().trim_end())
+    });
+    assert_eq!(eval_block(&b), "6 1-é!");
+}
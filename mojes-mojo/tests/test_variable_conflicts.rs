@@ -1,6 +1,6 @@
 // Test for variable name conflict resolution
 use mojes_mojo::*;
-use syn::{ItemImpl, parse_quote};
+use syn::{Block, ItemImpl, parse_quote};
 
 #[test]
 fn test_parameter_local_variable_conflict() {
@@ -111,4 +111,87 @@ fn test_webrtc_specific_pattern() {
     }
     
     println!("Variable names found: {:?}", var_names);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_len_and_insert_iife_params_do_not_shadow_user_names() {
+    // A user variable called `obj` (or `key`/`val`) inside the argument of
+    // another len()/insert() must keep referring to the user's value
+    let block: syn::Block = parse_quote!({
+        let obj = vec![1, 2, 3];
+        let key = 0;
+        let mut out = vec![9];
+        out.insert(key, obj.len());
+        let measure = |val: Vec<i32>| val.len() + obj.len();
+        out.insert(obj.len() - 2, measure(vec![5]));
+        out.join(",")
+    });
+    let js_code = rust_block_to_js(&block);
+    println!("Generated JavaScript:\n{}", js_code);
+    assert!(!js_code.contains("(obj)=>"), "IIFE params must not reuse `obj`:\n{js_code}");
+    assert!(!js_code.contains("(obj, key, val)=>"), "{js_code}");
+
+    let mut context = boa_engine::Context::default();
+    let result = context
+        .eval(boa_engine::Source::from_bytes(&format!(
+            "(function() {{ {js_code} }})()"
+        )))
+        .expect("JS execution failed");
+    let text = result.to_string(&mut context).unwrap().to_std_string().unwrap();
+    assert_eq!(text, "3,4,9");
+}
+
+#[test]
+fn test_shadowing_params_keep_signature_and_body_in_sync() {
+    // `x` and the reserved-word `class` shadow outer bindings, so they are
+    // renamed - identically in the signature and the body
+    let block: Block = parse_quote!({
+        let x = 1;
+        let class = 5;
+        fn total(x: i32, class: i32, new: i32) -> i32 {
+            x + class + new
+        }
+        total(x, class, 3) * 10 + total(2, 0, 0)
+    });
+    let js_code = rust_block_to_js(&block);
+    println!("JS: {}", &js_code);
+    assert!(js_code.contains("function total(x_1, class__1, new_) {"), "{js_code}");
+    assert!(js_code.contains("return x_1 + class__1 + new_;"), "{js_code}");
+
+    let mut context = boa_engine::Context::default();
+    let result = context
+        .eval(boa_engine::Source::from_bytes(&format!("(function() {{ {js_code} }})()")))
+        .expect("JS execution failed");
+    assert_eq!(result.as_number().unwrap(), 92.0);
+}
+
+#[test]
+fn test_deferred_init_assigns_the_declared_name() {
+    // The shadowing `class` is declared without a value; the assignments in
+    // both branches must target the renamed binding, not the outer one
+    let block: Block = parse_quote!({
+        let class = 100;
+        let c = false;
+        let mut class;
+        let size: i32;
+        if c {
+            class = 1;
+            size = 10;
+        } else {
+            class = 2;
+            size = 20;
+        }
+        class + size
+    });
+    let js_code = rust_block_to_js(&block);
+    println!("JS: {}", &js_code);
+    assert!(js_code.contains("let class__1;"), "{js_code}");
+    assert!(js_code.contains("class__1 = 2;"), "{js_code}");
+    assert!(js_code.contains("let size;"), "{js_code}");
+
+    let mut context = boa_engine::Context::default();
+    let result = context
+        .eval(boa_engine::Source::from_bytes(&format!("(function() {{ {js_code} }})()")))
+        .expect("JS execution failed");
+    assert_eq!(result.as_number().unwrap(), 22.0);
+}
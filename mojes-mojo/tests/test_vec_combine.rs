@@ -0,0 +1,112 @@
+// Tests for building a Vec out of other Vecs: repeat() concatenates copies
+// of an array, while str::repeat stays the JS string method; concat()
+// flattens a slice of slices (or joins strings), join() puts a separator
+// between strings or between inner Vecs, and extend_from_slice() appends in
+// place.
+use mojes_mojo::*;
+use syn::{parse_quote, Block, Expr};
+
+fn eval_block(b: &Block) -> String {
+    let js = rust_block_to_js(b);
+    println!("JS: {}", &js);
+    let mut context = boa_engine::Context::default();
+    let value = context
+        .eval(boa_engine::Source::from_bytes(&format!(
+            "(function() {{ return JSON.stringify((function() {{ {js} }})()); }})()"
+        )))
+        .expect("JS execution failed");
+    value.to_string(&mut context).unwrap().to_std_string().unwrap()
+}
+
+#[test]
+fn test_vec_literal_repeat() {
+    let b: Block = parse_quote!({ vec![1, 2].repeat(2) });
+    assert_eq!(eval_block(&b), "[1,2,1,2]");
+}
+
+#[test]
+fn test_typed_vec_repeat_evaluates_receiver_once() {
+    let b: Block = parse_quote!({
+        let row: Vec<u8> = vec![0, 1];
+        let grid = row.repeat(3);
+        let empty = row.repeat(0);
+        (grid, empty)
+    });
+    assert_eq!(eval_block(&b), "[[0,1,0,1,0,1],[]]");
+}
+
+#[test]
+fn test_str_repeat_is_unchanged() {
+    let expr: Expr = parse_quote! { "ab".repeat(3) };
+    assert_eq!(rust_expr_to_js(&expr), "\"ab\".repeat(3)");
+
+    let b: Block = parse_quote!({
+        let name = "na".to_string();
+        name.repeat(2)
+    });
+    assert_eq!(eval_block(&b), "\"nana\"");
+}
+
+#[test]
+fn test_concat_flattens_slices() {
+    let b: Block = parse_quote!({
+        let a = vec![1, 2];
+        let b = vec![3];
+        [a, b].concat()
+    });
+    assert_eq!(eval_block(&b), "[1,2,3]");
+
+    let b: Block = parse_quote!({
+        let nested: Vec<Vec<i32>> = Vec::new();
+        nested.concat()
+    });
+    assert_eq!(eval_block(&b), "[]");
+}
+
+#[test]
+fn test_concat_of_strings_is_a_string() {
+    let b: Block = parse_quote!({ ["ab", "cd"].concat() });
+    assert_eq!(eval_block(&b), "\"abcd\"");
+}
+
+#[test]
+fn test_extend_from_slice_appends_in_place() {
+    let expr: Expr = parse_quote! { v.extend_from_slice(&other) };
+    assert_eq!(rust_expr_to_js(&expr), "v.push(...other)");
+
+    let b: Block = parse_quote!({
+        let mut v = vec![1];
+        let more = vec![2, 3];
+        v.extend_from_slice(&more);
+        v.extend_from_slice(&[4]);
+        v
+    });
+    assert_eq!(eval_block(&b), "[1,2,3,4]");
+}
+
+#[test]
+fn test_join_strings_with_separator() {
+    let b: Block = parse_quote!({ vec!["a", "b"].join("-") });
+    assert_eq!(eval_block(&b), "\"a-b\"");
+
+    let b: Block = parse_quote!({
+        let words: Vec<String> = vec!["x".to_string(), "y".to_string(), "z".to_string()];
+        words.join(", ")
+    });
+    assert_eq!(eval_block(&b), "\"x, y, z\"");
+}
+
+#[test]
+fn test_join_nested_vecs_with_separator() {
+    let b: Block = parse_quote!({
+        let mut rows: Vec<Vec<i32>> = Vec::new();
+        rows.push(vec![1, 2]);
+        rows.push(vec![3]);
+        rows.push(vec![4]);
+        let flat = rows.concat();
+        let joined = rows.join(&0);
+        let with_slice = rows.join(&[8, 9][..]);
+        (flat, joined, with_slice)
+    });
+    assert_eq!(eval_block(&b), "[[1,2,3,4],[1,2,0,3,0,4],[1,2,8,9,3,8,9,4]]");
+}
@@ -0,0 +1,60 @@
+// Tests for Vec::get: a bounds-checked index that yields null (None) past
+// the end, without disturbing HashMap/Map lookups through the same method.
+use mojes_mojo::*;
+use syn::{parse_quote, Block};
+
+fn eval_block(b: &Block) -> String {
+    let js = rust_block_to_js(b);
+    println!("JS: {}", &js);
+    let mut context = boa_engine::Context::default();
+    let value = context
+        .eval(boa_engine::Source::from_bytes(&format!("(function() {{ {js} }})()")))
+        .expect("JS execution failed");
+    value.to_string(&mut context).unwrap().to_std_string().unwrap()
+}
+
+#[test]
+fn test_vec_get_is_bounds_checked() {
+    let block: Block = parse_quote!({
+        let v = vec![10, 20];
+        let first = v.get(0);
+    });
+    let js = rust_block_to_js(&block);
+    println!("JS: {}", &js);
+    assert!(
+        js.contains("((_temp1, _temp2)=>_temp2 < _temp1.length ? _temp1[_temp2] : null)(v, 0)"),
+        "{js}"
+    );
+    assert!(!js.contains("typeof"), "a known Vec needs no Map check:\n{js}");
+}
+
+#[test]
+fn test_vec_get_out_of_range_is_none() {
+    let b: Block = parse_quote!({
+        let v: Vec<i32> = vec![10, 20];
+        let hit = v.get(1);
+        let miss = v.get(5);
+        format!("{}/{}", hit.unwrap_or(0), miss.is_none())
+    });
+    assert_eq!(eval_block(&b), "20/true");
+}
+
+#[test]
+fn test_untyped_get_handles_arrays_and_maps() {
+    let b: Block = parse_quote!({
+        let items = make_items();
+        let lookup = make_lookup();
+        format!("{}/{}/{}", items.get(2).is_none(), items.get(0).unwrap(), lookup.get("k").unwrap())
+    });
+    let js = rust_block_to_js(&b);
+    let code = format!(
+        "function make_items() {{ return [7]; }}\n\
+         function make_lookup() {{ return new Map([[\"k\", \"v\"]]); }}\n\
+         (function() {{ {js} }})()"
+    );
+    let mut context = boa_engine::Context::default();
+    let value = context
+        .eval(boa_engine::Source::from_bytes(&code))
+        .expect("JS execution failed");
+    assert_eq!(value.to_string(&mut context).unwrap().to_std_string().unwrap(), "true/7/v");
+}
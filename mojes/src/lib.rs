@@ -19,7 +19,7 @@ pub use linkme::{DistributedSlice, distributed_slice};
 /// Convenient prelude that imports commonly used items
 pub mod prelude {
     pub use crate::dom::{alert, confirm, console, document, prompt, window};
-    pub use crate::{distributed_slice, js_type, to_js};
+    pub use crate::{distributed_slice, js_export, js_type, to_js};
 }
 
 /// Type aliases for common patterns
@@ -34,6 +34,15 @@ macro_rules! js_slice {
     };
 }
 
+/// Marks a slice argument to be spread into a variadic JavaScript call:
+/// `f(spread!(&args))` becomes `f(...args)`. In Rust it is the argument itself.
+#[macro_export]
+macro_rules! spread {
+    ($e:expr) => {
+        $e
+    };
+}
+
 // Optional: Version information
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
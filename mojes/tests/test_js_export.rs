@@ -0,0 +1,35 @@
+// #[js_export] on a free function: the Rust function is kept as is and a
+// top-level JS function with the same name lands in the JS slice.
+
+use linkme::distributed_slice;
+use mojes_derive::js_export;
+
+#[distributed_slice]
+static JS: [&str] = [..];
+
+#[js_export]
+pub fn initials(first: &str, last: &str) -> String {
+    let joined = format!("{}{}", &first[0..1], &last[0..1]);
+    joined.to_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use boa_engine::{Context, Source};
+
+    #[test]
+    fn js_export_emits_top_level_function() {
+        let full_js = JS.join("\n");
+        println!("JS: {}", &full_js);
+        assert!(full_js.contains("function initials(first, last) {"), "{full_js}");
+
+        assert_eq!(initials("ada", "lovelace"), "AL");
+
+        let mut context = Context::default();
+        let result = context
+            .eval(Source::from_bytes(&format!("{full_js}\ninitials(\"ada\", \"lovelace\")")))
+            .expect("JS execution failed");
+        assert_eq!(result.to_string(&mut context).unwrap().to_std_string().unwrap(), "AL");
+    }
+}
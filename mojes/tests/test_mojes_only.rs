@@ -0,0 +1,43 @@
+// `#[mojes_only]` methods are transpiled but removed from the Rust impl, and
+// `#[js_ignore]` methods stay in Rust but not in the JS - checked through the
+// derive macros so the markers are also known to compile.
+
+use linkme::distributed_slice;
+use mojes_derive::{js_object, js_type};
+
+#[distributed_slice]
+static JS: [&str] = [..];
+
+#[js_type]
+struct Panel {
+    title: String,
+    #[js_ignore]
+    cache_key: u64,
+}
+
+#[js_object]
+impl Panel {
+    #[mojes_only]
+    fn render(&self) -> String {
+        format!("<h1>{}</h1>", self.title)
+    }
+
+    #[js_ignore]
+    fn cache_key(&self) -> u64 {
+        self.cache_key
+    }
+}
+
+#[test]
+fn test_mojes_only_and_js_ignore_split() {
+    let js = JS.join("\n");
+    println!("JS: {}", &js);
+    assert!(js.contains("Panel.prototype.render"), "{js}");
+    assert!(!js.contains("prototype.cache_key"), "{js}");
+    assert!(js.contains("constructor(title)"), "{js}");
+
+    // The Rust side keeps the server-only method
+    let panel = Panel { title: "x".to_string(), cache_key: 7 };
+    assert_eq!(panel.cache_key(), 7);
+    assert_eq!(panel.title, "x");
+}